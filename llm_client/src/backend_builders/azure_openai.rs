@@ -0,0 +1,99 @@
+use crate::LlmClient;
+use llm_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use llm_interface::llms::{
+    api::{
+        azure_openai::{AzureOpenAiBackend, AzureOpenAiConfig},
+        config::{ApiConfig, LlmApiConfigTrait},
+    },
+    LlmBackend,
+};
+use llm_models::api_model::{openai::OpenAiModelTrait, ApiLlmModel};
+
+// Everything here can be implemented for any struct.
+pub struct AzureOpenAiBackendBuilder {
+    pub config: AzureOpenAiConfig,
+    pub model: ApiLlmModel,
+}
+
+impl Default for AzureOpenAiBackendBuilder {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            model: ApiLlmModel::gpt_4_o_mini(),
+        }
+    }
+}
+
+impl AzureOpenAiBackendBuilder {
+    /// Sets the Azure OpenAI resource endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The resource endpoint, e.g. `https://my-resource.openai.azure.com`. `https://` is added if no scheme is given.
+    ///
+    /// # Notes
+    ///
+    /// Required. `init` returns an error if it is not set.
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.config.api_config.host = endpoint.into();
+        self
+    }
+
+    /// Sets the name of the model deployment.
+    ///
+    /// # Arguments
+    ///
+    /// * `deployment` - The deployment name given in the Azure portal. Requests are sent to `/openai/deployments/{deployment}`.
+    ///
+    /// # Notes
+    ///
+    /// Required. `init` returns an error if it is not set.
+    /// Azure routes requests by deployment, so also set the model with the `OpenAiModelTrait` methods
+    /// (e.g. `gpt_4_o()`) to match the deployed model. This is used for tokenization and context size.
+    pub fn deployment<S: Into<String>>(mut self, deployment: S) -> Self {
+        self.config.deployment = deployment.into();
+        self
+    }
+
+    /// Sets the `api-version` query parameter sent with every request.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_version` - An Azure OpenAI data plane API version, e.g. `2024-06-01`.
+    ///
+    /// # Default
+    ///
+    /// [llm_interface::llms::api::azure_openai::AZURE_OPENAI_API_VERSION]
+    pub fn api_version<S: Into<String>>(mut self, api_version: S) -> Self {
+        self.config.api_version = api_version.into();
+        self
+    }
+
+    pub fn init(self) -> crate::Result<LlmClient> {
+        Ok(LlmClient::new(std::sync::Arc::new(
+            LlmBackend::AzureOpenAi(AzureOpenAiBackend::new(self.config, self.model)?),
+        )))
+    }
+}
+
+impl LlmApiConfigTrait for AzureOpenAiBackendBuilder {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.config.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.config.api_config
+    }
+}
+
+impl OpenAiModelTrait for AzureOpenAiBackendBuilder {
+    fn model(&mut self) -> &mut ApiLlmModel {
+        &mut self.model
+    }
+}
+
+impl LoggingConfigTrait for AzureOpenAiBackendBuilder {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.config.logging_config
+    }
+}
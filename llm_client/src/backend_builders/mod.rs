@@ -1,7 +1,10 @@
 pub mod anthropic;
+pub mod azure_openai;
+pub mod generic_api;
 #[cfg(feature = "llama_cpp_backend")]
 pub mod llama_cpp;
 #[cfg(feature = "mistral_rs_backend")]
 pub mod mistral_rs;
+pub mod ollama;
 pub mod openai;
 pub mod perplexity;
@@ -0,0 +1,105 @@
+use crate::LlmClient;
+use llm_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use llm_interface::llms::{
+    api::{
+        config::{ApiConfig, LlmApiConfigTrait},
+        ollama::{OllamaBackend, OllamaConfig, OllamaEndpoint},
+    },
+    LlmBackend,
+};
+use llm_models::api_model::{
+    ollama::{OllamaModelTrait, OLLAMA_DEFAULT_CTX_SIZE, OLLAMA_DEFAULT_MODEL_ID},
+    ApiLlmModel,
+};
+
+// Everything here can be implemented for any struct.
+pub struct OllamaBackendBuilder {
+    pub config: OllamaConfig,
+    pub model: ApiLlmModel,
+    pub pull_if_missing: bool,
+}
+
+impl Default for OllamaBackendBuilder {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            model: ApiLlmModel::ollama_model(OLLAMA_DEFAULT_MODEL_ID, OLLAMA_DEFAULT_CTX_SIZE),
+            pull_if_missing: false,
+        }
+    }
+}
+
+impl OllamaBackendBuilder {
+    /// Sets which of Ollama's native endpoints completions are sent to. See [OllamaEndpoint].
+    ///
+    /// # Default
+    ///
+    /// [OllamaEndpoint::Chat].
+    pub fn endpoint(mut self, endpoint: OllamaEndpoint) -> Self {
+        self.config.endpoint = endpoint;
+        self
+    }
+
+    /// Sets how long Ollama keeps the model loaded after each request.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep_alive` - A duration such as `"10m"` or `"1h"`, `"0"` to unload after each request, or `"-1"` to keep it loaded.
+    ///
+    /// # Default
+    ///
+    /// If not set, the server's default (5 minutes) is used.
+    pub fn keep_alive<S: Into<String>>(mut self, keep_alive: S) -> Self {
+        self.config.keep_alive = Some(keep_alive.into());
+        self
+    }
+
+    /// Adds a model parameter sent in Ollama's `options` with every request, e.g. `num_gpu` or `mirostat`.
+    ///
+    /// # Notes
+    ///
+    /// Parameters set from the request, such as `temperature`, `num_predict`, and `num_ctx`, take precedence.
+    /// Use [OllamaModelTrait::model_ctx_size] to set `num_ctx`.
+    pub fn option<K: Into<String>, V: Into<serde_json::Value>>(mut self, key: K, value: V) -> Self {
+        self.config.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Pulls the model on `init` if it isn't available on the Ollama server.
+    pub fn pull_if_missing(mut self, pull_if_missing: bool) -> Self {
+        self.pull_if_missing = pull_if_missing;
+        self
+    }
+
+    pub async fn init(self) -> crate::Result<LlmClient> {
+        let backend = OllamaBackend::new(self.config, self.model)?;
+        if self.pull_if_missing {
+            backend.pull_model_if_missing().await?;
+        }
+        Ok(LlmClient::new(std::sync::Arc::new(LlmBackend::Ollama(
+            backend,
+        ))))
+    }
+}
+
+impl LlmApiConfigTrait for OllamaBackendBuilder {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.config.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.config.api_config
+    }
+}
+
+impl OllamaModelTrait for OllamaBackendBuilder {
+    fn model(&mut self) -> &mut ApiLlmModel {
+        &mut self.model
+    }
+}
+
+impl LoggingConfigTrait for OllamaBackendBuilder {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.config.logging_config
+    }
+}
@@ -25,6 +25,26 @@ impl Default for OpenAiBackendBuilder {
 }
 
 impl OpenAiBackendBuilder {
+    /// Sends requests to a different base url instead of `https://api.openai.com/v1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - The full base url including the scheme, e.g. `https://my-gateway.example.com/openai/v1`.
+    ///
+    /// # Notes
+    ///
+    /// Useful for enterprise gateways and OpenAI compatible proxies. Request paths such as
+    /// `/chat/completions` are appended to this url. Combine with `header` for gateways that
+    /// need extra headers.
+    ///
+    /// # Default
+    ///
+    /// If not set, requests are sent to the official OpenAI API.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.config.base_url = Some(base_url.into());
+        self
+    }
+
     pub fn init(self) -> crate::Result<LlmClient> {
         Ok(LlmClient::new(std::sync::Arc::new(LlmBackend::OpenAi(
             OpenAiBackend::new(self.config, self.model)?,
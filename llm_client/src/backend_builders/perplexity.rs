@@ -23,7 +23,7 @@ impl Default for PerplexityBackendBuilder {
         config.logging_config.logger_name = "perplexity".to_string();
         Self {
             config,
-            model: ApiLlmModel::sonar_large(),
+            model: ApiLlmModel::sonar(),
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::components::cascade::{render_prompt, render_prompt_messages};
 use llm_interface::{
     llms::LlmBackend,
     requests::{
@@ -7,26 +8,182 @@ use llm_interface::{
     },
 };
 use llm_prompt::LlmPrompt;
+use std::{collections::HashMap, io::Write};
 
 #[derive(Clone)]
 pub struct BasicCompletion {
     pub base_req: CompletionRequest,
+    pub max_words: Option<u32>,
+    pub max_chars: Option<u32>,
+    pub continue_from: Option<String>,
+    pub num_completions: u8,
 }
 
 impl BasicCompletion {
     pub fn new(backend: std::sync::Arc<LlmBackend>) -> Self {
         Self {
             base_req: CompletionRequest::new(backend),
+            max_words: None,
+            max_chars: None,
+            continue_from: None,
+            num_completions: 1,
         }
     }
 
+    /// Creates a completion that sends an already built prompt. See [CompletionRequest::from_prompt].
+    pub fn from_prompt(backend: std::sync::Arc<LlmBackend>, prompt: LlmPrompt) -> Self {
+        let mut basic_completion = Self::new(backend);
+        basic_completion.base_req.prompt = prompt;
+        basic_completion
+    }
+
     pub fn prompt(&mut self) -> &mut LlmPrompt {
         &mut self.base_req.prompt
     }
 
+    /// Returns the prompt [BasicCompletion::run] would send, without making a request.
+    /// Local LLMs get the chat template output, and API LLMs get the messages as JSON.
+    pub fn preview_prompt(&self) -> crate::Result<String> {
+        render_prompt(&self.base_req.prompt)
+    }
+
+    /// Returns the prompt's messages, without making a request.
+    pub fn preview_prompt_messages(&self) -> crate::Result<Vec<HashMap<String, String>>> {
+        render_prompt_messages(&self.base_req.prompt)
+    }
+
+    /// Limits the response to at most `max_words` words.
+    /// The token limit for the request is capped to roughly fit the word count, and the response is truncated at a word boundary if it still exceeds it.
+    /// Check [CompletionResponse::content_truncated] to see if truncation occurred.
+    pub fn max_words(&mut self, max_words: u32) -> &mut Self {
+        self.max_words = Some(max_words);
+        self
+    }
+
+    /// Limits the response to at most `max_chars` characters.
+    /// The token limit for the request is capped to roughly fit the character count, and the response is truncated at a word boundary if it still exceeds it.
+    /// Check [CompletionResponse::content_truncated] to see if truncation occurred.
+    pub fn max_chars(&mut self, max_chars: u32) -> &mut Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    /// Has the LLM continue a partially written assistant turn. The prompt should end with the user message the assistant is responding to.
+    /// The response contains only the continuation, not `assistant_text`. See [CompletionRequest::set_assistant_prefill] for how each backend handles it.
+    pub fn continue_from<T: AsRef<str>>(&mut self, assistant_text: T) -> &mut Self {
+        self.continue_from = Some(assistant_text.as_ref().to_owned());
+        self.base_req.set_assistant_prefill(assistant_text);
+        self
+    }
+
+    /// Sets how many independent completions [BasicCompletion::run_completions] returns. Defaults to 1.
+    ///
+    /// OpenAI and Azure OpenAI generate them in a single request. Other backends emulate it by sending the requests concurrently.
+    /// See [CompletionRequest::request_n].
+    pub fn num_completions(&mut self, num_completions: u8) -> &mut Self {
+        self.num_completions = num_completions.max(1);
+        self
+    }
+
+    #[tracing::instrument(
+        name = "llm_client.basic_completion",
+        skip_all,
+        fields(backend = self.base_req.backend.backend_name(), model = self.base_req.backend.model_id())
+    )]
     pub async fn run(&mut self) -> crate::Result<CompletionResponse> {
-        let mut res = self.base_req.request().await?;
+        let mut responses = self.request_completions(1).await?;
+        self.process_response(responses.remove(0))
+    }
+
+    /// Returns [BasicCompletion::num_completions] independent completions of the prompt, e.g. for self-consistency sampling.
+    #[tracing::instrument(
+        name = "llm_client.basic_completion",
+        skip_all,
+        fields(
+            backend = self.base_req.backend.backend_name(),
+            model = self.base_req.backend.model_id(),
+            num_completions = self.num_completions
+        )
+    )]
+    pub async fn run_completions(&mut self) -> crate::Result<Vec<CompletionResponse>> {
+        let responses = self.request_completions(self.num_completions).await?;
+        responses
+            .into_iter()
+            .map(|res| self.process_response(res))
+            .collect()
+    }
 
+    async fn request_completions(
+        &mut self,
+        num_completions: u8,
+    ) -> crate::Result<Vec<CompletionResponse>> {
+        let requested_response_tokens = self.base_req.config.requested_response_tokens;
+        if let Some(length_limit_tokens) = self.length_limit_tokens() {
+            self.base_req.config.requested_response_tokens = Some(
+                requested_response_tokens
+                    .map_or(length_limit_tokens, |t| t.min(length_limit_tokens)),
+            );
+        }
+        let res = self.base_req.request_n(num_completions).await;
+        self.base_req.config.requested_response_tokens = requested_response_tokens;
+        Ok(res?)
+    }
+
+    /// Streams the response into the file at `path` as it's generated, appending if the file exists.
+    /// Memory use doesn't grow with the length of the response, and the text written so far stays on disk if the run fails.
+    ///
+    /// Backends that can't stream write the whole response once it's finished. See [LlmBackend::supports_streaming].
+    /// When streaming, the request isn't retried, and [BasicCompletion::max_words] and [BasicCompletion::max_chars]
+    /// only cap the token limit, since the text is written before it could be truncated.
+    ///
+    /// # Returns
+    ///
+    /// The number of tokens generated.
+    #[tracing::instrument(
+        name = "llm_client.basic_completion",
+        skip_all,
+        fields(backend = self.base_req.backend.backend_name(), model = self.base_req.backend.model_id())
+    )]
+    pub async fn run_to_file<P: AsRef<std::path::Path>>(&mut self, path: P) -> crate::Result<u64> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())?;
+        if !self.base_req.backend.supports_streaming() {
+            let res = self.run().await?;
+            file.write_all(res.content.as_bytes())?;
+            return Ok(res.token_usage.completion_tokens as u64);
+        }
+
+        let requested_response_tokens = self.base_req.config.requested_response_tokens;
+        if let Some(length_limit_tokens) = self.length_limit_tokens() {
+            self.base_req.config.requested_response_tokens = Some(
+                requested_response_tokens
+                    .map_or(length_limit_tokens, |t| t.min(length_limit_tokens)),
+            );
+        }
+        // Leading whitespace is trimmed like in a non-streamed response, unless it joins a continuation to its text.
+        let mut started = self.continue_from.is_some();
+        let res = self
+            .base_req
+            .request_stream(|content| {
+                let content = if started {
+                    content
+                } else {
+                    content.trim_start()
+                };
+                if !content.is_empty() {
+                    started = true;
+                    file.write_all(content.as_bytes())?;
+                }
+                Ok(())
+            })
+            .await;
+        self.base_req.config.requested_response_tokens = requested_response_tokens;
+        Ok(res?.token_usage.completion_tokens as u64)
+    }
+
+    fn process_response(&self, mut res: CompletionResponse) -> crate::Result<CompletionResponse> {
         match *self.base_req.backend {
             #[cfg(feature = "llama_cpp_backend")]
             LlmBackend::LlamaCpp(_) => {
@@ -38,9 +195,36 @@ impl BasicCompletion {
             }
             _ => (),
         }
+        if let Some(continue_from) = &self.continue_from {
+            res.content = strip_continued_text(&res.content, continue_from);
+        }
+        if let Some(max_words) = self.max_words {
+            if let Some(content) = truncate_to_words(&res.content, max_words as usize) {
+                res.content = content;
+                res.content_truncated = true;
+            }
+        }
+        if let Some(max_chars) = self.max_chars {
+            if let Some(content) = truncate_to_chars(&res.content, max_chars as usize) {
+                res.content = content;
+                res.content_truncated = true;
+            }
+        }
         Ok(res)
     }
 
+    // Conservative token budgets for the length limits. Roughly two tokens per word and two characters per token,
+    // so the response is long enough to reach the limit and is then truncated.
+    fn length_limit_tokens(&self) -> Option<u64> {
+        let word_tokens = self.max_words.map(|words| words as u64 * 2);
+        let char_tokens = self.max_chars.map(|chars| (chars as u64).div_ceil(2));
+        match (word_tokens, char_tokens) {
+            (Some(word_tokens), Some(char_tokens)) => Some(word_tokens.min(char_tokens).max(1)),
+            (Some(tokens), None) | (None, Some(tokens)) => Some(tokens.max(1)),
+            (None, None) => None,
+        }
+    }
+
     fn parse_response(&self, content: &str) -> crate::Result<String> {
         if content.is_empty() {
             return Err(anyhow::format_err!(
@@ -72,7 +256,12 @@ impl BasicCompletion {
             .unwrap_or(content)
             .to_string();
 
-        Ok(content.trim().to_owned())
+        // A continuation may rely on leading whitespace to join the text it continues.
+        if self.continue_from.is_some() {
+            Ok(content.trim_end().to_owned())
+        } else {
+            Ok(content.trim().to_owned())
+        }
     }
 }
 
@@ -82,6 +271,7 @@ impl RequestConfigTrait for BasicCompletion {
     }
 
     fn reset_request(&mut self) {
+        self.continue_from = None;
         self.base_req.reset_completion_request();
     }
 }
@@ -91,3 +281,100 @@ impl LogitBiasTrait for BasicCompletion {
         &mut self.base_req.logit_bias
     }
 }
+
+/// Removes the continued text if the LLM repeated it at the start of the response.
+fn strip_continued_text(content: &str, continue_from: &str) -> String {
+    let continue_from = continue_from.trim();
+    match content.trim_start().strip_prefix(continue_from) {
+        Some(continuation) if !continue_from.is_empty() => continuation.to_owned(),
+        _ => content.to_owned(),
+    }
+}
+
+/// Returns the content cut after the `max_words` word, or None if it's already short enough.
+fn truncate_to_words(content: &str, max_words: usize) -> Option<String> {
+    let mut words = content
+        .split_whitespace()
+        .map(|word| word.as_ptr() as usize - content.as_ptr() as usize + word.len());
+    if max_words == 0 {
+        return words.next().map(|_| String::new());
+    }
+    let end = words.nth(max_words - 1)?;
+    words.next()?;
+    Some(content[..end].to_owned())
+}
+
+/// Returns the content cut at the last word boundary within `max_chars` characters, or None if it's already short enough.
+/// A single word longer than `max_chars` is cut mid-word.
+fn truncate_to_chars(content: &str, max_chars: usize) -> Option<String> {
+    let (cut, next_char) = match content.char_indices().nth(max_chars) {
+        Some((cut, next_char)) => (cut, next_char),
+        None => return None,
+    };
+    let truncated = &content[..cut];
+    if next_char.is_whitespace() {
+        return Some(truncated.trim_end().to_owned());
+    }
+    match truncated.rfind(char::is_whitespace) {
+        Some(boundary) if !truncated[..boundary].trim_end().is_empty() => {
+            Some(truncated[..boundary].trim_end().to_owned())
+        }
+        _ => Some(truncated.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_continued_text() {
+        assert_eq!(
+            strip_continued_text(" jumps over the dog.", "The quick brown fox"),
+            " jumps over the dog."
+        );
+        assert_eq!(
+            strip_continued_text(
+                "The quick brown fox jumps over the dog.",
+                "The quick brown fox "
+            ),
+            " jumps over the dog."
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_words() {
+        let content = "The quick brown  fox jumps.";
+        assert_eq!(truncate_to_words(content, 5), None);
+        assert_eq!(truncate_to_words(content, 10), None);
+        assert_eq!(
+            truncate_to_words(content, 4),
+            Some("The quick brown  fox".to_owned())
+        );
+        assert_eq!(truncate_to_words(content, 1), Some("The".to_owned()));
+        assert_eq!(truncate_to_words(content, 0), Some("".to_owned()));
+    }
+
+    #[test]
+    fn test_truncate_to_chars() {
+        let content = "The quick brown fox jumps.";
+        assert_eq!(truncate_to_chars(content, 26), None);
+        assert_eq!(truncate_to_chars(content, 100), None);
+        assert_eq!(
+            truncate_to_chars(content, 19),
+            Some("The quick brown fox".to_owned())
+        );
+        assert_eq!(
+            truncate_to_chars(content, 18),
+            Some("The quick brown".to_owned())
+        );
+        assert_eq!(
+            truncate_to_chars(content, 16),
+            Some("The quick brown".to_owned())
+        );
+        assert_eq!(
+            truncate_to_chars("Supercalifragilistic", 5),
+            Some("Super".to_owned())
+        );
+    }
+}
@@ -7,9 +7,14 @@ use llm_interface::requests::{
     completion::{CompletionFinishReason, CompletionRequest},
     stop_sequence::StoppingSequence,
 };
+use llm_prompt::LlmPrompt;
 pub use round::CascadeRound;
+use std::collections::HashMap;
 use step::InferenceStep;
 
+/// Stands in for each inference step's output in [CascadeFlow::preview_prompt].
+pub const PREVIEW_LLM_OUTPUT: &str = "<LLM output>";
+
 #[derive(Clone)]
 pub struct CascadeFlow {
     pub cascade_name: String,
@@ -74,6 +79,60 @@ impl CascadeFlow {
         Ok(())
     }
 
+    /// Builds the prompt of the flow's final request without making any requests.
+    /// Each inference step's output is shown as [PREVIEW_LLM_OUTPUT], and the final step's prefix is set as the generation prefix.
+    pub fn preview_prompt(&self, base_req: &CompletionRequest) -> Result<LlmPrompt> {
+        self.build_final_prompt(base_req.prompt.clone(), |round, is_last| {
+            round.preview_outcome(!is_last)
+        })
+    }
+
+    /// Rebuilds the prompt the flow's final request was sent with, from the outcomes of a flow that has run.
+    /// `base_prompt` is the prompt the flow was run with, before any rounds were added to it.
+    pub fn final_request_prompt(&self, base_prompt: &LlmPrompt) -> Result<LlmPrompt> {
+        self.build_final_prompt(base_prompt.clone(), |round, is_last| {
+            if is_last {
+                Ok(round.final_step_generation_prefix()?.unwrap_or_default())
+            } else {
+                round.display_outcome()
+            }
+        })
+    }
+
+    fn build_final_prompt<F>(&self, prompt: LlmPrompt, round_outcome: F) -> Result<LlmPrompt>
+    where
+        F: Fn(&CascadeRound, bool) -> Result<String>,
+    {
+        for (i, round) in self.rounds.iter().enumerate() {
+            prompt.add_user_message()?.set_content(&round.task);
+            if i + 1 < self.rounds.len() {
+                prompt
+                    .add_assistant_message()?
+                    .set_content(round_outcome(round, false)?);
+            } else {
+                let generation_prefix = round_outcome(round, true)?;
+                if generation_prefix.is_empty() {
+                    prompt.clear_generation_prefix();
+                } else {
+                    prompt.set_generation_prefix(generation_prefix);
+                }
+            }
+        }
+        Ok(prompt)
+    }
+
+    /// The thinking blocks of every inference step, separated by blank lines. `None` if there were none.
+    /// See [llm_interface::requests::req_components::RequestConfig::thinking_tags].
+    pub fn thinking(&self) -> Option<String> {
+        let thinking: Vec<&str> = self
+            .rounds
+            .iter()
+            .flat_map(|round| round.resolved_steps.iter())
+            .filter_map(|step| step.thinking())
+            .collect();
+        (!thinking.is_empty()).then(|| thinking.join("\n\n"))
+    }
+
     pub fn primitive_result(&self) -> Option<String> {
         match self.rounds.last() {
             Some(round) => round.primitive_result(),
@@ -87,6 +146,7 @@ pub(crate) async fn cascade_request(
     step: &mut InferenceStep,
 ) -> Result<()> {
     let res = base_req.request().await?;
+    step.thinking = res.thinking;
     if matches!(
         res.finish_reason,
         CompletionFinishReason::MatchingStoppingSequence(StoppingSequence::NoResult(_))
@@ -106,6 +166,21 @@ pub(crate) async fn cascade_request(
     Ok(())
 }
 
+/// Renders a prompt as it would be sent: the chat template output for local LLMs, or the messages as JSON for APIs.
+pub(crate) fn render_prompt(prompt: &LlmPrompt) -> Result<String> {
+    if prompt.local_prompt.is_some() {
+        Ok(prompt.local_prompt()?.get_built_prompt()?)
+    } else {
+        Ok(serde_json::to_string_pretty(
+            &prompt.get_built_prompt_messages()?,
+        )?)
+    }
+}
+
+pub(crate) fn render_prompt_messages(prompt: &LlmPrompt) -> Result<Vec<HashMap<String, String>>> {
+    prompt.get_built_prompt_messages()
+}
+
 impl std::fmt::Display for CascadeFlow {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f)?;
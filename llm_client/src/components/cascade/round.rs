@@ -94,6 +94,57 @@ impl CascadeRound {
         Ok(round_outcome)
     }
 
+    /// The outcome of the steps before the final one, e.g. the reasoning that led to a workflow's answer.
+    pub fn outcome_before_final_step(&self) -> crate::Result<String> {
+        let mut round = self.clone();
+        round.drop_last_step()?;
+        round.display_outcome()
+    }
+
+    /// The generation prefix the round's final step was requested with, once the round has run.
+    pub(super) fn final_step_generation_prefix(&self) -> crate::Result<Option<String>> {
+        let mut round = self.clone();
+        match round.resolved_steps.pop_back() {
+            Some(final_step) => round.generation_prefix(&final_step),
+            None => crate::bail!("No steps in round"),
+        }
+    }
+
+    /// The round's outcome with each inference step's output replaced by [super::PREVIEW_LLM_OUTPUT].
+    /// If `include_final_output` is false, the final step contributes only its prefix.
+    pub(super) fn preview_outcome(&self, include_final_output: bool) -> crate::Result<String> {
+        let step_count = self.resolved_steps.len() + self.unresolved_steps.len();
+        let mut outcome = String::new();
+        for (i, step) in self
+            .resolved_steps
+            .iter()
+            .chain(self.unresolved_steps.iter())
+            .enumerate()
+        {
+            let step_outcome = match step {
+                CascadeStep::Guidance(_) => step.display_step_outcome()?,
+                CascadeStep::Inference(_) => {
+                    let step_prefix = step.display_step_prefix().unwrap_or_default();
+                    if i + 1 == step_count && !include_final_output {
+                        step_prefix
+                    } else {
+                        format!("{step_prefix}{}", super::PREVIEW_LLM_OUTPUT)
+                    }
+                }
+            };
+            if step_outcome.is_empty() {
+                continue;
+            }
+            if !outcome.is_empty() {
+                if let Some(step_separator) = self.step_separator {
+                    outcome.push(step_separator);
+                }
+            }
+            outcome.push_str(&step_outcome);
+        }
+        Ok(outcome)
+    }
+
     pub async fn run_all_steps(&mut self, base_req: &mut CompletionRequest) -> crate::Result<()> {
         base_req.prompt.add_user_message()?.set_content(&self.task);
         while !self.unresolved_steps.is_empty() {
@@ -12,6 +12,7 @@ impl CascadeStep {
     pub fn new_inference_step(step_config: StepConfig, step_counter: usize) -> Self {
         CascadeStep::Inference(InferenceStep {
             llm_content: None,
+            thinking: None,
             dynamic_suffix: None,
             outcome: std::cell::RefCell::new(None),
             step_config,
@@ -61,7 +62,8 @@ impl CascadeStep {
             .backend
             .set_cache(&base_req.prompt)
             .await
-            .map_err(|e| crate::anyhow!("Failed to set cache up to step: {}", e))?;
+            // Kept as the source so callers can still match on the CompletionError, e.g. a refusal.
+            .map_err(|e| crate::Error::from(e).context("Failed to set cache up to step"))?;
         Ok(())
     }
 
@@ -85,11 +87,20 @@ impl CascadeStep {
             Self::Guidance(_) => panic!("GuidanceStep does not have primitive_result."),
         }
     }
+
+    pub fn thinking(&self) -> Option<&str> {
+        match self {
+            Self::Inference(step) => step.thinking.as_deref(),
+            Self::Guidance(_) => None,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct InferenceStep {
     pub llm_content: Option<String>, // raw, unformatted result from llm.
+    /// The thinking block of a reasoning model, split off the response with [llm_interface::requests::req_components::RequestConfig::thinking_tags].
+    pub thinking: Option<String>,
     pub dynamic_suffix: Option<String>, // suffix to be added to the result.
     pub outcome: std::cell::RefCell<Option<String>>,
     pub step_config: StepConfig,
@@ -1,12 +1,11 @@
-use super::{Grammar, GrammarError, GrammarSetterTrait};
-use std::{cell::RefCell, str::FromStr};
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
+use std::str::FromStr;
 use url::Url;
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Debug)]
 pub struct BasicUrlGrammar {
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl BasicUrlGrammar {
@@ -14,7 +13,6 @@ impl BasicUrlGrammar {
         Self {
             stop_word_done: None,
             stop_word_no_result: None,
-            grammar_string: RefCell::new(None),
         }
     }
 }
@@ -25,11 +23,9 @@ impl BasicUrlGrammar {
     }
 
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(url_grammar(&self.stop_word_done, &self.stop_word_no_result));
-        }
-        grammar_string.as_ref().unwrap().clone()
+        cached_grammar_string(self, || {
+            url_grammar(&self.stop_word_done, &self.stop_word_no_result)
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
@@ -1,11 +1,9 @@
-use super::{Grammar, GrammarError, GrammarSetterTrait};
-use std::cell::RefCell;
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Debug)]
 pub struct BooleanGrammar {
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl BooleanGrammar {
@@ -14,14 +12,9 @@ impl BooleanGrammar {
     }
 
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(boolean_grammar(
-                &self.stop_word_done,
-                &self.stop_word_no_result,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+        cached_grammar_string(self, || {
+            boolean_grammar(&self.stop_word_done, &self.stop_word_no_result)
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
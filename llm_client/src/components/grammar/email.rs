@@ -0,0 +1,144 @@
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct EmailGrammar {
+    pub stop_word_done: Option<String>,
+    pub stop_word_no_result: Option<String>,
+}
+
+impl EmailGrammar {
+    pub fn new() -> Self {
+        Self {
+            stop_word_done: None,
+            stop_word_no_result: None,
+        }
+    }
+}
+
+impl EmailGrammar {
+    pub fn wrap(self) -> Grammar {
+        Grammar::Email(self)
+    }
+
+    pub fn grammar_string(&self) -> String {
+        cached_grammar_string(self, || {
+            email_grammar(&self.stop_word_done, &self.stop_word_no_result)
+        })
+    }
+
+    pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
+        email_validate_clean(content)
+    }
+
+    pub fn grammar_parse(&self, content: &str) -> Result<String, GrammarError> {
+        email_parse(content)
+    }
+}
+
+impl GrammarSetterTrait for EmailGrammar {
+    fn stop_word_done_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_done
+    }
+
+    fn stop_word_no_result_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_no_result
+    }
+}
+
+pub fn email_grammar<T: AsRef<str>>(
+    stop_word_done: &Option<T>,
+    stop_word_no_result: &Option<T>,
+) -> String {
+    match (stop_word_done, stop_word_no_result) {
+        (Some(stop_word_done), Some(stop_word_no_result)) => format!(
+            "root ::= \" \" ( email | \"{}\" ) \" {}\"\n{EMAIL_GRAMMAR}",
+            stop_word_no_result.as_ref(),
+            stop_word_done.as_ref()
+        ),
+        (None, Some(stop_word_no_result)) => {
+            format!(
+                "root ::= \" \" ( email | \"{}\" )\n{EMAIL_GRAMMAR}",
+                stop_word_no_result.as_ref()
+            )
+        }
+        (Some(stop_word_done), None) => {
+            format!(
+                "root ::= \" \" email \" {}\"\n{EMAIL_GRAMMAR}",
+                stop_word_done.as_ref()
+            )
+        }
+        (None, None) => format!("root ::= \" \" email\n{EMAIL_GRAMMAR}"),
+    }
+}
+
+pub const EMAIL_GRAMMAR: &str = r##"email ::= local-part "@" domain
+local-part ::= atom ("." atom){0,5}
+atom ::= [a-zA-Z0-9!#$%&'*+/=?^_`{|}~-]{1,64}
+domain ::= (label "."){1,4} tld
+label ::= [a-zA-Z0-9] [a-zA-Z0-9-]{0,62}
+tld ::= [a-zA-Z]{2,63}
+"##;
+
+pub fn email_validate_clean(content: &str) -> Result<String, GrammarError> {
+    email_parse(content)
+}
+
+/// Loosely validates the address against the same shape the grammar produces, and lowercases the domain.
+pub fn email_parse(content: &str) -> Result<String, GrammarError> {
+    let content = content.trim();
+    let parse_error = || GrammarError::ParseValueError {
+        content: content.to_string(),
+        parse_type: "email".to_string(),
+    };
+    let (local_part, domain) = content.split_once('@').ok_or_else(parse_error)?;
+
+    let atom_is_valid = |atom: &str| {
+        !atom.is_empty()
+            && atom
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~-".contains(c))
+    };
+    if local_part.len() > 64 || !local_part.split('.').all(atom_is_valid) {
+        return Err(parse_error());
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let label_is_valid = |label: &&str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    let tld_is_valid = labels
+        .last()
+        .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+    if labels.len() < 2 || !tld_is_valid || !labels.iter().all(label_is_valid) {
+        return Err(parse_error());
+    }
+
+    Ok(format!("{local_part}@{}", domain.to_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut grammar = Grammar::email();
+        let grammar_string = grammar.set_stop_word_done("stop").grammar_string();
+        assert!(grammar_string.starts_with("root ::= \" \" email \" stop\"\n"));
+
+        assert_eq!(
+            grammar
+                .grammar_parse(" Jane.Doe+news@Mail.Example.com ")
+                .unwrap(),
+            "Jane.Doe+news@mail.example.com"
+        );
+        assert!(grammar.grammar_parse("jane.doe").is_err());
+        assert!(grammar.grammar_parse("jane@localhost").is_err());
+        assert!(grammar.grammar_parse("jane..doe@example.com").is_err());
+        assert!(grammar.grammar_parse("jane@-example.com").is_err());
+        assert!(grammar.grammar_parse("jane@doe@example.com").is_err());
+    }
+}
@@ -1,12 +1,10 @@
-use super::{Grammar, GrammarError, GrammarSetterTrait};
-use std::cell::RefCell;
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Debug)]
 pub struct ExactStringGrammar {
     pub exact_strings: Vec<String>,
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl ExactStringGrammar {
@@ -37,15 +35,13 @@ impl ExactStringGrammar {
         if self.exact_strings.is_empty() {
             panic!("ExactStringGrammar must have at least one exact string");
         }
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(exact_string_grammar(
+        cached_grammar_string(self, || {
+            exact_string_grammar(
                 &self.exact_strings,
                 &self.stop_word_done,
                 &self.stop_word_no_result,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+            )
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
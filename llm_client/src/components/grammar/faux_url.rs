@@ -1,7 +1,6 @@
-use super::{Grammar, GrammarError, GrammarSetterTrait};
-use std::cell::RefCell;
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct FauxUrlGrammar {
     pub min_count: u8,
     pub max_count: u8,
@@ -9,7 +8,6 @@ pub struct FauxUrlGrammar {
     pub base_url: String,
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl Default for FauxUrlGrammar {
@@ -21,7 +19,6 @@ impl Default for FauxUrlGrammar {
             base_url: "https://example.com/".to_string(),
             stop_word_done: None,
             stop_word_no_result: None,
-            grammar_string: RefCell::new(None),
         }
     }
 }
@@ -52,18 +49,16 @@ impl FauxUrlGrammar {
     }
 
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(faux_url_grammar(
+        cached_grammar_string(self, || {
+            faux_url_grammar(
                 self.min_count,
                 self.max_count,
                 self.word_char_length,
                 &self.base_url,
                 &self.stop_word_done,
                 &self.stop_word_no_result,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+            )
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
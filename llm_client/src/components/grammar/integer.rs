@@ -1,13 +1,28 @@
-use super::{Grammar, GrammarError, GrammarSetterTrait};
-use std::cell::RefCell;
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct IntegerGrammar {
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
     pub lower_bound: u32,
     pub upper_bound: u32,
-    grammar_string: RefCell<Option<String>>,
+    /// The base of the integer: 2, 10, or 16. Defaults to 10.
+    pub radix: u32,
+    /// Whether [IntegerGrammar::grammar_parse] rejects values outside the bounds. Defaults to false.
+    pub enforce_bounds: bool,
+}
+
+impl Default for IntegerGrammar {
+    fn default() -> Self {
+        Self {
+            stop_word_done: None,
+            stop_word_no_result: None,
+            lower_bound: 0,
+            upper_bound: 0,
+            radix: 10,
+            enforce_bounds: false,
+        }
+    }
 }
 
 impl IntegerGrammar {
@@ -17,7 +32,8 @@ impl IntegerGrammar {
             stop_word_no_result: None,
             lower_bound: 1,
             upper_bound: 9,
-            grammar_string: RefCell::new(None),
+            radix: 10,
+            enforce_bounds: false,
         }
     }
 
@@ -37,25 +53,51 @@ impl IntegerGrammar {
         self
     }
 
+    /// Sets the base of the integer: 2, 10, or 16. Binary values may be prefixed with `0b` and hexadecimal values with `0x`.
+    pub fn radix(mut self, radix: u32) -> Result<Self, GrammarError> {
+        if !is_valid_radix(radix) {
+            return Err(GrammarError::UnsupportedRadix(radix));
+        }
+        self.radix = radix;
+
+        Ok(self)
+    }
+
+    /// Rejects parsed values outside the bounds. The grammar only limits the number of digits, so without this a value
+    /// with the right number of digits but out of range, e.g. 12 for bounds 0-10, is accepted.
+    pub fn enforce_bounds(mut self, enforce_bounds: bool) -> Self {
+        self.enforce_bounds = enforce_bounds;
+
+        self
+    }
+
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(integer_grammar(
+        cached_grammar_string(self, || {
+            integer_grammar_radix(
                 self.lower_bound,
                 self.upper_bound,
+                self.radix,
                 &self.stop_word_done,
                 &self.stop_word_no_result,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+            )
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
-        integer_validate_clean(content)
+        let content: &str = content.trim();
+        self.grammar_parse(content)?;
+        Ok(content.to_string())
     }
 
     pub fn grammar_parse(&self, content: &str) -> Result<u32, GrammarError> {
-        integer_parse(content)
+        let value = integer_parse_radix(content, self.radix)?;
+        if self.enforce_bounds && (value < self.lower_bound || value > self.upper_bound) {
+            return Err(GrammarError::ParseValueError {
+                content: content.to_string(),
+                parse_type: format!("u32 between {}-{}", self.lower_bound, self.upper_bound),
+            });
+        }
+        Ok(value)
     }
 }
 
@@ -75,6 +117,23 @@ pub fn integer_grammar<T: AsRef<str>>(
     stop_word_done: &Option<T>,
     stop_word_no_result: &Option<T>,
 ) -> String {
+    integer_grammar_radix(
+        lower_bound,
+        upper_bound,
+        10,
+        stop_word_done,
+        stop_word_no_result,
+    )
+}
+
+pub fn integer_grammar_radix<T: AsRef<str>>(
+    lower_bound: u32,
+    upper_bound: u32,
+    radix: u32,
+    stop_word_done: &Option<T>,
+    stop_word_no_result: &Option<T>,
+) -> String {
+    assert_valid_radix(radix);
     match upper_bound.cmp(&lower_bound) {
         std::cmp::Ordering::Less => {
             panic!("Upper bound must be greater than or equal to lower bound.")
@@ -83,7 +142,14 @@ pub fn integer_grammar<T: AsRef<str>>(
         _ => (),
     }
     // let mut base = "root ::= \" \" ".to_string();
-    let range = create_range(lower_bound, upper_bound, stop_word_done);
+    let range = match radix_prefix(radix) {
+        Some(prefix) => format!(
+            "(\"{prefix}\" | \"{}\")? {}",
+            prefix.to_uppercase(),
+            create_range(lower_bound, upper_bound, radix, stop_word_done)
+        ),
+        None => create_range(lower_bound, upper_bound, radix, stop_word_done),
+    };
     match (stop_word_done, stop_word_no_result) {
         (Some(stop_word_done), Some(stop_word_no_result)) => format!(
             "root ::= \" \" ( {range} | \"{}\" ) \" {}\"",
@@ -106,30 +172,74 @@ pub fn integer_grammar<T: AsRef<str>>(
 fn create_range<T: AsRef<str>>(
     lower_bound: u32,
     upper_bound: u32,
+    radix: u32,
     stop_word_done: &Option<T>,
 ) -> String {
-    let digits = (upper_bound as f64).log10().floor() as u32 + 1;
+    let mut digits = 1;
+    while (upper_bound as u64) >= (radix as u64).pow(digits) {
+        digits += 1;
+    }
     let mut range = String::new();
     if digits == 1 {
-        range.push_str(&format!("[{}-{}]", lower_bound, upper_bound));
+        if radix == 10 {
+            range.push_str(&format!("[{}-{}]", lower_bound, upper_bound));
+        } else {
+            let chars: String = (lower_bound..=upper_bound)
+                .filter_map(|digit| char::from_digit(digit, radix))
+                .flat_map(|c| [Some(c), c.is_alphabetic().then(|| c.to_ascii_uppercase())])
+                .flatten()
+                .collect();
+            range.push_str(&format!("[{chars}]"));
+        }
         return range;
     }
 
     // Need to add the actual math here to restrict the range.
+    let digit_class = digit_class(radix);
     for i in 1..=digits {
-        if i > 1 && 10_u32.pow(i - 1) > lower_bound {
+        if i > 1 && (radix as u64).pow(i - 1) > lower_bound as u64 {
             if let Some(stop_word_done) = stop_word_done {
-                range.push_str(&format!("([0-9] | \" {}\")", stop_word_done.as_ref()));
+                range.push_str(&format!(
+                    "({digit_class} | \" {}\")",
+                    stop_word_done.as_ref()
+                ));
             } else {
-                range.push_str("[0-9]?");
+                range.push_str(&format!("{digit_class}?"));
             }
         } else {
-            range.push_str("[0-9]");
+            range.push_str(digit_class);
         }
     }
     range
 }
 
+fn is_valid_radix(radix: u32) -> bool {
+    matches!(radix, 2 | 10 | 16)
+}
+
+fn assert_valid_radix(radix: u32) {
+    assert!(
+        is_valid_radix(radix),
+        "Radix must be 2, 10, or 16, got {radix}."
+    );
+}
+
+fn digit_class(radix: u32) -> &'static str {
+    match radix {
+        2 => "[01]",
+        16 => "[0-9a-fA-F]",
+        _ => "[0-9]",
+    }
+}
+
+fn radix_prefix(radix: u32) -> Option<&'static str> {
+    match radix {
+        2 => Some("0b"),
+        16 => Some("0x"),
+        _ => None,
+    }
+}
+
 pub fn integer_validate_clean(content: &str) -> Result<String, GrammarError> {
     let content: &str = content.trim();
     if integer_parse(content).is_ok() {
@@ -143,13 +253,38 @@ pub fn integer_validate_clean(content: &str) -> Result<String, GrammarError> {
 }
 
 pub fn integer_parse(content: &str) -> Result<u32, GrammarError> {
-    content
-        .trim()
-        .parse::<u32>()
-        .map_err(|_| GrammarError::ParseValueError {
-            content: content.to_string(),
-            parse_type: "u32".to_string(),
+    integer_parse_radix(content, 10)
+}
+
+/// Parses an integer in the given base, accepting an optional `0b` or `0x` prefix for binary or hexadecimal.
+pub fn integer_parse_radix(content: &str, radix: u32) -> Result<u32, GrammarError> {
+    if !is_valid_radix(radix) {
+        return Err(GrammarError::UnsupportedRadix(radix));
+    }
+    let trimmed = content.trim();
+    let digits = radix_prefix(radix)
+        .and_then(|prefix| {
+            trimmed
+                .strip_prefix(prefix)
+                .or_else(|| trimmed.strip_prefix(&prefix.to_uppercase()))
         })
+        .unwrap_or(trimmed);
+    // from_str_radix accepts a leading sign, which the grammar doesn't.
+    if digits.starts_with(['+', '-']) {
+        return Err(parse_value_error(content, radix));
+    }
+    u32::from_str_radix(digits, radix).map_err(|_| parse_value_error(content, radix))
+}
+
+fn parse_value_error(content: &str, radix: u32) -> GrammarError {
+    GrammarError::ParseValueError {
+        content: content.to_string(),
+        parse_type: if radix == 10 {
+            "u32".to_string()
+        } else {
+            format!("base {radix} u32")
+        },
+    }
 }
 
 #[cfg(test)]
@@ -194,5 +329,62 @@ mod tests {
             "root ::= \" \" ( [0-9]([0-9] | \" stop\")([0-9] | \" stop\")([0-9] | \" stop\")([0-9] | \" stop\") | \"unknown\" ) \" stop\""
         );
         assert_eq!(5555, grammar.grammar_parse(" 5555 ").unwrap());
+        // The bounds are only checked when enforced.
+        assert_eq!(10001, grammar.grammar_parse(" 10001 ").unwrap());
+        let grammar = grammar.enforce_bounds(true);
+        assert_eq!(10000, grammar.grammar_parse(" 10000 ").unwrap());
+        assert!(grammar.grammar_parse(" 10001 ").is_err());
+    }
+
+    #[test]
+    fn test_radix() {
+        let mut grammar = Grammar::integer()
+            .lower_bound(0)
+            .upper_bound(255)
+            .radix(16)
+            .unwrap()
+            .enforce_bounds(true);
+        let grammar_string = grammar.set_stop_word_done("stop").grammar_string();
+
+        assert_eq!(
+            grammar_string,
+            "root ::= \" \" (\"0x\" | \"0X\")? [0-9a-fA-F]([0-9a-fA-F] | \" stop\") \" stop\""
+        );
+        assert_eq!(255, grammar.grammar_parse(" 0xff ").unwrap());
+        assert_eq!(171, grammar.grammar_parse("AB").unwrap());
+        assert!(grammar.grammar_parse("0xfg").is_err());
+        assert!(grammar.grammar_parse("0x100").is_err());
+
+        let grammar = Grammar::integer()
+            .lower_bound(2)
+            .upper_bound(12)
+            .radix(16)
+            .unwrap();
+        assert_eq!(
+            grammar.grammar_string(),
+            "root ::= \" \" (\"0x\" | \"0X\")? [23456789aAbBcC]"
+        );
+
+        let grammar = Grammar::integer()
+            .lower_bound(0)
+            .upper_bound(7)
+            .radix(2)
+            .unwrap();
+        assert_eq!(
+            grammar.grammar_string(),
+            "root ::= \" \" (\"0b\" | \"0B\")? [01][01]?[01]?"
+        );
+        assert_eq!(5, grammar.grammar_parse("0b101").unwrap());
+        assert!(grammar.grammar_parse("102").is_err());
+        assert!(grammar.grammar_parse("-1").is_err());
+
+        assert_eq!(
+            Grammar::integer().radix(8),
+            Err(GrammarError::UnsupportedRadix(8))
+        );
+        assert_eq!(
+            integer_parse_radix("7", 40),
+            Err(GrammarError::UnsupportedRadix(40))
+        );
     }
 }
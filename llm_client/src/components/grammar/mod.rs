@@ -2,23 +2,31 @@ use thiserror::Error;
 pub mod basic_url;
 pub mod boolean;
 pub mod custom;
+pub mod email;
 pub mod exact_string;
 pub mod faux_url;
 pub mod integer;
 pub mod none;
+pub mod percentage;
+pub mod ranking;
 pub mod text;
+pub mod uuid;
 
 pub use basic_url::BasicUrlGrammar;
 pub use boolean::BooleanGrammar;
 pub use custom::CustomGrammar;
+pub use email::EmailGrammar;
 pub use exact_string::ExactStringGrammar;
 pub use faux_url::FauxUrlGrammar;
 pub use integer::IntegerGrammar;
 pub use none::NoneGrammar;
+pub use percentage::PercentageGrammar;
+pub use ranking::RankingGrammar;
 pub use text::sentences::SentencesGrammar;
 pub use text::text::TextGrammar;
 pub use text::text_list::TextListGrammar;
 pub use text::words::WordsGrammar;
+pub use uuid::UuidGrammar;
 
 #[derive(Clone, PartialEq)]
 pub enum Grammar {
@@ -31,6 +39,10 @@ pub enum Grammar {
     BasicUrl(BasicUrlGrammar),
     ExactString(ExactStringGrammar),
     FauxUrl(FauxUrlGrammar),
+    Uuid(UuidGrammar),
+    Email(EmailGrammar),
+    Percentage(PercentageGrammar),
+    Ranking(RankingGrammar),
     NoneGrammar(NoneGrammar),
     Custom(CustomGrammar),
 }
@@ -102,6 +114,10 @@ grammar_default! {
         BasicUrl => basic_url: BasicUrlGrammar,
         ExactString => exact_string: ExactStringGrammar,
         FauxUrl => faux_url: FauxUrlGrammar,
+        Uuid => uuid: UuidGrammar,
+        Email => email: EmailGrammar,
+        Percentage => percentage: PercentageGrammar,
+        Ranking => ranking: RankingGrammar,
         NoneGrammar => none: NoneGrammar,
         Custom => custom: CustomGrammar,
     }
@@ -139,6 +155,33 @@ pub trait GrammarSetterTrait {
     }
 }
 
+const GRAMMAR_CACHE_CAPACITY: usize = 256;
+
+/// Generated grammar strings keyed by the `Debug` output of the grammar that built them.
+/// Primitives build a new grammar for every request, so this is what lets repeated runs with the same config skip generation.
+static GRAMMAR_CACHE: std::sync::LazyLock<
+    std::sync::Mutex<std::collections::HashMap<String, String>>,
+> = std::sync::LazyLock::new(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+
+/// Returns the cached grammar string for `grammar`'s current config, or builds and caches it.
+/// Any change to the config, including the stop words, changes the key, so a stale grammar is never returned.
+pub(crate) fn cached_grammar_string<G: std::fmt::Debug>(
+    grammar: &G,
+    build: impl FnOnce() -> String,
+) -> String {
+    let key = format!("{grammar:?}");
+    if let Some(grammar_string) = GRAMMAR_CACHE.lock().unwrap().get(&key) {
+        return grammar_string.clone();
+    }
+    let grammar_string = build();
+    let mut cache = GRAMMAR_CACHE.lock().unwrap();
+    if cache.len() >= GRAMMAR_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(key, grammar_string.clone());
+    grammar_string
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum GrammarError {
     #[error("grammar not set")]
@@ -150,6 +193,8 @@ pub enum GrammarError {
     },
     #[error("failed to parse response_content ({content}) as type ({parse_type})")]
     ParseValueError { content: String, parse_type: String },
+    #[error("radix must be 2, 10, or 16, got {0}")]
+    UnsupportedRadix(u32),
     #[error("incorrect destructuring function ({function}) for grammar type ({grammar_type})")]
     DestructuringIncorrect {
         function: String,
@@ -178,4 +223,16 @@ mod tests {
         let res: bool = grammar.grammar_parse("true").unwrap();
         assert!(res);
     }
+
+    #[test]
+    fn test_cached_grammar_string() {
+        let mut grammar = Grammar::integer().lower_bound(0).upper_bound(100).wrap();
+        let first = grammar.grammar_string();
+        assert_eq!(first, grammar.clone().grammar_string());
+        assert!(!first.contains("done"));
+
+        let changed = grammar.set_stop_word_done("done").grammar_string();
+        assert_ne!(first, changed);
+        assert!(changed.contains("done"));
+    }
 }
@@ -0,0 +1,168 @@
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PercentageGrammar {
+    pub stop_word_done: Option<String>,
+    pub stop_word_no_result: Option<String>,
+    /// Whether values outside `0.0..=1.0` are clamped into range. If `false`, they fail to parse. Defaults to `true`.
+    pub clamp: bool,
+}
+
+impl Default for PercentageGrammar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PercentageGrammar {
+    pub fn new() -> Self {
+        Self {
+            stop_word_done: None,
+            stop_word_no_result: None,
+            clamp: true,
+        }
+    }
+
+    pub fn wrap(self) -> Grammar {
+        Grammar::Percentage(self)
+    }
+
+    pub fn clamp(mut self, clamp: bool) -> Self {
+        self.clamp = clamp;
+
+        self
+    }
+
+    pub fn grammar_string(&self) -> String {
+        cached_grammar_string(self, || {
+            percentage_grammar(&self.stop_word_done, &self.stop_word_no_result)
+        })
+    }
+
+    pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
+        let content: &str = content.trim();
+        self.grammar_parse(content)?;
+        Ok(content.to_string())
+    }
+
+    pub fn grammar_parse(&self, content: &str) -> Result<f32, GrammarError> {
+        percentage_parse(content, self.clamp)
+    }
+}
+
+impl GrammarSetterTrait for PercentageGrammar {
+    fn stop_word_done_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_done
+    }
+
+    fn stop_word_no_result_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_no_result
+    }
+}
+
+pub fn percentage_grammar<T: AsRef<str>>(
+    stop_word_done: &Option<T>,
+    stop_word_no_result: &Option<T>,
+) -> String {
+    match (stop_word_done, stop_word_no_result) {
+        (Some(stop_word_done), Some(stop_word_no_result)) => format!(
+            "root ::= \" \" ( percentage | \"{}\" ) \" {}\"\n{PERCENTAGE_GRAMMAR}",
+            stop_word_no_result.as_ref(),
+            stop_word_done.as_ref()
+        ),
+        (None, Some(stop_word_no_result)) => {
+            format!(
+                "root ::= \" \" ( percentage | \"{}\" )\n{PERCENTAGE_GRAMMAR}",
+                stop_word_no_result.as_ref()
+            )
+        }
+        (Some(stop_word_done), None) => {
+            format!(
+                "root ::= \" \" percentage \" {}\"\n{PERCENTAGE_GRAMMAR}",
+                stop_word_done.as_ref()
+            )
+        }
+        (None, None) => format!("root ::= \" \" percentage\n{PERCENTAGE_GRAMMAR}"),
+    }
+}
+
+pub const PERCENTAGE_GRAMMAR: &str = r##"percentage ::= [0-9]{1,3} ("." [0-9]{1,2})? "%"?
+"##;
+
+pub fn percentage_validate_clean(content: &str) -> Result<String, GrammarError> {
+    let content: &str = content.trim();
+    percentage_parse(content, true)?;
+    Ok(content.to_string())
+}
+
+/// Parses a percentage to a fraction in `0.0..=1.0`.
+///
+/// * With a `%` sign, the value is divided by 100: `35%` is `0.35` and `1.5%` is `0.015`.
+/// * Without one, values up to 1 are taken as fractions and larger values as percents: `0.35` is `0.35`, `1` is `1.0`,
+///   and `35` is `0.35`. So a bare `1.5` is `0.015`, not 150%.
+///
+/// Values above 100% are clamped to `1.0` if `clamp` is true, and are an error otherwise.
+pub fn percentage_parse(content: &str, clamp: bool) -> Result<f32, GrammarError> {
+    let trimmed = content.trim();
+    let parse_error = || GrammarError::ParseValueError {
+        content: content.to_string(),
+        parse_type: "percentage".to_string(),
+    };
+    let (number, has_percent_sign) = match trimmed.strip_suffix('%') {
+        Some(number) => (number.trim_end(), true),
+        None => (trimmed, false),
+    };
+    if !number.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(parse_error());
+    }
+    let value: f32 = number.parse().map_err(|_| parse_error())?;
+    if !value.is_finite() {
+        return Err(parse_error());
+    }
+    let fraction = if has_percent_sign || value > 1.0 {
+        value / 100.0
+    } else {
+        value
+    };
+    if fraction > 1.0 {
+        if clamp {
+            Ok(1.0)
+        } else {
+            Err(parse_error())
+        }
+    } else {
+        Ok(fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut grammar = Grammar::percentage();
+        let grammar_string = grammar
+            .set_stop_word_done("stop")
+            .set_stop_word_no_result("unknown")
+            .grammar_string();
+        assert!(
+            grammar_string.starts_with("root ::= \" \" ( percentage | \"unknown\" ) \" stop\"\n")
+        );
+
+        assert_eq!(grammar.grammar_parse(" 35% ").unwrap(), 0.35);
+        assert_eq!(grammar.grammar_parse("35").unwrap(), 0.35);
+        assert_eq!(grammar.grammar_parse("0.35").unwrap(), 0.35);
+        assert_eq!(grammar.grammar_parse("1").unwrap(), 1.0);
+        assert_eq!(grammar.grammar_parse("1.5").unwrap(), 0.015);
+        assert_eq!(grammar.grammar_parse("0%").unwrap(), 0.0);
+        assert_eq!(grammar.grammar_parse("150%").unwrap(), 1.0);
+        assert!(grammar.grammar_parse("-5%").is_err());
+        assert!(grammar.grammar_parse("about 35%").is_err());
+
+        let grammar = Grammar::percentage().clamp(false);
+        assert_eq!(grammar.grammar_parse("100%").unwrap(), 1.0);
+        assert!(grammar.grammar_parse("150%").is_err());
+        assert!(grammar.validate_clean("150%").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
+
+/// The maximum number of items the grammar will constrain to an exact permutation.
+/// Above this, the grammar only constrains the count and range of the indices, and
+/// duplicates or omissions are rejected when parsing.
+pub const RANKING_EXACT_PERMUTATION_MAX_ITEMS: usize = 8;
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct RankingGrammar {
+    pub item_count: usize,
+    pub stop_word_done: Option<String>,
+    pub stop_word_no_result: Option<String>,
+}
+
+impl RankingGrammar {
+    pub fn wrap(self) -> Grammar {
+        Grammar::Ranking(self)
+    }
+
+    pub fn item_count(mut self, item_count: usize) -> Self {
+        self.item_count = item_count;
+        self
+    }
+
+    pub fn grammar_string(&self) -> String {
+        if self.item_count == 0 {
+            panic!("RankingGrammar must have at least one item");
+        }
+        cached_grammar_string(self, || {
+            ranking_grammar(
+                self.item_count,
+                &self.stop_word_done,
+                &self.stop_word_no_result,
+            )
+        })
+    }
+
+    pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
+        ranking_validate_clean(content, self.item_count)
+    }
+
+    /// Returns the zero-based item indices in ranked order.
+    pub fn grammar_parse(&self, content: &str) -> Result<Vec<usize>, GrammarError> {
+        ranking_parse(content, self.item_count)
+    }
+}
+
+impl GrammarSetterTrait for RankingGrammar {
+    fn stop_word_done_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_done
+    }
+
+    fn stop_word_no_result_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_no_result
+    }
+}
+
+pub fn ranking_grammar<T: AsRef<str>>(
+    item_count: usize,
+    stop_word_done: &Option<T>,
+    stop_word_no_result: &Option<T>,
+) -> String {
+    let rules = ranking_rules(item_count);
+    match (stop_word_done, stop_word_no_result) {
+        (Some(stop_word_done), Some(stop_word_no_result)) => format!(
+            "root ::= \" \" ( ranking | \"{}\" ) \" {}\"\n{rules}",
+            stop_word_no_result.as_ref(),
+            stop_word_done.as_ref()
+        ),
+        (None, Some(stop_word_no_result)) => {
+            format!(
+                "root ::= \" \" ( ranking | \"{}\" )\n{rules}",
+                stop_word_no_result.as_ref()
+            )
+        }
+        (Some(stop_word_done), None) => {
+            format!(
+                "root ::= \" \" ranking \" {}\"\n{rules}",
+                stop_word_done.as_ref()
+            )
+        }
+        (None, None) => format!("root ::= \" \" ranking\n{rules}"),
+    }
+}
+
+fn ranking_rules(item_count: usize) -> String {
+    if item_count > RANKING_EXACT_PERMUTATION_MAX_ITEMS {
+        let index = (1..=item_count)
+            .map(|i| format!("\"{i}\""))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        return format!(
+            "ranking ::= index (\", \" index){{{}}}\nindex ::= {index}\n",
+            item_count - 1
+        );
+    }
+    // One rule per set of remaining items, so each index can only be chosen once and
+    // the list can only end once every index has been used.
+    let full_set: usize = (1 << item_count) - 1;
+    let mut rules = format!("ranking ::= remaining-{full_set:x}\n");
+    for remaining in (1..=full_set).rev() {
+        let alternatives = (0..item_count)
+            .filter(|i| remaining & (1 << i) != 0)
+            .map(|i| {
+                let next = remaining & !(1 << i);
+                if next == 0 {
+                    format!("\"{}\"", i + 1)
+                } else {
+                    format!("\"{}, \" remaining-{next:x}", i + 1)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        rules.push_str(&format!("remaining-{remaining:x} ::= {alternatives}\n"));
+    }
+    rules
+}
+
+pub fn ranking_validate_clean(content: &str, item_count: usize) -> Result<String, GrammarError> {
+    ranking_parse(content, item_count).map(|ranking| {
+        ranking
+            .iter()
+            .map(|i| (i + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    })
+}
+
+/// Parses a comma separated list of one-based indices, and rejects anything that isn't a complete permutation.
+pub fn ranking_parse(content: &str, item_count: usize) -> Result<Vec<usize>, GrammarError> {
+    let parse_error = || GrammarError::ParseValueError {
+        content: content.to_string(),
+        parse_type: format!("ranking of {item_count} items"),
+    };
+    let mut seen = vec![false; item_count];
+    let mut ranking = Vec::with_capacity(item_count);
+    for index in content.trim().split(',') {
+        let index: usize = index.trim().parse().map_err(|_| parse_error())?;
+        if index == 0 || index > item_count || seen[index - 1] {
+            return Err(parse_error());
+        }
+        seen[index - 1] = true;
+        ranking.push(index - 1);
+    }
+    if ranking.len() != item_count {
+        return Err(parse_error());
+    }
+    Ok(ranking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut grammar = Grammar::ranking().item_count(2);
+        let grammar_string = grammar.set_stop_word_done("stop").grammar_string();
+        assert_eq!(
+            grammar_string,
+            "root ::= \" \" ranking \" stop\"\nranking ::= remaining-3\nremaining-3 ::= \"1, \" remaining-2 | \"2, \" remaining-1\nremaining-2 ::= \"2\"\nremaining-1 ::= \"1\"\n"
+        );
+
+        let grammar = Grammar::ranking().item_count(3);
+        assert_eq!(grammar.grammar_parse(" 3, 1, 2 ").unwrap(), vec![2, 0, 1]);
+        assert_eq!(grammar.validate_clean("3,1,2").unwrap(), "3, 1, 2");
+        // Duplicates
+        assert!(grammar.grammar_parse("3, 1, 1").is_err());
+        // Omissions
+        assert!(grammar.grammar_parse("3, 1").is_err());
+        // Out of range
+        assert!(grammar.grammar_parse("3, 1, 4").is_err());
+        assert!(grammar.grammar_parse("0, 1, 2").is_err());
+
+        let grammar = Grammar::ranking().item_count(10);
+        assert!(grammar
+            .grammar_string()
+            .contains("ranking ::= index (\", \" index){9}"));
+        assert!(grammar
+            .grammar_parse("10, 9, 8, 7, 6, 5, 4, 3, 2, 1")
+            .is_ok());
+        assert!(grammar
+            .grammar_parse("10, 10, 8, 7, 6, 5, 4, 3, 2, 1")
+            .is_err());
+    }
+}
@@ -3,8 +3,7 @@ pub mod text;
 pub mod text_list;
 pub mod words;
 
-use super::{Grammar, GrammarError, GrammarSetterTrait};
-use std::cell::RefCell;
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
 
 // const NEWLINE_CHARS: [char; 8] = [
 //     '\r', '\n', '\u{000C}', '\u{000B}', '\u{000C}', '\u{0085}', '\u{2028}', '\u{2029}',
@@ -1,9 +1,9 @@
 use super::{
-    build_disallowed, build_quotes, create_range, Grammar, GrammarError, GrammarSetterTrait,
-    RefCell, NEWLINE_CHARS,
+    build_disallowed, build_quotes, cached_grammar_string, create_range, Grammar, GrammarError,
+    GrammarSetterTrait, NEWLINE_CHARS,
 };
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct SentencesGrammar {
     pub min_count: u8,
     pub max_count: u8,
@@ -13,7 +13,6 @@ pub struct SentencesGrammar {
     pub stop_word_no_result: Option<String>,
     pub concatenator: String,
     pub disallowed_chars: Vec<char>,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl Default for SentencesGrammar {
@@ -31,7 +30,6 @@ impl Default for SentencesGrammar {
             stop_word_no_result: None,
             concatenator: " ".to_string(),
             disallowed_chars,
-            grammar_string: RefCell::new(None),
         }
     }
 }
@@ -72,9 +70,8 @@ impl SentencesGrammar {
     }
 
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(sentences_grammar(
+        cached_grammar_string(self, || {
+            sentences_grammar(
                 self.min_count,
                 self.max_count,
                 self.sentence_token_length,
@@ -83,9 +80,8 @@ impl SentencesGrammar {
                 &self.stop_word_done,
                 &self.stop_word_no_result,
                 &self.disallowed_chars,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+            )
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
@@ -1,13 +1,15 @@
-use super::{build_disallowed, Grammar, GrammarError, GrammarSetterTrait, RefCell, NEWLINE_CHARS};
+use super::{
+    build_disallowed, cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait,
+    NEWLINE_CHARS,
+};
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct TextGrammar {
     pub item_token_length: u32,
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
     pub disallowed_chars: Vec<char>,
     pub allow_newline: bool,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl Default for TextGrammar {
@@ -18,7 +20,6 @@ impl Default for TextGrammar {
             stop_word_no_result: None,
             disallowed_chars: vec![],
             allow_newline: false,
-            grammar_string: RefCell::new(None),
         }
     }
 }
@@ -49,17 +50,15 @@ impl TextGrammar {
     }
 
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(text_grammar(
+        cached_grammar_string(self, || {
+            text_grammar(
                 self.item_token_length,
                 &self.stop_word_done,
                 &self.stop_word_no_result,
                 self.allow_newline,
                 &self.disallowed_chars,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+            )
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
@@ -106,7 +105,6 @@ pub fn text_grammar(
             format!(
                 "root ::= item{{1,{}}} \" {stop_word_done}\"\nitem ::= {disallowed}",
                 (item_token_length as f32 * 4.5).floor() as u32,
-      
             )
         }
         (None, Some(stop_word_no_result)) => {
@@ -1,9 +1,9 @@
 use super::{
-    build_disallowed, create_range, Grammar, GrammarError, GrammarSetterTrait, RefCell,
-    NEWLINE_CHARS,
+    build_disallowed, cached_grammar_string, create_range, Grammar, GrammarError,
+    GrammarSetterTrait, NEWLINE_CHARS,
 };
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct TextListGrammar {
     pub item_token_length: u32,
     pub min_count: u8,
@@ -12,7 +12,6 @@ pub struct TextListGrammar {
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
     pub disallowed_chars: Vec<char>,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl Default for TextListGrammar {
@@ -25,7 +24,6 @@ impl Default for TextListGrammar {
             stop_word_done: None,
             stop_word_no_result: None,
             disallowed_chars: vec![],
-            grammar_string: RefCell::new(None),
         }
     }
 }
@@ -75,9 +73,8 @@ impl TextListGrammar {
     }
 
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(list_grammar(
+        cached_grammar_string(self, || {
+            list_grammar(
                 self.min_count,
                 self.max_count,
                 self.item_token_length,
@@ -85,9 +82,8 @@ impl TextListGrammar {
                 &self.stop_word_done,
                 &self.stop_word_no_result,
                 &self.disallowed_chars,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+            )
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
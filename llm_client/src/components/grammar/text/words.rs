@@ -1,6 +1,6 @@
-use super::{create_range, Grammar, GrammarError, GrammarSetterTrait, RefCell};
+use super::{cached_grammar_string, create_range, Grammar, GrammarError, GrammarSetterTrait};
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Debug)]
 pub struct WordsGrammar {
     pub min_count: u8,
     pub max_count: u8,
@@ -8,7 +8,6 @@ pub struct WordsGrammar {
     pub stop_word_done: Option<String>,
     pub stop_word_no_result: Option<String>,
     pub concatenator: String,
-    grammar_string: RefCell<Option<String>>,
 }
 
 impl WordsGrammar {
@@ -20,7 +19,6 @@ impl WordsGrammar {
             stop_word_done: None,
             stop_word_no_result: None,
             concatenator: " ".to_string(),
-            grammar_string: RefCell::new(None),
         }
     }
 }
@@ -51,18 +49,16 @@ impl WordsGrammar {
     }
 
     pub fn grammar_string(&self) -> String {
-        let mut grammar_string = self.grammar_string.borrow_mut();
-        if grammar_string.is_none() {
-            *grammar_string = Some(words_grammar(
+        cached_grammar_string(self, || {
+            words_grammar(
                 self.min_count,
                 self.max_count,
                 self.word_char_length,
                 &self.concatenator,
                 &self.stop_word_done,
                 &self.stop_word_no_result,
-            ));
-        }
-        grammar_string.as_ref().unwrap().clone()
+            )
+        })
     }
 
     pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
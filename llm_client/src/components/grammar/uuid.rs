@@ -0,0 +1,129 @@
+use super::{cached_grammar_string, Grammar, GrammarError, GrammarSetterTrait};
+use uuid::Uuid;
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct UuidGrammar {
+    pub stop_word_done: Option<String>,
+    pub stop_word_no_result: Option<String>,
+}
+
+impl UuidGrammar {
+    pub fn new() -> Self {
+        Self {
+            stop_word_done: None,
+            stop_word_no_result: None,
+        }
+    }
+}
+
+impl UuidGrammar {
+    pub fn wrap(self) -> Grammar {
+        Grammar::Uuid(self)
+    }
+
+    pub fn grammar_string(&self) -> String {
+        cached_grammar_string(self, || {
+            uuid_grammar(&self.stop_word_done, &self.stop_word_no_result)
+        })
+    }
+
+    pub fn validate_clean(&self, content: &str) -> Result<String, GrammarError> {
+        uuid_validate_clean(content)
+    }
+
+    pub fn grammar_parse(&self, content: &str) -> Result<Uuid, GrammarError> {
+        uuid_parse(content)
+    }
+}
+
+impl GrammarSetterTrait for UuidGrammar {
+    fn stop_word_done_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_done
+    }
+
+    fn stop_word_no_result_mut(&mut self) -> &mut Option<String> {
+        &mut self.stop_word_no_result
+    }
+}
+
+pub fn uuid_grammar<T: AsRef<str>>(
+    stop_word_done: &Option<T>,
+    stop_word_no_result: &Option<T>,
+) -> String {
+    match (stop_word_done, stop_word_no_result) {
+        (Some(stop_word_done), Some(stop_word_no_result)) => format!(
+            "root ::= \" \" ( uuid | \"{}\" ) \" {}\"\n{UUID_GRAMMAR}",
+            stop_word_no_result.as_ref(),
+            stop_word_done.as_ref()
+        ),
+        (None, Some(stop_word_no_result)) => {
+            format!(
+                "root ::= \" \" ( uuid | \"{}\" )\n{UUID_GRAMMAR}",
+                stop_word_no_result.as_ref()
+            )
+        }
+        (Some(stop_word_done), None) => {
+            format!(
+                "root ::= \" \" uuid \" {}\"\n{UUID_GRAMMAR}",
+                stop_word_done.as_ref()
+            )
+        }
+        (None, None) => format!("root ::= \" \" uuid\n{UUID_GRAMMAR}"),
+    }
+}
+
+pub const UUID_GRAMMAR: &str = r##"uuid ::= hexdig{8} "-" hexdig{4} "-" hexdig{4} "-" hexdig{4} "-" hexdig{12}
+hexdig ::= [0-9a-fA-F]
+"##;
+
+pub fn uuid_validate_clean(content: &str) -> Result<String, GrammarError> {
+    uuid_parse(content).map(|uuid| uuid.hyphenated().to_string())
+}
+
+pub fn uuid_parse(content: &str) -> Result<Uuid, GrammarError> {
+    let content = content.trim();
+    // Uuid::parse_str also accepts the simple, braced, and urn formats, but the grammar only allows hyphenated.
+    if content.len() != uuid::fmt::Hyphenated::LENGTH {
+        return Err(GrammarError::ParseValueError {
+            content: content.to_string(),
+            parse_type: "Uuid".to_string(),
+        });
+    }
+    Uuid::parse_str(content).map_err(|_| GrammarError::ParseValueError {
+        content: content.to_string(),
+        parse_type: "Uuid".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut grammar = Grammar::uuid();
+        let grammar_string = grammar
+            .set_stop_word_done("stop")
+            .set_stop_word_no_result("unknown")
+            .grammar_string();
+        assert!(grammar_string.starts_with("root ::= \" \" ( uuid | \"unknown\" ) \" stop\"\n"));
+
+        let uuid = grammar
+            .grammar_parse(" 67E55044-10B1-426F-9247-BB680E5FE0C8 ")
+            .unwrap();
+        assert_eq!(uuid.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert_eq!(
+            grammar
+                .validate_clean("67E55044-10B1-426F-9247-BB680E5FE0C8")
+                .unwrap(),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+
+        assert!(grammar
+            .grammar_parse("67e5504410b1426f9247bb680e5fe0c8")
+            .is_err());
+        assert!(grammar
+            .grammar_parse("67e55044-10b1-426f-9247-bb680e5fe0cz")
+            .is_err());
+    }
+}
@@ -5,6 +5,13 @@ use llm_prompt::{PromptMessage, PromptMessageType, TextConcatenator, TextConcate
 pub struct InstructPrompt {
     pub instructions: Option<PromptMessage>,
     pub supporting_material: Option<PromptMessage>,
+    /// Material shared by every request made with a workflow, like a long document that many questions are asked about.
+    /// It's sent at the start of the first user message, ahead of the instructions and supporting material, so the
+    /// backend can reuse its prompt cache for it rather than evaluating it again on each request.
+    /// Unlike supporting material, it isn't cleared by [InstructPrompt::reset_instruct_prompt].
+    ///
+    /// Set with `shared_context` on the basic primitive and reason workflow builders.
+    pub shared_context: Option<String>,
     pub concatenator: TextConcatenator,
 }
 
@@ -19,6 +26,7 @@ impl InstructPrompt {
         Self {
             instructions: None,
             supporting_material: None,
+            shared_context: None,
             concatenator: TextConcatenator::default(),
         }
     }
@@ -44,6 +52,16 @@ impl InstructPrompt {
         }
     }
 
+    /// Prepends the shared context, if any, to the task of a workflow's first round.
+    pub fn prepend_shared_context(&self, task: String) -> String {
+        match &self.shared_context {
+            Some(shared_context) => format!(
+                "The user provided some shared supporting material: {shared_context}\n{task}"
+            ),
+            None => task,
+        }
+    }
+
     pub fn build_instruct_prompt(&self, supporting_material_first: bool) -> Result<String> {
         Ok(
             match (self.build_instructions(), self.build_supporting_material()) {
@@ -0,0 +1,220 @@
+//! A chat session that keeps its history within the model's context by summarizing the oldest turns.
+use crate::{basic_completion::BasicCompletion, LlmClient};
+use llm_interface::requests::{
+    completion::CompletionResponse,
+    req_components::{RequestConfig, RequestConfigTrait},
+};
+use llm_prompt::{LlmPrompt, PromptMessageType};
+
+const SUMMARY_HEADING: &str = "Summary of the earlier conversation:";
+
+/// Holds a conversation's prompt and sends each user message with [ConversationManager::send].
+///
+/// Before each request, if the prompt is over [ConversationManager::trigger_tokens], the oldest user/assistant turns are
+/// replaced with a summary written by the summarizer client, which is added to the system message with
+/// [LlmPrompt::set_summary]. Unlike
+/// [LlmPrompt::trim_to_token_budget], the context of the dropped turns is kept. The summarizer can be a cheaper model than the chat model.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut conversation = llm_client.conversation_manager();
+/// conversation.summarizer(&cheap_client).trigger_tokens(4000);
+/// conversation.prompt().add_system_message()?.set_content("You are a helpful assistant.");
+/// let res = conversation.send("What's the tallest mountain in Europe?").await?;
+/// ```
+pub struct ConversationManager {
+    /// The completion that generates the replies. Its request config applies to every reply.
+    pub completion: BasicCompletion,
+    pub summarizer: LlmClient,
+    pub trigger_tokens: u64,
+    pub summarize_turns: usize,
+    pub summary_max_tokens: u64,
+    /// The running summary of the turns removed so far.
+    pub summary: Option<String>,
+}
+
+impl ConversationManager {
+    pub fn new(llm_client: &LlmClient) -> Self {
+        let mut completion = BasicCompletion::new(llm_client.backend.clone());
+        completion.base_req.observer = llm_client.observer.clone();
+        completion.base_req.circuit_breaker = llm_client.circuit_breaker.clone();
+        // API models' inference_ctx_size is their max output, which is the most a response needs. A local model's context
+        // holds both the prompt and the response, so a quarter of it is left for the response.
+        let model_ctx_size = llm_client.backend.model_ctx_size();
+        let response_tokens = llm_client
+            .backend
+            .inference_ctx_size()
+            .min(model_ctx_size / 4);
+        Self {
+            completion,
+            summarizer: llm_client.clone(),
+            trigger_tokens: model_ctx_size - response_tokens,
+            summarize_turns: 4,
+            summary_max_tokens: 300,
+            summary: None,
+        }
+    }
+
+    pub fn prompt(&mut self) -> &mut LlmPrompt {
+        &mut self.completion.base_req.prompt
+    }
+
+    /// Sets the client that writes the summaries, e.g. a cheaper or faster model than the chat model.
+    ///
+    /// # Default
+    ///
+    /// The chat client.
+    pub fn summarizer(&mut self, summarizer: &LlmClient) -> &mut Self {
+        self.summarizer = summarizer.clone();
+        self
+    }
+
+    /// Sets the prompt size, in tokens, at which the oldest turns are summarized.
+    /// Leave room for the response, since the prompt can grow up to this size before each request.
+    ///
+    /// # Default
+    ///
+    /// The chat model's context size, less room for the response: its max output for API models, or a quarter of the
+    /// context for local models.
+    pub fn trigger_tokens(&mut self, trigger_tokens: u64) -> &mut Self {
+        self.trigger_tokens = trigger_tokens;
+        self
+    }
+
+    /// Sets how many of the oldest user/assistant pairs are summarized at a time when the prompt is over the trigger.
+    ///
+    /// # Default
+    ///
+    /// 4 pairs.
+    pub fn summarize_turns(&mut self, summarize_turns: usize) -> &mut Self {
+        self.summarize_turns = summarize_turns.max(1);
+        self
+    }
+
+    /// Sets the token limit for each summary.
+    ///
+    /// # Default
+    ///
+    /// 300 tokens.
+    pub fn summary_max_tokens(&mut self, summary_max_tokens: u64) -> &mut Self {
+        self.summary_max_tokens = summary_max_tokens;
+        self
+    }
+
+    /// Adds `content` as a user message, summarizes the oldest turns if the prompt is over the trigger, and sends it.
+    /// The reply is added to the prompt as an assistant message, so the next call continues the conversation.
+    /// On error, the user message is removed again.
+    #[tracing::instrument(
+        name = "llm_client.conversation",
+        skip_all,
+        fields(backend = self.completion.base_req.backend.backend_name(), model = self.completion.base_req.backend.model_id())
+    )]
+    pub async fn send<T: AsRef<str>>(&mut self, content: T) -> crate::Result<CompletionResponse> {
+        self.prompt().add_user_message()?.set_content(content);
+        let res = match self.compact().await {
+            Ok(_) => self.completion.run().await,
+            Err(e) => Err(e),
+        };
+        match res {
+            Ok(res) => {
+                self.prompt()
+                    .add_assistant_message()?
+                    .set_content(&res.content);
+                Ok(res)
+            }
+            Err(e) => {
+                // Remove the user message so the conversation can continue with the next one.
+                let message_count = self.prompt().message_count();
+                self.prompt().truncate_to(message_count - 1)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Summarizes the oldest turns until the prompt is within [ConversationManager::trigger_tokens], or only the last turn is left.
+    ///
+    /// # Returns
+    ///
+    /// The number of user/assistant pairs summarized.
+    pub async fn compact(&mut self) -> crate::Result<usize> {
+        let mut summarized = 0;
+        while self.prompt().total_conversation_tokens()? > self.trigger_tokens {
+            let turns = self.oldest_turns(self.summarize_turns)?;
+            if turns.is_empty() {
+                crate::warn!(
+                    "Conversation is over the trigger of {} tokens with no turns left to summarize.",
+                    self.trigger_tokens
+                );
+                break;
+            }
+            let summary = self.summarize(&turns).await?;
+            let dropped = self.prompt().drop_oldest_turns(turns.len() / 2);
+            self.prompt()
+                .set_summary(format!("{SUMMARY_HEADING}\n{summary}"));
+            self.summary = Some(summary);
+            summarized += dropped;
+        }
+        Ok(summarized)
+    }
+
+    /// Returns the role and content of the messages in the oldest `count` user/assistant pairs that can be dropped.
+    fn oldest_turns(&mut self, count: usize) -> crate::Result<Vec<(PromptMessageType, String)>> {
+        let prompt = self.prompt();
+        let message_count = prompt.message_count();
+        let first_turn = match prompt.message(0) {
+            Ok(first) if first.message_type == PromptMessageType::System => 1,
+            _ => 0,
+        };
+        let droppable = message_count.saturating_sub(first_turn + 1) / 2;
+        (first_turn..first_turn + count.min(droppable) * 2)
+            .map(|i| {
+                let message = prompt.message(i)?;
+                Ok((
+                    message.message_type.clone(),
+                    message.get_built_prompt_message().unwrap_or_default(),
+                ))
+            })
+            .collect()
+    }
+
+    async fn summarize(&self, turns: &[(PromptMessageType, String)]) -> crate::Result<String> {
+        let mut transcript = String::new();
+        for (message_type, content) in turns {
+            let role = match message_type {
+                PromptMessageType::User => "User",
+                _ => "Assistant",
+            };
+            transcript.push_str(&format!("{role}: {content}\n\n"));
+        }
+        let mut gen = self.summarizer.basic_completion();
+        gen.prompt().add_system_message()?.set_content(
+            "You summarize conversations between a user and an assistant. Keep the facts, decisions, names, and open questions the rest of the conversation may rely on. Write only the summary.",
+        );
+        let user_message = gen.prompt().add_user_message()?;
+        if let Some(summary) = &self.summary {
+            user_message.set_content(format!(
+                "The summary of the conversation so far:\n{summary}"
+            ));
+            user_message.append_content(format!(
+                "Update the summary with the turns that follow it:\n\n{transcript}"
+            ));
+        } else {
+            user_message.set_content(format!("Summarize this conversation:\n\n{transcript}"));
+        }
+        gen.max_tokens(self.summary_max_tokens);
+        let res = gen.run().await?;
+        Ok(res.content.trim().to_owned())
+    }
+}
+
+impl RequestConfigTrait for ConversationManager {
+    fn config(&mut self) -> &mut RequestConfig {
+        &mut self.completion.base_req.config
+    }
+
+    fn reset_request(&mut self) {
+        self.completion.reset_request();
+        self.summary = None;
+    }
+}
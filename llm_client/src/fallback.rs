@@ -0,0 +1,85 @@
+//! Runs a workflow on a primary client, falling back to another client if the primary doesn't finish in time.
+use crate::LlmClient;
+use std::future::Future;
+
+/// Which client produced a [FallbackOutput].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientUsed {
+    Primary,
+    Fallback,
+}
+
+impl std::fmt::Display for ClientUsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientUsed::Primary => write!(f, "Primary"),
+            ClientUsed::Fallback => write!(f, "Fallback"),
+        }
+    }
+}
+
+pub struct FallbackOutput<T> {
+    pub output: T,
+    pub client_used: ClientUsed,
+    /// How long the primary ran before it finished or was cancelled.
+    pub primary_duration: std::time::Duration,
+}
+
+/// Runs `run` with the primary client, and if it hasn't finished within `timeout`, cancels it and runs `run` with the fallback client.
+///
+/// Cancelling drops the primary's future, which closes the connection of its in-flight request. API servers and llama-server
+/// stop generating once the connection is closed, so the primary doesn't keep using resources in the background.
+///
+/// Only a timeout triggers the fallback. If the primary returns an error in time, the error is returned. The fallback runs without a deadline.
+///
+/// # Arguments
+///
+/// * `primary` - The client to try first, e.g. a stronger model.
+/// * `fallback` - The client used on timeout, e.g. a faster or cheaper model.
+/// * `timeout` - How long to wait for the primary.
+/// * `run` - Builds and runs the workflow with the given client. It's called once, or twice if the primary times out.
+///
+/// # Example
+///
+/// ```ignore
+/// let res = with_timeout_and_fallback(&strong, &fast, Duration::from_secs(5), |client| async move {
+///     let mut gen = client.basic_completion();
+///     gen.prompt().add_user_message()?.set_content("Summarize the report.");
+///     gen.run().await
+/// })
+/// .await?;
+/// println!("{} answered: {}", res.client_used, res.output.content);
+/// ```
+pub async fn with_timeout_and_fallback<'a, T, F, Fut>(
+    primary: &'a LlmClient,
+    fallback: &'a LlmClient,
+    timeout: std::time::Duration,
+    run: F,
+) -> crate::Result<FallbackOutput<T>>
+where
+    F: Fn(&'a LlmClient) -> Fut,
+    Fut: Future<Output = crate::Result<T>> + 'a,
+{
+    let start = std::time::Instant::now();
+    match tokio::time::timeout(timeout, run(primary)).await {
+        Ok(output) => Ok(FallbackOutput {
+            output: output?,
+            client_used: ClientUsed::Primary,
+            primary_duration: start.elapsed(),
+        }),
+        Err(_) => {
+            let primary_duration = start.elapsed();
+            crate::warn!(
+                "Primary client ({}) timed out after {:?}. Running the fallback client ({}).",
+                primary.backend.model_id(),
+                primary_duration,
+                fallback.backend.model_id()
+            );
+            Ok(FallbackOutput {
+                output: run(fallback).await?,
+                client_used: ClientUsed::Fallback,
+                primary_duration,
+            })
+        }
+    }
+}
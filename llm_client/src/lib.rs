@@ -1,8 +1,12 @@
 pub mod backend_builders;
 pub mod basic_completion;
 pub mod components;
+pub mod conversation;
+pub mod fallback;
 pub mod prelude;
 pub mod primitives;
+pub mod search_completion;
+pub mod similarity;
 pub mod workflows;
 #[allow(unused_imports)]
 pub(crate) use anyhow::{anyhow, bail, Error, Result};
@@ -12,8 +16,14 @@ pub use prelude::*;
 #[allow(unused_imports)]
 pub(crate) use tracing::{debug, error, info, span, trace, warn, Level};
 
+/// Clones share the backend, including its HTTP connection pool for API backends.
+#[derive(Clone)]
 pub struct LlmClient {
     pub backend: std::sync::Arc<llm_interface::llms::LlmBackend>,
+    /// Called for each request the client's workflows send to the backend. See [LlmClient::observer].
+    pub observer: Option<std::sync::Arc<dyn RequestObserver>>,
+    /// Shared by every request the client sends. See [LlmClient::circuit_breaker].
+    pub circuit_breaker: Option<std::sync::Arc<CircuitBreaker>>,
 }
 
 impl LlmClient {
@@ -25,10 +35,70 @@ impl LlmClient {
                 colorful::RGB::new(94, 244, 39)
             ))
         );
-        Self { backend }
+        Self {
+            backend,
+            observer: None,
+            circuit_breaker: None,
+        }
+    }
+
+    /// Sets a [RequestObserver] that's called before each request and after each response or error, for every internal
+    /// request made by the client's completions and workflows. Useful for emitting metrics or spans without changing the crate.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer. It's shared with each request, so keep a clone of the `Arc` to read back any state it collects.
+    ///
+    /// # Default
+    ///
+    /// No observer.
+    pub fn observer(mut self, observer: std::sync::Arc<dyn RequestObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Fails requests fast with `CompletionError::CircuitOpen` while the backend keeps failing, instead of letting each one time out.
+    ///
+    /// # Arguments
+    ///
+    /// * `failure_threshold` - Consecutive failed requests that open the circuit.
+    /// * `cooldown` - How long requests are short-circuited before a single trial request tests whether the backend has recovered.
+    ///
+    /// # Notes
+    ///
+    /// Pairs with [fallback::with_timeout_and_fallback] or a second client to fail over to another provider.
+    /// The [CircuitBreaker] is kept in the `circuit_breaker` field, e.g. to check `is_open`.
+    ///
+    /// # Default
+    ///
+    /// No circuit breaker.
+    pub fn circuit_breaker(
+        mut self,
+        failure_threshold: u32,
+        cooldown: std::time::Duration,
+    ) -> Self {
+        self.circuit_breaker = Some(std::sync::Arc::new(CircuitBreaker::new(
+            failure_threshold,
+            cooldown,
+        )));
+        self
     }
     #[cfg(feature = "llama_cpp_backend")]
     /// Creates a new instance of the [`LlamaCppBackendBuilder`]. This builder that allows you to specify the model and other parameters. It is converted to an `LlmClient` instance using the `init` method.
+    ///
+    /// # Example
+    ///
+    /// Loading the largest quant from a GGUF repo that fits in the available VRAM:
+    ///
+    /// ```no_run
+    /// use llm_client::prelude::*;
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let mut builder = LlmClient::llama_cpp();
+    /// builder.hf_repo_id("bartowski/Meta-Llama-3.1-8B-Instruct-GGUF");
+    /// let llm_client = builder.init().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn llama_cpp() -> backend_builders::llama_cpp::LlamaCppBackendBuilder {
         backend_builders::llama_cpp::LlamaCppBackendBuilder::default()
     }
@@ -44,6 +114,12 @@ impl LlmClient {
         backend_builders::openai::OpenAiBackendBuilder::default()
     }
 
+    /// Creates a new instance of the [`AzureOpenAiBackendBuilder`]. This builder that allows you to specify the endpoint, deployment, api version, and other parameters. It is converted to an `LlmClient` instance using the `init` method.
+    /// The api key is loaded from `AZURE_OPENAI_API_KEY` unless set with `with_api_key`.
+    pub fn azure_openai() -> backend_builders::azure_openai::AzureOpenAiBackendBuilder {
+        backend_builders::azure_openai::AzureOpenAiBackendBuilder::default()
+    }
+
     /// Creates a new instance of the [`AnthropicBackendBuilder`]. This builder that allows you to specify the model and other parameters. It is converted to an `LlmClient` instance using the `init` method.
     pub fn anthropic() -> backend_builders::anthropic::AnthropicBackendBuilder {
         backend_builders::anthropic::AnthropicBackendBuilder::default()
@@ -54,27 +130,237 @@ impl LlmClient {
         backend_builders::perplexity::PerplexityBackendBuilder::default()
     }
 
+    /// Creates a new instance of the [`OllamaBackendBuilder`] for a running Ollama server, using its native `/api/chat` or `/api/generate` endpoint. This builder that allows you to specify the model, `keep_alive`, `options`, and other parameters. It is converted to an `LlmClient` instance using the async `init` method.
+    /// Models on the server can be listed, pulled, and unloaded through `llm_client.backend.ollama()`.
+    pub fn ollama() -> backend_builders::ollama::OllamaBackendBuilder {
+        backend_builders::ollama::OllamaBackendBuilder::default()
+    }
+
+    /// Creates a new instance of the [`GenericApiBackendBuilder`] for a self-hosted OpenAI compatible server, e.g. vLLM, the llama.cpp server, LocalAI, or text-generation-webui.
+    /// Set the server with `base_url` and the model with `model_id`. Servers that only implement the legacy completions endpoint are supported with `endpoint_style`.
+    pub fn generic_api() -> backend_builders::generic_api::GenericApiBackendBuilder {
+        backend_builders::generic_api::GenericApiBackendBuilder::default()
+    }
+
     pub fn basic_completion(&self) -> basic_completion::BasicCompletion {
-        basic_completion::BasicCompletion::new(self.backend.clone())
+        let mut basic_completion = basic_completion::BasicCompletion::new(self.backend.clone());
+        basic_completion.base_req.observer = self.observer.clone();
+        basic_completion.base_req.circuit_breaker = self.circuit_breaker.clone();
+        basic_completion
+    }
+
+    /// The sampling parameters the client's backend accepts and their ranges. See [llm_interface::requests::param_ranges::ParamRanges].
+    pub fn param_ranges(&self) -> llm_interface::requests::param_ranges::ParamRanges {
+        self.backend.param_ranges()
+    }
+
+    /// Creates an empty prompt for this client's backend, to build separately and then run with [LlmClient::basic_completion_from_prompt].
+    pub fn new_prompt(&self) -> LlmPrompt {
+        self.backend.new_prompt()
+    }
+
+    /// A completion that sends `prompt` as is. Clone the prompt first to reuse it across requests.
+    pub fn basic_completion_from_prompt(
+        &self,
+        prompt: LlmPrompt,
+    ) -> basic_completion::BasicCompletion {
+        let mut basic_completion =
+            basic_completion::BasicCompletion::from_prompt(self.backend.clone(), prompt);
+        basic_completion.base_req.observer = self.observer.clone();
+        basic_completion.base_req.circuit_breaker = self.circuit_breaker.clone();
+        basic_completion
+    }
+
+    /// A chat session that summarizes its oldest turns when the prompt grows past a token threshold. See [conversation::ConversationManager].
+    pub fn conversation_manager(&self) -> conversation::ConversationManager {
+        conversation::ConversationManager::new(self)
+    }
+
+    /// A completion grounded in a web search, returning the answer and its sources.
+    /// Requires a backend with built in search, i.e. a Perplexity sonar model or an OpenAI search model. Other backends return an error.
+    pub fn search_completion(&self) -> search_completion::SearchCompletion {
+        let mut search_completion = search_completion::SearchCompletion::new(self.backend.clone());
+        search_completion.base_req.observer = self.observer.clone();
+        search_completion.base_req.circuit_breaker = self.circuit_breaker.clone();
+        search_completion
     }
 
     pub fn basic_primitive(&self) -> workflows::basic_primitive::BasicPrimitiveWorkflowBuilder {
-        workflows::basic_primitive::BasicPrimitiveWorkflowBuilder::new(self.backend.clone())
+        let mut builder =
+            workflows::basic_primitive::BasicPrimitiveWorkflowBuilder::new(self.backend.clone());
+        builder.base_req.observer = self.observer.clone();
+        builder.base_req.circuit_breaker = self.circuit_breaker.clone();
+        builder
     }
 
     pub fn reason(&self) -> workflows::reason::ReasonWorkflowBuilder {
-        workflows::reason::ReasonWorkflowBuilder::new(self.backend.clone())
+        workflows::reason::ReasonWorkflowBuilder {
+            base_req: self.base_request(),
+            shared_context: None,
+        }
+    }
+
+    pub fn classify(&self) -> workflows::classify::Classify {
+        workflows::classify::Classify {
+            base_req: self.base_request(),
+        }
     }
 
     pub fn nlp(&self) -> workflows::nlp::Nlp {
-        workflows::nlp::Nlp::new(self.backend.clone())
+        workflows::nlp::Nlp {
+            base_req: self.base_request(),
+        }
     }
 
     pub fn shutdown(&self) {
         self.backend.shutdown();
     }
 
+    /// Shuts down the backend, waiting for in-flight requests to finish and confirming the server process has exited.
+    /// For API backends this is a no-op.
+    ///
+    /// Dropping the client remains a best-effort fallback, but it can't wait on in-flight requests,
+    /// and it won't run if the program exits without unwinding.
+    pub async fn shutdown_gracefully(&self) -> crate::Result<()> {
+        self.backend.shutdown_gracefully().await
+    }
+
+    /// Checks whether the backend can take requests, for readiness and liveness probes or before sending the first request.
+    ///
+    /// For llama.cpp this asks the server's `/health` endpoint, which reports `Loading` until the model is loaded. For API
+    /// backends it requests the models list, so a bad API key shows up as `AuthFailed` and a wrong base url as
+    /// `Misconfigured` without paying for a completion.
+    /// The check isn't retried and gives up after a few seconds, so an unreachable server returns `Unreachable` quickly.
+    pub async fn health_check(&self) -> HealthStatus {
+        self.backend.health_check().await
+    }
+
+    /// Sends a one token completion so the first real request doesn't pay the warmup cost, and returns how long it took.
+    ///
+    /// For local backends this loads the model weights into memory and compiles the compute graph and any Metal or CUDA kernels,
+    /// which otherwise makes the first request after `init` much slower than the rest. For API backends it opens the connection.
+    ///
+    /// The response is discarded, and an empty response isn't an error.
+    pub async fn warmup(&self) -> crate::Result<std::time::Duration> {
+        let start = std::time::Instant::now();
+        let mut req = self.base_request();
+        req.config.requested_response_tokens = Some(1);
+        req.config.retry_on_empty = false;
+        req.config.increase_limit_on_fail = false;
+        req.prompt.add_user_message()?.set_content("Hi");
+        req.request().await?;
+        let duration = start.elapsed();
+        crate::info!("Warmed up {} in {:?}", self.backend.model_id(), duration);
+        Ok(duration)
+    }
+
+    /// Swaps the llama.cpp backend's model, keeping the client, its observer, and the backend's config.
+    ///
+    /// The current server is shut down gracefully and a new one is started with the model, with the available VRAM
+    /// re-estimated once the old model is unloaded. Completion requests that still hold the old backend, e.g. workflows
+    /// built before the swap, are rejected once the swap starts, while requests already in-flight are given time to finish.
+    ///
+    /// If the new model fails to load, the previous model is started again and the client keeps using it.
+    ///
+    /// # Arguments
+    ///
+    /// * `llm_loader` - The model to load, e.g. `GgufLoader::default().llama3_1_8b_instruct()` or a loader with a GGUF URL.
+    ///   Embedding presets start the server in embedding mode.
+    #[cfg(feature = "llama_cpp_backend")]
+    pub async fn load_model(
+        &mut self,
+        llm_loader: llm_models::local_model::gguf::GgufLoader,
+    ) -> crate::Result<()> {
+        let backend = self.backend.llama_cpp()?;
+        let loading_preset = llm_loader.gguf_local_loader.local_quant_file_path.is_none()
+            && llm_loader.gguf_hf_loader.hf_quant_file_url.is_none();
+        let embedding_mode = if loading_preset {
+            llm_loader
+                .gguf_preset_loader
+                .llm_preset
+                .is_embedding_model()
+        } else {
+            backend.server.embedding_mode
+        };
+        match backend.reload(llm_loader, embedding_mode).await {
+            Ok(backend) => {
+                self.backend =
+                    std::sync::Arc::new(llm_interface::llms::LlmBackend::LlamaCpp(backend));
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(restored) = e.restored {
+                    self.backend =
+                        std::sync::Arc::new(llm_interface::llms::LlmBackend::LlamaCpp(restored));
+                }
+                Err(e.error)
+            }
+        }
+    }
+
+    /// Tokenizes text with the backend's local tokenizer, the same one used for counting prompt tokens.
+    ///
+    /// For llama.cpp this is the Hugging Face `tokenizer.json` (or one converted from the GGUF), which can
+    /// differ slightly from llama-server's tokenizer. Use [Self::server_tokenize] when the ids must match the loaded model exactly.
+    pub fn tokenize(&self, text: &str) -> Vec<u32> {
+        self.backend.tokenizer().tokenize(text)
+    }
+
+    /// Detokenizes token ids with the backend's local tokenizer. See [Self::tokenize].
+    pub fn detokenize(&self, tokens: &[u32]) -> crate::Result<String> {
+        self.backend.tokenizer().detokenize_many(tokens)
+    }
+
+    /// Tokenizes text with the backend's local tokenizer, with control over special tokens and optionally returning each token's text.
+    /// See [TokenizeOptions]. Useful for building logit bias or raw token prompts.
+    pub fn tokenize_with_options(
+        &self,
+        text: &str,
+        options: &TokenizeOptions,
+    ) -> crate::Result<Vec<TokenPiece>> {
+        self.backend
+            .tokenizer()
+            .tokenize_with_options(text, options)
+    }
+
+    /// Tokenizes text with llama-server's `/tokenize` endpoint, which uses the tokenizer of the loaded GGUF.
+    /// Backends without a tokenizer endpoint fall back to the local tokenizer.
+    pub async fn server_tokenize(&self, text: &str) -> crate::Result<Vec<u32>> {
+        let tokens = self
+            .backend
+            .server_tokenize(text, &TokenizeOptions::default())
+            .await?;
+        Ok(tokens.into_iter().map(|token| token.id).collect())
+    }
+
+    /// Tokenizes text with llama-server's `/tokenize` endpoint, using its `add_special`, `parse_special`, and `with_pieces` options.
+    /// This is exactly how the loaded model will see the text. Backends without a tokenizer endpoint fall back to the local tokenizer.
+    pub async fn server_tokenize_with_options(
+        &self,
+        text: &str,
+        options: &TokenizeOptions,
+    ) -> crate::Result<Vec<TokenPiece>> {
+        self.backend.server_tokenize(text, options).await
+    }
+
+    /// Detokenizes token ids with llama-server's `/detokenize` endpoint. See [Self::server_tokenize].
+    pub async fn server_detokenize(&self, tokens: &[u32]) -> crate::Result<String> {
+        self.backend.server_detokenize(tokens).await
+    }
+
+    /// Embeds each input with the backend's model, returning one vector per input in the same order.
+    /// Requires a llama.cpp backend running an embedding model, e.g. `LlmClient::llama_cpp().nomic_embed_text_v1_5().init()`.
+    /// See [similarity] for comparing the embeddings.
+    pub async fn embeddings<S: AsRef<str>>(&self, inputs: &[S]) -> crate::Result<Vec<Vec<f32>>> {
+        self.backend.embeddings(inputs).await
+    }
+
     pub fn base_request(&self) -> llm_interface::requests::completion::request::CompletionRequest {
-        llm_interface::requests::completion::request::CompletionRequest::new(self.backend.clone())
+        let mut base_req = llm_interface::requests::completion::request::CompletionRequest::new(
+            self.backend.clone(),
+        );
+        base_req.observer = self.observer.clone();
+        base_req.circuit_breaker = self.circuit_breaker.clone();
+        base_req
     }
 }
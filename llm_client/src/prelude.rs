@@ -11,18 +11,22 @@ pub use llm_devices::logging::LoggingConfigTrait;
 #[cfg(target_os = "macos")]
 pub use llm_devices::devices::MetalConfig;
 pub use llm_interface::{
-    llms::local::LlmLocalTrait,
+    llms::{health::HealthStatus, local::LlmLocalTrait},
     requests::{
+        circuit_breaker::CircuitBreaker,
         completion::{CompletionRequest, CompletionResponse},
         logit_bias::LogitBiasTrait,
+        observer::RequestObserver,
         req_components::RequestConfigTrait,
     },
 };
 pub use llm_models::{
     api_model::{
-        anthropic::AnthropicModelTrait, openai::OpenAiModelTrait, perplexity::PerplexityModelTrait,
+        anthropic::AnthropicModelTrait, ollama::OllamaModelTrait, openai::OpenAiModelTrait,
+        perplexity::PerplexityModelTrait,
     },
     local_model::{GgufLoaderTrait, GgufPresetTrait, HfTokenTrait},
+    tokenizer::{TokenPiece, TokenizeOptions},
 };
 pub use llm_prompt::*;
 #[cfg(test)]
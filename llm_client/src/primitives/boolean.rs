@@ -3,7 +3,7 @@ use crate::components::grammar::{BooleanGrammar, Grammar};
 use crate::workflows::reason::ReasonTrait;
 use anyhow::Result;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct BooleanPrimitive {}
 
 impl BooleanPrimitive {
@@ -0,0 +1,51 @@
+use super::PrimitiveTrait;
+use crate::components::grammar::{EmailGrammar, Grammar};
+use anyhow::Result;
+
+#[derive(Default, Clone)]
+pub struct EmailPrimitive {}
+
+impl EmailPrimitive {
+    fn grammar_inner(&self) -> EmailGrammar {
+        Grammar::email()
+    }
+}
+
+impl PrimitiveTrait for EmailPrimitive {
+    type PrimitiveResult = String;
+
+    fn clear_primitive(&mut self) {}
+
+    fn type_description(&self, result_can_be_none: bool) -> &str {
+        if result_can_be_none {
+            "email address or 'Not present.'"
+        } else {
+            "email address"
+        }
+    }
+
+    fn solution_description(&self, result_can_be_none: bool) -> String {
+        if result_can_be_none {
+            "an email address or, if no email address is present, 'Not present.'".to_owned()
+        } else {
+            "an email address".to_owned()
+        }
+    }
+
+    fn stop_word_result_is_none(&self, result_can_be_none: bool) -> Option<String> {
+        if result_can_be_none {
+            Some("Not present.".to_string())
+        } else {
+            None
+        }
+    }
+
+    fn grammar(&self) -> Grammar {
+        self.grammar_inner().wrap()
+    }
+
+    fn parse_to_primitive(&self, content: &str) -> Result<Self::PrimitiveResult> {
+        let parsed: Self::PrimitiveResult = self.grammar_inner().grammar_parse(content)?;
+        Ok(parsed)
+    }
+}
@@ -96,6 +96,17 @@ impl ReasonTrait for ExactStringPrimitive {
         }
     }
 
+    fn choice_count(&self) -> usize {
+        self.allowed_strings.len()
+    }
+
+    fn reorder_choices(&mut self, order: &[usize]) {
+        self.allowed_strings = order
+            .iter()
+            .map(|&i| self.allowed_strings[i].clone())
+            .collect();
+    }
+
     fn result_index_to_primitive(&self, result_index: Option<u32>) -> Result<Option<String>> {
         if let Some(result_index) = result_index {
             if let Some(result) = self.allowed_strings.get(result_index as usize) {
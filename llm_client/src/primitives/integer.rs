@@ -1,11 +1,13 @@
 use super::PrimitiveTrait;
+use crate::components::grammar::{Grammar, IntegerGrammar};
 use crate::workflows::reason::ReasonTrait;
 use anyhow::Result;
-use crate::components::grammar::{Grammar, IntegerGrammar};
 
+#[derive(Clone)]
 pub struct IntegerPrimitive {
     pub lower_bound: u32,
     pub upper_bound: u32,
+    pub radix: u8,
 }
 
 impl Default for IntegerPrimitive {
@@ -13,6 +15,7 @@ impl Default for IntegerPrimitive {
         IntegerPrimitive {
             lower_bound: 0,
             upper_bound: 9999,
+            radix: 10,
         }
     }
 }
@@ -34,10 +37,38 @@ impl IntegerPrimitive {
         self
     }
 
+    /// Set the base of the integer: 2, 10, or 16. Default is 10. The response may include a `0b` or `0x` prefix.
+    /// Bounds are still given as values, e.g. `upper_bound(0xff)`. Errors on any other radix.
+    pub fn radix(&mut self, radix: u8) -> Result<&mut Self> {
+        Grammar::integer().radix(radix as u32)?;
+        self.radix = radix;
+        Ok(self)
+    }
+
     fn grammar_inner(&self) -> IntegerGrammar {
-        Grammar::integer()
-            .lower_bound(self.lower_bound)
-            .upper_bound(self.upper_bound)
+        IntegerGrammar {
+            radix: self.radix as u32,
+            ..Grammar::integer()
+                .lower_bound(self.lower_bound)
+                .upper_bound(self.upper_bound)
+                .enforce_bounds(true)
+        }
+    }
+
+    fn radix_name(&self) -> &str {
+        match self.radix {
+            2 => "binary number",
+            16 => "hexadecimal number",
+            _ => "number",
+        }
+    }
+
+    fn format_bound(&self, bound: u32) -> String {
+        match self.radix {
+            2 => format!("{bound:#b}"),
+            16 => format!("{bound:#x}"),
+            _ => bound.to_string(),
+        }
     }
 }
 
@@ -47,21 +78,27 @@ impl PrimitiveTrait for IntegerPrimitive {
     fn clear_primitive(&mut self) {}
 
     fn type_description(&self, result_can_be_none: bool) -> &str {
-        if result_can_be_none {
-            "number or 'Unknown.'"
-        } else {
-            "number"
+        match (self.radix, result_can_be_none) {
+            (2, true) => "binary number or 'Unknown.'",
+            (16, true) => "hexadecimal number or 'Unknown.'",
+            (_, true) => "number or 'Unknown.'",
+            _ => self.radix_name(),
         }
     }
 
     fn solution_description(&self, result_can_be_none: bool) -> String {
+        let lower_bound = self.format_bound(self.lower_bound);
+        let upper_bound = self.format_bound(self.upper_bound);
         if result_can_be_none {
             format!(
-                "a number between {}-{} or, if the solution is unknown or not in range, 'Unknown.'",
-                self.lower_bound, self.upper_bound
+                "a {} between {lower_bound}-{upper_bound} or, if the solution is unknown or not in range, 'Unknown.'",
+                self.radix_name()
             )
         } else {
-            format!("a number between {}-{}", self.lower_bound, self.upper_bound)
+            format!(
+                "a {} between {lower_bound}-{upper_bound}",
+                self.radix_name()
+            )
         }
     }
 
@@ -96,3 +133,22 @@ impl ReasonTrait for IntegerPrimitive {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut primitive = IntegerPrimitive::default();
+        primitive.lower_bound(3).upper_bound(7);
+        assert!(primitive.grammar().grammar_string().contains("[3-7]"));
+        assert_eq!(primitive.parse_to_primitive("5").unwrap(), 5);
+        assert!(primitive.parse_to_primitive("2").is_err());
+        assert!(primitive.parse_to_primitive("8").is_err());
+
+        primitive.lower_bound(10).upper_bound(99);
+        assert!(primitive.parse_to_primitive("9").is_err());
+        assert!(primitive.grammar().validate_clean(" 100").is_err());
+    }
+}
@@ -1,21 +1,35 @@
 pub mod boolean;
+pub mod email;
 pub mod exact_string;
 pub mod integer;
+pub mod percentage;
+pub mod ranking;
 pub mod sentences;
 pub mod text;
 pub mod text_list;
+pub mod uuid;
 pub mod words;
 
 use crate::components::grammar::Grammar;
 use anyhow::Result;
 pub use boolean::BooleanPrimitive;
+pub use email::EmailPrimitive;
 pub use exact_string::ExactStringPrimitive;
 pub use integer::IntegerPrimitive;
+pub use percentage::PercentagePrimitive;
+pub use ranking::{RankedItem, Ranking, RankingPrimitive};
 pub use sentences::SentencesPrimitive;
 pub use text::TextPrimitive;
 pub use text_list::TextListPrimitive;
+pub use uuid::UuidPrimitive;
 pub use words::WordsPrimitive;
 
+/// The primitive's type name without its module path, e.g. `BooleanPrimitive`. Used as a tracing span field.
+pub(crate) fn primitive_name<P>() -> &'static str {
+    let type_name = std::any::type_name::<P>();
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
 pub trait PrimitiveTrait: Default {
     type PrimitiveResult: std::fmt::Display;
 
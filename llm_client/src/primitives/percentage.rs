@@ -0,0 +1,68 @@
+use super::PrimitiveTrait;
+use crate::components::grammar::{Grammar, PercentageGrammar};
+use anyhow::Result;
+
+/// A percentage, returned as a fraction in `0.0..=1.0`. See [crate::components::grammar::percentage::percentage_parse]
+/// for how values with and without a `%` sign are read.
+#[derive(Clone)]
+pub struct PercentagePrimitive {
+    pub clamp: bool,
+}
+
+impl Default for PercentagePrimitive {
+    fn default() -> Self {
+        PercentagePrimitive { clamp: true }
+    }
+}
+
+impl PercentagePrimitive {
+    /// Whether values above 100% are clamped to `1.0`. If `false`, they're treated as an invalid response. Default is true.
+    pub fn clamp(&mut self, clamp: bool) -> &mut Self {
+        self.clamp = clamp;
+        self
+    }
+
+    fn grammar_inner(&self) -> PercentageGrammar {
+        Grammar::percentage().clamp(self.clamp)
+    }
+}
+
+impl PrimitiveTrait for PercentagePrimitive {
+    type PrimitiveResult = f32;
+
+    fn clear_primitive(&mut self) {}
+
+    fn type_description(&self, result_can_be_none: bool) -> &str {
+        if result_can_be_none {
+            "percentage or 'Not present.'"
+        } else {
+            "percentage"
+        }
+    }
+
+    fn solution_description(&self, result_can_be_none: bool) -> String {
+        if result_can_be_none {
+            "a percentage between 0% and 100%, like '35%', or, if no percentage is present, 'Not present.'"
+                .to_owned()
+        } else {
+            "a percentage between 0% and 100%, like '35%'".to_owned()
+        }
+    }
+
+    fn stop_word_result_is_none(&self, result_can_be_none: bool) -> Option<String> {
+        if result_can_be_none {
+            Some("Not present.".to_string())
+        } else {
+            None
+        }
+    }
+
+    fn grammar(&self) -> Grammar {
+        self.grammar_inner().wrap()
+    }
+
+    fn parse_to_primitive(&self, content: &str) -> Result<Self::PrimitiveResult> {
+        let parsed: Self::PrimitiveResult = self.grammar_inner().grammar_parse(content)?;
+        Ok(parsed)
+    }
+}
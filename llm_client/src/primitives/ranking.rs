@@ -0,0 +1,265 @@
+use super::PrimitiveTrait;
+use crate::components::grammar::{Grammar, RankingGrammar};
+use crate::workflows::reason::ReasonTrait;
+use anyhow::Result;
+
+/// The maximum number of items that can be ranked. Rankings are converted to a result index for
+/// voting in decisions, and 12! is the largest factorial that fits in a u32.
+pub const RANKING_MAX_ITEMS: usize = 12;
+
+#[derive(Default, Debug, Clone)]
+pub struct RankingPrimitive {
+    pub items: Vec<String>,
+}
+
+impl RankingPrimitive {
+    /// Adds each item, skipping duplicates. Returns an error, without adding any of them, if there would be more than
+    /// [RANKING_MAX_ITEMS] items.
+    pub fn add_items<T: AsRef<str>>(&mut self, items: &[T]) -> Result<&mut Self> {
+        let mut new_items: Vec<&str> = vec![];
+        for item in items {
+            let item = item.as_ref();
+            if !self.items.iter().any(|text| text == item) && !new_items.contains(&item) {
+                new_items.push(item);
+            }
+        }
+        if self.items.len() + new_items.len() > RANKING_MAX_ITEMS {
+            anyhow::bail!(
+                "RankingPrimitive can rank at most {RANKING_MAX_ITEMS} items, but {} were given",
+                self.items.len() + new_items.len()
+            );
+        }
+        self.items
+            .extend(new_items.into_iter().map(|item| item.to_owned()));
+        Ok(self)
+    }
+
+    /// Adds an item, skipping duplicates. Returns an error if there are already [RANKING_MAX_ITEMS] items.
+    pub fn add_item<T: AsRef<str>>(&mut self, item: T) -> Result<&mut Self> {
+        self.add_items(&[item])
+    }
+
+    pub fn remove_item<T: AsRef<str>>(&mut self, item: T) -> &mut Self {
+        self.items.retain(|i| i != item.as_ref());
+        self
+    }
+
+    fn grammar_inner(&self) -> RankingGrammar {
+        Grammar::ranking().item_count(self.items.len())
+    }
+
+    fn ranking_from_order(&self, order: &[usize]) -> Ranking {
+        Ranking {
+            ranked_items: order
+                .iter()
+                .enumerate()
+                .map(|(position, &original_index)| RankedItem {
+                    rank: position + 1,
+                    item: self.items[original_index].clone(),
+                    original_index,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PrimitiveTrait for RankingPrimitive {
+    type PrimitiveResult = Ranking;
+
+    fn clear_primitive(&mut self) {
+        self.items.clear();
+    }
+
+    fn type_description(&self, result_can_be_none: bool) -> &str {
+        if result_can_be_none {
+            "ranking or 'No ranking.'"
+        } else {
+            "ranking"
+        }
+    }
+
+    fn solution_description(&self, result_can_be_none: bool) -> String {
+        let items = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| format!("{}: {item}", i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if result_can_be_none {
+            format!(
+                "a ranking of every one of the following items, from first to last, as a comma separated list of their numbers: {items}, or, if they can't be ranked, 'No ranking.'"
+            )
+        } else {
+            format!(
+                "a ranking of every one of the following items, from first to last, as a comma separated list of their numbers: {items}"
+            )
+        }
+    }
+
+    fn stop_word_result_is_none(&self, result_can_be_none: bool) -> Option<String> {
+        if result_can_be_none {
+            Some("No ranking.".to_string())
+        } else {
+            None
+        }
+    }
+
+    fn grammar(&self) -> Grammar {
+        self.grammar_inner().wrap()
+    }
+
+    fn parse_to_primitive(&self, content: &str) -> Result<Self::PrimitiveResult> {
+        let order = self.grammar_inner().grammar_parse(content)?;
+        Ok(self.ranking_from_order(&order))
+    }
+}
+
+impl ReasonTrait for RankingPrimitive {
+    fn primitive_to_result_index(&self, content: &str) -> u32 {
+        let order = self.grammar_inner().grammar_parse(content).unwrap();
+        permutation_to_index(&order)
+    }
+
+    fn result_index_to_primitive(&self, result_index: Option<u32>) -> Result<Option<Ranking>> {
+        if let Some(result_index) = result_index {
+            let order = index_to_permutation(result_index, self.items.len());
+            Ok(Some(self.ranking_from_order(&order)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranking {
+    /// The items in ranked order, first to last.
+    pub ranked_items: Vec<RankedItem>,
+}
+
+impl Ranking {
+    /// The items in ranked order, first to last.
+    pub fn ordered_items(&self) -> Vec<&str> {
+        self.ranked_items
+            .iter()
+            .map(|ranked| ranked.item.as_str())
+            .collect()
+    }
+
+    /// The one-based rank of each item, in the order the items were added.
+    pub fn ranks(&self) -> Vec<usize> {
+        let mut ranks = vec![0; self.ranked_items.len()];
+        for ranked in &self.ranked_items {
+            ranks[ranked.original_index] = ranked.rank;
+        }
+        ranks
+    }
+
+    /// The one-based rank of an item, or None if the item wasn't ranked.
+    pub fn rank_of<T: AsRef<str>>(&self, item: T) -> Option<usize> {
+        self.ranked_items
+            .iter()
+            .find(|ranked| ranked.item == item.as_ref())
+            .map(|ranked| ranked.rank)
+    }
+}
+
+impl std::fmt::Display for Ranking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for ranked in &self.ranked_items {
+            writeln!(f, "{}. {}", ranked.rank, ranked.item)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedItem {
+    /// One-based position in the ranking.
+    pub rank: usize,
+    pub item: String,
+    /// Index of the item in the order it was added.
+    pub original_index: usize,
+}
+
+// Lehmer code: maps each permutation of n items to a unique index in 0..n!
+fn permutation_to_index(order: &[usize]) -> u32 {
+    let mut remaining: Vec<usize> = (0..order.len()).collect();
+    let mut index = 0;
+    for (position, item) in order.iter().enumerate() {
+        let digit = remaining.iter().position(|r| r == item).unwrap();
+        remaining.remove(digit);
+        index += digit as u32 * factorial(order.len() - position - 1);
+    }
+    index
+}
+
+fn index_to_permutation(mut index: u32, item_count: usize) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (0..item_count).collect();
+    let mut order = Vec::with_capacity(item_count);
+    for position in 0..item_count {
+        let place = factorial(item_count - position - 1);
+        order.push(remaining.remove((index / place) as usize));
+        index %= place;
+    }
+    order
+}
+
+fn factorial(n: usize) -> u32 {
+    (1..=n as u32).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permutation_index_round_trip() {
+        assert_eq!(permutation_to_index(&[0, 1, 2]), 0);
+        assert_eq!(permutation_to_index(&[2, 1, 0]), 5);
+        for index in 0..factorial(4) {
+            assert_eq!(permutation_to_index(&index_to_permutation(index, 4)), index);
+        }
+        let order: Vec<usize> = (0..RANKING_MAX_ITEMS).rev().collect();
+        let index = permutation_to_index(&order);
+        assert_eq!(index, factorial(RANKING_MAX_ITEMS) - 1);
+        assert_eq!(index_to_permutation(index, RANKING_MAX_ITEMS), order);
+    }
+
+    #[test]
+    fn test_parse_ranking() {
+        let mut primitive = RankingPrimitive::default();
+        primitive.add_items(&["apple", "banana", "cherry"]).unwrap();
+        let ranking = primitive.parse_to_primitive(" 2, 3, 1").unwrap();
+        assert_eq!(ranking.ordered_items(), vec!["banana", "cherry", "apple"]);
+        assert_eq!(ranking.ranks(), vec![3, 1, 2]);
+        assert_eq!(ranking.rank_of("cherry"), Some(2));
+
+        let index = primitive.primitive_to_result_index(" 2, 3, 1");
+        assert_eq!(
+            primitive.result_index_to_primitive(Some(index)).unwrap(),
+            Some(ranking)
+        );
+        assert!(primitive.parse_to_primitive("2, 2, 1").is_err());
+    }
+
+    #[test]
+    fn test_max_items() {
+        let items: Vec<String> = (1..=RANKING_MAX_ITEMS)
+            .map(|n| format!("item {n}"))
+            .collect();
+        let mut primitive = RankingPrimitive::default();
+        primitive.add_items(&items).unwrap();
+        // Duplicates don't count towards the limit.
+        primitive.add_item("item 1").unwrap();
+        assert_eq!(primitive.items.len(), RANKING_MAX_ITEMS);
+        assert!(primitive.add_item("one too many").is_err());
+
+        let mut primitive = RankingPrimitive::default();
+        primitive
+            .add_items(&items[..RANKING_MAX_ITEMS - 1])
+            .unwrap();
+        assert!(primitive.add_items(&["a", "b"]).is_err());
+        assert_eq!(primitive.items.len(), RANKING_MAX_ITEMS - 1);
+    }
+}
@@ -1,6 +1,7 @@
 use super::PrimitiveTrait;
 use crate::components::grammar::{Grammar, TextGrammar};
 use anyhow::Result;
+#[derive(Clone)]
 pub struct TextPrimitive {
     pub text_token_length: u32,
     pub disallowed_chars: Vec<char>,
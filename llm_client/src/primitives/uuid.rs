@@ -0,0 +1,51 @@
+use super::PrimitiveTrait;
+use crate::components::grammar::{Grammar, UuidGrammar};
+use anyhow::Result;
+
+#[derive(Default, Clone)]
+pub struct UuidPrimitive {}
+
+impl UuidPrimitive {
+    fn grammar_inner(&self) -> UuidGrammar {
+        Grammar::uuid()
+    }
+}
+
+impl PrimitiveTrait for UuidPrimitive {
+    type PrimitiveResult = uuid::Uuid;
+
+    fn clear_primitive(&mut self) {}
+
+    fn type_description(&self, result_can_be_none: bool) -> &str {
+        if result_can_be_none {
+            "UUID or 'Not present.'"
+        } else {
+            "UUID"
+        }
+    }
+
+    fn solution_description(&self, result_can_be_none: bool) -> String {
+        if result_can_be_none {
+            "a UUID in the hyphenated 8-4-4-4-12 hex format or, if no UUID is present, 'Not present.'".to_owned()
+        } else {
+            "a UUID in the hyphenated 8-4-4-4-12 hex format".to_owned()
+        }
+    }
+
+    fn stop_word_result_is_none(&self, result_can_be_none: bool) -> Option<String> {
+        if result_can_be_none {
+            Some("Not present.".to_string())
+        } else {
+            None
+        }
+    }
+
+    fn grammar(&self) -> Grammar {
+        self.grammar_inner().wrap()
+    }
+
+    fn parse_to_primitive(&self, content: &str) -> Result<Self::PrimitiveResult> {
+        let parsed: Self::PrimitiveResult = self.grammar_inner().grammar_parse(content)?;
+        Ok(parsed)
+    }
+}
@@ -1,6 +1,7 @@
 use super::PrimitiveTrait;
 use anyhow::Result;
 use crate::components::grammar::{Grammar, WordsGrammar};
+#[derive(Clone)]
 pub struct WordsPrimitive {
     pub min_count: u8,
     pub max_count: u8,
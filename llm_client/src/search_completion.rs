@@ -0,0 +1,79 @@
+use llm_interface::{
+    llms::LlmBackend,
+    requests::{
+        completion::{Citation, CompletionRequest, CompletionResponse},
+        req_components::{RequestConfig, RequestConfigTrait},
+    },
+};
+use llm_prompt::LlmPrompt;
+
+/// A completion grounded in a web search, for backends with built in search: Perplexity's sonar models and OpenAI's search models.
+/// The sources are returned in the same format for every backend.
+#[derive(Clone)]
+pub struct SearchCompletion {
+    pub base_req: CompletionRequest,
+}
+
+pub struct SearchCompletionResponse {
+    /// The answer, with any thinking block split off into [CompletionResponse::thinking].
+    pub content: String,
+    /// The sources the answer was based on. Empty if the search found nothing to cite.
+    pub citations: Vec<Citation>,
+    pub response: CompletionResponse,
+}
+
+impl SearchCompletion {
+    pub fn new(backend: std::sync::Arc<LlmBackend>) -> Self {
+        Self {
+            base_req: CompletionRequest::new(backend),
+        }
+    }
+
+    pub fn prompt(&mut self) -> &mut LlmPrompt {
+        &mut self.base_req.prompt
+    }
+
+    #[tracing::instrument(
+        name = "llm_client.search_completion",
+        skip_all,
+        fields(backend = self.base_req.backend.backend_name(), model = self.base_req.backend.model_id())
+    )]
+    pub async fn run(&mut self) -> crate::Result<SearchCompletionResponse> {
+        if !self.base_req.backend.supports_search() {
+            crate::bail!(
+                "The {} backend with model {} doesn't have built in web search. Use a Perplexity sonar model or an OpenAI search model, e.g. gpt-4o-search-preview.",
+                self.base_req.backend.backend_name(),
+                self.base_req.backend.model_id()
+            );
+        }
+        let response = self.base_req.request().await?;
+        Ok(SearchCompletionResponse {
+            content: response.content.clone(),
+            citations: response.citations.clone().unwrap_or_default(),
+            response,
+        })
+    }
+}
+
+impl RequestConfigTrait for SearchCompletion {
+    fn config(&mut self) -> &mut RequestConfig {
+        &mut self.base_req.config
+    }
+
+    fn reset_request(&mut self) {
+        self.base_req.reset_completion_request();
+    }
+}
+
+impl std::fmt::Display for SearchCompletionResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "SearchCompletionResponse:")?;
+        writeln!(f, "    content: {:?}", self.content)?;
+        writeln!(f, "    citations:")?;
+        for (i, citation) in self.citations.iter().enumerate() {
+            writeln!(f, "        [{}] {citation}", i + 1)?;
+        }
+        Ok(())
+    }
+}
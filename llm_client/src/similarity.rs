@@ -0,0 +1,192 @@
+//! Vector similarity helpers for the embeddings returned by [crate::LlmClient::embeddings].
+
+/// The cosine similarity of two vectors of the same length, from `-1.0` to `1.0`.
+/// `0.0` if either vector is all zeros.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0_f32, 0.0_f32, 0.0_f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+fn check_dimensions(embeddings: &[Vec<f32>]) -> crate::Result<()> {
+    if let Some(first) = embeddings.first() {
+        if let Some(other) = embeddings.iter().find(|e| e.len() != first.len()) {
+            crate::bail!(
+                "Embeddings have different dimensions: {} and {}",
+                first.len(),
+                other.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The result of [dedupe_by_similarity].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DedupedChunks<T> {
+    /// The chunks that were kept, in their original order.
+    pub chunks: Vec<T>,
+    /// The original index of each kept chunk.
+    pub kept: Vec<usize>,
+    /// For each original chunk, the index in `chunks` of the chunk it was kept as or merged into.
+    pub merged_into: Vec<usize>,
+}
+
+impl<T> DedupedChunks<T> {
+    /// The original indexes of the chunks merged into each kept chunk, not including the kept chunk itself.
+    pub fn merged(&self) -> Vec<Vec<usize>> {
+        let mut merged = vec![Vec::new(); self.chunks.len()];
+        for (index, &into) in self.merged_into.iter().enumerate() {
+            if self.kept[into] != index {
+                merged[into].push(index);
+            }
+        }
+        merged
+    }
+}
+
+/// Removes near-duplicate chunks, e.g. before adding chunks to a prompt, so the same content doesn't use up the context twice.
+///
+/// Chunks are compared in order, and a chunk whose embedding has a cosine similarity of at least `threshold` with a kept chunk
+/// is merged into the most similar kept chunk. So of a group of near-duplicates, the first is kept.
+///
+/// # Arguments
+///
+/// * `chunks` - The chunks, e.g. from `llm_utils`'s text chunker.
+/// * `embeddings` - The embedding of each chunk, in the same order, e.g. from [crate::LlmClient::embeddings].
+/// * `threshold` - The similarity at which chunks are duplicates. Around `0.95` removes reworded and overlapping chunks.
+pub fn dedupe_by_similarity<T: Clone>(
+    chunks: &[T],
+    embeddings: &[Vec<f32>],
+    threshold: f32,
+) -> crate::Result<DedupedChunks<T>> {
+    if chunks.len() != embeddings.len() {
+        crate::bail!(
+            "Got {} chunks but {} embeddings",
+            chunks.len(),
+            embeddings.len()
+        );
+    }
+    check_dimensions(embeddings)?;
+
+    let mut deduped = DedupedChunks {
+        chunks: Vec::new(),
+        kept: Vec::new(),
+        merged_into: Vec::with_capacity(chunks.len()),
+    };
+    for (index, embedding) in embeddings.iter().enumerate() {
+        let most_similar = deduped
+            .kept
+            .iter()
+            .enumerate()
+            .map(|(into, &kept)| (into, cosine_similarity(embedding, &embeddings[kept])))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        match most_similar {
+            Some((into, _)) => deduped.merged_into.push(into),
+            None => {
+                deduped.merged_into.push(deduped.chunks.len());
+                deduped.chunks.push(chunks[index].clone());
+                deduped.kept.push(index);
+            }
+        }
+    }
+    Ok(deduped)
+}
+
+/// The indexes of the `k` chunks most similar to the query, with their cosine similarities, most similar first.
+///
+/// Returns every chunk if `k` is more than the number of chunks. Equally similar chunks are in their original order.
+/// Filter the scores to drop chunks that are in the top `k` but not similar enough to be relevant.
+///
+/// # Arguments
+///
+/// * `query_embedding` - The embedding of the query, e.g. a question.
+/// * `chunk_embeddings` - The embedding of each chunk, from the same model as the query.
+/// * `k` - The most chunks to return.
+pub fn top_k_similar(
+    query_embedding: &[f32],
+    chunk_embeddings: &[Vec<f32>],
+    k: usize,
+) -> crate::Result<Vec<(usize, f32)>> {
+    check_dimensions(chunk_embeddings)?;
+    if let Some(first) = chunk_embeddings.first() {
+        if first.len() != query_embedding.len() {
+            crate::bail!(
+                "The query embedding has {} dimensions but the chunk embeddings have {}",
+                query_embedding.len(),
+                first.len()
+            );
+        }
+    }
+    let mut scores: Vec<(usize, f32)> = chunk_embeddings
+        .iter()
+        .map(|embedding| cosine_similarity(query_embedding, embedding))
+        .enumerate()
+        .collect();
+    // A stable sort keeps ties in their original order.
+    scores.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    scores.truncate(k);
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedupe_by_similarity() {
+        let chunks = ["a", "a reworded", "b", "a again", "c"];
+        let embeddings = vec![
+            vec![1.0, 0.0, 0.0],
+            vec![0.99, 0.1, 0.0],
+            vec![0.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.01],
+            vec![0.0, 0.0, 0.0],
+        ];
+        let deduped = dedupe_by_similarity(&chunks, &embeddings, 0.95).unwrap();
+        assert_eq!(deduped.chunks, vec!["a", "b", "c"]);
+        assert_eq!(deduped.kept, vec![0, 2, 4]);
+        assert_eq!(deduped.merged_into, vec![0, 0, 1, 0, 2]);
+        assert_eq!(deduped.merged(), vec![vec![1, 3], vec![], vec![]]);
+
+        // Nothing is a duplicate above a similarity of 1.
+        let deduped = dedupe_by_similarity(&chunks, &embeddings, 1.01).unwrap();
+        assert_eq!(deduped.chunks.len(), chunks.len());
+
+        assert!(dedupe_by_similarity(&chunks[..2], &embeddings, 0.95).is_err());
+        assert!(dedupe_by_similarity(&chunks[..2], &[vec![1.0], vec![1.0, 0.0]], 0.95).is_err());
+    }
+
+    #[test]
+    fn test_top_k_similar() {
+        let chunk_embeddings = vec![
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+            vec![0.7, 0.7],
+            vec![1.0, 0.0],
+        ];
+        let top = top_k_similar(&[1.0, 0.0], &chunk_embeddings, 3).unwrap();
+        let indexes: Vec<usize> = top.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, vec![1, 3, 2]);
+        assert!((top[0].1 - 1.0).abs() < 1e-6);
+        assert!((top[2].1 - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+        // k past the number of chunks returns them all.
+        assert_eq!(
+            top_k_similar(&[1.0, 0.0], &chunk_embeddings, 10)
+                .unwrap()
+                .len(),
+            4
+        );
+        assert!(top_k_similar(&[1.0, 0.0], &[], 3).unwrap().is_empty());
+        assert!(top_k_similar(&[1.0, 0.0, 0.0], &chunk_embeddings, 3).is_err());
+    }
+}
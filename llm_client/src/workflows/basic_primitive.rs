@@ -1,24 +1,31 @@
 use crate::{
     components::{
-        cascade::{step::StepConfig, CascadeFlow},
+        cascade::{render_prompt, render_prompt_messages, step::StepConfig, CascadeFlow},
         instruct_prompt::InstructPrompt,
         InstructPromptTrait,
     },
     primitives::*,
 };
+use futures::stream::{self, StreamExt};
 use llm_interface::{
     llms::LlmBackend,
     requests::{
-        completion::CompletionRequest,
+        completion::{AggregateUsage, CompletionRequest},
         req_components::{RequestConfig, RequestConfigTrait},
     },
 };
+use llm_prompt::LlmPrompt;
+use std::collections::HashMap;
+
+const DEFAULT_BATCH_CONCURRENCY: usize = 4;
 
 pub struct BasicPrimitiveWorkflow<P> {
     pub primitive: P,
     pub base_req: CompletionRequest,
     pub result_can_be_none: bool,
     pub instruct_prompt: InstructPrompt,
+    /// The most requests [BasicPrimitiveWorkflow::run_batch] sends at once.
+    pub batch_concurrency: usize,
 }
 
 impl<P: PrimitiveTrait> BasicPrimitiveWorkflow<P> {
@@ -28,6 +35,7 @@ impl<P: PrimitiveTrait> BasicPrimitiveWorkflow<P> {
             base_req: CompletionRequest::new(backend),
             result_can_be_none: false,
             instruct_prompt: InstructPrompt::default(),
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
         }
     }
 
@@ -51,23 +59,136 @@ impl<P: PrimitiveTrait> BasicPrimitiveWorkflow<P> {
         }
     }
 
+    #[tracing::instrument(
+        name = "llm_client.basic_primitive",
+        skip_all,
+        fields(primitive = crate::primitives::primitive_name::<P>(), result_can_be_none = false)
+    )]
     pub async fn return_result(&mut self) -> crate::Result<BasicPrimitiveResult> {
         self.result_can_be_none = false;
+        self.base_req.usage = AggregateUsage::default();
         let mut flow = self.basic_primitive()?;
         flow.run_all_rounds(&mut self.base_req).await?;
-        BasicPrimitiveResult::new(flow)
+        let mut res = BasicPrimitiveResult::new(flow)?;
+        res.usage = std::mem::take(&mut self.base_req.usage);
+        Ok(res)
     }
 
+    #[tracing::instrument(
+        name = "llm_client.basic_primitive",
+        skip_all,
+        fields(primitive = crate::primitives::primitive_name::<P>(), result_can_be_none = true)
+    )]
     pub async fn return_optional_result(&mut self) -> crate::Result<BasicPrimitiveResult> {
         self.result_can_be_none = true;
+        self.base_req.usage = AggregateUsage::default();
         let mut flow = self.basic_primitive()?;
         flow.run_all_rounds(&mut self.base_req).await?;
-        BasicPrimitiveResult::new(flow)
+        let mut res = BasicPrimitiveResult::new(flow)?;
+        res.usage = std::mem::take(&mut self.base_req.usage);
+        Ok(res)
+    }
+
+    /// Extracts the primitive from each input, e.g. classifying a list of texts with the same instructions.
+    ///
+    /// Each input is sent as the supporting material of a copy of this workflow, so the primitive, instructions, shared context,
+    /// and request config are configured once and reused. Any supporting material already set is replaced.
+    /// Up to [BasicPrimitiveWorkflowBuilder::batch_concurrency] requests are sent at once, limited to the number of
+    /// requests the backend can serve in parallel, e.g. the llama.cpp server's slots.
+    ///
+    /// # Returns
+    ///
+    /// A result for each input, in the order of the inputs. A failed input doesn't stop the others.
+    #[tracing::instrument(
+        name = "llm_client.basic_primitive.batch",
+        skip_all,
+        fields(primitive = crate::primitives::primitive_name::<P>(), inputs = inputs.len())
+    )]
+    pub async fn run_batch(&self, inputs: Vec<String>) -> Vec<crate::Result<P::PrimitiveResult>>
+    where
+        P: Clone,
+    {
+        let concurrency = self.batch_concurrency().await;
+        stream::iter(inputs.into_iter().map(|input| {
+            let mut workflow = self.batch_workflow(input);
+            async move { workflow.return_primitive().await }
+        }))
+        .buffered(concurrency)
+        .collect()
+        .await
+    }
+
+    /// Like [BasicPrimitiveWorkflow::run_batch], but an input can have no result, as with [BasicPrimitiveWorkflow::return_optional_primitive].
+    #[tracing::instrument(
+        name = "llm_client.basic_primitive.batch",
+        skip_all,
+        fields(primitive = crate::primitives::primitive_name::<P>(), inputs = inputs.len())
+    )]
+    pub async fn run_optional_batch(
+        &self,
+        inputs: Vec<String>,
+    ) -> Vec<crate::Result<Option<P::PrimitiveResult>>>
+    where
+        P: Clone,
+    {
+        let concurrency = self.batch_concurrency().await;
+        stream::iter(inputs.into_iter().map(|input| {
+            let mut workflow = self.batch_workflow(input);
+            async move { workflow.return_optional_primitive().await }
+        }))
+        .buffered(concurrency)
+        .collect()
+        .await
+    }
+
+    async fn batch_concurrency(&self) -> usize {
+        match self.base_req.backend.max_concurrent_requests().await {
+            Some(max_concurrent_requests) => self.batch_concurrency.min(max_concurrent_requests),
+            None => self.batch_concurrency,
+        }
+        .max(1)
+    }
+
+    fn batch_workflow(&self, input: String) -> Self
+    where
+        P: Clone,
+    {
+        let mut workflow = Self {
+            primitive: self.primitive.clone(),
+            base_req: self.base_req.clone(),
+            result_can_be_none: self.result_can_be_none,
+            instruct_prompt: self.instruct_prompt.clone(),
+            batch_concurrency: self.batch_concurrency,
+        };
+        workflow.set_supporting_material(input);
+        workflow
+    }
+
+    /// Returns the prompt [BasicPrimitiveWorkflow::return_result] would send, without making a request.
+    /// Local LLMs get the chat template output, and API LLMs get the messages as JSON.
+    /// Pass `true` to preview [BasicPrimitiveWorkflow::return_optional_result] instead.
+    pub fn preview_prompt(&mut self, result_can_be_none: bool) -> crate::Result<String> {
+        render_prompt(&self.preview_llm_prompt(result_can_be_none)?)
+    }
+
+    /// Returns the messages [BasicPrimitiveWorkflow::preview_prompt] is built from.
+    pub fn preview_prompt_messages(
+        &mut self,
+        result_can_be_none: bool,
+    ) -> crate::Result<Vec<HashMap<String, String>>> {
+        render_prompt_messages(&self.preview_llm_prompt(result_can_be_none)?)
+    }
+
+    fn preview_llm_prompt(&mut self, result_can_be_none: bool) -> crate::Result<LlmPrompt> {
+        self.result_can_be_none = result_can_be_none;
+        self.basic_primitive()?.preview_prompt(&self.base_req)
     }
 
     fn basic_primitive(&mut self) -> crate::Result<CascadeFlow> {
         let mut flow = CascadeFlow::new("BasicPrimitive");
-        let task = self.instruct_prompt.build_instruct_prompt(false)?;
+        let task = self
+            .instruct_prompt
+            .prepend_shared_context(self.instruct_prompt.build_instruct_prompt(false)?);
 
         let step_config = StepConfig {
             step_prefix: Some(format!(
@@ -106,21 +227,46 @@ impl<P: PrimitiveTrait> InstructPromptTrait for BasicPrimitiveWorkflow<P> {
 
 pub struct BasicPrimitiveWorkflowBuilder {
     pub base_req: CompletionRequest,
+    pub shared_context: Option<String>,
+    pub batch_concurrency: usize,
 }
 
 impl BasicPrimitiveWorkflowBuilder {
     pub fn new(backend: std::sync::Arc<LlmBackend>) -> Self {
         Self {
             base_req: CompletionRequest::new(backend),
+            shared_context: None,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
         }
     }
 
+    /// Sets material shared by every request made with the workflow. See [InstructPrompt::shared_context].
+    pub fn shared_context<T: AsRef<str>>(mut self, shared_context: T) -> Self {
+        self.shared_context = Some(shared_context.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the most requests [BasicPrimitiveWorkflow::run_batch] sends at once.
+    /// It's also limited to the number of requests the backend can serve in parallel.
+    ///
+    /// # Default
+    ///
+    /// 4 requests.
+    pub fn batch_concurrency(mut self, batch_concurrency: usize) -> Self {
+        self.batch_concurrency = batch_concurrency.max(1);
+        self
+    }
+
     fn build<P: PrimitiveTrait>(self) -> BasicPrimitiveWorkflow<P> {
         BasicPrimitiveWorkflow {
             primitive: P::default(),
             base_req: self.base_req,
             result_can_be_none: false,
-            instruct_prompt: InstructPrompt::default(),
+            instruct_prompt: InstructPrompt {
+                shared_context: self.shared_context,
+                ..InstructPrompt::default()
+            },
+            batch_concurrency: self.batch_concurrency,
         }
     }
 }
@@ -143,7 +289,10 @@ basic_primitive_workflow_primitive_impl! {
     sentences => SentencesPrimitive,
     words => WordsPrimitive,
     exact_string => ExactStringPrimitive,
-    text_list => TextListPrimitive
+    text_list => TextListPrimitive,
+    uuid => UuidPrimitive,
+    email => EmailPrimitive,
+    percentage => PercentagePrimitive
 }
 
 #[derive(Clone)]
@@ -151,6 +300,8 @@ pub struct BasicPrimitiveResult {
     pub primitive_result: Option<String>,
     pub duration: std::time::Duration,
     pub workflow: CascadeFlow,
+    /// Token, cost, and time totals of every request made by the workflow, including retried requests.
+    pub usage: AggregateUsage,
 }
 
 impl BasicPrimitiveResult {
@@ -159,6 +310,7 @@ impl BasicPrimitiveResult {
             primitive_result: flow.primitive_result(),
             duration: flow.duration,
             workflow: flow,
+            usage: AggregateUsage::default(),
         };
         Ok(reason_result)
     }
@@ -4,7 +4,6 @@ use crate::components::cascade::CascadeFlow;
 use crate::components::grammar::*;
 use crate::LlmClient;
 
-use llm_interface::llms::LlmBackend;
 use llm_interface::requests::completion::CompletionRequest;
 
 use super::tag::Tag;
@@ -25,7 +24,7 @@ impl Critera {
 }
 
 pub struct HierarchicalEntityTagger {
-    backend: std::sync::Arc<LlmBackend>,
+    base_req: CompletionRequest,
     pub entity: String,
     pub input_text: String,
     pub criteria: Critera,
@@ -45,7 +44,7 @@ impl HierarchicalEntityTagger {
         tag_collection: Tag,
     ) -> Self {
         Self {
-            backend: llm_client.backend.clone(),
+            base_req: llm_client.base_request(),
             entity: entity.to_owned(),
             input_text: input_text.to_owned(),
             criteria: criteria.clone(),
@@ -60,7 +59,7 @@ impl HierarchicalEntityTagger {
     pub async fn run(&mut self) -> crate::Result<()> {
         self.start_time = std::time::Instant::now();
         let tag_collection = self.tag_collection.clone();
-        let mut base_req = CompletionRequest::new(self.backend.clone());
+        let mut base_req = self.base_req.clone();
         let flow: CascadeFlow = self.refine_instructions(&mut base_req).await?;
 
         self.evaluate_root_tags(tag_collection, flow, base_req)
@@ -79,14 +79,11 @@ mod test {
             instructions: INSTRUCTIONS.to_owned(),
         };
 
-        let entity = ClassifySubjectOfText::new(
-            CompletionRequest::new(llm_client.backend.clone()),
-            input_text,
-        )
-        .run()
-        .await?
-        .subject
-        .ok_or_else(|| anyhow::anyhow!("Entity not classified from the text."))?;
+        let entity = ClassifySubjectOfText::new(llm_client.base_request(), input_text)
+            .run()
+            .await?
+            .subject
+            .ok_or_else(|| anyhow::anyhow!("Entity not classified from the text."))?;
 
         let mut req = HierarchicalEntityTagger::new(
             &llm_client,
@@ -139,14 +136,11 @@ mod test {
         };
         let mut results = vec![];
         for (input_text, _) in CASES {
-            let entity = ClassifySubjectOfText::new(
-                CompletionRequest::new(llm_client.backend.clone()),
-                input_text,
-            )
-            .run()
-            .await?
-            .subject
-            .ok_or_else(|| anyhow::anyhow!("Entity not classified from the text."))?;
+            let entity = ClassifySubjectOfText::new(llm_client.base_request(), input_text)
+                .run()
+                .await?
+                .subject
+                .ok_or_else(|| anyhow::anyhow!("Entity not classified from the text."))?;
 
             let mut req = HierarchicalEntityTagger::new(
                 &llm_client,
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 const OUTPUT_DIR: &str = "generations";
 const DEFAULT_COLLECTION_NAME: &str = "default_collection";
+const DEFAULT_DESCRIPTION_CONCURRENCY: usize = 4;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagCollection {
@@ -11,6 +12,10 @@ pub struct TagCollection {
     pub from_string: Option<String>,
     pub output_dir_path: std::path::PathBuf,
     pub tag_path_seperator: String,
+    #[serde(skip)]
+    pub force_regenerate: bool,
+    #[serde(skip, default = "default_description_concurrency")]
+    pub description_concurrency: usize,
     contents: String,
     pub root_tag: Option<Tag>,
 }
@@ -23,6 +28,8 @@ impl Default for TagCollection {
             from_string: None,
             output_dir_path: OUTPUT_DIR.into(),
             tag_path_seperator: ":".into(),
+            force_regenerate: false,
+            description_concurrency: DEFAULT_DESCRIPTION_CONCURRENCY,
             contents: String::new(),
             root_tag: None,
         }
@@ -52,8 +59,8 @@ impl TagCollection {
         self
     }
 
-    pub fn output_dir_path<P: AsRef<std::path::PathBuf>>(mut self, output_dir_path: P) -> Self {
-        self.output_dir_path = output_dir_path.as_ref().to_owned();
+    pub fn output_dir_path<P: Into<std::path::PathBuf>>(mut self, output_dir_path: P) -> Self {
+        self.output_dir_path = output_dir_path.into();
         self
     }
 
@@ -62,6 +69,20 @@ impl TagCollection {
         self
     }
 
+    /// Regenerate descriptions for tags that already have one when calling [TagCollection::populate_descriptions].
+    /// By default, tags with a description are skipped, so an interrupted run resumes where it stopped.
+    pub fn force_regenerate(mut self, force_regenerate: bool) -> Self {
+        self.force_regenerate = force_regenerate;
+        self
+    }
+
+    /// The maximum number of tags described at once by [TagCollection::populate_descriptions]. Defaults to 4.
+    /// The limit is further capped by the number of requests the backend can process in parallel, such as the llama.cpp server's slot count.
+    pub fn description_concurrency(mut self, description_concurrency: usize) -> Self {
+        self.description_concurrency = description_concurrency.max(1);
+        self
+    }
+
     pub fn load(self) -> crate::Result<Self> {
         match (
             &self.collection_name,
@@ -88,6 +109,9 @@ impl TagCollection {
         }
     }
 
+    /// Generates descriptions for the top level tags. Tags that already have a description are skipped unless
+    /// [TagCollection::force_regenerate] is set. Tags are described concurrently, up to [TagCollection::description_concurrency],
+    /// and the collection is saved as each tag's subtree is described.
     pub async fn populate_descriptions(
         &mut self,
         llm_client: &crate::LlmClient,
@@ -192,14 +216,18 @@ impl TagCollection {
 
         match std::fs::read_to_string(&file_path) {
             Ok(json_content) => {
-                let loaded_data: Self = serde_json::from_str(&json_content)?;
+                let mut loaded_data: Self = serde_json::from_str(&json_content)?;
+                // Settings from the caller take precedence over those saved with the collection.
+                loaded_data.output_dir_path = self.output_dir_path.clone();
+                loaded_data.force_regenerate = self.force_regenerate;
+                loaded_data.description_concurrency = self.description_concurrency;
                 Ok(Some(loaded_data))
             }
             Err(_) => Ok(None),
         }
     }
 
-    fn save_as_json(&self) -> crate::Result<()> {
+    pub(super) fn save_as_json(&self) -> crate::Result<()> {
         let collection_name = if let Some(collection_name) = &self.collection_name {
             collection_name.to_owned()
         } else {
@@ -208,7 +236,7 @@ impl TagCollection {
         let filename = format!("{collection_name}.json");
 
         // Create a PathBuf for the directory
-        let mut file_path = std::path::PathBuf::from(OUTPUT_DIR);
+        let mut file_path = self.output_dir_path.clone();
 
         // Create the directory if it doesn't exist
         std::fs::create_dir_all(&file_path)?;
@@ -231,6 +259,10 @@ impl TagCollection {
     }
 }
 
+fn default_description_concurrency() -> usize {
+    DEFAULT_DESCRIPTION_CONCURRENCY
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,6 +308,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_tag_collection_custom_output_dir_round_trip() -> crate::Result<()> {
+        let output_dir_path = std::env::temp_dir().join(format!(
+            "llm_client_tag_collection_test_{}",
+            std::process::id()
+        ));
+        let collection = TagCollection::default()
+            .collection_name("custom_dir_collection")
+            .from_string("aquatic\naquatic:fresh water\nterrestrial")
+            .output_dir_path(&output_dir_path)
+            .load()?;
+        let saved_path = output_dir_path.join("custom_dir_collection.json");
+        assert!(saved_path.exists());
+        assert!(!std::path::Path::new(OUTPUT_DIR)
+            .join("custom_dir_collection.json")
+            .exists());
+
+        let reloaded = TagCollection::default()
+            .collection_name("custom_dir_collection")
+            .output_dir_path(&output_dir_path)
+            .force_regenerate(true)
+            .load()?;
+        assert_eq!(reloaded.contents, collection.contents);
+        assert!(reloaded.force_regenerate);
+        assert!(reloaded.get_root_tag()?.get_tag("aquatic").is_some());
+
+        std::fs::remove_dir_all(&output_dir_path)?;
+        Ok(())
+    }
+
     #[test]
     #[ignore]
     fn test_tag_collection_creation_from_file() -> crate::Result<()> {
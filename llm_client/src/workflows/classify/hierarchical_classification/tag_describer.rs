@@ -3,6 +3,7 @@ use crate::components::cascade::CascadeFlow;
 use crate::components::grammar::NoneGrammar;
 use crate::LlmClient;
 
+use futures::stream::{self, StreamExt};
 use llm_interface::requests::completion::CompletionRequest;
 use serde::{Deserialize, Serialize};
 
@@ -20,14 +21,49 @@ impl TagCollectionDescriber {
         criteria: &str,
         tag_collection: &mut TagCollection,
     ) -> crate::Result<()> {
-        let mut describer = Self {
-            base_req: CompletionRequest::new(llm_client.backend.clone()),
-            criteria: criteria.to_owned(),
-            flow: CascadeFlow::new("TagDescription"),
-        };
         let mut root_tag = tag_collection.get_root_tag()?;
-        for tag in root_tag.tags.values_mut() {
-            describer.describe_tag(tag).await?;
+        let mut tag_names: Vec<String> = root_tag.tags.keys().cloned().collect();
+        tag_names.sort();
+        let mut pending_tags = Vec::new();
+        for tag_name in tag_names {
+            let mut tag = root_tag.tags.get(&tag_name).unwrap().clone();
+            if tag.description.is_some() {
+                if !tag_collection.force_regenerate {
+                    continue;
+                }
+                tag.description = None;
+            }
+            pending_tags.push((tag_name, tag));
+        }
+
+        // Each top level tag is independent, so they can be described in parallel, up to what the backend can serve.
+        let concurrency = match llm_client.backend.max_concurrent_requests().await {
+            Some(max_concurrent_requests) => tag_collection
+                .description_concurrency
+                .min(max_concurrent_requests),
+            None => tag_collection.description_concurrency,
+        }
+        .max(1);
+        let mut described_tags =
+            stream::iter(pending_tags.into_iter().map(|(tag_name, mut tag)| {
+                let mut describer = Self {
+                    base_req: llm_client.base_request(),
+                    criteria: criteria.to_owned(),
+                    flow: CascadeFlow::new("TagDescription"),
+                };
+                async move {
+                    describer.describe_tag(&mut tag).await?;
+                    Ok::<_, crate::Error>((tag_name, tag))
+                }
+            }))
+            .buffer_unordered(concurrency);
+
+        while let Some(described_tag) = described_tags.next().await {
+            let (tag_name, tag) = described_tag?;
+            root_tag.tags.insert(tag_name, tag);
+            // Saving after each subtree lets an interrupted run resume from here.
+            tag_collection.root_tag = Some(root_tag.clone());
+            tag_collection.save_as_json()?;
         }
         tag_collection.root_tag = Some(root_tag);
         Ok(())
@@ -5,15 +5,17 @@ pub mod hierarchical_classification;
 pub mod subject_of_text;
 
 pub struct Classify {
-    backend: std::sync::Arc<LlmBackend>,
+    pub base_req: CompletionRequest,
 }
 
 impl Classify {
     pub fn new(backend: std::sync::Arc<LlmBackend>) -> Self {
-        Self { backend }
+        Self {
+            base_req: CompletionRequest::new(backend),
+        }
     }
 
     pub fn subject_of_text<T: AsRef<str>>(self, content: T) -> ClassifySubjectOfText {
-        ClassifySubjectOfText::new(CompletionRequest::new(self.backend), content)
+        ClassifySubjectOfText::new(self.base_req, content)
     }
 }
@@ -243,10 +243,7 @@ mod test {
         let llm_client = LlmClient::llama_cpp().llama3_1_8b_instruct().init().await?;
 
         for (case, answer) in CASES {
-            let entity = ClassifySubjectOfText::new(
-                CompletionRequest::new(llm_client.backend.clone()),
-                case,
-            );
+            let entity = ClassifySubjectOfText::new(llm_client.base_request(), case);
             let entity = entity.run().await?;
             println!("{}", entity.flow);
             println!("{}", entity);
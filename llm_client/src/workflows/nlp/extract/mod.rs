@@ -1,5 +1,6 @@
 use llm_interface::requests::completion::CompletionRequest;
 
+pub mod pii;
 pub mod urls;
 
 pub struct Extract {
@@ -14,4 +15,27 @@ impl Extract {
     pub fn urls(self) -> urls::ExtractUrls {
         urls::ExtractUrls::new(self.base_req)
     }
+
+    pub fn pii(self, kinds: &[pii::PiiKind]) -> pii::ExtractPii {
+        pii::ExtractPii::new(self.base_req, kinds)
+    }
+}
+
+/// The sentence or line around the `len` bytes at `start`, trimmed.
+/// A period only ends a sentence when followed by whitespace, so the dots in URLs, emails, and IP addresses don't.
+pub(super) fn sentence_context(text: &str, start: usize, len: usize) -> String {
+    let is_boundary = |window: &[u8]| {
+        window[0] == b'\n'
+            || (matches!(window[0], b'.' | b'!' | b'?') && window[1].is_ascii_whitespace())
+    };
+    let before = text.as_bytes()[..=start]
+        .windows(2)
+        .rposition(is_boundary)
+        .map_or(0, |i| i + 1);
+    let after_start = start + len;
+    let after = text.as_bytes()[after_start..]
+        .windows(2)
+        .position(is_boundary)
+        .map_or(text.len(), |i| after_start + i + 1);
+    text[before..after].trim().to_owned()
 }
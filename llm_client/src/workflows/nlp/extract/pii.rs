@@ -0,0 +1,321 @@
+use super::sentence_context;
+use crate::{
+    components::instruct_prompt::{InstructPrompt, InstructPromptTrait},
+    primitives::*,
+    workflows::basic_primitive::BasicPrimitiveWorkflowBuilder,
+};
+use anyhow::Result;
+use llm_interface::requests::{
+    completion::{AggregateUsage, CompletionRequest},
+    req_components::{RequestConfig, RequestConfigTrait},
+};
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::LazyLock,
+};
+
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b").unwrap());
+static IPV4: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap());
+static IPV6: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(?:[0-9a-f]{0,4}:){2,7}[0-9a-f]{0,4}").unwrap());
+static PHONE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)|\d{2,4})(?:[\s.-]?\d{2,4}){1,4}").unwrap()
+});
+
+/// A kind of personally identifiable information found by [ExtractPii].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PiiKind {
+    Email,
+    Phone,
+    /// An IPv4 or IPv6 address.
+    IpAddress,
+}
+
+impl PiiKind {
+    pub const ALL: [PiiKind; 3] = [PiiKind::Email, PiiKind::Phone, PiiKind::IpAddress];
+
+    /// Look-alikes the regex matches that the LLM should reject.
+    fn false_positives(&self) -> &'static str {
+        match self {
+            PiiKind::Email => "a placeholder like user@example.com, or part of a code snippet",
+            PiiKind::Phone => {
+                "a date, an order, account, or serial number, a version number, or a measurement"
+            }
+            PiiKind::IpAddress => "a version number, a section number, or an OID",
+        }
+    }
+}
+
+impl std::fmt::Display for PiiKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PiiKind::Email => write!(f, "email address"),
+            PiiKind::Phone => write!(f, "phone number"),
+            PiiKind::IpAddress => write!(f, "IP address"),
+        }
+    }
+}
+
+/// A match of a [PiiKind] in the text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PiiSpan {
+    pub kind: PiiKind,
+    /// The matched text, exactly as it appears.
+    pub text: String,
+    /// The offset of the match's first character in the text, counted in characters rather than bytes.
+    pub char_offset: usize,
+    /// The sentence or line the match appears in, trimmed.
+    pub context: String,
+}
+
+/// Finds emails, phone numbers, and IP addresses in the supporting material.
+///
+/// Regex finds every well-formed candidate, so nothing that looks like PII is missed, and then the LLM is asked whether
+/// each candidate really is one, to drop false positives like version numbers that parse as IP addresses.
+/// The text is sent as shared context, so the backend can reuse its prompt cache across the candidates.
+/// Instructions, if set, are added to each question, e.g. to describe which addresses count as placeholders.
+#[derive(Clone)]
+pub struct ExtractPii {
+    pub base_req: CompletionRequest,
+    pub instruct_prompt: InstructPrompt,
+    pub kinds: Vec<PiiKind>,
+}
+
+impl ExtractPii {
+    pub fn new(base_req: CompletionRequest, kinds: &[PiiKind]) -> Self {
+        ExtractPii {
+            base_req,
+            instruct_prompt: InstructPrompt::new(),
+            kinds: kinds.to_vec(),
+        }
+    }
+
+    /// The regex matches in the supporting material, before the LLM checks them, in the order they appear.
+    /// Doesn't make any requests.
+    pub fn candidates(&self) -> Result<Vec<PiiSpan>> {
+        match self.instruct_prompt.build_supporting_material() {
+            Some(text) => Ok(find_candidates(&text, &self.kinds)),
+            None => Err(anyhow::anyhow!("No text to extract PII from.")),
+        }
+    }
+
+    pub async fn run_return_spans(&mut self) -> Result<Vec<PiiSpan>> {
+        Ok(self.run_return_result().await?.spans)
+    }
+
+    #[tracing::instrument(name = "llm_client.extract_pii", skip_all, fields(kinds = ?self.kinds))]
+    pub async fn run_return_result(&mut self) -> Result<ExtractPiiResult> {
+        let start = std::time::Instant::now();
+        let text = match self.instruct_prompt.build_supporting_material() {
+            Some(text) => text,
+            None => return Err(anyhow::anyhow!("No text to extract PII from.")),
+        };
+        let instructions = self.instruct_prompt.build_instructions();
+        let mut workflow = BasicPrimitiveWorkflowBuilder {
+            base_req: self.base_req.clone(),
+            shared_context: Some(text.clone()),
+            batch_concurrency: 1,
+        }
+        .boolean();
+
+        let mut usage = AggregateUsage::default();
+        let mut verdicts: HashMap<(PiiKind, String), bool> = HashMap::new();
+        let mut spans = Vec::new();
+        let mut rejected = Vec::new();
+        for candidate in find_candidates(&text, &self.kinds) {
+            // The same match elsewhere in the text gets the same answer.
+            let key = (candidate.kind, candidate.text.clone());
+            let confirmed = match verdicts.get(&key) {
+                Some(confirmed) => *confirmed,
+                None => {
+                    let mut question = format!(
+                        "Is `{}` a real {} in the shared supporting material? Answer false if it only looks like one, e.g. {}.",
+                        candidate.text,
+                        candidate.kind,
+                        candidate.kind.false_positives()
+                    );
+                    if let Some(instructions) = &instructions {
+                        question.push_str(&format!("\nAdditional instructions: {instructions}"));
+                    }
+                    workflow.set_instructions(question);
+                    workflow
+                        .set_supporting_material(format!("It appears in: {}", candidate.context));
+                    let res = workflow.return_result().await?;
+                    usage.merge(&res.usage);
+                    let confirmed = match res.primitive_result {
+                        Some(result) => workflow.primitive.parse_to_primitive(&result)?,
+                        None => false,
+                    };
+                    verdicts.insert(key, confirmed);
+                    confirmed
+                }
+            };
+            if confirmed {
+                spans.push(candidate);
+            } else {
+                rejected.push(candidate);
+            }
+        }
+
+        Ok(ExtractPiiResult {
+            spans,
+            rejected,
+            duration: start.elapsed(),
+            usage,
+        })
+    }
+}
+
+impl RequestConfigTrait for ExtractPii {
+    fn config(&mut self) -> &mut RequestConfig {
+        &mut self.base_req.config
+    }
+
+    fn reset_request(&mut self) {
+        self.instruct_prompt.reset_instruct_prompt();
+        self.base_req.reset_completion_request();
+    }
+}
+
+impl InstructPromptTrait for ExtractPii {
+    fn instruct_prompt_mut(&mut self) -> &mut InstructPrompt {
+        &mut self.instruct_prompt
+    }
+}
+
+#[derive(Clone)]
+pub struct ExtractPiiResult {
+    /// The matches the LLM confirmed, in the order they appear.
+    pub spans: Vec<PiiSpan>,
+    /// The matches the LLM rejected as false positives, in the order they appear.
+    pub rejected: Vec<PiiSpan>,
+    pub duration: std::time::Duration,
+    /// Token, cost, and time totals of every request made by the workflow, including retried requests.
+    pub usage: AggregateUsage,
+}
+
+impl std::fmt::Display for ExtractPiiResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "\x1b[38;5;45m\x1b[1mExtractPii\x1b[0m")?;
+        writeln!(f)?;
+        for span in &self.spans {
+            writeln!(
+                f,
+                "\x1b[38;5;42m{}\x1b[0m: {} (at {}): {:?}",
+                span.kind, span.text, span.char_offset, span.context
+            )?;
+        }
+        for span in &self.rejected {
+            writeln!(
+                f,
+                "\x1b[38;5;196mrejected {}\x1b[0m: {} (at {}): {:?}",
+                span.kind, span.text, span.char_offset, span.context
+            )?;
+        }
+        writeln!(f, "\x1b[38;5;43mduration\x1b[0m: {:?}", self.duration)?;
+        writeln!(f, "\x1b[38;5;43musage\x1b[0m: {}", self.usage)?;
+        Ok(())
+    }
+}
+
+/// True if the match at `start..end` isn't part of a longer word, number, or address.
+/// A period after the match only continues it if it's followed by more of it, rather than ending a sentence.
+fn stands_alone(text: &str, start: usize, end: usize) -> bool {
+    let continues = |c: char| c.is_alphanumeric() || matches!(c, ':' | '@');
+    let before = text[..start].chars().next_back();
+    let mut after = text[end..].chars();
+    let after = match after.next() {
+        Some('.') => after.next(),
+        next => next,
+    };
+    !before.is_some_and(|c| continues(c) || matches!(c, '.' | '+')) && !after.is_some_and(continues)
+}
+
+/// The well-formed matches of each of `kinds` in `text`, in the order they appear.
+///
+/// Overlapping matches go to the most specific kind, email then IP address then phone number, whether or not that kind
+/// was asked for, so the digits of an IP address are never reported as a phone number.
+fn find_candidates(text: &str, kinds: &[PiiKind]) -> Vec<PiiSpan> {
+    let mut found: Vec<(usize, usize, PiiKind)> = Vec::new();
+    let mut add = |start: usize, end: usize, kind: PiiKind| {
+        if !found.iter().any(|(s, e, _)| start < *e && *s < end) {
+            found.push((start, end, kind));
+        }
+    };
+    for m in EMAIL.find_iter(text) {
+        add(m.start(), m.end(), PiiKind::Email);
+    }
+    for m in IPV4.find_iter(text) {
+        if m.as_str().parse::<Ipv4Addr>().is_ok() && stands_alone(text, m.start(), m.end()) {
+            add(m.start(), m.end(), PiiKind::IpAddress);
+        }
+    }
+    for m in IPV6.find_iter(text) {
+        let is_address = m.as_str().parse::<Ipv6Addr>().is_ok()
+            && m.as_str().chars().any(|c| c.is_ascii_hexdigit());
+        if is_address && stands_alone(text, m.start(), m.end()) {
+            add(m.start(), m.end(), PiiKind::IpAddress);
+        }
+    }
+    for m in PHONE.find_iter(text) {
+        let digits = m.as_str().chars().filter(char::is_ascii_digit).count();
+        if (7..=15).contains(&digits) && stands_alone(text, m.start(), m.end()) {
+            add(m.start(), m.end(), PiiKind::Phone);
+        }
+    }
+
+    found.sort_by_key(|(start, _, _)| *start);
+    found
+        .into_iter()
+        .filter(|(_, _, kind)| kinds.contains(kind))
+        .map(|(start, end, kind)| PiiSpan {
+            kind,
+            text: text[start..end].to_owned(),
+            char_offset: text[..start].chars().count(),
+            context: sentence_context(text, start, end - start),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_candidates() {
+        let text = "Email jane.doe@example.org or call +1 (555) 123-4567.\nThe server at 10.0.0.12 runs version 1.2.3.4, and fe80::1ff:fe23:4567:890a is its link-local address.\nOrder 12 shipped. Not an IP: 999.1.1.1.";
+        let candidates = find_candidates(text, &PiiKind::ALL);
+        let found: Vec<(PiiKind, &str)> = candidates
+            .iter()
+            .map(|span| (span.kind, span.text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (PiiKind::Email, "jane.doe@example.org"),
+                (PiiKind::Phone, "+1 (555) 123-4567"),
+                (PiiKind::IpAddress, "10.0.0.12"),
+                (PiiKind::IpAddress, "1.2.3.4"),
+                (PiiKind::IpAddress, "fe80::1ff:fe23:4567:890a"),
+            ]
+        );
+        assert_eq!(
+            candidates[2].context,
+            "The server at 10.0.0.12 runs version 1.2.3.4, and fe80::1ff:fe23:4567:890a is its link-local address."
+        );
+
+        // Only the asked for kinds, and the IP address's digits aren't a phone number.
+        let candidates = find_candidates(text, &[PiiKind::Phone]);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].text, "+1 (555) 123-4567");
+
+        // Offsets count characters, not bytes.
+        let candidates = find_candidates("Café → a@b.io", &[PiiKind::Email]);
+        assert_eq!(candidates[0].char_offset, 7);
+    }
+}
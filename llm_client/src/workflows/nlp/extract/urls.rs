@@ -1,5 +1,7 @@
+use super::sentence_context;
 use crate::{
     components::{
+        cascade::{render_prompt, render_prompt_messages},
         cascade::{step::StepConfig, CascadeFlow},
         instruct_prompt::{InstructPrompt, InstructPromptTrait},
     },
@@ -7,10 +9,11 @@ use crate::{
 };
 use anyhow::Result;
 use llm_interface::requests::{
-    completion::CompletionRequest,
+    completion::{AggregateUsage, CompletionRequest},
     req_components::{RequestConfig, RequestConfigTrait},
 };
 use llm_utils::extract::extract_urls;
+use std::collections::HashMap;
 use url::Url;
 
 #[derive(Clone)]
@@ -42,13 +45,22 @@ impl ExtractUrls {
         Ok(self.run_return_result().await?.results)
     }
 
+    /// Extracts URLs from the supporting material that satisfy the instructions.
+    /// Returns an error without making any requests if the supporting material isn't set.
+    #[tracing::instrument(name = "llm_client.extract_urls", skip_all, fields(max_urls = self.max_urls))]
     pub async fn run_return_result(&mut self) -> Result<ExtractUrlResult> {
+        let supporting_material = self.supporting_material_text()?;
+        self.base_req.usage = AggregateUsage::default();
         let flow = self.run_backend().await?;
+        let usage = std::mem::take(&mut self.base_req.usage);
+        let extracted = locate_urls(&supporting_material, &self.results);
         if self.results.is_empty() {
             Ok(ExtractUrlResult::new(
                 flow,
                 None,
+                extracted,
                 self.criteria.as_ref().unwrap(),
+                usage,
             ))
         } else {
             Ok(ExtractUrlResult::new(
@@ -59,11 +71,19 @@ impl ExtractUrls {
                         .map(|url| Url::parse(url).unwrap())
                         .collect(),
                 ),
+                extracted,
                 self.criteria.as_ref().unwrap(),
+                usage,
             ))
         }
     }
 
+    fn supporting_material_text(&self) -> Result<String> {
+        self.instruct_prompt
+            .build_supporting_material()
+            .ok_or_else(|| anyhow::anyhow!("No supporting material to extract URLs from."))
+    }
+
     async fn run_backend(&mut self) -> Result<CascadeFlow> {
         let mut primitive = ExactStringPrimitive::default();
 
@@ -86,17 +106,31 @@ impl ExtractUrls {
         Ok(flow)
     }
 
-    async fn set_criteria(&mut self) -> Result<CascadeFlow> {
+    /// Returns the prompt of the request that asks the LLM to describe the extraction criteria, without making any requests.
+    /// Later prompts are built from the LLM's responses, so they can't be previewed.
+    /// Local LLMs get the chat template output, and API LLMs get the messages as JSON.
+    pub fn preview_prompt(&self) -> Result<String> {
+        render_prompt(&self.criteria_flow()?.preview_prompt(&self.base_req)?)
+    }
+
+    /// Returns the messages [ExtractUrls::preview_prompt] is built from.
+    pub fn preview_prompt_messages(&self) -> Result<Vec<HashMap<String, String>>> {
+        render_prompt_messages(&self.criteria_flow()?.preview_prompt(&self.base_req)?)
+    }
+
+    fn criteria_flow(&self) -> Result<CascadeFlow> {
+        let instructions = match self.instruct_prompt.build_instructions() {
+            Some(instructions) => instructions,
+            None => return Err(anyhow::anyhow!("No instructions provided.")),
+        };
         let mut flow = CascadeFlow::new("ExtractUrls");
-        flow.open_cascade();
         flow.new_round(
             "We are extracting URLs from text. Please provide examples of extracting URLs with the instructions: 'Which of these URLs are commonly used in webdev tutorials?'").add_guidance_step(
             &StepConfig::default(),
             "`https://www.example.com is commonly used in webdev tutorials: true.` In this example, the URL satisfies the criteria: 'is commonly used in webdev tutorials.' Therefore, the URL should be extracted from the text.\n`https://www.zombo.com is commonly used in webdev tutorials: false.`. In this example, the URL does not satisfy the criteria: 'is commonly used in webdev tutorials.' Therefore, the URL should not be extracted from the text.",
         );
-        flow.last_round()?.run_all_steps(&mut self.base_req).await?;
 
-        let initial_qualities_task = format!("We are extracting URLs from text using the instructions:\n{} Briefly describe the criteria of the URLs to be extracted.", self.instruct_prompt.build_instructions().unwrap());
+        let initial_qualities_task = format!("We are extracting URLs from text using the instructions:\n{instructions} Briefly describe the criteria of the URLs to be extracted.");
         let config = StepConfig {
             step_prefix: Some("Criteria: ".to_owned()),
             grammar: TextPrimitive::default().text_token_length(200).grammar(),
@@ -104,7 +138,12 @@ impl ExtractUrls {
         };
         flow.new_round(initial_qualities_task)
             .add_inference_step(&config);
-        flow.last_round()?.run_all_steps(&mut self.base_req).await?;
+        Ok(flow)
+    }
+
+    async fn set_criteria(&mut self) -> Result<CascadeFlow> {
+        let mut flow = self.criteria_flow()?;
+        flow.run_all_rounds(&mut self.base_req).await?;
 
         let refine_criteria_task = format!("Reframe the instructions and criteria into a statment used to evaluate if a URL should be extracted. This statement should have a boolean answer. The answer should represent whether or not the URL satisfies the criteria. This should be a single sentence 'is' statment; as in, 'The URL is <criteria>: true or false'.\nCriteria:\n{}\nInstructions:\n{}", flow.primitive_result().unwrap(), self.instruct_prompt.build_instructions().unwrap());
         let config = StepConfig {
@@ -189,7 +228,7 @@ impl ExtractUrls {
         flow: &mut CascadeFlow,
         primitive: &mut ExactStringPrimitive,
     ) -> Result<()> {
-        let task = format!("Text with URLs to extract:\n{}\nReturn the URL that is most likely relevant to the criteria. If you are certain the text contains no qualifying URLs say 'No qualifying URLs.'.\nCriteria:\n This URL is {}.",self.instruct_prompt.build_supporting_material().unwrap(), self.criteria.as_ref().unwrap());
+        let task = format!("Text with URLs to extract:\n{}\nReturn the URL that is most likely relevant to the criteria. If you are certain the text contains no qualifying URLs say 'No qualifying URLs.'.\nCriteria:\n This URL is {}.",self.supporting_material_text()?, self.criteria.as_ref().unwrap());
         flow.new_round(task).step_separator = None;
         flow.last_round()?.open_round(&mut self.base_req)?;
         for i in 1..=primitive.allowed_strings.len() {
@@ -244,20 +283,99 @@ impl InstructPromptTrait for ExtractUrls {
 #[derive(Clone)]
 pub struct ExtractUrlResult {
     pub results: Option<Vec<Url>>,
+    /// The extracted URLs in the order they were extracted, each with where it appears in the supporting material.
+    /// A URL found more than once is listed once, at its first appearance. Only the supporting material is searched,
+    /// so a URL that appears only in the instructions is in `results` but not here.
+    pub extracted: Vec<ExtractedUrl>,
     pub criteria: String,
     pub duration: std::time::Duration,
     pub workflow: CascadeFlow,
+    /// Token, cost, and time totals of every request made by the workflow, including retried requests.
+    pub usage: AggregateUsage,
 }
 
 impl ExtractUrlResult {
-    fn new(flow: CascadeFlow, results: Option<Vec<Url>>, criteria: &str) -> Self {
+    fn new(
+        flow: CascadeFlow,
+        results: Option<Vec<Url>>,
+        extracted: Vec<ExtractedUrl>,
+        criteria: &str,
+        usage: AggregateUsage,
+    ) -> Self {
         ExtractUrlResult {
             results,
+            extracted,
             criteria: criteria.to_owned(),
             duration: flow.duration,
             workflow: flow,
+            usage,
+        }
+    }
+}
+
+impl ExtractUrlResult {
+    /// The extracted URLs as strings, in the order they were extracted, including any found only in the instructions.
+    pub fn urls(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .flatten()
+            .map(|url| url.to_string())
+            .collect()
+    }
+}
+
+/// An extracted URL and where it appears in the supporting material, e.g. for highlighting links in a review UI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractedUrl {
+    /// The URL, parsed and normalized by the `url` crate, so it's always well-formed.
+    pub url: Url,
+    /// The sentence or line the URL appears in, trimmed.
+    pub context: String,
+    /// The offset of the URL's first character in the supporting material, counted in characters rather than bytes.
+    pub char_offset: usize,
+}
+
+/// Characters that end a URL in plain text, markdown, or HTML.
+fn ends_url(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '`' | '|' | '{' | '}' | '^')
+}
+
+/// Finds the first appearance of each of `urls` in `text`, in the order of `urls`. URLs are compared after parsing,
+/// so `https://example.com` in the text matches `https://example.com/`. URLs that don't appear in `text` are left out.
+fn locate_urls(text: &str, urls: &[String]) -> Vec<ExtractedUrl> {
+    let mut found: Vec<(usize, usize, Url)> = Vec::new();
+    let mut search_from = 0;
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| text[search_from..].find(scheme))
+        .min()
+        .map(|start| search_from + start)
+    {
+        let end = text[start..]
+            .find(ends_url)
+            .map_or(text.len(), |end| start + end);
+        // Punctuation after a URL usually belongs to the sentence.
+        let raw = text[start..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']']);
+        if let Ok(url) = Url::parse(raw) {
+            found.push((start, raw.len(), url));
         }
+        search_from = end.max(start + 1);
     }
+
+    let mut extracted: Vec<ExtractedUrl> = Vec::new();
+    for url in urls.iter().filter_map(|url| Url::parse(url).ok()) {
+        if extracted.iter().any(|extracted| extracted.url == url) {
+            continue;
+        }
+        if let Some((start, len, _)) = found.iter().find(|(_, _, found_url)| *found_url == url) {
+            extracted.push(ExtractedUrl {
+                context: sentence_context(text, *start, *len),
+                char_offset: text[..*start].chars().count(),
+                url,
+            });
+        }
+    }
+    extracted
 }
 
 impl std::fmt::Display for ExtractUrlResult {
@@ -273,8 +391,60 @@ impl std::fmt::Display for ExtractUrlResult {
             writeln!(f, "\x1b[38;5;44mRound {}\x1b[0m", i + 1)?;
             writeln!(f, "{round}",)?;
         }
+        for extracted in &self.extracted {
+            writeln!(
+                f,
+                "\x1b[38;5;42murl\x1b[0m: {} (at {}): {:?}",
+                extracted.url, extracted.char_offset, extracted.context
+            )?;
+        }
         writeln!(f, "\x1b[38;5;42mcriteria\x1b[0m: {:?}", self.criteria)?;
         writeln!(f, "\x1b[38;5;43mduration\x1b[0m: {:?}", self.duration)?;
+        writeln!(f, "\x1b[38;5;43musage\x1b[0m: {}", self.usage)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_urls() {
+        let text = "Read the docs first. The guide at https://docs.rs/llm_client, and the repo (https://github.com/ShelbyJenkins/llm_client) are useful!\n<a href='https://example.com/a?b=1'>link</a> and https://docs.rs/llm_client again.";
+        let urls = vec![
+            "https://github.com/ShelbyJenkins/llm_client".to_owned(),
+            "https://docs.rs/llm_client".to_owned(),
+            "https://example.com/a?b=1".to_owned(),
+            "https://docs.rs/llm_client".to_owned(),
+            "https://not-in-the-text.com/".to_owned(),
+        ];
+        let extracted = locate_urls(text, &urls);
+        assert_eq!(extracted.len(), 3);
+
+        assert_eq!(
+            extracted[0].url.as_str(),
+            "https://github.com/ShelbyJenkins/llm_client"
+        );
+        assert_eq!(
+            extracted[0].context,
+            "The guide at https://docs.rs/llm_client, and the repo (https://github.com/ShelbyJenkins/llm_client) are useful!"
+        );
+        // The first appearance, not the repeat at the end.
+        assert_eq!(
+            extracted[1].char_offset,
+            text.find("https://docs.rs").unwrap()
+        );
+        assert_eq!(
+            extracted[2].context,
+            "<a href='https://example.com/a?b=1'>link</a> and https://docs.rs/llm_client again."
+        );
+
+        // Offsets count characters, not bytes.
+        let extracted = locate_urls(
+            "Café → https://example.com/",
+            &["https://example.com/".to_owned()],
+        );
+        assert_eq!(extracted[0].char_offset, 7);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod extract;
+pub mod rag;
 
 use extract::Extract;
 use llm_interface::{llms::LlmBackend, requests::completion::CompletionRequest};
@@ -17,4 +18,13 @@ impl Nlp {
     pub fn extract(self) -> Extract {
         Extract::new(self.base_req)
     }
+
+    /// Shorthand for `extract().pii(kinds)`.
+    pub fn extract_pii(self, kinds: &[extract::pii::PiiKind]) -> extract::pii::ExtractPii {
+        Extract::new(self.base_req).pii(kinds)
+    }
+
+    pub fn rag(self) -> rag::Rag {
+        rag::Rag::new(self.base_req)
+    }
 }
@@ -0,0 +1,292 @@
+use crate::similarity::top_k_similar;
+use llm_interface::{
+    llms::LlmBackend,
+    requests::{
+        completion::{CompletionRequest, CompletionResponse},
+        req_components::{RequestConfig, RequestConfigTrait},
+    },
+};
+use std::sync::Arc;
+
+const DEFAULT_PROMPT_TEMPLATE: &str = "Answer the question using only the numbered sources below. If the sources don't contain the answer, say so.\n\nSources:\n{context}\n\nQuestion: {query}";
+
+/// Answers a query from a corpus: retrieves the chunks most similar to the query and answers with them as the only context.
+///
+/// Documents are chunked with `llm_utils`'s text chunker, and chunks are embedded with the embedder, once each,
+/// so the same workflow can answer many queries over a corpus without embedding it again.
+/// Embeddings need a llama.cpp backend running an embedding model, which is usually a different client from the one
+/// answering, so set it with [Rag::embedder].
+#[derive(Clone)]
+pub struct Rag {
+    pub base_req: CompletionRequest,
+    /// The backend that embeds the chunks and the query. Defaults to the answering backend.
+    pub embedder: Arc<LlmBackend>,
+    pub query: Option<String>,
+    /// The chunks of the corpus, in the order they were added.
+    pub chunks: Vec<String>,
+    /// The embeddings of the first `embeddings.len()` chunks. The rest are embedded on the next run.
+    pub embeddings: Vec<Vec<f32>>,
+    /// Documents not yet chunked. They're chunked on the next run, so the chunk size can be set after adding them.
+    pub documents: Vec<String>,
+    pub top_k: usize,
+    pub chunk_token_size: u32,
+    pub prompt_template: String,
+}
+
+impl Rag {
+    pub fn new(base_req: CompletionRequest) -> Self {
+        Self {
+            embedder: base_req.backend.clone(),
+            base_req,
+            query: None,
+            chunks: Vec::new(),
+            embeddings: Vec::new(),
+            documents: Vec::new(),
+            top_k: 4,
+            chunk_token_size: 256,
+            prompt_template: DEFAULT_PROMPT_TEMPLATE.to_owned(),
+        }
+    }
+
+    /// Sets the client whose backend embeds the chunks and the query, e.g. `LlmClient::llama_cpp().nomic_embed_text_v1_5().init()`.
+    pub fn embedder(mut self, embedder: &crate::LlmClient) -> Self {
+        self.embedder = embedder.backend.clone();
+        self
+    }
+
+    /// Sets the most chunks given to the LLM as context.
+    ///
+    /// # Default
+    ///
+    /// 4 chunks.
+    pub fn top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k.max(1);
+        self
+    }
+
+    /// Sets the most tokens in a chunk of a document added with [Rag::add_document].
+    ///
+    /// # Default
+    ///
+    /// 256 tokens.
+    pub fn chunk_token_size(mut self, chunk_token_size: u32) -> Self {
+        self.chunk_token_size = chunk_token_size;
+        self
+    }
+
+    /// Sets the prompt the answer is generated from. `{context}` is replaced with the retrieved chunks, numbered from 1,
+    /// and `{query}` with the query.
+    pub fn prompt_template<T: AsRef<str>>(mut self, prompt_template: T) -> Self {
+        self.prompt_template = prompt_template.as_ref().to_owned();
+        self
+    }
+
+    pub fn set_query<T: AsRef<str>>(&mut self, query: T) -> &mut Self {
+        self.query = Some(query.as_ref().to_owned());
+        self
+    }
+
+    /// Adds a document to the corpus. It's split into chunks of up to [Rag::chunk_token_size] tokens on the next run.
+    pub fn add_document<T: AsRef<str>>(&mut self, document: T) -> &mut Self {
+        self.documents.push(document.as_ref().to_owned());
+        self
+    }
+
+    /// Adds already chunked text to the corpus as is.
+    pub fn add_chunks<T: AsRef<str>>(&mut self, chunks: &[T]) -> &mut Self {
+        self.chunks
+            .extend(chunks.iter().map(|chunk| chunk.as_ref().to_owned()));
+        self
+    }
+
+    /// Adds chunks with embeddings from an earlier run, or from [crate::LlmClient::embeddings], so they aren't embedded again.
+    /// The embeddings must come from the same model as the embedder.
+    pub fn add_embedded_chunks<T: AsRef<str>>(
+        &mut self,
+        chunks: &[T],
+        embeddings: Vec<Vec<f32>>,
+    ) -> crate::Result<&mut Self> {
+        if chunks.len() != embeddings.len() {
+            crate::bail!(
+                "Got {} chunks but {} embeddings",
+                chunks.len(),
+                embeddings.len()
+            );
+        }
+        // Keeps the embedded chunks ahead of the ones waiting to be embedded.
+        let embedded = self.embeddings.len();
+        self.chunks.splice(
+            embedded..embedded,
+            chunks.iter().map(|chunk| chunk.as_ref().to_owned()),
+        );
+        self.embeddings.extend(embeddings);
+        Ok(self)
+    }
+
+    /// Chunks the documents and embeds the chunks that haven't been yet. Called by [Rag::run_return_result], or call it
+    /// ahead of time to index the corpus before the first query.
+    pub async fn index(&mut self) -> crate::Result<()> {
+        // The documents are only removed once all of them are chunked, so a failed run can be retried.
+        let mut chunks = Vec::new();
+        for document in &self.documents {
+            match llm_utils::chunking::chunk_text(document, self.chunk_token_size, None)? {
+                Some(document_chunks) => chunks.extend(document_chunks),
+                None => crate::bail!(
+                    "Failed to chunk a document into chunks of {} tokens",
+                    self.chunk_token_size
+                ),
+            }
+        }
+        self.documents.clear();
+        self.chunks.extend(chunks);
+        if self.embeddings.len() < self.chunks.len() {
+            let embeddings = self
+                .embedder
+                .embeddings(&self.chunks[self.embeddings.len()..])
+                .await?;
+            self.embeddings.extend(embeddings);
+        }
+        Ok(())
+    }
+
+    pub async fn run_return_answer(&mut self) -> crate::Result<String> {
+        Ok(self.run_return_result().await?.content)
+    }
+
+    #[tracing::instrument(
+        name = "llm_client.rag",
+        skip_all,
+        fields(backend = self.base_req.backend.backend_name(), model = self.base_req.backend.model_id(), top_k = self.top_k)
+    )]
+    pub async fn run_return_result(&mut self) -> crate::Result<RagResponse> {
+        let query = match &self.query {
+            Some(query) => query.clone(),
+            None => crate::bail!("No query provided."),
+        };
+        if !self.prompt_template.contains("{context}") || !self.prompt_template.contains("{query}")
+        {
+            crate::bail!("The prompt template must contain {{context}} and {{query}}.");
+        }
+        self.index().await?;
+        if self.chunks.is_empty() {
+            crate::bail!("No documents or chunks to answer from.");
+        }
+
+        let query_embedding = self.embedder.embeddings(&[&query]).await?.remove(0);
+        let retrieved: Vec<RetrievedChunk> =
+            top_k_similar(&query_embedding, &self.embeddings, self.top_k)?
+                .into_iter()
+                .map(|(index, score)| RetrievedChunk {
+                    index,
+                    text: self.chunks[index].clone(),
+                    score,
+                })
+                .collect();
+        let context = retrieved
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| format!("[{}] {}", i + 1, chunk.text))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        self.base_req.reset_completion_request();
+        self.base_req
+            .prompt
+            .add_user_message()?
+            .set_content(fill_template(&self.prompt_template, &context, &query));
+        let response = self.base_req.request().await?;
+        Ok(RagResponse {
+            content: response.content.clone(),
+            retrieved,
+            response,
+        })
+    }
+}
+
+/// Replaces `{context}` and `{query}` in the template in a single pass, so placeholders inside the chunks or the query
+/// are left as they are.
+fn fill_template(template: &str, context: &str, query: &str) -> String {
+    let mut filled = String::with_capacity(template.len() + context.len() + query.len());
+    let mut rest = template;
+    loop {
+        let next = [("{context}", context), ("{query}", query)]
+            .into_iter()
+            .filter_map(|(placeholder, value)| {
+                rest.find(placeholder).map(|i| (i, placeholder, value))
+            })
+            .min_by_key(|(i, _, _)| *i);
+        match next {
+            Some((i, placeholder, value)) => {
+                filled.push_str(&rest[..i]);
+                filled.push_str(value);
+                rest = &rest[i + placeholder.len()..];
+            }
+            None => {
+                filled.push_str(rest);
+                return filled;
+            }
+        }
+    }
+}
+
+impl RequestConfigTrait for Rag {
+    fn config(&mut self) -> &mut RequestConfig {
+        &mut self.base_req.config
+    }
+
+    fn reset_request(&mut self) {
+        self.query = None;
+        self.base_req.reset_completion_request();
+    }
+}
+
+/// A chunk retrieved as context for the answer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetrievedChunk {
+    /// The chunk's index in [Rag::chunks].
+    pub index: usize,
+    pub text: String,
+    /// The cosine similarity of the chunk's embedding to the query's.
+    pub score: f32,
+}
+
+pub struct RagResponse {
+    pub content: String,
+    /// The chunks the answer was generated from, most similar first, in the order they're numbered in the prompt.
+    pub retrieved: Vec<RetrievedChunk>,
+    pub response: CompletionResponse,
+}
+
+impl std::fmt::Display for RagResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "RagResponse:")?;
+        writeln!(f, "    content: {:?}", self.content)?;
+        writeln!(f, "    retrieved:")?;
+        for (i, chunk) in self.retrieved.iter().enumerate() {
+            writeln!(
+                f,
+                "        [{}] chunk {} (score {:.3}): {:?}",
+                i + 1,
+                chunk.index,
+                chunk.score,
+                chunk.text
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_template() {
+        assert_eq!(
+            fill_template(DEFAULT_PROMPT_TEMPLATE, "[1] Use {query} here.", "Why {context}?"),
+            "Answer the question using only the numbered sources below. If the sources don't contain the answer, say so.\n\nSources:\n[1] Use {query} here.\n\nQuestion: Why {context}?"
+        );
+        assert_eq!(fill_template("{query}{context}{query}", "c", "q"), "qcq");
+    }
+}
@@ -0,0 +1,153 @@
+use crate::components::cascade::{step::CascadeStep, CascadeFlow};
+
+/// The prefix of the step that asks the LLM to quote the supporting material. See [super::one_round::ReasonOneRound::cite_sources].
+pub(super) const QUOTE_STEP_PREFIX: &str =
+    "Quoting, word for word and in double quotes, the parts of the supporting material this reasoning is based on...";
+
+/// A quote from the supporting material that the LLM based its answer on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuoteSpan {
+    /// The quoted text as it appears in the supporting material, which can differ from the LLM's quote in whitespace.
+    pub quote: String,
+    /// The offset of the quote's first character in the supporting material, counted in characters rather than bytes.
+    pub char_offset: usize,
+    /// The length of the quote in characters.
+    pub char_len: usize,
+}
+
+impl std::fmt::Display for QuoteSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} (at {})", self.quote, self.char_offset)
+    }
+}
+
+/// The citations of the quotes in the flow's quote step that appear in `supporting_material`.
+pub(super) fn flow_citations(flow: &CascadeFlow, supporting_material: &str) -> Vec<QuoteSpan> {
+    let quote_step = flow
+        .rounds
+        .iter()
+        .flat_map(|round| round.resolved_steps.iter())
+        .find_map(|step| match step {
+            CascadeStep::Inference(step)
+                if step.step_config.step_prefix.as_deref() == Some(QUOTE_STEP_PREFIX) =>
+            {
+                step.llm_content.as_deref()
+            }
+            _ => None,
+        });
+    match quote_step {
+        Some(content) => locate_quotes(supporting_material, &parse_quotes(content)),
+        None => Vec::new(),
+    }
+}
+
+/// The text between each pair of straight or curly double quotes.
+fn parse_quotes(content: &str) -> Vec<String> {
+    let mut quotes = Vec::new();
+    let mut current: Option<String> = None;
+    for c in content.chars() {
+        match (&mut current, c) {
+            (None, '"' | '“') => current = Some(String::new()),
+            (Some(quote), '"' | '”') => {
+                let quote = quote.trim().trim_end_matches("...").trim().to_owned();
+                if !quote.is_empty() {
+                    quotes.push(quote);
+                }
+                current = None;
+            }
+            (Some(quote), c) => quote.push(c),
+            (None, _) => (),
+        }
+    }
+    quotes
+}
+
+/// Collapses each run of whitespace to a single space, returning the collapsed text and the byte offset in `text` of each
+/// byte of the collapsed text.
+fn collapse_whitespace(text: &str) -> (String, Vec<usize>) {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for (offset, c) in text.char_indices() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+                offsets.push(offset);
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            offsets.extend(std::iter::repeat_n(offset, c.len_utf8()));
+            in_whitespace = false;
+        }
+    }
+    (collapsed, offsets)
+}
+
+/// The byte range of the first appearance of `quote` in `text`, ignoring differences in whitespace.
+fn locate_quote(text: &str, quote: &str) -> Option<(usize, usize)> {
+    if let Some(start) = text.find(quote) {
+        return Some((start, start + quote.len()));
+    }
+    let (collapsed_text, offsets) = collapse_whitespace(text);
+    let (collapsed_quote, _) = collapse_whitespace(quote.trim());
+    let start = collapsed_text.find(&collapsed_quote)?;
+    let last_char = collapsed_quote.chars().next_back()?;
+    let last_byte = start + collapsed_quote.len() - 1;
+    Some((offsets[start], offsets[last_byte] + last_char.len_utf8()))
+}
+
+/// The citations of the quotes that appear in `text`, in the order they were quoted. Quotes that don't appear, i.e. the
+/// LLM misquoted or made them up, are dropped, as are repeats.
+fn locate_quotes(text: &str, quotes: &[String]) -> Vec<QuoteSpan> {
+    let mut citations: Vec<QuoteSpan> = Vec::new();
+    for quote in quotes {
+        let Some((start, end)) = locate_quote(text, quote) else {
+            crate::warn!("Dropping a quote that isn't in the supporting material: {quote:?}");
+            continue;
+        };
+        let char_offset = text[..start].chars().count();
+        if citations
+            .iter()
+            .any(|citation| citation.char_offset == char_offset)
+        {
+            continue;
+        }
+        citations.push(QuoteSpan {
+            quote: text[start..end].to_owned(),
+            char_offset,
+            char_len: text[start..end].chars().count(),
+        });
+    }
+    citations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_quotes() {
+        let text = "The café opens at 9am.\nIt closes at   5pm on weekdays. Pets are welcome.";
+        let quotes = parse_quotes(
+            "The material says \"The café opens at 9am.\" and “It closes at 5pm on weekdays...” but not \"It is open on Sundays.\" Again, \"The café opens at 9am.\"",
+        );
+        assert_eq!(quotes.len(), 4);
+
+        let citations = locate_quotes(text, &quotes);
+        assert_eq!(citations.len(), 2);
+        assert_eq!(citations[0].quote, "The café opens at 9am.");
+        assert_eq!(citations[0].char_offset, 0);
+        assert_eq!(citations[0].char_len, 22);
+        // Matched despite the different whitespace, and returned as it appears in the text.
+        assert_eq!(citations[1].quote, "It closes at   5pm on weekdays");
+        assert_eq!(citations[1].char_offset, 23);
+        assert_eq!(
+            text.chars()
+                .skip(citations[1].char_offset)
+                .take(citations[1].char_len)
+                .collect::<String>(),
+            citations[1].quote
+        );
+    }
+}
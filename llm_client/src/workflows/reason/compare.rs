@@ -0,0 +1,340 @@
+use super::{
+    decision::{DecisionResult, DecisionTrait},
+    ReasonWorkflowBuilder,
+};
+use crate::components::{instruct_prompt::InstructPrompt, InstructPromptTrait};
+use llm_interface::requests::{
+    completion::{AggregateUsage, CompletionRequest},
+    req_components::{RequestConfig, RequestConfigTrait},
+};
+use std::cmp::Ordering;
+
+const OPTION_A: &str = "A";
+const OPTION_B: &str = "B";
+const OPTION_TIE: &str = "Tie";
+
+/// Compares two items with a criterion given as the instructions, using the decision voting machinery.
+///
+/// LLMs tend to favor whichever option is presented first. With `mitigate_position_bias` enabled (the default),
+/// the decision is run a second time with the items swapped, and the votes from both orderings are combined.
+pub struct PairwiseComparison {
+    pub base_req: CompletionRequest,
+    pub a: String,
+    pub b: String,
+    pub allow_tie: bool,
+    pub mitigate_position_bias: bool,
+    pub best_of_n_votes: u8,
+    pub dynamic_temperature: bool,
+    pub instruct_prompt: InstructPrompt,
+}
+
+impl PairwiseComparison {
+    pub(super) fn new<T: AsRef<str>>(base_req: CompletionRequest, a: T, b: T) -> Self {
+        Self {
+            base_req,
+            a: a.as_ref().to_owned(),
+            b: b.as_ref().to_owned(),
+            allow_tie: true,
+            mitigate_position_bias: true,
+            best_of_n_votes: 3,
+            dynamic_temperature: true,
+            instruct_prompt: InstructPrompt::default(),
+        }
+    }
+
+    /// Returns how `a` compares to `b`. `Ordering::Greater` means `a` is better, `Ordering::Less` means `b` is better.
+    pub async fn return_primitive(&mut self) -> crate::Result<Ordering> {
+        Ok(self.return_result().await?.ordering)
+    }
+
+    #[tracing::instrument(
+        name = "llm_client.compare",
+        skip_all,
+        fields(
+            mitigate_position_bias = self.mitigate_position_bias,
+            ordering = tracing::field::Empty,
+            confidence = tracing::field::Empty,
+        )
+    )]
+    pub async fn return_result(&mut self) -> crate::Result<ComparisonResult> {
+        let start = std::time::Instant::now();
+        let criterion = match self.instruct_prompt.build_instructions() {
+            Some(criterion) => criterion,
+            None => {
+                return Err(anyhow::format_err!(
+                    "No criterion provided. Set it with `set_instructions`."
+                ))
+            }
+        };
+        let mut result = ComparisonResult {
+            ordering: Ordering::Equal,
+            confidence: 0.0,
+            votes_a: 0,
+            votes_b: 0,
+            votes_tie: 0,
+            duration: std::time::Duration::default(),
+            decision_results: Vec::new(),
+            usage: AggregateUsage::default(),
+        };
+
+        let a = self.a.clone();
+        let b = self.b.clone();
+        let decision_result = self.run_decision(&criterion, &a, &b).await?;
+        result.add_votes(&decision_result, false);
+        result.usage.merge(&decision_result.usage);
+        result.decision_results.push(decision_result);
+
+        if self.mitigate_position_bias {
+            let decision_result = self.run_decision(&criterion, &b, &a).await?;
+            result.add_votes(&decision_result, true);
+            result.usage.merge(&decision_result.usage);
+            result.decision_results.push(decision_result);
+        }
+
+        result.tally();
+        result.duration = start.elapsed();
+        let span = tracing::Span::current();
+        span.record("ordering", format!("{:?}", result.ordering));
+        span.record("confidence", result.confidence);
+        tracing::info!("{}", result.to_string());
+        Ok(result)
+    }
+
+    async fn run_decision(
+        &mut self,
+        criterion: &str,
+        first: &str,
+        second: &str,
+    ) -> crate::Result<DecisionResult> {
+        let mut reason = ReasonWorkflowBuilder {
+            base_req: self.base_req.clone(),
+            shared_context: self.instruct_prompt.shared_context.clone(),
+        }
+        .exact_string();
+        reason
+            .primitive
+            .add_strings_to_allowed(&[OPTION_A, OPTION_B]);
+        if self.allow_tie {
+            reason.primitive.add_string_to_allowed(OPTION_TIE);
+        }
+        let choices = if self.allow_tie {
+            format!("'{OPTION_A}', '{OPTION_B}', or '{OPTION_TIE}' if neither is better")
+        } else {
+            format!("'{OPTION_A}' or '{OPTION_B}'")
+        };
+        reason.set_instructions(format!(
+            "Compare option {OPTION_A} and option {OPTION_B} using the criterion: {criterion} Which option is better? Answer with {choices}."
+        ));
+        let mut supporting_material =
+            format!("Option {OPTION_A}: {first}\nOption {OPTION_B}: {second}");
+        if let Some(user_material) = self.instruct_prompt.build_supporting_material() {
+            supporting_material = format!("{user_material}\n{supporting_material}");
+        }
+        reason.set_supporting_material(supporting_material);
+
+        let mut decision = reason.decision();
+        decision
+            .best_of_n_votes(self.best_of_n_votes)
+            .dynamic_temperature(self.dynamic_temperature);
+        decision.return_result().await
+    }
+
+    /// Whether the LLM may answer that neither item is better. Defaults to `true`.
+    pub fn allow_tie(&mut self, allow_tie: bool) -> &mut Self {
+        self.allow_tie = allow_tie;
+        self
+    }
+
+    /// Runs the comparison in both orderings and combines the votes, to cancel out the preference LLMs have for the first option.
+    /// Doubles the number of requests. Defaults to `true`.
+    pub fn mitigate_position_bias(&mut self, mitigate_position_bias: bool) -> &mut Self {
+        self.mitigate_position_bias = mitigate_position_bias;
+        self
+    }
+
+    /// Sets the number of votes for each ordering. See [super::decision::Decision::best_of_n_votes].
+    pub fn best_of_n_votes(&mut self, best_of_n_votes: u8) -> &mut Self {
+        self.best_of_n_votes = best_of_n_votes;
+        self
+    }
+
+    /// See [super::decision::Decision::dynamic_temperature].
+    pub fn dynamic_temperature(&mut self, dynamic_temperature: bool) -> &mut Self {
+        self.dynamic_temperature = dynamic_temperature;
+        self
+    }
+}
+
+impl RequestConfigTrait for PairwiseComparison {
+    fn config(&mut self) -> &mut RequestConfig {
+        &mut self.base_req.config
+    }
+
+    fn reset_request(&mut self) {
+        self.instruct_prompt.reset_instruct_prompt();
+        self.base_req.reset_completion_request();
+    }
+}
+
+impl InstructPromptTrait for PairwiseComparison {
+    fn instruct_prompt_mut(&mut self) -> &mut InstructPrompt {
+        &mut self.instruct_prompt
+    }
+}
+
+#[derive(Clone)]
+pub struct ComparisonResult {
+    /// How `a` compares to `b`. `Ordering::Greater` means `a` is better, `Ordering::Less` means `b` is better.
+    pub ordering: Ordering,
+    /// The share of all votes, across both orderings, that went to the most voted outcome.
+    pub confidence: f32,
+    pub votes_a: usize,
+    pub votes_b: usize,
+    pub votes_tie: usize,
+    pub duration: std::time::Duration,
+    /// One result per ordering that was run. The second, if present, had `a` and `b` swapped.
+    pub decision_results: Vec<DecisionResult>,
+    /// Token, cost, and time totals of every request made across both orderings.
+    pub usage: AggregateUsage,
+}
+
+impl ComparisonResult {
+    pub fn a_is_better(&self) -> bool {
+        self.ordering == Ordering::Greater
+    }
+
+    pub fn b_is_better(&self) -> bool {
+        self.ordering == Ordering::Less
+    }
+
+    pub fn is_tie(&self) -> bool {
+        self.ordering == Ordering::Equal
+    }
+
+    fn add_votes(&mut self, decision_result: &DecisionResult, swapped: bool) {
+        // Indexes match the order the options were added to the ExactStringPrimitive.
+        for (index, votes) in &decision_result.votes {
+            match (index, swapped) {
+                (0, false) | (1, true) => self.votes_a += *votes as usize,
+                (1, false) | (0, true) => self.votes_b += *votes as usize,
+                _ => self.votes_tie += *votes as usize,
+            }
+        }
+    }
+
+    fn tally(&mut self) {
+        let total_votes = self.votes_a + self.votes_b + self.votes_tie;
+        let winner_votes = self.votes_a.max(self.votes_b).max(self.votes_tie);
+        // An option only wins with more votes than both the other option and a tie. Any split, such as each option winning
+        // in the ordering where it was listed first, or as many tie votes as votes for an option, is a tie.
+        self.ordering = if self.votes_a > self.votes_b.max(self.votes_tie) {
+            Ordering::Greater
+        } else if self.votes_b > self.votes_a.max(self.votes_tie) {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        };
+        self.confidence = if total_votes == 0 {
+            0.0
+        } else {
+            winner_votes as f32 / total_votes as f32
+        };
+    }
+}
+
+impl std::fmt::Display for ComparisonResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "\x1b[38;5;45m\x1b[1mComparisonResult\x1b[0m:")?;
+        writeln!(
+            f,
+            "\x1b[38;5;44mvote results\x1b[0m: a: {}, b: {}, tie: {}",
+            self.votes_a, self.votes_b, self.votes_tie
+        )?;
+        writeln!(f, "\x1b[38;5;44mconfidence\x1b[0m: {}", self.confidence)?;
+        writeln!(
+            f,
+            "\x1b[38;5;43mcomparison duration\x1b[0m: {:?}",
+            self.duration
+        )?;
+        writeln!(f, "\x1b[38;5;43mcomparison usage\x1b[0m: {}", self.usage)?;
+        writeln!(
+            f,
+            "\x1b[38;5;42m\x1b[1mComparison result\x1b[0m: {:?}",
+            self.ordering
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn result_with_votes(a: usize, b: usize, tie: usize) -> ComparisonResult {
+        ComparisonResult {
+            ordering: Ordering::Equal,
+            confidence: 0.0,
+            votes_a: a,
+            votes_b: b,
+            votes_tie: tie,
+            duration: std::time::Duration::default(),
+            decision_results: Vec::new(),
+            usage: AggregateUsage::default(),
+        }
+    }
+
+    #[test]
+    fn test_tally() {
+        let mut result = result_with_votes(3, 1, 0);
+        result.tally();
+        assert!(result.a_is_better());
+        assert_eq!(result.confidence, 0.75);
+
+        let mut result = result_with_votes(0, 2, 1);
+        result.tally();
+        assert!(result.b_is_better());
+
+        // Each option won when listed first, so position bias cancels out.
+        let mut result = result_with_votes(2, 2, 0);
+        result.tally();
+        assert!(result.is_tie());
+        assert_eq!(result.confidence, 0.5);
+
+        // As many tie votes as votes for a isn't a win for a.
+        let mut result = result_with_votes(2, 0, 2);
+        result.tally();
+        assert!(result.is_tie());
+        assert_eq!(result.confidence, 0.5);
+
+        let mut result = result_with_votes(0, 3, 3);
+        result.tally();
+        assert!(result.is_tie());
+    }
+
+    #[test]
+    fn test_tally_many_votes() {
+        let mut decision_result = DecisionResult::new();
+        decision_result.votes = HashMap::from([(0, 200), (1, 100)]);
+        let mut result = result_with_votes(0, 0, 0);
+        result.add_votes(&decision_result, false);
+        result.add_votes(&decision_result, false);
+        assert_eq!((result.votes_a, result.votes_b), (400, 200));
+        result.tally();
+        assert!(result.a_is_better());
+        assert_eq!(result.confidence, 400.0 / 600.0);
+    }
+
+    #[test]
+    fn test_add_votes_swapped() {
+        let mut decision_result = DecisionResult::new();
+        decision_result.votes = HashMap::from([(0, 2), (2, 1)]);
+        let mut result = result_with_votes(0, 0, 0);
+        result.add_votes(&decision_result, false);
+        result.add_votes(&decision_result, true);
+        assert_eq!(
+            (result.votes_a, result.votes_b, result.votes_tie),
+            (2, 2, 2)
+        );
+    }
+}
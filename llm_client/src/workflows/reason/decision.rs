@@ -1,13 +1,19 @@
 use super::{ReasonResult, ReasonTrait};
 use crate::{
-    components::{instruct_prompt::InstructPrompt, InstructPromptTrait},
+    components::{
+        cascade::{render_prompt, render_prompt_messages},
+        instruct_prompt::InstructPrompt,
+        InstructPromptTrait,
+    },
     primitives::*,
 };
 use llm_interface::requests::{
-    completion::CompletionRequest,
+    completion::{AggregateUsage, CompletionError, CompletionRequest},
     req_components::{RequestConfig, RequestConfigTrait},
 };
+use llm_prompt::LlmPrompt;
 use std::collections::HashMap;
+use tracing::Instrument;
 
 const DYNAMIC_TEMPERATURE_MIN: f32 = 0.11;
 const DYNAMIC_TEMPERATURE_MAX: f32 = 1.89;
@@ -18,6 +24,12 @@ pub struct Decision<D: DecisionTrait> {
     pub dynamic_temperature: bool,
     pub reason: D,
     pub result_can_be_none: bool,
+    pub shuffle_choices: bool,
+    pub shuffle_seed: Option<u64>,
+    pub tie_break: TieBreak,
+    pub min_confidence: Option<f32>,
+    pub max_votes: Option<u8>,
+    pub early_stop_on_unanimous: Option<u8>,
 }
 
 impl<D: DecisionTrait> Decision<D> {
@@ -67,6 +79,18 @@ impl<D: DecisionTrait> Decision<D> {
         }
     }
 
+    #[tracing::instrument(
+        name = "llm_client.decision",
+        skip_all,
+        fields(
+            primitive = crate::primitives::primitive_name::<D::ReasonPrimitive>(),
+            best_of_n_votes = self.best_of_n_votes,
+            result_can_be_none = self.result_can_be_none,
+            total_votes = tracing::field::Empty,
+            winner_votes = tracing::field::Empty,
+            confidence = tracing::field::Empty,
+        )
+    )]
     async fn run_decision(&mut self) -> crate::Result<DecisionResult> {
         let start = std::time::Instant::now();
         let mut decision_result = DecisionResult::new();
@@ -74,63 +98,150 @@ impl<D: DecisionTrait> Decision<D> {
         let mut none_count = 0;
 
         self.set_dynamic_temperature_on_initial(self.dynamic_temperature, self.best_of_n_votes);
+        let choice_count = self.reason.primitive().choice_count();
+        let mut shuffle_rng = (self.shuffle_choices && choice_count > 1).then(|| {
+            ShuffleRng::new(self.shuffle_seed.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or_default()
+            }))
+        });
 
         while failed_attempts < self.base_req.config.retry_after_fail_n_times {
             if failed_attempts >= self.base_req.config.retry_after_fail_n_times {
                 break;
             }
             *self.reason.base_req_mut() = self.base_req.clone();
-            let reason_result = match self
+            let attempt = decision_result.vote_log.len() as u8 + 1;
+            let temperature = self.base_req.config.temperature;
+            let choice_order = shuffle_rng.as_mut().map(|rng| {
+                let order = rng.permutation(choice_count);
+                self.reason.primitive_mut().reorder_choices(&order);
+                order
+            });
+            let reason_result = self
                 .reason
                 .return_reason_result(self.result_can_be_none)
-                .await
-            {
+                .instrument(tracing::info_span!(
+                    "llm_client.decision.vote",
+                    vote = attempt,
+                    temperature
+                ))
+                .await;
+            decision_result.usage.merge(&self.reason.base_req().usage);
+            // Restore the original order so results are parsed and reported by original index.
+            if let Some(order) = &choice_order {
+                let mut inverse = vec![0; order.len()];
+                for (position, &original_index) in order.iter().enumerate() {
+                    inverse[original_index] = position;
+                }
+                self.reason.primitive_mut().reorder_choices(&inverse);
+            }
+            let mut reason_result = match reason_result {
                 Ok(reason_result) => reason_result,
-                Err(_) => {
+                // A refusal would be repeated by every vote, so don't spend the remaining attempts on it.
+                Err(e)
+                    if matches!(
+                        e.downcast_ref::<CompletionError>(),
+                        Some(CompletionError::ContentFiltered(_))
+                    ) =>
+                {
+                    return Err(e);
+                }
+                Err(e) => {
+                    decision_result.vote_log.push(VoteRecord {
+                        attempt,
+                        temperature,
+                        justification: None,
+                        parsed_result: Err(e.to_string()),
+                        counted: false,
+                        choice_order,
+                    });
                     self.set_dynamic_temperature_on_fail(self.dynamic_temperature);
                     failed_attempts += 1;
                     continue;
                 }
             };
+            if let Some(order) = &choice_order {
+                reason_result.result_index = reason_result
+                    .result_index
+                    .map(|position| order[position as usize] as u32);
+            }
+            let justification = reason_result
+                .workflow
+                .rounds
+                .last()
+                .and_then(|round| round.display_outcome().ok());
 
             match self.reason.primitive().parse_reason_result(&reason_result) {
-                Err(_) => {
+                Err(e) => {
+                    decision_result.vote_log.push(VoteRecord {
+                        attempt,
+                        temperature,
+                        justification,
+                        parsed_result: Err(e.to_string()),
+                        counted: false,
+                        choice_order,
+                    });
                     self.set_dynamic_temperature_on_fail(self.dynamic_temperature);
                     failed_attempts += 1;
                 }
                 Ok(primitive_result) => {
+                    decision_result.vote_log.push(VoteRecord {
+                        attempt,
+                        temperature,
+                        justification,
+                        parsed_result: Ok(primitive_result.as_ref().map(|r| r.to_string())),
+                        counted: true,
+                        choice_order,
+                    });
                     decision_result.total_votes += 1;
                     if let Some(result_index) = reason_result.result_index {
                         *decision_result.votes.entry(result_index).or_insert(0) += 1;
-                        for (choice_index, choice_votes) in &mut decision_result.votes {
-                            if *choice_votes > decision_result.winner_votes {
-                                decision_result.winner_votes = *choice_votes;
-                                decision_result.winner_index = Some(*choice_index);
-                            }
+                        if let Some((choice_index, choice_votes)) =
+                            leading_choice(&decision_result.votes)
+                        {
+                            decision_result.winner_index = Some(choice_index);
+                            decision_result.winner_votes = choice_votes;
                         }
                     } else {
                         none_count += 1;
                     }
-                    if decision_result.winner_votes
-                        >= (self.best_of_n_votes + (self.best_of_n_votes % 2)) / 2
+                    let votes_required_to_win =
+                        (self.best_of_n_votes + (self.best_of_n_votes % 2)) / 2;
+                    if decision_result.winner_votes >= votes_required_to_win
+                        && decision_result.winner_votes >= none_count
+                        && self.confidence_met(
+                            decision_result.winner_votes,
+                            decision_result.total_votes,
+                        )
                     {
                         decision_result.confidence = decision_result.winner_votes as f32
                             / decision_result.total_votes as f32;
                         decision_result.duration = start.elapsed();
+                        decision_result.record_span_fields();
                         tracing::info!("{}", decision_result.to_string());
 
-                        decision_result.winner_primitive_result =
-                            Some(primitive_result.unwrap().to_string());
+                        // Past the first majority, the latest vote isn't necessarily for the winner.
+                        decision_result.winner_primitive_result = self
+                            .reason
+                            .primitive()
+                            .result_index_to_primitive(decision_result.winner_index)?
+                            .map(|primitive_result| primitive_result.to_string());
 
                         decision_result.reason_results.push(reason_result);
 
                         return Ok(decision_result);
-                    } else if none_count >= (self.best_of_n_votes + (self.best_of_n_votes % 2)) / 2
+                    } else if none_count >= votes_required_to_win
+                        && none_count > decision_result.winner_votes
+                        && self.confidence_met(none_count, decision_result.total_votes)
                     {
                         decision_result.winner_votes = none_count;
                         decision_result.confidence =
                             none_count as f32 / decision_result.total_votes as f32;
                         decision_result.duration = start.elapsed();
+                        decision_result.record_span_fields();
                         tracing::info!("{}", decision_result.to_string());
 
                         decision_result.winner_primitive_result = Some("none".to_string());
@@ -138,13 +249,34 @@ impl<D: DecisionTrait> Decision<D> {
                         decision_result.reason_results.push(reason_result);
 
                         return Ok(decision_result);
-                    } else {
-                        self.set_dynamic_temperature_on_success(
-                            self.best_of_n_votes,
-                            &decision_result,
-                        );
-                        decision_result.reason_results.push(reason_result);
                     }
+                    decision_result.reason_results.push(reason_result);
+                    let winner =
+                        match self.unanimous_streak_winner(&mut decision_result, none_count) {
+                            Some(winner) => Some(winner),
+                            None if decision_result.total_votes >= self.best_of_n_votes => {
+                                self.plurality_winner(&mut decision_result, none_count)?
+                            }
+                            None => None,
+                        };
+                    if let Some(winner) = winner {
+                        decision_result.winner_index = winner;
+                        decision_result.winner_primitive_result = match winner {
+                            Some(winner_index) => self
+                                .reason
+                                .primitive()
+                                .result_index_to_primitive(Some(winner_index))?
+                                .map(|primitive_result| primitive_result.to_string()),
+                            None => Some("none".to_string()),
+                        };
+                        decision_result.confidence = decision_result.winner_votes as f32
+                            / decision_result.total_votes as f32;
+                        decision_result.duration = start.elapsed();
+                        decision_result.record_span_fields();
+                        tracing::info!("{}", decision_result.to_string());
+                        return Ok(decision_result);
+                    }
+                    self.set_dynamic_temperature_on_success(self.best_of_n_votes, &decision_result);
                 }
             }
         }
@@ -154,6 +286,98 @@ impl<D: DecisionTrait> Decision<D> {
         ))
     }
 
+    /// Picks the winner once [Decision::best_of_n_votes] votes are in and no choice has a majority.
+    /// The choice with the most votes wins, and a tie is settled by [Decision::tie_break].
+    ///
+    /// # Returns
+    ///
+    /// The winning result index, `Some(None)` for the 'none' result, or `None` to take another vote.
+    fn plurality_winner(
+        &self,
+        decision_result: &mut DecisionResult,
+        none_count: u8,
+    ) -> crate::Result<Option<Option<u32>>> {
+        let (leaders, leader_votes) = leading_choices(&decision_result.votes, none_count);
+        if !self.confidence_met(leader_votes, decision_result.total_votes) {
+            return Ok(None);
+        }
+        if leaders.len() == 1 {
+            decision_result.winner_votes = leader_votes;
+            return Ok(Some(leaders[0]));
+        }
+        let votes: Vec<(Option<u32>, f32)> = decision_result
+            .reason_results
+            .iter()
+            .map(|res| (res.result_index, res.temperature))
+            .collect();
+        let winner = match self.tie_break {
+            TieBreak::Error => {
+                crate::bail!(
+                    "Decision tied between results {leaders:?} with {leader_votes} votes each after {} votes.",
+                    decision_result.total_votes
+                );
+            }
+            // Each extra vote can break the tie, up to the vote cap.
+            TieBreak::AdditionalVote if decision_result.total_votes < self.vote_cap() => {
+                return Ok(None);
+            }
+            TieBreak::AdditionalVote => TieBreak::FirstChoice.resolve(&leaders, &votes),
+            tie_break => tie_break.resolve(&leaders, &votes),
+        };
+        decision_result.winner_votes = leader_votes;
+        decision_result.tied_choices = leaders;
+        Ok(Some(winner))
+    }
+
+    /// The result of the last [Decision::early_stop_on_unanimous] votes if they all agree, it leads the other results outright,
+    /// and it meets [Decision::min_confidence].
+    fn unanimous_streak_winner(
+        &self,
+        decision_result: &mut DecisionResult,
+        none_count: u8,
+    ) -> Option<Option<u32>> {
+        let streak = self.early_stop_on_unanimous? as usize;
+        let last_votes = decision_result
+            .reason_results
+            .len()
+            .checked_sub(streak.max(1))
+            .map(|start| &decision_result.reason_results[start..])?;
+        let result_index = last_votes[0].result_index;
+        if last_votes
+            .iter()
+            .any(|res| res.result_index != result_index)
+        {
+            return None;
+        }
+        let (leaders, leader_votes) = leading_choices(&decision_result.votes, none_count);
+        if leaders != [result_index]
+            || !self.confidence_met(leader_votes, decision_result.total_votes)
+        {
+            return None;
+        }
+        decision_result.winner_votes = leader_votes;
+        Some(result_index)
+    }
+
+    /// The most votes a decision takes: [Decision::max_votes] if set, or twice [Decision::best_of_n_votes].
+    /// Never fewer than [Decision::best_of_n_votes].
+    fn vote_cap(&self) -> u8 {
+        self.max_votes
+            .unwrap_or(self.best_of_n_votes.saturating_mul(2))
+            .max(self.best_of_n_votes)
+    }
+
+    /// True if a result with `winner_votes` of `total_votes` meets [Decision::min_confidence], or the vote cap is reached.
+    fn confidence_met(&self, winner_votes: u8, total_votes: u8) -> bool {
+        match self.min_confidence {
+            Some(min_confidence) => {
+                winner_votes as f32 / total_votes as f32 >= min_confidence
+                    || total_votes >= self.vote_cap()
+            }
+            None => true,
+        }
+    }
+
     fn set_dynamic_temperature_on_initial(
         &mut self,
         dynamic_temperature: bool,
@@ -170,13 +394,14 @@ impl<D: DecisionTrait> Decision<D> {
         decision_result: &DecisionResult,
     ) {
         let votes_required_to_win = (best_of_n_votes + (best_of_n_votes % 2)) / 2;
-        if votes_required_to_win - decision_result.winner_votes == 1 {
+        // With a confidence goal, voting can continue after the winner already has a majority.
+        let minimum_votes_remaining =
+            votes_required_to_win.saturating_sub(decision_result.winner_votes);
+        if minimum_votes_remaining <= 1 {
             self.base_req.config.temperature = DYNAMIC_TEMPERATURE_MAX;
             return;
         }
 
-        let minimum_votes_remaining = votes_required_to_win - decision_result.winner_votes;
-
         let maybe_average_votes_remaining =
             (votes_required_to_win + minimum_votes_remaining) as f32 / 2.0;
 
@@ -194,6 +419,7 @@ impl<D: DecisionTrait> Decision<D> {
     /// Sets the number of votes to reach consensus. It is the maxium number of votes for a decision, but often the decision is reached before this number is reached.
     /// For example, with the default of `3` votes, the first decision is made after 2 votes for a choice.
     /// If given an even number, it will round up to the nearest odd number.
+    /// If no choice has a majority after this many votes, the choice with the most votes wins, and a tie is settled by [Decision::tie_break].
     pub fn best_of_n_votes(&mut self, best_of_n_votes: u8) -> &mut Self {
         if best_of_n_votes % 2 == 0 {
             self.best_of_n_votes = best_of_n_votes + 1;
@@ -203,6 +429,80 @@ impl<D: DecisionTrait> Decision<D> {
         self
     }
 
+    /// Returns the prompt of the final request of a vote, without making any requests. Votes differ only in temperature,
+    /// and in choice order if [Decision::shuffle_choices] is set, which isn't applied here.
+    /// Pass `true` to preview [Decision::return_optional_result] instead.
+    pub fn preview_prompt(&mut self, result_can_be_none: bool) -> crate::Result<String> {
+        render_prompt(
+            &self
+                .reason
+                .preview_reason_prompt(&self.base_req, result_can_be_none)?,
+        )
+    }
+
+    /// Returns the messages [Decision::preview_prompt] is built from.
+    pub fn preview_prompt_messages(
+        &mut self,
+        result_can_be_none: bool,
+    ) -> crate::Result<Vec<HashMap<String, String>>> {
+        render_prompt_messages(
+            &self
+                .reason
+                .preview_reason_prompt(&self.base_req, result_can_be_none)?,
+        )
+    }
+
+    /// Runs a single reason pass and returns its result as a [DecisionResult] without voting.
+    /// Equivalent to `best_of_n_votes(1)` with dynamic temperature disabled. The returned confidence is always `1.0`.
+    /// Useful when the structured result is wanted, but the cost of multiple votes is not.
+    pub fn single_shot(&mut self) -> &mut Self {
+        self.best_of_n_votes = 1;
+        self.dynamic_temperature = false;
+        self
+    }
+
+    /// Presents the choices in a random order for each vote, so the consensus isn't skewed by the LLM's preference for earlier choices.
+    /// Results, votes, and the winner are still reported by the choices' original indexes. Only affects primitives with a list of choices, such as [ExactStringPrimitive].
+    pub fn shuffle_choices(&mut self, shuffle_choices: bool) -> &mut Self {
+        self.shuffle_choices = shuffle_choices;
+        self
+    }
+
+    /// Seeds the shuffle from [Decision::shuffle_choices] so the order of choices for each vote is reproducible.
+    /// Without a seed, the shuffle is seeded from the system time.
+    pub fn shuffle_seed(&mut self, shuffle_seed: u64) -> &mut Self {
+        self.shuffle_seed = Some(shuffle_seed);
+        self
+    }
+
+    /// Sets how a tie for the most votes is settled once [Decision::best_of_n_votes] votes are in. See [TieBreak].
+    pub fn tie_break(&mut self, tie_break: TieBreak) -> &mut Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Keeps voting after a result has a majority until its share of the votes reaches `min_confidence`, e.g. `0.8`,
+    /// or the vote cap set by [Decision::max_votes] is reached. Without a confidence goal, the first majority wins.
+    pub fn min_confidence(&mut self, min_confidence: f32) -> &mut Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    /// Caps the votes taken while chasing [Decision::min_confidence] or breaking a tie with [TieBreak::AdditionalVote].
+    /// Defaults to twice [Decision::best_of_n_votes], and is never fewer than [Decision::best_of_n_votes].
+    pub fn max_votes(&mut self, max_votes: u8) -> &mut Self {
+        self.max_votes = Some(max_votes);
+        self
+    }
+
+    /// Stops voting as soon as `k` consecutive votes agree, without waiting for a majority of [Decision::best_of_n_votes].
+    /// The agreed result must also lead outright and meet [Decision::min_confidence], so a late streak can't overturn an earlier lead.
+    /// With `k` of `2` and the default of `3` votes, this changes nothing; it pays off with larger `best_of_n_votes`.
+    pub fn early_stop_on_unanimous(&mut self, k: u8) -> &mut Self {
+        self.early_stop_on_unanimous = Some(k);
+        self
+    }
+
     /// Dynamically scales temperature during the voting process. Starts at a low temperature and increases towards max temperature as the number of votes increases.
     pub fn dynamic_temperature(&mut self, dynamic_temperature: bool) -> &mut Self {
         self.dynamic_temperature = dynamic_temperature;
@@ -219,11 +519,20 @@ pub trait DecisionTrait: Sized + InstructPromptTrait {
 
     fn primitive(&self) -> &Self::ReasonPrimitive;
 
+    fn primitive_mut(&mut self) -> &mut Self::ReasonPrimitive;
+
     async fn return_reason_result(
         &mut self,
         result_can_be_none: bool,
     ) -> crate::Result<ReasonResult>;
 
+    /// Builds the prompt of the final request of a reason pass sent with `base_req`, without making any requests.
+    fn preview_reason_prompt(
+        &mut self,
+        base_req: &CompletionRequest,
+        result_can_be_none: bool,
+    ) -> crate::Result<LlmPrompt>;
+
     fn decision(self) -> Decision<Self> {
         Decision {
             base_req: self.base_req().clone(),
@@ -231,6 +540,12 @@ pub trait DecisionTrait: Sized + InstructPromptTrait {
             dynamic_temperature: true,
             reason: self,
             result_can_be_none: false,
+            shuffle_choices: false,
+            shuffle_seed: None,
+            tie_break: TieBreak::default(),
+            min_confidence: None,
+            max_votes: None,
+            early_stop_on_unanimous: None,
         }
     }
 }
@@ -262,10 +577,17 @@ pub struct DecisionResult {
     pub total_votes: u8,
     pub winner_votes: u8,
     pub winner_index: Option<u32>,
+    /// Every attempt in the order it was made, including attempts that failed and didn't count as a vote.
+    pub vote_log: Vec<VoteRecord>,
+    /// Token, cost, and time totals of every request made across all votes, including failed attempts.
+    pub usage: AggregateUsage,
+    /// The results that tied for the most votes when the winner was picked by [Decision::tie_break], in index order with
+    /// `None` for the 'none' result last. Empty if there was no tie.
+    pub tied_choices: Vec<Option<u32>>,
 }
 
 impl DecisionResult {
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         Self {
             votes: HashMap::new(),
             confidence: 0.0,
@@ -275,10 +597,139 @@ impl DecisionResult {
             total_votes: 0,
             winner_votes: 0,
             winner_index: None,
+            vote_log: Vec::new(),
+            usage: AggregateUsage::default(),
+            tied_choices: Vec::new(),
+        }
+    }
+
+    fn record_span_fields(&self) {
+        let span = tracing::Span::current();
+        span.record("total_votes", self.total_votes);
+        span.record("winner_votes", self.winner_votes);
+        span.record("confidence", self.confidence);
+    }
+}
+
+/// How a [Decision] settles a tie for the most votes once [Decision::best_of_n_votes] votes are in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// The tied result with the lowest index, i.e. the choice listed first. The 'none' result is last.
+    FirstChoice,
+    /// The tied result whose votes were cast at the lowest average temperature, i.e. the closest to the LLM's most likely answer.
+    /// Falls back to [TieBreak::FirstChoice] if those are equal too.
+    HighestConfidence,
+    /// Takes more votes until one of the tied results leads, up to [Decision::max_votes] votes in total.
+    /// If it's still tied after that, falls back to [TieBreak::FirstChoice]. Either way, [DecisionResult::tied_choices] marks a tie that wasn't broken by a vote.
+    #[default]
+    AdditionalVote,
+    /// Returns an error naming the tied results.
+    Error,
+}
+
+impl TieBreak {
+    /// Picks from `tied`, which is in index order with `None` for the 'none' result last.
+    /// `votes` is the result index and temperature of each counted vote.
+    fn resolve(&self, tied: &[Option<u32>], votes: &[(Option<u32>, f32)]) -> Option<u32> {
+        match self {
+            TieBreak::HighestConfidence => {
+                let average_temperature = |choice: &Option<u32>| {
+                    let temperatures: Vec<f32> = votes
+                        .iter()
+                        .filter(|(result_index, _)| result_index == choice)
+                        .map(|(_, temperature)| *temperature)
+                        .collect();
+                    temperatures.iter().sum::<f32>() / temperatures.len().max(1) as f32
+                };
+                // min_by keeps the first of equal elements, so equal temperatures fall back to the first choice.
+                *tied
+                    .iter()
+                    .min_by(|a, b| average_temperature(a).total_cmp(&average_temperature(b)))
+                    .expect("a tie has at least two results")
+            }
+            _ => tied[0],
         }
     }
 }
 
+/// The result with the most votes and its vote count.
+///
+/// Choices are checked in index order and only a strictly greater count (`>`, not `>=`) takes the lead,
+/// so between choices with equal votes the lowest index leads, whatever the map's iteration order.
+fn leading_choice(votes: &HashMap<u32, u8>) -> Option<(u32, u8)> {
+    let mut choice_indexes: Vec<u32> = votes.keys().copied().collect();
+    choice_indexes.sort_unstable();
+    let mut leader: Option<(u32, u8)> = None;
+    for choice_index in choice_indexes {
+        let choice_votes = votes[&choice_index];
+        if leader.is_none_or(|(_, leader_votes)| choice_votes > leader_votes) {
+            leader = Some((choice_index, choice_votes));
+        }
+    }
+    leader
+}
+
+/// The results with the most votes, in index order with `None` for the 'none' result last, and their vote count.
+fn leading_choices(votes: &HashMap<u32, u8>, none_count: u8) -> (Vec<Option<u32>>, u8) {
+    let leader_votes = votes.values().copied().max().unwrap_or(0).max(none_count);
+    let mut leaders: Vec<Option<u32>> = votes
+        .iter()
+        .filter(|(_, choice_votes)| **choice_votes == leader_votes)
+        .map(|(choice_index, _)| Some(*choice_index))
+        .collect();
+    leaders.sort();
+    if none_count == leader_votes {
+        leaders.push(None);
+    }
+    (leaders, leader_votes)
+}
+
+/// A single attempt made while reaching a decision. See [DecisionResult::vote_log].
+#[derive(Clone, Debug)]
+pub struct VoteRecord {
+    /// The attempt number, starting at 1. Failed attempts are numbered too.
+    pub attempt: u8,
+    /// The temperature the attempt was requested with.
+    pub temperature: f32,
+    /// The reasoning the LLM gave for its choice, if the reason workflow completed.
+    pub justification: Option<String>,
+    /// The parsed choice, `Ok(None)` for a valid 'none' result, or the error if the request or parsing failed.
+    pub parsed_result: Result<Option<String>, String>,
+    /// True if the attempt counted as a vote.
+    pub counted: bool,
+    /// With [Decision::shuffle_choices], the original indexes of the choices in the order they were presented.
+    pub choice_order: Option<Vec<usize>>,
+}
+
+/// SplitMix64, so shuffles are reproducible from a seed without an extra dependency.
+struct ShuffleRng {
+    state: u64,
+}
+
+impl ShuffleRng {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A Fisher-Yates shuffle of `0..len`.
+    fn permutation(&mut self, len: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..len).collect();
+        for i in (1..len).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        order
+    }
+}
+
 impl std::fmt::Display for DecisionResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f)?;
@@ -320,11 +771,19 @@ impl std::fmt::Display for DecisionResult {
             self.winner_votes, self.total_votes
         )?;
         writeln!(f, "\x1b[38;5;44mconfidence\x1b[0m: {}", self.confidence)?;
+        for record in &self.vote_log {
+            writeln!(
+                f,
+                "\x1b[38;5;44mattempt {}\x1b[0m: temperature: {:?}, result: {:?}, counted: {}",
+                record.attempt, record.temperature, record.parsed_result, record.counted
+            )?;
+        }
         writeln!(
             f,
             "\x1b[38;5;43mdecision duration\x1b[0m: {:?}",
             self.duration
         )?;
+        writeln!(f, "\x1b[38;5;43mdecision usage\x1b[0m: {}", self.usage)?;
         if let Some(winner_primitive_result) = &self.winner_primitive_result {
             writeln!(
                 f,
@@ -340,3 +799,73 @@ impl std::fmt::Display for DecisionResult {
         writeln!(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle_is_reproducible() {
+        let order = ShuffleRng::new(42).permutation(5);
+        assert_eq!(order, ShuffleRng::new(42).permutation(5));
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_leading_choice() {
+        assert_eq!(leading_choice(&HashMap::new()), None);
+        // Inserted in a different order each time, the lowest tied index always leads.
+        for _ in 0..16 {
+            let votes: HashMap<u32, u8> = HashMap::from([(3, 2), (1, 2), (0, 1), (2, 2)]);
+            assert_eq!(leading_choice(&votes), Some((1, 2)));
+        }
+        let votes: HashMap<u32, u8> = HashMap::from([(0, 1), (4, 3), (2, 2)]);
+        assert_eq!(leading_choice(&votes), Some((4, 3)));
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let votes: HashMap<u32, u8> = HashMap::from([(2, 2), (0, 1), (1, 2)]);
+        let (tied, leader_votes) = leading_choices(&votes, 0);
+        assert_eq!(tied, vec![Some(1), Some(2)]);
+        assert_eq!(leader_votes, 2);
+
+        // Choice 2 was voted for at the lower temperatures.
+        let cast = [
+            (Some(1), 0.9),
+            (Some(2), 0.1),
+            (Some(0), 0.5),
+            (Some(2), 0.3),
+            (Some(1), 1.2),
+        ];
+        assert_eq!(TieBreak::FirstChoice.resolve(&tied, &cast), Some(1));
+        assert_eq!(TieBreak::HighestConfidence.resolve(&tied, &cast), Some(2));
+
+        // The 'none' result ties too, and is ordered last.
+        let (tied, _) = leading_choices(&votes, 2);
+        assert_eq!(tied, vec![Some(1), Some(2), None]);
+        let (tied, leader_votes) = leading_choices(&votes, 3);
+        assert_eq!(tied, vec![None]);
+        assert_eq!(leader_votes, 3);
+    }
+
+    #[test]
+    fn test_reorder_choices_round_trip() {
+        let mut primitive = ExactStringPrimitive::default();
+        primitive.add_strings_to_allowed(&["red", "green", "blue"]);
+        let order = vec![2, 0, 1];
+        primitive.reorder_choices(&order);
+        assert_eq!(primitive.allowed_strings, vec!["blue", "red", "green"]);
+        // The choice shown at position 0 maps back to original index 2.
+        assert_eq!(order[0], 2);
+
+        let mut inverse = vec![0; order.len()];
+        for (position, &original_index) in order.iter().enumerate() {
+            inverse[original_index] = position;
+        }
+        primitive.reorder_choices(&inverse);
+        assert_eq!(primitive.allowed_strings, vec!["red", "green", "blue"]);
+    }
+}
@@ -1,12 +1,21 @@
+pub mod citation;
+pub mod compare;
 pub mod decision;
 pub mod one_round;
+pub mod trace;
 
 use crate::{
     components::{cascade::CascadeFlow, instruct_prompt::InstructPrompt},
     primitives::*,
 };
-use llm_interface::{llms::LlmBackend, requests::completion::CompletionRequest};
+use citation::QuoteSpan;
+use compare::PairwiseComparison;
+use llm_interface::{
+    llms::LlmBackend,
+    requests::completion::{AggregateUsage, CompletionRequest},
+};
 use one_round::ReasonOneRound;
+use trace::WorkflowTrace;
 
 pub trait ReasonTrait: PrimitiveTrait {
     fn primitive_to_result_index(&self, content: &str) -> u32;
@@ -16,6 +25,15 @@ pub trait ReasonTrait: PrimitiveTrait {
         result_index: Option<u32>,
     ) -> crate::Result<Option<Self::PrimitiveResult>>;
 
+    /// The number of choices the primitive presents to the LLM, for primitives that present a list of choices.
+    /// Used by [decision::Decision::shuffle_choices]. Primitives that don't present choices return 0.
+    fn choice_count(&self) -> usize {
+        0
+    }
+
+    /// Presents the choices in the given order, where `order[i]` is the current index of the choice to show at position `i`.
+    fn reorder_choices(&mut self, _order: &[usize]) {}
+
     fn parse_reason_result(
         &self,
         reason_result: &ReasonResult,
@@ -30,15 +48,23 @@ pub trait ReasonTrait: PrimitiveTrait {
 
 pub struct ReasonWorkflowBuilder {
     pub base_req: CompletionRequest,
+    pub shared_context: Option<String>,
 }
 
 impl ReasonWorkflowBuilder {
     pub fn new(backend: std::sync::Arc<LlmBackend>) -> Self {
         Self {
             base_req: CompletionRequest::new(backend),
+            shared_context: None,
         }
     }
 
+    /// Sets material shared by every request made with the workflow. See [InstructPrompt::shared_context].
+    pub fn shared_context<T: AsRef<str>>(mut self, shared_context: T) -> Self {
+        self.shared_context = Some(shared_context.as_ref().to_owned());
+        self
+    }
+
     fn build<P: PrimitiveTrait>(self) -> ReasonOneRound<P> {
         ReasonOneRound {
             primitive: P::default(),
@@ -46,11 +72,26 @@ impl ReasonWorkflowBuilder {
             reasoning_sentences: 3,
             conclusion_sentences: 2,
             result_can_be_none: false,
-            instruct_prompt: InstructPrompt::default(),
+            balanced_justification: false,
+            cite_sources: false,
+            trace: false,
+            instruct_prompt: InstructPrompt {
+                shared_context: self.shared_context,
+                ..InstructPrompt::default()
+            },
         }
     }
 }
 
+impl ReasonWorkflowBuilder {
+    /// Compares `a` and `b` with the criterion set as the instructions. See [PairwiseComparison].
+    pub fn compare<T: AsRef<str>>(self, a: T, b: T) -> PairwiseComparison {
+        let mut comparison = PairwiseComparison::new(self.base_req, a, b);
+        comparison.instruct_prompt.shared_context = self.shared_context;
+        comparison
+    }
+}
+
 macro_rules! reason_workflow_primitive_impl {
     ($($name:ident => $type:ty),*) => {
         impl ReasonWorkflowBuilder {
@@ -66,7 +107,8 @@ macro_rules! reason_workflow_primitive_impl {
 reason_workflow_primitive_impl! {
     boolean => BooleanPrimitive,
     integer => IntegerPrimitive,
-    exact_string => ExactStringPrimitive
+    exact_string => ExactStringPrimitive,
+    ranking => RankingPrimitive
 }
 
 #[derive(Clone)]
@@ -76,6 +118,16 @@ pub struct ReasonResult {
     pub workflow: CascadeFlow,
     pub result_index: Option<u32>,
     pub temperature: f32,
+    /// Token, cost, and time totals of every request made by the reason workflow, including retried requests.
+    pub usage: AggregateUsage,
+    /// The thinking blocks of a reasoning model, kept out of the justification and the parsed result.
+    /// Requires [RequestConfigTrait::thinking_tags](llm_interface::requests::req_components::RequestConfigTrait::thinking_tags).
+    pub thinking: Option<String>,
+    /// The run from prompt construction to the parsed result. `None` unless [ReasonOneRound::trace] is set. See [WorkflowTrace].
+    pub trace: Option<WorkflowTrace>,
+    /// The quotes of the supporting material the answer is based on. Empty unless
+    /// [ReasonOneRound::cite_sources] is set.
+    pub citations: Vec<QuoteSpan>,
 }
 
 impl ReasonResult {
@@ -83,17 +135,22 @@ impl ReasonResult {
         flow: CascadeFlow,
         primitive: &P,
         base_req: &CompletionRequest,
+        trace: Option<WorkflowTrace>,
     ) -> crate::Result<Self> {
         let primitive_result = flow.primitive_result();
         let result_index = primitive_result
             .as_ref()
             .map(|primitive_result| primitive.primitive_to_result_index(primitive_result));
         Ok(ReasonResult {
+            thinking: flow.thinking(),
             primitive_result,
             duration: flow.duration,
             workflow: flow,
             result_index,
             temperature: base_req.config.temperature,
+            usage: base_req.usage.clone(),
+            trace,
+            citations: Vec::new(),
         })
     }
 }
@@ -111,6 +168,18 @@ impl std::fmt::Display for ReasonResult {
             "{}Reason temperature\x1b[0m: {:?}",
             SETTINGS_GRADIENT[1], self.temperature
         )?;
+        writeln!(
+            f,
+            "{}Reason usage\x1b[0m: {}",
+            SETTINGS_GRADIENT[1], self.usage
+        )?;
+        for citation in &self.citations {
+            writeln!(
+                f,
+                "{}Reason citation\x1b[0m: {}",
+                SETTINGS_GRADIENT[0], citation
+            )?;
+        }
         if let Some(primitive_result) = &self.primitive_result {
             writeln!(
                 f,
@@ -1,24 +1,31 @@
 use super::{
+    citation::{flow_citations, QUOTE_STEP_PREFIX},
     decision::DecisionTrait,
-    PrimitiveTrait,
-    ReasonResult,
-    ReasonTrait,
-    SentencesPrimitive,
+    trace::WorkflowTrace,
+    BooleanPrimitive, PrimitiveTrait, ReasonResult, ReasonTrait, SentencesPrimitive, TextPrimitive,
 };
 use crate::components::{
-    cascade::{step::StepConfig, CascadeFlow},
+    cascade::{render_prompt, render_prompt_messages, step::StepConfig, CascadeFlow},
     instruct_prompt::InstructPrompt,
     InstructPromptTrait,
 };
 use llm_interface::requests::{
-    completion::CompletionRequest,
+    completion::{AggregateUsage, CompletionRequest},
     req_components::{RequestConfig, RequestConfigTrait},
 };
+use llm_prompt::LlmPrompt;
+use std::collections::HashMap;
 
 pub struct ReasonOneRound<P> {
     pub reasoning_sentences: u8,
     pub conclusion_sentences: u8,
     pub result_can_be_none: bool,
+    /// Argues both the true and false cases before concluding. See [ReasonOneRound::balanced_justification].
+    pub balanced_justification: bool,
+    /// Quotes the supporting material the answer is based on. See [ReasonOneRound::cite_sources].
+    pub cite_sources: bool,
+    /// Records the run as [ReasonResult::trace]. See [ReasonOneRound::trace].
+    pub trace: bool,
     pub primitive: P,
     pub base_req: CompletionRequest,
     pub instruct_prompt: InstructPrompt,
@@ -41,23 +48,29 @@ impl<P: PrimitiveTrait + ReasonTrait> ReasonOneRound<P> {
         self.primitive.result_index_to_primitive(res.result_index)
     }
 
+    #[tracing::instrument(
+        name = "llm_client.reason",
+        skip_all,
+        fields(primitive = crate::primitives::primitive_name::<P>(), result_can_be_none = false)
+    )]
     pub async fn return_result(&mut self) -> crate::Result<ReasonResult> {
         self.result_can_be_none = false;
-
-        let mut flow = match self.reason_one_round() {
-            Ok(flow) => flow,
-            Err(e) => {
-                crate::error!("Error creating reason one round flow: {}", e);
-                return Err(e);
-            }
-        };
-        flow.run_all_rounds(&mut self.base_req).await?;
-
-        ReasonResult::new(flow, &self.primitive, &self.base_req)
+        self.run_reason().await
     }
 
+    #[tracing::instrument(
+        name = "llm_client.reason",
+        skip_all,
+        fields(primitive = crate::primitives::primitive_name::<P>(), result_can_be_none = true)
+    )]
     pub async fn return_optional_result(&mut self) -> crate::Result<ReasonResult> {
         self.result_can_be_none = true;
+        self.run_reason().await
+    }
+
+    async fn run_reason(&mut self) -> crate::Result<ReasonResult> {
+        self.base_req.usage = AggregateUsage::default();
+        let construction_start = std::time::Instant::now();
         let mut flow = match self.reason_one_round() {
             Ok(flow) => flow,
             Err(e) => {
@@ -65,8 +78,64 @@ impl<P: PrimitiveTrait + ReasonTrait> ReasonOneRound<P> {
                 return Err(e);
             }
         };
+        let (supporting_material_tokens, shared_context_tokens, base_prompt) = if self.trace {
+            let tokenizer = self.base_req.backend.tokenizer();
+            (
+                self.instruct_prompt
+                    .supporting_material
+                    .as_ref()
+                    .map(|supporting_material| {
+                        supporting_material.count_tokens(tokenizer.as_ref())
+                    }),
+                self.instruct_prompt
+                    .shared_context
+                    .as_ref()
+                    .map(|shared_context| tokenizer.count_tokens(shared_context)),
+                Some(self.base_req.prompt.clone()),
+            )
+        } else {
+            (None, None, None)
+        };
+        let prompt_construction_duration = construction_start.elapsed();
+
         flow.run_all_rounds(&mut self.base_req).await?;
-        ReasonResult::new(flow, &self.primitive, &self.base_req)
+
+        let trace = base_prompt.map(|base_prompt| {
+            let trace = WorkflowTrace {
+                workflow: flow.cascade_name.clone(),
+                primitive: crate::primitives::primitive_name::<P>(),
+                result_can_be_none: self.result_can_be_none,
+                instructions: self.instruct_prompt.build_instructions(),
+                supporting_material_tokens,
+                shared_context_tokens,
+                prompt_construction_duration,
+                justification: flow
+                    .rounds
+                    .last()
+                    .and_then(|round| round.outcome_before_final_step().ok()),
+                thinking: flow.thinking(),
+                parser_prompt: flow
+                    .final_request_prompt(&base_prompt)
+                    .and_then(|prompt| render_prompt(&prompt))
+                    .ok(),
+                primitive_result: flow.primitive_result(),
+                requests: self.base_req.usage.attempt_count,
+                retries: self
+                    .base_req
+                    .usage
+                    .attempt_count
+                    .saturating_sub(WorkflowTrace::inference_steps(&flow)),
+                duration: flow.duration,
+            };
+            trace.log();
+            trace
+        });
+        let supporting_material = self.instruct_prompt.build_supporting_material();
+        let mut res = ReasonResult::new(flow, &self.primitive, &self.base_req, trace)?;
+        if let (true, Some(supporting_material)) = (self.cite_sources, supporting_material) {
+            res.citations = flow_citations(&res.workflow, &supporting_material);
+        }
+        Ok(res)
     }
 
     pub fn reasoning_sentences(&mut self, reasoning_sentences: u8) -> &mut Self {
@@ -79,11 +148,52 @@ impl<P: PrimitiveTrait + ReasonTrait> ReasonOneRound<P> {
         self
     }
 
+    /// After reasoning, has the LLM quote the parts of the supporting material its reasoning is based on, and returns
+    /// the quotes as [ReasonResult::citations] with their offsets in the supporting material, so an answer can be checked
+    /// against its sources. Quotes that aren't in the supporting material are dropped. Requires supporting material.
+    ///
+    /// Defaults to `false`.
+    pub fn cite_sources(&mut self, cite_sources: bool) -> &mut Self {
+        self.cite_sources = cite_sources;
+        self
+    }
+
+    /// Records the run from prompt construction to the parsed result as [ReasonResult::trace], and logs it as a
+    /// `workflow trace` info event. This counts the tokens of the supporting material and shared context, and renders the
+    /// final request's prompt again, so it's off unless needed for auditing a run.
+    ///
+    /// Defaults to `false`.
+    pub fn trace(&mut self, trace: bool) -> &mut Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Returns the prompt of the final request [ReasonOneRound::return_result] would send, without making any requests.
+    /// The reasoning and conclusion the LLM would write are shown as [crate::components::cascade::PREVIEW_LLM_OUTPUT].
+    /// Local LLMs get the chat template output, and API LLMs get the messages as JSON.
+    /// Pass `true` to preview [ReasonOneRound::return_optional_result] instead.
+    pub fn preview_prompt(&mut self, result_can_be_none: bool) -> crate::Result<String> {
+        render_prompt(&self.preview_llm_prompt(result_can_be_none)?)
+    }
+
+    /// Returns the messages [ReasonOneRound::preview_prompt] is built from.
+    pub fn preview_prompt_messages(
+        &mut self,
+        result_can_be_none: bool,
+    ) -> crate::Result<Vec<HashMap<String, String>>> {
+        render_prompt_messages(&self.preview_llm_prompt(result_can_be_none)?)
+    }
+
+    fn preview_llm_prompt(&mut self, result_can_be_none: bool) -> crate::Result<LlmPrompt> {
+        self.result_can_be_none = result_can_be_none;
+        self.reason_one_round()?.preview_prompt(&self.base_req)
+    }
+
     fn reason_one_round(&mut self) -> crate::Result<CascadeFlow> {
         let mut flow = CascadeFlow::new("Reason One Round");
 
-        flow.new_round(
-        "A request will be provided. Think out loud about the request. State the arguments before arriving at a conclusion with, 'Therefore, we can conclude:...', and finish with a solution by saying, 'Thus, the solution...'. With no yapping.").add_guidance_step(
+        flow.new_round(self.instruct_prompt.prepend_shared_context(
+        "A request will be provided. Think out loud about the request. State the arguments before arriving at a conclusion with, 'Therefore, we can conclude:...', and finish with a solution by saying, 'Thus, the solution...'. With no yapping.".to_owned())).add_guidance_step(
         &StepConfig {
             ..StepConfig::default()
         },
@@ -92,24 +202,74 @@ impl<P: PrimitiveTrait + ReasonTrait> ReasonOneRound<P> {
 
         let task = self.build_task()?;
 
-        // CoT reasoning
-        let step_config = StepConfig {
-            step_prefix: Some("Thinking out loud about the users request...".to_string()),
-            stop_word_done: "Therefore, we can conclude".to_string(),
-            cache_prompt: false, // Clears the cache on the initial request
-            grammar: SentencesPrimitive::default()
-                .min_count(1)
-                .max_count(self.reasoning_sentences)
-                .grammar(),
-            ..StepConfig::default()
+        let conclusion_prefix = if self.balanced_justification {
+            // Balanced CoT reasoning
+            let step_config = StepConfig {
+                step_prefix: Some(
+                    "Making the strongest case that the answer is true...".to_string(),
+                ),
+                stop_word_done: "Now, making the strongest case that the answer is false"
+                    .to_string(),
+                // Clears the cache on the initial request, unless there's a shared context to reuse.
+                cache_prompt: self.instruct_prompt.shared_context.is_some(),
+                grammar: SentencesPrimitive::default()
+                    .min_count(1)
+                    .max_count(self.reasoning_sentences)
+                    .grammar(),
+                ..StepConfig::default()
+            };
+            flow.new_round(task).add_inference_step(&step_config);
+
+            let step_config = StepConfig {
+                step_prefix: Some(
+                    "Now, making the strongest case that the answer is false...".to_string(),
+                ),
+                stop_word_done: "Therefore, we can conclude".to_string(),
+                grammar: SentencesPrimitive::default()
+                    .min_count(1)
+                    .max_count(self.reasoning_sentences)
+                    .grammar(),
+                ..StepConfig::default()
+            };
+            flow.last_round()?.add_inference_step(&step_config);
+            "Having weighed the cases for true and false, the user requested a conclusion of"
+        } else {
+            // CoT reasoning
+            let step_config = StepConfig {
+                step_prefix: Some("Thinking out loud about the users request...".to_string()),
+                stop_word_done: "Therefore, we can conclude".to_string(),
+                // Clears the cache on the initial request, unless there's a shared context to reuse.
+                cache_prompt: self.instruct_prompt.shared_context.is_some(),
+                grammar: SentencesPrimitive::default()
+                    .min_count(1)
+                    .max_count(self.reasoning_sentences)
+                    .grammar(),
+                ..StepConfig::default()
+            };
+            flow.new_round(task).add_inference_step(&step_config);
+            "The user requested a conclusion of"
         };
 
-        flow.new_round(task).add_inference_step(&step_config);
+        // Citations
+        if self.cite_sources {
+            if self.instruct_prompt.supporting_material.is_none() {
+                return Err(anyhow::format_err!(
+                    "Citing sources requires supporting material to quote."
+                ));
+            }
+            let step_config = StepConfig {
+                step_prefix: Some(QUOTE_STEP_PREFIX.to_owned()),
+                stop_word_done: "Therefore, we can conclude".to_string(),
+                grammar: TextPrimitive::default().text_token_length(300).grammar(),
+                ..StepConfig::default()
+            };
+            flow.last_round()?.add_inference_step(&step_config);
+        }
 
         // Conclusion
         let step_config = StepConfig {
             step_prefix: Some(format!(
-                "The user requested a conclusion of {}. Therefore, we can conclude:",
+                "{conclusion_prefix} {}. Therefore, we can conclude:",
                 self.primitive.solution_description(self.result_can_be_none),
             )),
             stop_word_done: "Thus, the solution".to_string(),
@@ -176,6 +336,18 @@ impl<P: PrimitiveTrait + ReasonTrait> ReasonOneRound<P> {
     }
 }
 
+impl ReasonOneRound<BooleanPrimitive> {
+    /// Has the LLM argue the strongest case for both true and false before concluding, rather than reasoning once.
+    /// This reduces one-sided reasoning on ambiguous questions at the cost of an extra inference step.
+    /// Each case is limited to [ReasonOneRound::reasoning_sentences].
+    ///
+    /// Defaults to `false`.
+    pub fn balanced_justification(&mut self, balanced_justification: bool) -> &mut Self {
+        self.balanced_justification = balanced_justification;
+        self
+    }
+}
+
 impl<P: PrimitiveTrait> RequestConfigTrait for ReasonOneRound<P> {
     fn config(&mut self) -> &mut RequestConfig {
         &mut self.base_req.config
@@ -207,6 +379,10 @@ impl<P: PrimitiveTrait + ReasonTrait> DecisionTrait for ReasonOneRound<P> {
         &self.primitive
     }
 
+    fn primitive_mut(&mut self) -> &mut Self::ReasonPrimitive {
+        &mut self.primitive
+    }
+
     async fn return_reason_result(
         &mut self,
         result_can_be_none: bool,
@@ -217,4 +393,13 @@ impl<P: PrimitiveTrait + ReasonTrait> DecisionTrait for ReasonOneRound<P> {
             self.return_result().await
         }
     }
+
+    fn preview_reason_prompt(
+        &mut self,
+        base_req: &CompletionRequest,
+        result_can_be_none: bool,
+    ) -> crate::Result<LlmPrompt> {
+        self.result_can_be_none = result_can_be_none;
+        self.reason_one_round()?.preview_prompt(base_req)
+    }
 }
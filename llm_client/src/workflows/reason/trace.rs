@@ -0,0 +1,102 @@
+use crate::components::cascade::step::CascadeStep;
+
+/// A record of a reason workflow run from prompt construction to the parsed result, for auditing a run end to end.
+/// Recorded when [super::one_round::ReasonOneRound::trace] is set, returned as [super::ReasonResult::trace], and logged
+/// as a single `tracing` event when the run finishes.
+#[derive(Clone, Debug)]
+pub struct WorkflowTrace {
+    pub workflow: String,
+    /// The primitive's type name, e.g. `BooleanPrimitive`.
+    pub primitive: &'static str,
+    pub result_can_be_none: bool,
+    pub instructions: Option<String>,
+    /// Tokens in the supporting material, counted with the backend's tokenizer. `None` if there's no supporting material.
+    pub supporting_material_tokens: Option<u32>,
+    /// Tokens in the shared context, counted with the backend's tokenizer. `None` if there's no shared context.
+    pub shared_context_tokens: Option<u32>,
+    /// Time spent building the prompts before the first request.
+    pub prompt_construction_duration: std::time::Duration,
+    /// The reasoning and conclusion the LLM wrote before giving its answer.
+    pub justification: Option<String>,
+    /// The thinking blocks of a reasoning model, split off the responses with the request's thinking tags.
+    pub thinking: Option<String>,
+    /// The prompt of the final request, which produced the primitive result.
+    /// Local LLMs get the chat template output, and API LLMs get the messages as JSON. `None` if it couldn't be rendered.
+    pub parser_prompt: Option<String>,
+    /// The primitive result after grammar validation, or `None` if the LLM gave no result.
+    pub primitive_result: Option<String>,
+    /// Requests sent to the backend, including retries and attempts that failed without a response.
+    pub requests: u32,
+    /// Requests beyond one per inference step, i.e. retries of failed requests and of empty, banned, or malformed responses.
+    pub retries: u32,
+    pub duration: std::time::Duration,
+}
+
+impl WorkflowTrace {
+    /// The number of inference steps in the flow, which is the number of requests a run without retries makes.
+    pub(super) fn inference_steps(flow: &crate::components::cascade::CascadeFlow) -> u32 {
+        flow.rounds
+            .iter()
+            .flat_map(|round| round.resolved_steps.iter().chain(&round.unresolved_steps))
+            .filter(|step| matches!(step, CascadeStep::Inference(_)))
+            .count() as u32
+    }
+
+    /// Logs the trace as a single `tracing` info event.
+    pub fn log(&self) {
+        tracing::info!(
+            workflow = %self.workflow,
+            primitive = self.primitive,
+            result_can_be_none = self.result_can_be_none,
+            instructions = ?self.instructions,
+            supporting_material_tokens = ?self.supporting_material_tokens,
+            shared_context_tokens = ?self.shared_context_tokens,
+            prompt_construction_ms = self.prompt_construction_duration.as_millis() as u64,
+            justification = ?self.justification,
+            thinking = ?self.thinking,
+            parser_prompt = ?self.parser_prompt,
+            primitive_result = ?self.primitive_result,
+            requests = self.requests,
+            retries = self.retries,
+            duration_ms = self.duration.as_millis() as u64,
+            "workflow trace"
+        );
+    }
+}
+
+impl std::fmt::Display for WorkflowTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "WorkflowTrace:")?;
+        writeln!(f, "  workflow: {}", self.workflow)?;
+        writeln!(f, "  primitive: {}", self.primitive)?;
+        writeln!(f, "  result_can_be_none: {}", self.result_can_be_none)?;
+        writeln!(f, "  instructions: {:?}", self.instructions)?;
+        writeln!(
+            f,
+            "  supporting_material_tokens: {:?}",
+            self.supporting_material_tokens
+        )?;
+        writeln!(
+            f,
+            "  shared_context_tokens: {:?}",
+            self.shared_context_tokens
+        )?;
+        writeln!(
+            f,
+            "  prompt_construction_duration: {:?}",
+            self.prompt_construction_duration
+        )?;
+        writeln!(f, "  justification: {:?}", self.justification)?;
+        writeln!(f, "  thinking: {:?}", self.thinking)?;
+        if let Some(parser_prompt) = &self.parser_prompt {
+            writeln!(f, "  parser_prompt:\n{parser_prompt}")?;
+        } else {
+            writeln!(f, "  parser_prompt: None")?;
+        }
+        writeln!(f, "  primitive_result: {:?}", self.primitive_result)?;
+        writeln!(f, "  requests: {}", self.requests)?;
+        writeln!(f, "  retries: {}", self.retries)?;
+        write!(f, "  duration: {:?}", self.duration)
+    }
+}
@@ -12,3 +12,64 @@ pub async fn api_backends() -> crate::Result<()> {
     basic_completion_tests::basic_completion_integration_tester(&llm_client).await?;
     Ok(())
 }
+
+#[test]
+fn conversation_manager_trigger_tokens() -> crate::Result<()> {
+    use llm_interface::llms::api::config::LlmApiConfigTrait;
+
+    // gpt-4o has a 128k context and a 4096 token max output.
+    let llm_client = LlmClient::openai()
+        .gpt_4_o()
+        .with_api_key("test-key")
+        .init()?;
+    assert_eq!(
+        llm_client.conversation_manager().trigger_tokens,
+        128000 - 4096
+    );
+
+    let llm_client = LlmClient::generic_api()
+        .base_url("http://localhost:8000/v1")
+        .model_id("test-model")
+        .model_ctx_size(8192)
+        .init()?;
+    assert_eq!(llm_client.conversation_manager().trigger_tokens, 6144);
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn azure_openai_backend() -> crate::Result<()> {
+    let llm_client = LlmClient::azure_openai()
+        .endpoint(std::env::var("AZURE_OPENAI_ENDPOINT")?)
+        .deployment(std::env::var("AZURE_OPENAI_DEPLOYMENT")?)
+        .gpt_4_o()
+        .init()?;
+    basic_completion_tests::basic_completion_integration_tester(&llm_client).await?;
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn search_completion() -> crate::Result<()> {
+    for llm_client in [
+        LlmClient::perplexity().sonar().init()?,
+        LlmClient::openai().gpt_4_o_mini_search_preview().init()?,
+    ] {
+        let mut gen = llm_client.search_completion();
+        gen.prompt()
+            .add_user_message()?
+            .set_content("What is the latest stable release of Rust?");
+        let res = gen.run().await?;
+        println!("{res}");
+        assert!(!res.content.is_empty());
+        assert!(!res.citations.is_empty());
+    }
+
+    let llm_client = LlmClient::anthropic().claude_3_haiku().init()?;
+    let mut gen = llm_client.search_completion();
+    gen.prompt().add_user_message()?.set_content("Hello");
+    assert!(gen.run().await.is_err());
+    Ok(())
+}
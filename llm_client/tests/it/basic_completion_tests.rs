@@ -23,6 +23,16 @@ mod basic_completion_unit_tests {
         Ok(())
     }
 
+    #[cfg(feature = "llama_cpp_backend")]
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_llama_greedy() -> crate::Result<()> {
+        let llm_client = llama_cpp_tiny_llm().await?;
+        basic_completion_greedy_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
     #[cfg(feature = "mistral_rs_backend")]
     #[tokio::test]
     #[serial]
@@ -60,6 +70,92 @@ mod basic_completion_unit_tests {
         Ok(())
     }
 
+    #[cfg(feature = "llama_cpp_backend")]
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_llama_continue_from() -> crate::Result<()> {
+        let llm_client = llama_cpp_tiny_llm().await?;
+        basic_completion_continue_from_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_openai_continue_from() -> crate::Result<()> {
+        let llm_client = LlmClient::openai().gpt_3_5_turbo().init()?;
+        basic_completion_continue_from_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_anthropic_continue_from() -> crate::Result<()> {
+        let llm_client = LlmClient::anthropic().claude_3_haiku().init()?;
+        basic_completion_continue_from_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "llama_cpp_backend")]
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_llama_num_completions() -> crate::Result<()> {
+        let llm_client = llama_cpp_tiny_llm().await?;
+        basic_completion_num_completions_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_openai_num_completions() -> crate::Result<()> {
+        let llm_client = LlmClient::openai().gpt_3_5_turbo().init()?;
+        basic_completion_num_completions_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "llama_cpp_backend")]
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_llama_timeout_and_fallback() -> crate::Result<()> {
+        let llm_client = llama_cpp_tiny_llm().await?;
+        basic_completion_fallback_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "llama_cpp_backend")]
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_llama_from_prompt() -> crate::Result<()> {
+        let llm_client = llama_cpp_tiny_llm().await?;
+        basic_completion_from_prompt_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "llama_cpp_backend")]
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_llama_run_to_file() -> crate::Result<()> {
+        let llm_client = llama_cpp_tiny_llm().await?;
+        basic_completion_run_to_file_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    pub async fn test_ollama() -> crate::Result<()> {
+        let llm_client = LlmClient::ollama().pull_if_missing(true).init().await?;
+        basic_completion_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     #[ignore]
@@ -86,6 +182,142 @@ pub(super) async fn basic_completion_integration_tester(
     Ok(())
 }
 
+pub(super) async fn basic_completion_continue_from_integration_tester(
+    llm_client: &LlmClient,
+) -> crate::Result<()> {
+    let mut gen = llm_client.basic_completion();
+    let draft = "Roses are red, violets are blue,";
+    gen.prompt()
+        .add_user_message()
+        .unwrap()
+        .set_content("Write a four line poem.");
+    gen.max_tokens(50).continue_from(draft);
+    let res = gen.run().await?;
+    println!("Response:\n {draft}{}\n", res.content);
+    assert!(!res.content.is_empty());
+    assert!(!res.content.trim_start().starts_with(draft));
+    Ok(())
+}
+
+pub(super) async fn basic_completion_from_prompt_integration_tester(
+    llm_client: &LlmClient,
+) -> crate::Result<()> {
+    let prompt = llm_client.new_prompt();
+    prompt
+        .add_system_message()?
+        .set_content("You answer in one short sentence.");
+    prompt.add_user_message()?.set_content("Name a fruit.");
+    prompt.add_assistant_message()?.set_content("An apple.");
+    prompt.add_user_message()?.set_content("Name another one.");
+
+    let mut gen = llm_client.basic_completion_from_prompt(prompt.clone());
+    gen.max_tokens(20);
+    let res = gen.run().await?;
+    println!("Response:\n {}\n", res.content);
+    assert!(!res.content.is_empty());
+
+    // The original prompt is untouched and can be sent again.
+    assert_eq!(prompt.message_count(), 4);
+    let mut gen = llm_client.basic_completion_from_prompt(prompt);
+    gen.max_tokens(20);
+    let res = gen.run().await?;
+    assert!(!res.content.is_empty());
+    Ok(())
+}
+
+pub(super) async fn basic_completion_run_to_file_integration_tester(
+    llm_client: &LlmClient,
+) -> crate::Result<()> {
+    let path = std::env::temp_dir().join("llm_client_run_to_file_test.txt");
+    let _ = std::fs::remove_file(&path);
+    let mut gen = llm_client.basic_completion();
+    gen.prompt()
+        .add_user_message()?
+        .set_content("Write a short story about a lighthouse keeper.");
+    gen.max_tokens(200);
+    let completion_tokens = gen.run_to_file(&path).await?;
+    let content = std::fs::read_to_string(&path)?;
+    println!("Response ({completion_tokens} tokens):\n {content}\n");
+    assert!(completion_tokens > 0);
+    assert!(!content.is_empty());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+pub(super) async fn basic_completion_num_completions_integration_tester(
+    llm_client: &LlmClient,
+) -> crate::Result<()> {
+    let mut gen = llm_client.basic_completion();
+    gen.prompt()
+        .add_user_message()
+        .unwrap()
+        .set_content("Name a color.");
+    gen.max_tokens(20).temperature(1.0).num_completions(3);
+    let responses = gen.run_completions().await?;
+    assert_eq!(responses.len(), 3);
+    for (i, res) in responses.iter().enumerate() {
+        println!("Response {i}:\n {}\n", res.content);
+        assert!(!res.content.is_empty());
+        assert_eq!(res.index, Some(i as u32));
+    }
+    Ok(())
+}
+
+pub(super) async fn basic_completion_fallback_integration_tester(
+    llm_client: &LlmClient,
+) -> crate::Result<()> {
+    use llm_client::fallback::{with_timeout_and_fallback, ClientUsed};
+    let run = |client: &LlmClient| {
+        let mut gen = client.basic_completion();
+        gen.prompt()
+            .add_user_message()
+            .unwrap()
+            .set_content("Write a short poem about the ocean.");
+        gen.max_tokens(50);
+        async move { gen.run().await }
+    };
+    // The same client stands in for both, so only the deadline decides which is used.
+    let res = with_timeout_and_fallback(
+        llm_client,
+        llm_client,
+        std::time::Duration::from_millis(1),
+        run,
+    )
+    .await?;
+    assert_eq!(res.client_used, ClientUsed::Fallback);
+    assert!(!res.output.content.is_empty());
+
+    let res = with_timeout_and_fallback(
+        llm_client,
+        llm_client,
+        std::time::Duration::from_secs(120),
+        run,
+    )
+    .await?;
+    assert_eq!(res.client_used, ClientUsed::Primary);
+    assert!(!res.output.content.is_empty());
+    Ok(())
+}
+
+pub(super) async fn basic_completion_greedy_integration_tester(
+    llm_client: &LlmClient,
+) -> crate::Result<()> {
+    let mut gen = llm_client.basic_completion();
+    gen.prompt()
+        .add_user_message()
+        .unwrap()
+        .set_content("Write a short poem about the ocean.");
+    gen.max_tokens(50).temperature(0.0);
+    let first = gen.run().await?;
+    println!("Response:\n {}\n", first.content);
+    for _ in 0..2 {
+        let res = gen.run().await?;
+        println!("Response:\n {}\n", res.content);
+        assert_eq!(first.content, res.content);
+    }
+    Ok(())
+}
+
 pub(super) async fn basic_completion_logit_bias_integration_tester(
     llm_client: &LlmClient,
 ) -> crate::Result<()> {
@@ -115,6 +347,19 @@ pub(super) async fn basic_completion_logit_bias_integration_tester(
     println!("Response:\n {}\n", res.content);
     assert!(!res.content.contains("ya'll"));
 
+    gen.prompt().reset_prompt();
+    gen.clear_logit_bias();
+    gen.prompt()
+        .add_user_message()
+        .unwrap()
+        .set_content("Write a buzzfeed style listicle for the given input!")
+        .append_content("Boy howdy, how ya'll doing?");
+    gen.max_tokens(100).add_logit_bias_word("howdy", -100.0);
+
+    let res = gen.run().await?;
+    println!("Response:\n {}\n", res.content);
+    assert!(!res.content.to_lowercase().contains("howdy"));
+
     gen.prompt().reset_prompt();
     gen.clear_logit_bias();
     gen.prompt()
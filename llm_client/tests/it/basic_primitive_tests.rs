@@ -29,6 +29,26 @@ mod basic_primitive_unit_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_batch() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.basic_primitive().batch_concurrency(2).boolean();
+        gen.instructions().set_content("Is the review positive?");
+        let inputs = vec![
+            "I loved it, best purchase this year.".to_owned(),
+            "Broke after one day. Avoid.".to_owned(),
+            "Great value and fast shipping.".to_owned(),
+        ];
+        let res = gen.run_batch(inputs).await;
+        assert_eq!(res.len(), 3);
+        assert!(*res[0].as_ref().unwrap());
+        assert!(!*res[1].as_ref().unwrap());
+        assert!(*res[2].as_ref().unwrap());
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     #[ignore]
@@ -47,6 +67,44 @@ mod basic_primitive_unit_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn integer_hex() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.basic_primitive().integer();
+        gen.primitive.upper_bound(0xffffff).radix(16)?;
+        gen.instructions().set_content(
+            "The button uses the brand color #1e90ff. What is the color code as a hexadecimal number?",
+        );
+        let res = gen.return_primitive().await?;
+        print_results(&gen.base_req.prompt, &None::<String>, &Some(res));
+        assert_eq!(res, 0x1e90ff);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn percentage() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.basic_primitive().percentage();
+        gen.instructions().set_content(
+            "About 35% of respondents said they commute by bike. What share commute by bike?",
+        );
+        let res = gen.return_primitive().await?;
+        print_results(&gen.base_req.prompt, &None::<String>, &Some(res));
+        assert_eq!(res, 0.35);
+        gen.reset_request();
+
+        gen.instructions()
+            .set_content("Most respondents said they commute by car. What share commute by bike?");
+        let res = gen.return_optional_primitive().await?;
+        print_results(&gen.base_req.prompt, &None::<String>, &Some(res));
+        assert_eq!(res, None);
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     #[ignore]
@@ -64,6 +122,24 @@ mod basic_primitive_unit_tests {
         exact_string_optional_integration_tester(&llm_client, &TestLevel::IntegrationTest).await?;
         Ok(())
     }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn uuid() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        uuid_integration_tester(&llm_client).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn email() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        email_integration_tester(&llm_client).await?;
+        Ok(())
+    }
 }
 
 pub(super) async fn run(llm_client: &LlmClient, test_level: &TestLevel) -> crate::Result<()> {
@@ -210,6 +286,49 @@ pub(super) async fn exact_string_optional_integration_tester(
     Ok(())
 }
 
+pub(super) async fn uuid_integration_tester(llm_client: &LlmClient) -> crate::Result<()> {
+    let mut gen = llm_client.basic_primitive().uuid();
+    gen.instructions()
+        .set_content("Extract the order id from the support ticket.");
+    gen.supporting_material().set_content(
+        "Ticket: My order 67e55044-10b1-426f-9247-bb680e5fe0c8 arrived damaged. Please advise.",
+    );
+    let res = gen.return_primitive().await?;
+    print_results(&gen.base_req.prompt, &None::<String>, &Some(res));
+    assert_eq!(res.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+    gen.reset_request();
+
+    gen.instructions()
+        .set_content("Extract the order id from the support ticket.");
+    gen.supporting_material()
+        .set_content("Ticket: My order arrived damaged. Please advise.");
+    let res = gen.return_optional_primitive().await?;
+    print_results(&gen.base_req.prompt, &None::<String>, &Some(res));
+    assert_eq!(res, None);
+    Ok(())
+}
+
+pub(super) async fn email_integration_tester(llm_client: &LlmClient) -> crate::Result<()> {
+    let mut gen = llm_client.basic_primitive().email();
+    gen.instructions()
+        .set_content("Extract the sender's email address from the message.");
+    gen.supporting_material()
+        .set_content("Hi team, reach me at jane.doe@example.com if anything comes up. - Jane");
+    let res = gen.return_primitive().await?;
+    print_results(&gen.base_req.prompt, &None::<String>, &Some(res.clone()));
+    assert_eq!(res, "jane.doe@example.com");
+    gen.reset_request();
+
+    gen.instructions()
+        .set_content("Extract the sender's email address from the message.");
+    gen.supporting_material()
+        .set_content("Hi team, call me if anything comes up. - Jane");
+    let res = gen.return_optional_primitive().await?;
+    print_results(&gen.base_req.prompt, &None::<String>, &Some(res.clone()));
+    assert_eq!(res, None);
+    Ok(())
+}
+
 pub(super) async fn text_list_integration_tester(llm_client: &LlmClient) -> crate::Result<()> {
     let mut gen = llm_client.basic_primitive().text_list();
 
@@ -20,6 +20,135 @@ mod decision_unit_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_single_shot() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().boolean().decision();
+        gen.single_shot();
+        gen.instructions()
+            .set_content("Is the sky blue on a clear day?");
+        let result = gen.return_result().await?;
+        assert_eq!(result.total_votes, 1);
+        assert_eq!(result.reason_results.len(), 1);
+        assert_eq!(result.confidence, 1.0);
+        assert_eq!(result.vote_log.iter().filter(|v| v.counted).count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_usage() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().boolean().decision();
+        gen.instructions()
+            .set_content("Is the sky blue on a clear day?");
+        let result = gen.return_result().await?;
+        let reason_requests: u32 = result
+            .reason_results
+            .iter()
+            .map(|r| r.usage.request_count)
+            .sum();
+        assert!(result.usage.request_count >= reason_requests);
+        assert!(result.usage.request_count >= result.total_votes as u32);
+        assert!(result.usage.prompt_tokens > 0);
+        assert!(result.usage.completion_tokens > 0);
+        // Local backends have no cost.
+        assert_eq!(result.usage.dollar_cost, 0.0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_min_confidence() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().boolean().decision();
+        gen.best_of_n_votes(5).min_confidence(0.8).max_votes(7);
+        gen.instructions()
+            .set_content("Is the sky blue on a clear day?");
+        let result = gen.return_result().await?;
+        assert!(result.total_votes <= 7);
+        assert!(result.confidence >= 0.8 || result.total_votes == 7);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_early_stop_on_unanimous() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().boolean().decision();
+        gen.best_of_n_votes(9).early_stop_on_unanimous(3);
+        gen.instructions()
+            .set_content("Is the sky blue on a clear day?");
+        let result = gen.return_result().await?;
+        // A majority of nine takes five votes, so finishing after three means the first three agreed.
+        assert!(result.total_votes <= 9);
+        if result.total_votes == 3 {
+            assert_eq!(result.confidence, 1.0);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn exact_string_shuffle_choices() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().exact_string().decision();
+        gen.shuffle_choices(true).shuffle_seed(7);
+        gen.reason
+            .primitive
+            .add_strings_to_allowed(&["red", "green", "blue"]);
+        gen.instructions()
+            .set_content("What color is the sky on a clear day?");
+        let result = gen.return_result().await?;
+        assert_eq!(result.winner_index, Some(2));
+        assert!(result.vote_log.iter().all(|v| v.choice_order.is_some()));
+        assert_eq!(gen.return_primitive().await?, "blue");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn compare() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().compare(
+            "The mitochondria is the powerhouse of the cell.",
+            "The mitochondria is a type of cell wall.",
+        );
+        gen.set_instructions("Which statement is more factually accurate?");
+        let result = gen.return_result().await?;
+        assert_eq!(result.decision_results.len(), 2);
+        assert!(result.a_is_better());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn content_filtered_vote() -> crate::Result<()> {
+        let (base_url, requests) = content_filter_server().await;
+        let llm_client = LlmClient::generic_api()
+            .base_url(base_url)
+            .model_id("test-model")
+            .init()?;
+        let mut gen = llm_client.reason().boolean().decision();
+        gen.instructions().set_content("Is the sky blue?");
+        match gen.return_result().await {
+            Err(e) => assert!(matches!(
+                e.downcast_ref::<llm_interface::requests::completion::CompletionError>(),
+                Some(llm_interface::requests::completion::CompletionError::ContentFiltered(_))
+            )),
+            Ok(result) => panic!("expected ContentFiltered, got {result}"),
+        }
+        // The first vote's refusal ends the decision instead of being repeated by the other votes.
+        assert_eq!(*requests.lock().unwrap(), 1);
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     #[ignore]
@@ -88,6 +217,10 @@ pub(super) async fn boolean_integration_tester(
         gen.instructions().set_content(&case.question);
         let result = gen.return_result().await?;
         let res = gen.parse_decision_result(&result)?;
+        assert_eq!(
+            result.vote_log.iter().filter(|v| v.counted).count(),
+            result.total_votes as usize
+        );
         print_results(&gen.reason.base_req.prompt, &Some(result), &Some(res));
         case.result = res;
         gen.reset_request();
@@ -206,3 +339,58 @@ pub(super) async fn exact_string_optional_integration_tester(
     tests.check_results();
     Ok(())
 }
+
+/// Answers every chat request with an OpenAI response filtered with `finish_reason: "content_filter"`.
+/// Returns the base url and the number of requests.
+async fn content_filter_server() -> (String, std::sync::Arc<std::sync::Mutex<usize>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(0));
+    let requested = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let content_length = headers
+                        .lines()
+                        .find_map(|line| {
+                            line.to_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= content_length {
+                        break;
+                    }
+                }
+            }
+            *requested.lock().unwrap() += 1;
+            let body = serde_json::json!({
+                "id": "chatcmpl-1",
+                "created": 0,
+                "model": "test-model",
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": ""},
+                    "finish_reason": "content_filter"
+                }],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 0, "total_tokens": 5}
+            })
+            .to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (base_url, requests)
+}
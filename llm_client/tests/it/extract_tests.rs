@@ -10,6 +10,44 @@ mod extract_unit_tests {
         extract_urls_integration_tester(&llm_client, &TestLevel::IntegrationTest).await?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn extract_urls_without_supporting_material() -> crate::Result<()> {
+        // No requests are made, so the backend is never reached.
+        let llm_client = LlmClient::generic_api()
+            .base_url("http://127.0.0.1:1/v1")
+            .model_id("test-model")
+            .init()?;
+        let mut gen = llm_client.nlp().extract().urls();
+        gen.instructions()
+            .set_content("Which of these URLs, https://docs.rs or https://example.com, are docs?");
+        let err = gen.run_return_result().await.err().unwrap();
+        assert!(err.to_string().contains("No supporting material"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn extract_pii() -> crate::Result<()> {
+        use llm_client::workflows::nlp::extract::pii::PiiKind;
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.nlp().extract_pii(&PiiKind::ALL);
+        gen.set_supporting_material("Contact Jane at jane.doe@gmail.com or +1 (415) 555-0132. We upgraded the app to version 2.4.10.1 last week.");
+        let candidates = gen.candidates()?;
+        assert_eq!(candidates.len(), 3);
+        let result = gen.run_return_result().await?;
+        println!("{result}");
+        assert!(result
+            .spans
+            .iter()
+            .any(|span| span.kind == PiiKind::Email && span.text == "jane.doe@gmail.com"));
+        assert!(result
+            .rejected
+            .iter()
+            .any(|span| span.kind == PiiKind::IpAddress && span.text == "2.4.10.1"));
+        Ok(())
+    }
 }
 
 pub(super) async fn extract_urls_integration_tester(
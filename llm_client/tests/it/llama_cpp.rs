@@ -51,3 +51,268 @@ pub async fn llama_cpp_decision_integration_test() -> crate::Result<()> {
     decision_tests::run_optional(&llm_client, &TestLevel::IntegrationTest).await?;
     Ok(())
 }
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_warmup_integration_test() -> crate::Result<()> {
+    let llm_client = llama_cpp_tiny_llm().await?;
+    let warmup_duration = llm_client.warmup().await?;
+    assert!(warmup_duration > std::time::Duration::ZERO);
+    basic_completion_tests::basic_completion_integration_tester(&llm_client).await?;
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_load_model_integration_test() -> crate::Result<()> {
+    let mut llm_client = llama_cpp_tiny_llm().await?;
+    let old_backend = llm_client.backend.clone();
+    basic_completion_tests::basic_completion_integration_tester(&llm_client).await?;
+
+    let mut llm_loader = llm_models::local_model::gguf::GgufLoader::default();
+    llm_loader.gguf_preset_loader.llm_preset =
+        llm_models::local_model::gguf::preset::LlmPreset::Llama3_1_8bInstruct;
+    llm_client.load_model(llm_loader).await?;
+    assert!(llm_client.backend.model_id().to_lowercase().contains("8b"));
+    basic_completion_tests::basic_completion_integration_tester(&llm_client).await?;
+
+    // Requests to the old backend are rejected.
+    let mut req = CompletionRequest::new(old_backend);
+    req.prompt.add_user_message()?.set_content("Hi");
+    assert!(req.request().await.is_err());
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_load_model_failure_integration_test() -> crate::Result<()> {
+    let mut llm_client = llama_cpp_tiny_llm().await?;
+    let model_id = llm_client.backend.model_id().to_owned();
+
+    let mut llm_loader = llm_models::local_model::gguf::GgufLoader::default();
+    llm_loader.gguf_local_loader.local_quant_file_path =
+        Some(std::path::PathBuf::from("/nonexistent/model.gguf"));
+    assert!(llm_client.load_model(llm_loader).await.is_err());
+
+    // The previous model is started again.
+    assert_eq!(llm_client.backend.model_id(), model_id);
+    basic_completion_tests::basic_completion_integration_tester(&llm_client).await?;
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_dry_sampler_integration_test() -> crate::Result<()> {
+    let llm_client = LlmClient::llama_cpp()
+        .dry_sampler(llm_client::backend_builders::llama_cpp::DrySampler::default())
+        .init()
+        .await?;
+    basic_completion_tests::basic_completion_integration_tester(&llm_client).await?;
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_embeddings_integration_test() -> crate::Result<()> {
+    let llm_client = LlmClient::llama_cpp()
+        .nomic_embed_text_v1_5()
+        .init()
+        .await?;
+    let inputs = [
+        "The cat sat on the mat.",
+        "Rust is a systems programming language.",
+    ];
+    let embeddings = llm_client.embeddings(&inputs).await?;
+    assert_eq!(embeddings.len(), inputs.len());
+    assert!(embeddings.iter().all(|embedding| !embedding.is_empty()));
+    assert!(llm_client.basic_completion().run().await.is_err());
+    llm_client.shutdown_gracefully().await?;
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_rag_integration_test() -> crate::Result<()> {
+    use llm_interface::llms::api::config::LlmApiConfigTrait;
+    let embedder = LlmClient::llama_cpp()
+        .nomic_embed_text_v1_5()
+        .with_api_port("8081")
+        .init()
+        .await?;
+    let llm_client = llama_cpp_tiny_llm().await?;
+
+    let mut rag = llm_client.nlp().rag().embedder(&embedder).top_k(2);
+    rag.add_chunks(&[
+        "The office wifi password is hunter2.",
+        "Lunch is served at noon in the cafeteria.",
+        "Parking passes are renewed every January.",
+    ]);
+    rag.set_query("What is the wifi password?");
+    let res = rag.run_return_result().await?;
+    println!("{res}");
+    assert_eq!(res.retrieved.len(), 2);
+    assert_eq!(res.retrieved[0].index, 0);
+    assert!(res.content.contains("hunter2"));
+
+    // The corpus is embedded once and reused for the next query.
+    rag.set_query("When is lunch?");
+    let res = rag.run_return_result().await?;
+    assert_eq!(res.retrieved[0].index, 1);
+    assert_eq!(rag.embeddings.len(), 3);
+
+    embedder.shutdown_gracefully().await?;
+    llm_client.shutdown_gracefully().await?;
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_tokenize_integration_test() -> crate::Result<()> {
+    let llm_client = llama_cpp_tiny_llm().await?;
+    let text = "The quick brown fox jumps over the lazy dog.";
+
+    let tokens = llm_client.tokenize(text);
+    assert!(!tokens.is_empty());
+    assert_eq!(llm_client.detokenize(&tokens)?.trim(), text);
+
+    let server_tokens = llm_client.server_tokenize(text).await?;
+    assert!(!server_tokens.is_empty());
+    assert_eq!(
+        llm_client.server_detokenize(&server_tokens).await?.trim(),
+        text
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+#[ignore]
+pub async fn llama_cpp_tokenize_options_integration_test() -> crate::Result<()> {
+    let llm_client = llama_cpp_tiny_llm().await?;
+    let text = "Hello<|eot_id|>";
+
+    let plain = llm_client
+        .server_tokenize_with_options(text, &TokenizeOptions::default())
+        .await?;
+    let with_bos = llm_client
+        .server_tokenize_with_options(
+            text,
+            &TokenizeOptions {
+                add_special: true,
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert!(with_bos.len() > plain.len());
+
+    let unparsed = llm_client
+        .server_tokenize_with_options(
+            text,
+            &TokenizeOptions {
+                parse_special: false,
+                with_pieces: true,
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert!(unparsed.len() > plain.len());
+    assert!(unparsed.iter().all(|token| token.piece.is_some()));
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+#[ignore]
+pub async fn llama_cpp_ban_phrases_integration_test() -> crate::Result<()> {
+    let llm_client = llama_cpp_tiny_llm().await?;
+    let mut gen = llm_client.basic_completion();
+    gen.prompt()
+        .add_user_message()?
+        .set_content("Repeat after me: the quick brown fox jumps over the lazy dog.");
+    gen.max_tokens(50)
+        .retry_after_fail_n_times(5)
+        .ban_phrases(vec!["brown fox".to_owned()]);
+    match gen.run().await {
+        Ok(res) => assert!(!res.content.to_lowercase().contains("brown fox")),
+        Err(e) => match e.downcast_ref::<llm_interface::requests::completion::CompletionError>() {
+            Some(llm_interface::requests::completion::CompletionError::ExceededRetryCount {
+                errors,
+                ..
+            }) => assert!(errors.iter().all(|e| matches!(
+                e,
+                llm_interface::requests::completion::CompletionError::BannedPhrase(_)
+            ))),
+            _ => panic!("expected the banned phrase retries to run out, got {e}"),
+        },
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    requests: std::sync::atomic::AtomicU32,
+    responses: std::sync::atomic::AtomicU32,
+}
+
+impl RequestObserver for CountingObserver {
+    fn on_request(&self, _req: &CompletionRequest) {
+        self.requests
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    fn on_response(&self, _req: &CompletionRequest, _res: &CompletionResponse) {
+        self.responses
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+#[serial]
+#[ignore]
+pub async fn llama_cpp_observer_integration_test() -> crate::Result<()> {
+    let observer = std::sync::Arc::new(CountingObserver::default());
+    let llm_client = llama_cpp_tiny_llm().await?.observer(observer.clone());
+    let mut gen = llm_client.reason().boolean().decision();
+    gen.instructions()
+        .set_content("Is the sky blue on a clear day?");
+    let result = gen.return_result().await?;
+
+    let requests = observer.requests.load(std::sync::atomic::Ordering::SeqCst);
+    let responses = observer.responses.load(std::sync::atomic::Ordering::SeqCst);
+    assert!(requests > 0);
+    assert!(requests >= responses);
+    assert_eq!(responses, result.usage.request_count);
+    Ok(())
+}
+
+#[ignore]
+#[tokio::test]
+#[serial]
+pub async fn llama_cpp_conversation_manager_integration_test() -> crate::Result<()> {
+    let llm_client = llama_cpp_tiny_llm().await?;
+    let mut conversation = llm_client.conversation_manager();
+    conversation.trigger_tokens(200).summarize_turns(1);
+    conversation
+        .prompt()
+        .add_system_message()?
+        .set_content("You are a helpful assistant. Answer in one sentence.");
+    for question in [
+        "My name is Ada. What's the capital of France?",
+        "What's the capital of Italy?",
+        "What's the capital of Spain?",
+        "What's my name?",
+    ] {
+        let res = conversation.max_tokens(50).send(question).await?;
+        println!("{question}\n{}\n", res.content);
+    }
+    assert!(conversation.summary.is_some());
+    assert!(conversation.prompt().total_conversation_tokens()? <= 200 + 50);
+    Ok(())
+}
@@ -10,6 +10,122 @@ mod reason_unit_tests {
         boolean_integration_tester(&llm_client, &TestLevel::IntegrationTest).await?;
         Ok(())
     }
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_balanced() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        boolean_balanced_integration_tester(&llm_client, &TestLevel::IntegrationTest).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_shared_context() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client
+            .reason()
+            .shared_context(
+                "The Alder Street bridge was built in 1931 from riveted steel. It carries two lanes of traffic and a \
+                 pedestrian walkway over the Kettle River, and was closed for repairs from 2019 to 2021.",
+            )
+            .boolean();
+        for (question, expected) in [
+            ("Was the bridge built before 1950?", true),
+            ("Is the bridge made of wood?", false),
+            ("Does the bridge have a pedestrian walkway?", true),
+        ] {
+            gen.instructions().set_content(question);
+            let result = gen.return_result().await?;
+            let res = gen.primitive.parse_reason_result(&result)?;
+            print_results(&gen.base_req.prompt, &Some(result), &Some(res));
+            assert_eq!(res, Some(expected));
+            gen.reset_request();
+            assert!(gen.instruct_prompt.shared_context.is_some());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn boolean_cite_sources() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let supporting_material = "The Alder Street bridge was built in 1931 from riveted steel. It carries two lanes of \
+                                   traffic and a pedestrian walkway over the Kettle River.";
+        let mut gen = llm_client.reason().boolean();
+        gen.cite_sources(true);
+        gen.instructions()
+            .set_content("Was the bridge built before 1950?");
+        gen.supporting_material().set_content(supporting_material);
+        let result = gen.return_result().await?;
+        let res = gen.primitive.parse_reason_result(&result)?;
+        print_results(&gen.base_req.prompt, &Some(result.clone()), &Some(res));
+        assert_eq!(res, Some(true));
+        for citation in &result.citations {
+            assert_eq!(
+                supporting_material
+                    .chars()
+                    .skip(citation.char_offset)
+                    .take(citation.char_len)
+                    .collect::<String>(),
+                citation.quote
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn preview_prompt() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().boolean().decision();
+        gen.instructions().set_content("Is the sky blue?");
+        let prompt = gen.preview_prompt(false)?;
+        println!("{prompt}");
+        assert!(prompt.contains("Is the sky blue?"));
+        assert!(prompt.contains(llm_client::components::cascade::PREVIEW_LLM_OUTPUT));
+        assert!(prompt
+            .trim_end()
+            .ends_with("solution to the user's request is:"));
+        assert!(!gen.preview_prompt_messages(false)?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn workflow_trace() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        let mut gen = llm_client.reason().boolean();
+        gen.instructions().set_content("Is the sky blue?");
+        gen.supporting_material()
+            .set_content("The sky appears blue during the day.");
+        let res = gen.return_result().await?;
+        assert!(res.trace.is_none());
+
+        gen.trace(true);
+        let res = gen.return_result().await?;
+        let trace = res.trace.as_ref().unwrap();
+        println!("{trace}");
+        assert_eq!(trace.primitive, "BooleanPrimitive");
+        assert_eq!(trace.instructions.as_deref(), Some("Is the sky blue?"));
+        assert!(trace.supporting_material_tokens.unwrap() > 0);
+        assert!(!trace.justification.as_deref().unwrap().is_empty());
+        assert!(trace
+            .parser_prompt
+            .as_deref()
+            .unwrap()
+            .trim_end()
+            .ends_with("solution to the user's request is:"));
+        assert_eq!(trace.primitive_result, res.primitive_result);
+        assert_eq!(trace.requests, res.usage.attempt_count);
+        assert!(trace.requests >= res.usage.request_count);
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     #[ignore]
@@ -54,6 +170,15 @@ mod reason_unit_tests {
         exact_string_optional_integration_tester(&llm_client, &TestLevel::IntegrationTest).await?;
         Ok(())
     }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn ranking() -> crate::Result<()> {
+        let llm_client = default_tiny_llm().await?;
+        ranking_integration_tester(&llm_client).await?;
+        Ok(())
+    }
 }
 
 pub(super) async fn run(llm_client: &LlmClient, test_level: &TestLevel) -> crate::Result<()> {
@@ -95,6 +220,29 @@ pub(super) async fn boolean_integration_tester(
     Ok(())
 }
 
+pub(super) async fn boolean_balanced_integration_tester(
+    llm_client: &LlmClient,
+    test_level: &TestLevel,
+) -> crate::Result<()> {
+    let mut gen = llm_client.reason().boolean();
+    gen.balanced_justification(true);
+
+    let mut tests = TestSetsLoader::new()
+        .test_level_enum(test_level)
+        .boolean()?;
+    for case in &mut tests.cases {
+        gen.instructions().set_content(&case.question);
+
+        let result = gen.return_result().await.unwrap();
+        let res = gen.primitive.parse_reason_result(&result).unwrap();
+        print_results(&gen.base_req.prompt, &Some(result), &Some(res));
+        case.result = res;
+        gen.reset_request();
+    }
+    tests.check_results();
+    Ok(())
+}
+
 pub(super) async fn boolean_optional_integration_tester(
     llm_client: &LlmClient,
     test_level: &TestLevel,
@@ -205,3 +353,17 @@ pub(super) async fn exact_string_optional_integration_tester(
     tests.check_results();
     Ok(())
 }
+
+pub(super) async fn ranking_integration_tester(llm_client: &LlmClient) -> crate::Result<()> {
+    let mut gen = llm_client.reason().ranking();
+    let items = ["an elephant", "a mouse", "a horse", "a cat"];
+    gen.primitive.add_items(&items)?;
+    gen.instructions()
+        .set_content("Rank the animals by body weight, from heaviest to lightest.");
+    let res = gen.return_primitive().await?;
+    print_results(&gen.base_req.prompt, &None::<String>, &Some(res.clone()));
+    assert_eq!(res.ordered_items().len(), items.len());
+    assert_eq!(res.rank_of("an elephant"), Some(1));
+    assert_eq!(res.rank_of("a mouse"), Some(4));
+    Ok(())
+}
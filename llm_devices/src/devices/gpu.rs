@@ -17,6 +17,12 @@ impl GpuDevice {
         self.allocated_layers += 1;
         self.allocated_layer_bytes += layer_size;
     }
+
+    /// The VRAM left on the device after all allocated layers. Buffer layers are counted as allocated.
+    pub fn remaining_vram_bytes(&self) -> u64 {
+        self.available_vram_bytes
+            .saturating_sub(self.allocated_layer_bytes)
+    }
 }
 
 pub struct GpuLayerAllocator {
@@ -170,6 +176,10 @@ impl std::fmt::Display for GpuDevice {
                     "Available VRAM: {:.2} GB",
                     self.available_vram_bytes as f64 / 1_073_741_824.0
                 ),
+                format_args!(
+                    "Remaining VRAM: {:.2} GB",
+                    self.remaining_vram_bytes() as f64 / 1_073_741_824.0
+                ),
             ],
         )
     }
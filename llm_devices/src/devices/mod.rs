@@ -13,6 +13,10 @@ pub mod gpu;
 pub mod metal;
 pub mod ram;
 
+/// Remaining VRAM below which a warning is logged after allocating layers to GPUs.
+/// Large contexts with little headroom are a common cause of out of memory errors mid-inference.
+pub const LOW_VRAM_HEADROOM_BYTES: u64 = 1_073_741_824;
+
 /// Configuration for device-specific settings in LLM inference.
 #[derive(Debug, Clone)]
 pub struct DeviceConfig {
@@ -66,6 +70,11 @@ pub struct DeviceConfig {
     ///
     /// This is set at runtime.
     pub local_model_path: String,
+
+    /// The estimated VRAM remaining across all GPUs after the model weights and KV cache are allocated.
+    ///
+    /// This is set at runtime, whether the GPU layers are allocated automatically or set by the user.
+    pub remaining_vram_bytes: Option<u64>,
 }
 
 impl Default for DeviceConfig {
@@ -82,6 +91,7 @@ impl Default for DeviceConfig {
             layer_count: None,
             average_layer_size_bytes: None,
             local_model_path: Default::default(),
+            remaining_vram_bytes: None,
         }
     }
 }
@@ -244,34 +254,70 @@ impl DeviceConfig {
     }
 
     pub fn allocate_layers_to_gpus(
-        &self,
+        &mut self,
         buffer_layer_per_gpu: u64,
         buffer_layer_main_gpu: u64,
     ) -> crate::Result<Vec<gpu::GpuDevice>> {
+        let mut gpu_devices = self.gpu_devices()?;
+        let allocator = GpuLayerAllocator::new(
+            self.average_layer_size_bytes()?,
+            self.layer_count()?,
+            buffer_layer_per_gpu,
+            buffer_layer_main_gpu,
+        );
+        allocator.allocate(&mut gpu_devices)?;
+
+        self.set_remaining_vram_bytes(
+            gpu_devices
+                .iter()
+                .map(|gpu| gpu.remaining_vram_bytes())
+                .sum(),
+        );
+        Ok(gpu_devices)
+    }
+
+    /// Sets [DeviceConfig::remaining_vram_bytes] for a GPU layer count chosen by the user rather than by
+    /// [DeviceConfig::allocate_layers_to_gpus]. The layers are assumed to be spread over all GPUs.
+    pub fn estimate_remaining_vram(&mut self, gpu_layers: u64) -> crate::Result<u64> {
+        let available_vram_bytes: u64 = self
+            .gpu_devices()?
+            .iter()
+            .map(|gpu| gpu.available_vram_bytes)
+            .sum();
+        let allocated_bytes =
+            gpu_layers.min(self.layer_count()?) * self.average_layer_size_bytes()?;
+        let remaining_vram_bytes = available_vram_bytes.saturating_sub(allocated_bytes);
+        self.set_remaining_vram_bytes(remaining_vram_bytes);
+        Ok(remaining_vram_bytes)
+    }
+
+    fn gpu_devices(&self) -> crate::Result<Vec<gpu::GpuDevice>> {
         #[cfg(any(target_os = "linux", target_os = "windows"))]
-        let mut gpu_devices: Vec<gpu::GpuDevice> = if let Some(cuda_config) = &self.cuda_config {
-            cuda_config.to_generic_gpu_devices(self.error_on_config_issue)?
+        if let Some(cuda_config) = &self.cuda_config {
+            cuda_config.to_generic_gpu_devices(self.error_on_config_issue)
         } else {
             crate::bail!("No GPUs available")
-        };
+        }
         #[cfg(target_os = "macos")]
-        let mut gpu_devices: Vec<gpu::GpuDevice> = if let Some(metal_config) = &self.metal_config {
-            vec![metal_config.to_generic_gpu_device()]
+        if let Some(metal_config) = &self.metal_config {
+            Ok(vec![metal_config.to_generic_gpu_device()])
         } else {
             crate::bail!("No GPUs available")
-        };
+        }
         #[cfg(not(any(unix, windows, target_os = "macos")))]
         {
             crate::bail!("Unsupported OS");
         }
-        let allocator = GpuLayerAllocator::new(
-            self.average_layer_size_bytes()?,
-            self.layer_count()?,
-            buffer_layer_per_gpu,
-            buffer_layer_main_gpu,
-        );
-        allocator.allocate(&mut gpu_devices)?;
-        Ok(gpu_devices)
+    }
+
+    fn set_remaining_vram_bytes(&mut self, remaining_vram_bytes: u64) {
+        if remaining_vram_bytes < LOW_VRAM_HEADROOM_BYTES {
+            crate::warn!(
+                "Low VRAM headroom after allocating layers: {:.2} GB remaining. Consider reducing the context size to avoid running out of memory during inference.",
+                remaining_vram_bytes as f64 / 1_073_741_824.0
+            );
+        }
+        self.remaining_vram_bytes = Some(remaining_vram_bytes);
     }
 }
 
@@ -304,6 +350,12 @@ impl std::fmt::Display for DeviceConfig {
                 format_args!("average_layer_size_bytes: {}", average_layer_size_bytes),
             )?;
         }
+        if let Some(remaining_vram_bytes) = self.remaining_vram_bytes {
+            crate::i_ln(
+                f,
+                format_args!("remaining_vram_bytes: {}", remaining_vram_bytes),
+            )?;
+        }
 
         Ok(())
     }
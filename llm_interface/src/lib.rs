@@ -1,5 +1,5 @@
 #[allow(unused_imports)]
-pub(crate) use anyhow::{Error, anyhow, bail, Result};
+pub(crate) use anyhow::{anyhow, bail, Error, Result};
 #[allow(unused_imports)]
 pub(crate) use tracing::{debug, error, info, span, trace, warn, Level};
 
@@ -24,6 +24,10 @@ impl LlmInterface {
         llms::api::openai::builder::OpenAiBackendBuilder::default()
     }
 
+    pub fn azure_openai() -> llms::api::azure_openai::builder::AzureOpenAiBackendBuilder {
+        llms::api::azure_openai::builder::AzureOpenAiBackendBuilder::default()
+    }
+
     pub fn anthropic() -> llms::api::anthropic::builder::AnthropicBackendBuilder {
         llms::api::anthropic::builder::AnthropicBackendBuilder::default()
     }
@@ -31,4 +35,12 @@ impl LlmInterface {
     pub fn perplexity() -> llms::api::perplexity::builder::PerplexityBackendBuilder {
         llms::api::perplexity::builder::PerplexityBackendBuilder::default()
     }
+
+    pub fn ollama() -> llms::api::ollama::builder::OllamaBackendBuilder {
+        llms::api::ollama::builder::OllamaBackendBuilder::default()
+    }
+
+    pub fn generic_api() -> llms::api::generic_openai::builder::GenericApiBackendBuilder {
+        llms::api::generic_openai::builder::GenericApiBackendBuilder::default()
+    }
 }
@@ -22,6 +22,24 @@ impl Default for AnthropicBackendBuilder {
 }
 
 impl AnthropicBackendBuilder {
+    /// Sends requests to a different base url instead of `https://api.anthropic.com/v1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - The full base url including the scheme, e.g. `https://my-gateway.example.com/anthropic/v1`.
+    ///
+    /// # Notes
+    ///
+    /// Useful for enterprise gateways. Request paths such as `/messages` are appended to this url.
+    ///
+    /// # Default
+    ///
+    /// If not set, requests are sent to the official Anthropic API.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.config.base_url = Some(base_url.into());
+        self
+    }
+
     pub fn init(self) -> crate::Result<std::sync::Arc<LlmBackend>> {
         Ok(std::sync::Arc::new(LlmBackend::Anthropic(
             AnthropicBackend::new(self.config, self.model)?,
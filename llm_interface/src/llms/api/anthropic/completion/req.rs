@@ -48,6 +48,12 @@ pub struct AnthropicCompletionRequest {
     /// min: 0.0, max: 1.0, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
+
+    /// Only sample from the top K options for each subsequent token.
+    ///
+    /// Set to 1 when temperature is 0.0, as temperature alone is not fully deterministic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
 }
 
 impl AnthropicCompletionRequest {
@@ -92,8 +98,16 @@ impl AnthropicCompletionRequest {
             }
         }
 
+        if let Some(assistant_prefill) = &req.assistant_prefill {
+            messages.push(CompletionRequestMessage {
+                role: "assistant".to_string(),
+                content: assistant_prefill.trim_end().to_string(),
+            });
+        }
+
         let stop = req.stop_sequences.to_vec();
         let stop_sequences = if stop.is_empty() { None } else { Some(stop) };
+        req.config.log_ignored_repeat_penalty("Anthropic");
 
         Ok(AnthropicCompletionRequest {
             model: req.backend.model_id().to_owned(),
@@ -103,6 +117,11 @@ impl AnthropicCompletionRequest {
             system: system_prompt,
             temperature: temperature(req.config.temperature)?,
             top_p: top_p(req.config.top_p)?,
+            top_k: if req.config.is_greedy() {
+                Some(1)
+            } else {
+                None
+            },
         })
     }
 }
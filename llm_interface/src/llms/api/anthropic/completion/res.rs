@@ -29,6 +29,11 @@ impl CompletionResponse {
                     "StopReason::ToolUse is not supported".to_owned(),
                 ))
             }
+            StopReason::Refusal => {
+                return Err(CompletionError::ContentFiltered(
+                    "stop_reason: refusal".to_owned(),
+                ))
+            }
         };
 
         if res.content.is_empty() {
@@ -52,6 +57,9 @@ impl CompletionResponse {
             finish_reason,
             completion_probabilities: None,
             truncated: false,
+            content_truncated: false,
+            thinking: None,
+            citations: None,
             generation_settings: GenerationSettings::new_from_anthropic(req, &res),
             timing_usage: TimingUsage::new_from_generic(req.start_time),
             token_usage: TokenUsage::new_from_anthropic(&res),
@@ -120,4 +128,6 @@ pub enum StopReason {
     StopSequence,
     /// Claude wants to use an external tool.
     ToolUse,
+    /// Claude declined to respond for safety reasons.
+    Refusal,
 }
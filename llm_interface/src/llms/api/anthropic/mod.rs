@@ -3,7 +3,7 @@ pub mod completion;
 
 use super::{
     client::ApiClient,
-    config::{ApiConfig, ApiConfigTrait},
+    config::{ApiConfig, ApiConfigTrait, PoolConfig},
 };
 use crate::requests::completion::{
     error::CompletionError, request::CompletionRequest, response::CompletionResponse,
@@ -31,7 +31,7 @@ impl AnthropicBackend {
         config.logging_config.load_logger()?;
         config.api_config.api_key = Some(config.api_config.load_api_key()?);
         Ok(Self {
-            client: ApiClient::new(config),
+            client: ApiClient::new(config)?,
             model,
         })
     }
@@ -48,6 +48,16 @@ impl AnthropicBackend {
             Ok(res) => Ok(CompletionResponse::new_from_anthropic(request, res)?),
         }
     }
+
+    /// The URL and body [Self::completion_request] would post.
+    pub(crate) fn completion_payload(
+        &self,
+        request: &CompletionRequest,
+    ) -> crate::Result<(String, serde_json::Value), CompletionError> {
+        self.client
+            .dry_run("/messages", AnthropicCompletionRequest::new(request)?)
+            .map_err(CompletionError::ClientError)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -56,6 +66,9 @@ pub struct AnthropicConfig {
     pub logging_config: LoggingConfig,
     pub anthropic_version: String,
     pub anthropic_beta: Option<String>,
+    /// Overrides the scheme, host and base path used for requests, e.g. `https://my-gateway.example.com/anthropic/v1`.
+    /// When set, [ApiConfig::host] and [ApiConfig::port] are ignored.
+    pub base_url: Option<String>,
 }
 
 impl Default for AnthropicConfig {
@@ -66,6 +79,9 @@ impl Default for AnthropicConfig {
                 port: None,
                 api_key: None,
                 api_key_env_var: "ANTHROPIC_API_KEY".to_string(),
+                custom_headers: Vec::new(),
+                rate_limiter: None,
+                pool_config: Default::default(),
             },
             logging_config: LoggingConfig {
                 logger_name: "anthropic".to_string(),
@@ -73,6 +89,7 @@ impl Default for AnthropicConfig {
             },
             anthropic_version: "2023-06-01".to_string(),
             anthropic_beta: None,
+            base_url: None,
         }
     }
 }
@@ -91,6 +108,12 @@ impl AnthropicConfig {
         self.anthropic_beta = Some(beta.into());
         self
     }
+
+    /// Send requests to a different base url, such as a gateway
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
 }
 
 impl ApiConfigTrait for AnthropicConfig {
@@ -119,10 +142,22 @@ impl ApiConfigTrait for AnthropicConfig {
     }
 
     fn url(&self, path: &str) -> String {
-        format!("https://{}{}", self.api_config.host, path)
+        if let Some(base_url) = &self.base_url {
+            format!("{}{}", base_url.trim_end_matches('/'), path)
+        } else {
+            format!("https://{}{}", self.api_config.host, path)
+        }
     }
 
     fn api_key(&self) -> &Option<Secret<String>> {
         &self.api_config.api_key
     }
+
+    fn custom_headers(&self) -> crate::Result<HeaderMap> {
+        self.api_config.custom_header_map()
+    }
+
+    fn pool_config(&self) -> &PoolConfig {
+        &self.api_config.pool_config
+    }
 }
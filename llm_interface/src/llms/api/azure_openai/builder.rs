@@ -0,0 +1,69 @@
+use super::{AzureOpenAiBackend, AzureOpenAiConfig};
+use crate::llms::{
+    api::config::{ApiConfig, LlmApiConfigTrait},
+    LlmBackend,
+};
+use llm_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use llm_models::api_model::{openai::OpenAiModelTrait, ApiLlmModel};
+// Everything here can be implemented for any struct.
+pub struct AzureOpenAiBackendBuilder {
+    pub config: AzureOpenAiConfig,
+    pub model: ApiLlmModel,
+}
+
+impl Default for AzureOpenAiBackendBuilder {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            model: ApiLlmModel::gpt_4_o_mini(),
+        }
+    }
+}
+
+impl AzureOpenAiBackendBuilder {
+    /// Sets the Azure OpenAI resource endpoint, e.g. `https://my-resource.openai.azure.com`.
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.config.api_config.host = endpoint.into();
+        self
+    }
+
+    /// Sets the name of the model deployment. Requests are sent to `/openai/deployments/{deployment}`.
+    pub fn deployment<S: Into<String>>(mut self, deployment: S) -> Self {
+        self.config.deployment = deployment.into();
+        self
+    }
+
+    /// Sets the `api-version` query parameter.
+    pub fn api_version<S: Into<String>>(mut self, api_version: S) -> Self {
+        self.config.api_version = api_version.into();
+        self
+    }
+
+    pub fn init(self) -> crate::Result<std::sync::Arc<LlmBackend>> {
+        Ok(std::sync::Arc::new(LlmBackend::AzureOpenAi(
+            AzureOpenAiBackend::new(self.config, self.model)?,
+        )))
+    }
+}
+
+impl LlmApiConfigTrait for AzureOpenAiBackendBuilder {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.config.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.config.api_config
+    }
+}
+
+impl OpenAiModelTrait for AzureOpenAiBackendBuilder {
+    fn model(&mut self) -> &mut ApiLlmModel {
+        &mut self.model
+    }
+}
+
+impl LoggingConfigTrait for AzureOpenAiBackendBuilder {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.config.logging_config
+    }
+}
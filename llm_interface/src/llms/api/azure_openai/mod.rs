@@ -0,0 +1,206 @@
+pub mod builder;
+
+use super::{
+    client::ApiClient,
+    config::{ApiConfig, ApiConfigTrait, PoolConfig},
+    openai::completion::OpenAiCompletionRequest,
+};
+use crate::{
+    llms::health::HealthStatus,
+    requests::completion::{
+        error::CompletionError, request::CompletionRequest, response::CompletionResponse,
+    },
+};
+use llm_devices::logging::LoggingConfig;
+use llm_models::api_model::ApiLlmModel;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use secrecy::{ExposeSecret, Secret};
+
+/// Default Azure OpenAI data plane API version
+pub const AZURE_OPENAI_API_VERSION: &str = "2024-06-01";
+/// Azure authenticates with this header rather than a bearer token
+pub const AZURE_OPENAI_API_KEY_HEADER: &str = "api-key";
+
+pub struct AzureOpenAiBackend {
+    pub(crate) client: ApiClient<AzureOpenAiConfig>,
+    pub model: ApiLlmModel,
+}
+
+impl AzureOpenAiBackend {
+    pub fn new(mut config: AzureOpenAiConfig, model: ApiLlmModel) -> crate::Result<Self> {
+        config.logging_config.load_logger()?;
+        if config.api_config.host.is_empty() {
+            crate::bail!("Azure OpenAI endpoint not set");
+        }
+        if config.deployment.is_empty() {
+            crate::bail!("Azure OpenAI deployment name not set");
+        }
+        config.api_config.api_key = Some(config.api_config.load_api_key()?);
+        Ok(Self {
+            client: ApiClient::new(config)?,
+            model,
+        })
+    }
+
+    pub(crate) async fn completion_request(
+        &self,
+        request: &CompletionRequest,
+    ) -> crate::Result<CompletionResponse, CompletionError> {
+        match self
+            .client
+            .post("/chat/completions", OpenAiCompletionRequest::new(request)?)
+            .await
+        {
+            Err(e) => Err(CompletionError::ClientError(e)),
+            Ok(res) => Ok(CompletionResponse::new_from_openai(request, res)?),
+        }
+    }
+
+    /// The URL and body [Self::completion_request], or with `num_completions` [Self::completion_request_n], would post.
+    pub(crate) fn completion_payload(
+        &self,
+        request: &CompletionRequest,
+        num_completions: Option<u8>,
+    ) -> crate::Result<(String, serde_json::Value), CompletionError> {
+        self.client
+            .dry_run(
+                "/chat/completions",
+                OpenAiCompletionRequest::new_with_n(request, num_completions)?,
+            )
+            .map_err(CompletionError::ClientError)
+    }
+
+    pub(crate) async fn completion_request_n(
+        &self,
+        request: &CompletionRequest,
+        num_completions: u8,
+    ) -> crate::Result<Vec<CompletionResponse>, CompletionError> {
+        let req = OpenAiCompletionRequest::new_with_n(request, Some(num_completions))?;
+        match self.client.post("/chat/completions", req).await {
+            Err(e) => Err(CompletionError::ClientError(e)),
+            Ok(res) => Ok(CompletionResponse::new_all_from_openai(request, res)?),
+        }
+    }
+
+    pub(crate) async fn health_check(&self) -> HealthStatus {
+        HealthStatus::from_probe(
+            self.client.probe(self.client.config.models_url()).await,
+            false,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AzureOpenAiConfig {
+    /// `api_config.host` is the resource endpoint, e.g. `https://my-resource.openai.azure.com`.
+    pub api_config: ApiConfig,
+    pub logging_config: LoggingConfig,
+    /// The name given to the model deployment in the Azure portal.
+    pub deployment: String,
+    /// Sent as the `api-version` query parameter.
+    pub api_version: String,
+}
+
+impl Default for AzureOpenAiConfig {
+    fn default() -> Self {
+        Self {
+            api_config: ApiConfig {
+                host: Default::default(),
+                port: None,
+                api_key: None,
+                api_key_env_var: "AZURE_OPENAI_API_KEY".to_string(),
+                custom_headers: Vec::new(),
+                rate_limiter: None,
+                pool_config: Default::default(),
+            },
+            logging_config: LoggingConfig {
+                logger_name: "azure_openai".to_string(),
+                ..Default::default()
+            },
+            deployment: Default::default(),
+            api_version: AZURE_OPENAI_API_VERSION.to_string(),
+        }
+    }
+}
+
+impl AzureOpenAiConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn endpoint(&self) -> String {
+        let endpoint = self.api_config.host.trim_end_matches('/');
+        if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            endpoint.to_string()
+        } else {
+            format!("https://{endpoint}")
+        }
+    }
+
+    /// The resource's models list. Unlike the completion endpoints, it isn't under the deployment.
+    pub(crate) fn models_url(&self) -> String {
+        format!(
+            "{}/openai/models?api-version={}",
+            self.endpoint(),
+            self.api_version
+        )
+    }
+
+    /// The resource endpoint, e.g. `https://my-resource.openai.azure.com`
+    pub fn with_endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.api_config.host = endpoint.into();
+        self
+    }
+
+    /// The deployment name of the model
+    pub fn with_deployment<S: Into<String>>(mut self, deployment: S) -> Self {
+        self.deployment = deployment.into();
+        self
+    }
+
+    /// Non default api version
+    pub fn with_api_version<S: Into<String>>(mut self, api_version: S) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+}
+
+impl ApiConfigTrait for AzureOpenAiConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = self.api_key() {
+            if let Ok(header_value) = HeaderValue::from_str(api_key.expose_secret()) {
+                headers.insert(
+                    HeaderName::from_static(AZURE_OPENAI_API_KEY_HEADER),
+                    header_value,
+                );
+            } else {
+                crate::error!("Failed to create header value from api-key value");
+            }
+        }
+
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/openai/deployments/{}{}?api-version={}",
+            self.endpoint(),
+            self.deployment,
+            path,
+            self.api_version
+        )
+    }
+
+    fn api_key(&self) -> &Option<Secret<String>> {
+        &self.api_config.api_key
+    }
+
+    fn custom_headers(&self) -> crate::Result<HeaderMap> {
+        self.api_config.custom_header_map()
+    }
+
+    fn pool_config(&self) -> &PoolConfig {
+        &self.api_config.pool_config
+    }
+}
@@ -1,8 +1,9 @@
 use super::error::map_serialization_error;
 use super::{
     config::ApiConfigTrait,
-    error::{map_deserialization_error, ClientError, WrappedError},
+    error::{map_deserialization_error, parse_error_response, ClientError},
 };
+use crate::llms::health::{HealthStatus, HEALTH_CHECK_TIMEOUT};
 use bytes::Bytes;
 use serde::{de::DeserializeOwned, Serialize};
 
@@ -14,14 +15,28 @@ pub(crate) struct ApiClient<C: ApiConfigTrait> {
 }
 
 impl<C: ApiConfigTrait> ApiClient<C> {
-    pub fn new(config: C) -> Self {
-        Self {
-            http_client: reqwest::Client::new(),
+    pub fn new(config: C) -> crate::Result<Self> {
+        Ok(Self {
+            http_client: config
+                .pool_config()
+                .client_builder()
+                .default_headers(config.custom_headers()?)
+                .build()?,
             config,
             backoff: backoff::ExponentialBackoffBuilder::new()
                 .with_max_elapsed_time(Some(std::time::Duration::from_secs(60)))
                 .build(),
-        }
+        })
+    }
+
+    /// Builds the URL and body of a POST request to {path} without sending it.
+    pub(crate) fn dry_run<I: Serialize>(
+        &self,
+        path: &str,
+        request: I,
+    ) -> Result<(String, serde_json::Value), ClientError> {
+        let body = serde_json::to_value(&request).map_err(map_serialization_error)?;
+        Ok((self.config.url(path), body))
     }
 
     /// Make a POST request to {path} and deserialize the response body
@@ -48,6 +63,86 @@ impl<C: ApiConfigTrait> ApiClient<C> {
         self.execute(request_maker).await
     }
 
+    /// Make a POST request to {path} that responds with server-sent events, and call `on_event` with each event's data as it arrives.
+    ///
+    /// Unlike [ApiClient::post], the request isn't retried, since part of the response may already have been handled.
+    pub(crate) async fn post_stream<I, F>(
+        &self,
+        path: &str,
+        request: I,
+        mut on_event: F,
+    ) -> Result<(), ClientError>
+    where
+        I: Serialize + std::fmt::Debug,
+        F: FnMut(serde_json::Value) -> Result<(), ClientError>,
+    {
+        let serialized_request =
+            serde_json::to_string(&request).map_err(map_serialization_error)?;
+        crate::trace!("Serialized post stream request: {}", serialized_request);
+        let mut response = self
+            .http_client
+            .post(self.config.url(path))
+            .headers(self.config.headers())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serialized_request)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let bytes = response.bytes().await?;
+            return Err(ClientError::ApiError(parse_error_response(
+                status,
+                bytes.as_ref(),
+            )?));
+        }
+
+        let mut buffer: Vec<u8> = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            // Events can be split across chunks, so only complete lines are handled.
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = line.trim_ascii();
+                if let Some(error) = line.strip_prefix(b"error:") {
+                    return Err(ClientError::GenericError {
+                        message: String::from_utf8_lossy(error.trim_ascii()).into_owned(),
+                    });
+                }
+                if let Some(data) = line.strip_prefix(b"data:") {
+                    let data = data.trim_ascii();
+                    if data.is_empty() || data == b"[DONE]" {
+                        continue;
+                    }
+                    let value: serde_json::Value = serde_json::from_slice(data)
+                        .map_err(|e| map_deserialization_error(e, data))?;
+                    on_event(value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends a single GET request to {url}, without retrying, and returns the response status or why there was no response.
+    pub(crate) async fn probe(&self, url: String) -> Result<reqwest::StatusCode, String> {
+        match self
+            .http_client
+            .get(url)
+            .headers(self.config.headers())
+            .timeout(HEALTH_CHECK_TIMEOUT)
+            .send()
+            .await
+        {
+            Ok(response) => Ok(response.status()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Probes {path} and reports what the response says about the server's health. See [HealthStatus::from_status].
+    pub(crate) async fn health_check(&self, path: &str, is_local: bool) -> HealthStatus {
+        HealthStatus::from_probe(self.probe(self.config.url(path)).await, is_local)
+    }
+
     /// Make a GET request to {path} and deserialize the response body
     pub(crate) async fn get<O>(&self, path: &str) -> Result<O, ClientError>
     where
@@ -95,32 +190,29 @@ impl<C: ApiConfigTrait> ApiClient<C> {
 
             // Deserialize response body from either error object or actual response object
             if !status.is_success() {
-                let wrapped_error: WrappedError = serde_json::from_slice(bytes.as_ref())
-                    .map_err(|e| map_deserialization_error(e, bytes.as_ref()))
+                let api_error = parse_error_response(status, bytes.as_ref())
                     .map_err(backoff::Error::Permanent)?;
 
                 if status.as_u16() == 429
                     // API returns 429 also when:
                     // "You exceeded your current quota, please check your plan and billing details."
-                    && wrapped_error.error.r#type != Some("insufficient_quota".to_string())
+                    && api_error.r#type != Some("insufficient_quota".to_string())
                 {
                     // Rate limited retry...
-                    tracing::warn!("Rate limited: {}", wrapped_error.error.message);
+                    tracing::warn!("Rate limited: {}", api_error.message);
                     return Err(backoff::Error::Transient {
-                        err: ClientError::ApiError(wrapped_error.error),
+                        err: ClientError::ApiError(api_error),
                         retry_after: None,
                     });
                 } else if status.as_u16() == 503 {
                     return Err(backoff::Error::Transient {
                         err: ClientError::ServiceUnavailable {
-                            message: wrapped_error.error.message,
+                            message: api_error.message,
                         },
                         retry_after: None,
                     });
                 } else {
-                    return Err(backoff::Error::Permanent(ClientError::ApiError(
-                        wrapped_error.error,
-                    )));
+                    return Err(backoff::Error::Permanent(ClientError::ApiError(api_error)));
                 }
             }
 
@@ -1,5 +1,7 @@
-use reqwest::header::HeaderMap;
+use super::rate_limit::RateLimiter;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use secrecy::Secret;
+use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct ApiConfig {
@@ -7,6 +9,11 @@ pub struct ApiConfig {
     pub port: Option<String>,
     pub api_key: Option<Secret<String>>,
     pub api_key_env_var: String,
+    /// Extra headers sent with every request, for example routing keys required by a gateway or proxy.
+    pub custom_headers: Vec<(String, String)>,
+    /// Shared by every clone of the config, so all requests to the backend count against the same limits.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub pool_config: PoolConfig,
 }
 
 impl ApiConfig {
@@ -32,6 +39,61 @@ impl ApiConfig {
             }
         }
     }
+
+    /// Errors on the first header with an invalid name or value, rather than sending requests without it.
+    pub(crate) fn custom_header_map(&self) -> crate::Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.custom_headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| crate::anyhow!("Invalid custom header name {name:?}: {e}"))?;
+            let header_value = HeaderValue::from_str(value)
+                .map_err(|e| crate::anyhow!("Invalid value for custom header {name}: {e}"))?;
+            headers.insert(header_name, header_value);
+        }
+        Ok(headers)
+    }
+}
+
+/// Connection reuse for the backend's HTTP client.
+///
+/// Each backend has one client, and clones of an `LlmClient` share the backend, so they share its pool too.
+/// HTTP/2 is negotiated with servers that support it, and requests are then multiplexed over one connection.
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    /// The most idle connections kept open to a host. `None` for no limit.
+    pub max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept open. `None` to keep idle connections open until the server closes them.
+    pub idle_timeout: Option<std::time::Duration>,
+    /// The interval of TCP keep-alive probes, which stop idle connections from being dropped by proxies and load balancers.
+    pub tcp_keepalive: Option<std::time::Duration>,
+    /// Sends HTTP/2 without negotiating it first. Only for servers known to support it, e.g. a plain HTTP gateway.
+    pub http2_prior_knowledge: bool,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: None,
+            idle_timeout: Some(std::time::Duration::from_secs(90)),
+            tcp_keepalive: Some(std::time::Duration::from_secs(60)),
+            http2_prior_knowledge: false,
+        }
+    }
+}
+
+impl PoolConfig {
+    pub(crate) fn client_builder(&self) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder()
+            .pool_idle_timeout(self.idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+        if let Some(max_idle_per_host) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle_per_host);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder
+    }
 }
 
 pub trait LlmApiConfigTrait {
@@ -63,6 +125,90 @@ pub trait LlmApiConfigTrait {
         self
     }
 
+    /// Adds a header sent with every request to the API. Useful for gateways and proxies that
+    /// require routing keys or organization ids. Setting the same name twice replaces the value.
+    /// An invalid header name or value makes `init` return an error.
+    fn header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self
+    where
+        Self: Sized,
+    {
+        let name = name.into();
+        let custom_headers = &mut self.api_base_config_mut().custom_headers;
+        custom_headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+        custom_headers.push((name, value.into()));
+        self
+    }
+
+    /// Keeps requests within the API's rate limits by making them wait, instead of sending them to be rejected with a 429.
+    ///
+    /// # Arguments
+    ///
+    /// * `requests_per_minute` - The most requests sent in any minute. `0` for no limit.
+    /// * `tokens_per_minute` - The most tokens sent in any minute, estimated from the prompt and the response token limit. `0` for no limit.
+    ///
+    /// # Notes
+    ///
+    /// The limits are shared by everything using the backend, including clones of the client and concurrent workflows.
+    fn rate_limit(mut self, requests_per_minute: u32, tokens_per_minute: u64) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().rate_limiter = Some(Arc::new(RateLimiter::new(
+            requests_per_minute,
+            tokens_per_minute,
+        )));
+        self
+    }
+
+    /// Sets the most idle connections kept open to the API for reuse. See [PoolConfig].
+    ///
+    /// # Default
+    ///
+    /// No limit.
+    fn pool_max_idle_per_host(mut self, max_idle_per_host: usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().pool_config.max_idle_per_host = Some(max_idle_per_host);
+        self
+    }
+
+    /// Sets how long an idle connection is kept open for reuse. `None` keeps idle connections open until the server closes them.
+    ///
+    /// # Default
+    ///
+    /// 90 seconds.
+    fn pool_idle_timeout(mut self, idle_timeout: Option<std::time::Duration>) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().pool_config.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Sets the interval of TCP keep-alive probes on open connections. `None` disables them.
+    ///
+    /// # Default
+    ///
+    /// 60 seconds.
+    fn tcp_keepalive(mut self, interval: Option<std::time::Duration>) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().pool_config.tcp_keepalive = interval;
+        self
+    }
+
+    /// Sends requests over HTTP/2 without negotiating it. Only use this for servers known to support HTTP/2, such as a plain HTTP
+    /// gateway. HTTPS APIs negotiate HTTP/2 without it.
+    fn http2_prior_knowledge(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().pool_config.http2_prior_knowledge = true;
+        self
+    }
+
     /// Set the environment variable name for the API key. Default is set from the backend.
     fn with_api_key_env_var<S: Into<String>>(mut self, api_key_env_var: S) -> Self
     where
@@ -79,4 +225,10 @@ pub(crate) trait ApiConfigTrait {
     fn url(&self, path: &str) -> String;
 
     fn api_key(&self) -> &Option<Secret<String>>;
+
+    /// Headers added to the HTTP client's default headers. See [ApiConfig::custom_headers].
+    fn custom_headers(&self) -> crate::Result<HeaderMap>;
+
+    /// The HTTP client's connection pool settings. See [ApiConfig::pool_config].
+    fn pool_config(&self) -> &PoolConfig;
 }
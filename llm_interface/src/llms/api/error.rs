@@ -11,6 +11,9 @@ pub enum ClientError {
     /// Error when API returns 503 status code
     #[error("Service unavailable: {message}")]
     ServiceUnavailable { message: String },
+    /// The API returned an error status with a body that isn't an error object, e.g. a 404 for an endpoint the server doesn't have
+    #[error("HTTP {status}: {body}")]
+    HttpStatus { status: u16, body: String },
     /// Generic error message
     #[error("Generic error: {message}")]
     GenericError { message: String },
@@ -26,12 +29,75 @@ pub enum ClientError {
     InvalidArgument(String),
 }
 
+impl ClientError {
+    /// True if the backend couldn't be reached or failed to handle the request, as opposed to rejecting it. That is a
+    /// connection failure or timeout, a 5xx, or a 429 that outlasted the retries.
+    pub fn is_backend_failure(&self) -> bool {
+        let is_failure_status = |status: u16| status == 429 || status >= 500;
+        match self {
+            ClientError::Reqwest(e) => e.is_connect() || e.is_timeout(),
+            ClientError::ServiceUnavailable { .. } => true,
+            ClientError::HttpStatus { status, .. } => is_failure_status(*status),
+            ClientError::ApiError(e) => e.status.is_some_and(is_failure_status),
+            ClientError::GenericError { .. }
+            | ClientError::JSONSerialize(_)
+            | ClientError::JSONDeserialize(_)
+            | ClientError::InvalidArgument(_) => false,
+        }
+    }
+}
+
 /// Wrapper to deserialize the error object nested in "error" JSON key
 #[derive(Debug, Deserialize)]
 pub(crate) struct WrappedError {
     pub(crate) error: ApiError,
 }
 
+/// The error envelope returned by Azure API Management, e.g. for an invalid `api-key`.
+/// Errors from the model deployment itself use the nested "error" object.
+#[derive(Debug, Deserialize)]
+struct StatusCodeError {
+    #[serde(rename = "statusCode")]
+    status_code: u16,
+    message: String,
+}
+
+impl WrappedError {
+    pub(crate) fn from_slice(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        match serde_json::from_slice::<WrappedError>(bytes) {
+            Ok(wrapped_error) => Ok(wrapped_error),
+            Err(e) => match serde_json::from_slice::<StatusCodeError>(bytes) {
+                Ok(status_code_error) => Ok(WrappedError {
+                    error: ApiError {
+                        message: status_code_error.message,
+                        r#type: None,
+                        param: None,
+                        code: Some(status_code_error.status_code.to_string()),
+                        status: None,
+                    },
+                }),
+                Err(_) => Err(e),
+            },
+        }
+    }
+}
+
+/// The API's error object, or the status and body if the body isn't one.
+pub(crate) fn parse_error_response(
+    status: reqwest::StatusCode,
+    bytes: &[u8],
+) -> Result<ApiError, ClientError> {
+    WrappedError::from_slice(bytes)
+        .map(|wrapped_error| ApiError {
+            status: Some(status.as_u16()),
+            ..wrapped_error.error
+        })
+        .map_err(|_| ClientError::HttpStatus {
+            status: status.as_u16(),
+            body: String::from_utf8_lossy(bytes).trim().to_owned(),
+        })
+}
+
 pub(crate) fn map_deserialization_error(e: serde_json::Error, bytes: &[u8]) -> ClientError {
     tracing::error!(
         "failed deserialization of: {}",
@@ -51,4 +117,7 @@ pub struct ApiError {
     pub r#type: Option<String>,
     pub param: Option<String>,
     pub code: Option<String>,
+    /// The HTTP status of the response the error came in.
+    #[serde(skip)]
+    pub status: Option<u16>,
 }
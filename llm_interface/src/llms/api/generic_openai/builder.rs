@@ -0,0 +1,103 @@
+use super::{EndpointStyle, GenericApiBackend, GenericApiConfig};
+use crate::llms::{
+    api::config::{ApiConfig, LlmApiConfigTrait},
+    LlmBackend,
+};
+use llm_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use llm_models::api_model::ApiLlmModel;
+
+/// The context size used unless set with [GenericApiBackendBuilder::model_ctx_size].
+pub const GENERIC_API_DEFAULT_CTX_SIZE: u64 = 8192;
+
+// Everything here can be implemented for any struct.
+pub struct GenericApiBackendBuilder {
+    pub config: GenericApiConfig,
+    pub model: ApiLlmModel,
+}
+
+impl Default for GenericApiBackendBuilder {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            model: ApiLlmModel::openai_compatible_model("", GENERIC_API_DEFAULT_CTX_SIZE),
+        }
+    }
+}
+
+impl GenericApiBackendBuilder {
+    /// Sets the server's base url including the scheme and any version prefix, e.g. `http://localhost:8000/v1`.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.config.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Sets the model by the id the server serves it as, e.g. `meta-llama/Llama-3.1-8B-Instruct`.
+    pub fn model_id<S: AsRef<str>>(mut self, model_id: S) -> Self {
+        let model_ctx_size = self.model.model_base.model_ctx_size;
+        self.model = ApiLlmModel::openai_compatible_model(model_id.as_ref(), model_ctx_size);
+        self
+    }
+
+    /// Sets the model's context size, which the server doesn't report.
+    ///
+    /// # Default
+    ///
+    /// 8192 tokens.
+    pub fn model_ctx_size(mut self, model_ctx_size: u64) -> Self {
+        self.model.model_base.model_ctx_size = model_ctx_size;
+        self.model.model_base.inference_ctx_size = model_ctx_size;
+        self
+    }
+
+    /// Sets which endpoint completions are sent to. See [EndpointStyle].
+    ///
+    /// # Default
+    ///
+    /// [EndpointStyle::Chat].
+    pub fn endpoint_style(mut self, endpoint_style: EndpointStyle) -> Self {
+        self.config.endpoint_style = endpoint_style;
+        self
+    }
+
+    /// Sets the path of the chat endpoint, relative to the base url.
+    ///
+    /// # Default
+    ///
+    /// `/chat/completions`.
+    pub fn completion_path<S: Into<String>>(mut self, path: S) -> Self {
+        self.config.completion_path = path.into();
+        self
+    }
+
+    /// Sets the path of the legacy completions endpoint, relative to the base url.
+    ///
+    /// # Default
+    ///
+    /// `/completions`.
+    pub fn text_completion_path<S: Into<String>>(mut self, path: S) -> Self {
+        self.config.text_completion_path = path.into();
+        self
+    }
+
+    pub fn init(self) -> crate::Result<std::sync::Arc<LlmBackend>> {
+        Ok(std::sync::Arc::new(LlmBackend::GenericApi(
+            GenericApiBackend::new(self.config, self.model)?,
+        )))
+    }
+}
+
+impl LlmApiConfigTrait for GenericApiBackendBuilder {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.config.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.config.api_config
+    }
+}
+
+impl LoggingConfigTrait for GenericApiBackendBuilder {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.config.logging_config
+    }
+}
@@ -1,35 +1,97 @@
+pub mod builder;
+mod text_completion;
+
 use super::{
     client::ApiClient,
-    config::{ApiConfig, ApiConfigTrait},
-    openai::completion::OpenAiCompletionRequest,
+    config::{ApiConfig, ApiConfigTrait, PoolConfig},
+    error::ClientError,
+    openai::completion::{OpenAiCompletionRequest, OpenAiCompletionResponse},
 };
-use crate::requests::completion::{
-    error::CompletionError, request::CompletionRequest, response::CompletionResponse,
+use crate::{
+    llms::health::HealthStatus,
+    requests::completion::{
+        error::CompletionError, request::CompletionRequest, response::CompletionResponse,
+    },
 };
 use llm_devices::logging::LoggingConfig;
 use llm_models::api_model::ApiLlmModel;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    StatusCode,
+};
 use secrecy::{ExposeSecret, Secret};
 
 pub struct GenericApiBackend {
     pub(crate) client: ApiClient<GenericApiConfig>,
     pub model: ApiLlmModel,
+    /// The endpoint [EndpointStyle::Auto] settled on after the first successful request.
+    detected_style: std::sync::OnceLock<EndpointStyle>,
 }
 
 impl GenericApiBackend {
     pub fn new(mut config: GenericApiConfig, model: ApiLlmModel) -> crate::Result<Self> {
+        if config.base_url.is_none() && config.api_config.host.is_empty() {
+            crate::bail!("Set the server's base url or host");
+        }
+        if model.model_base.model_id.is_empty() {
+            crate::bail!("Set the id of the model the server serves");
+        }
         config.logging_config.load_logger()?;
         if let Ok(api_key) = config.api_config.load_api_key() {
             config.api_config.api_key = Some(api_key);
         }
         Ok(Self {
-            client: ApiClient::new(config),
+            client: ApiClient::new(config)?,
             model,
+            detected_style: std::sync::OnceLock::new(),
         })
     }
+
+    /// The endpoint requests are sent to. [EndpointStyle::Auto] until a request has succeeded on one of the endpoints.
+    pub fn endpoint_style(&self) -> EndpointStyle {
+        match self.client.config.endpoint_style {
+            EndpointStyle::Auto => self
+                .detected_style
+                .get()
+                .copied()
+                .unwrap_or(EndpointStyle::Auto),
+            endpoint_style => endpoint_style,
+        }
+    }
+
     pub(crate) async fn completion_request(
         &self,
         request: &CompletionRequest,
+    ) -> crate::Result<CompletionResponse, CompletionError> {
+        match self.endpoint_style() {
+            EndpointStyle::Chat => self.chat_request(request).await,
+            EndpointStyle::Completion => self.text_completion_request(request).await,
+            EndpointStyle::Auto => match self.chat_request(request).await {
+                Err(CompletionError::ClientError(e)) if is_missing_endpoint(&e) => {
+                    crate::info!(
+                        "{} not found, so falling back to {}",
+                        self.client.config.completion_path,
+                        self.client.config.text_completion_path
+                    );
+                    let res = self.text_completion_request(request).await;
+                    if res.is_ok() {
+                        let _ = self.detected_style.set(EndpointStyle::Completion);
+                    }
+                    res
+                }
+                res => {
+                    if res.is_ok() {
+                        let _ = self.detected_style.set(EndpointStyle::Chat);
+                    }
+                    res
+                }
+            },
+        }
+    }
+
+    async fn chat_request(
+        &self,
+        request: &CompletionRequest,
     ) -> crate::Result<CompletionResponse, CompletionError> {
         match self
             .client
@@ -43,13 +105,103 @@ impl GenericApiBackend {
             Ok(res) => Ok(CompletionResponse::new_from_openai(request, res)?),
         }
     }
+
+    async fn text_completion_request(
+        &self,
+        request: &CompletionRequest,
+    ) -> crate::Result<CompletionResponse, CompletionError> {
+        let res: text_completion::TextCompletionResponse = self
+            .client
+            .post(
+                &self.client.config.text_completion_path,
+                text_completion::TextCompletionRequest::new(request)?,
+            )
+            .await
+            .map_err(CompletionError::ClientError)?;
+        let mut res =
+            CompletionResponse::new_from_openai(request, OpenAiCompletionResponse::from(res))?;
+        // The prompt ends with "Assistant:", so the reply usually starts with a space. A prefill's continuation is kept as is.
+        if request.assistant_prefill.is_none() {
+            res.content = res.content.trim_start().to_owned();
+        }
+        Ok(res)
+    }
+
+    /// The URL and body [Self::completion_request] would post.
+    pub(crate) fn completion_payload(
+        &self,
+        request: &CompletionRequest,
+    ) -> crate::Result<(String, serde_json::Value), CompletionError> {
+        match self.endpoint_style() {
+            EndpointStyle::Completion => self.client.dry_run(
+                &self.client.config.text_completion_path,
+                text_completion::TextCompletionRequest::new(request)?,
+            ),
+            EndpointStyle::Chat | EndpointStyle::Auto => self.client.dry_run(
+                &self.client.config.completion_path,
+                OpenAiCompletionRequest::new(request)?,
+            ),
+        }
+        .map_err(CompletionError::ClientError)
+    }
+
+    pub(crate) async fn health_check(&self) -> HealthStatus {
+        match self.client.probe(self.client.config.url("/models")).await {
+            // Some servers, e.g. Perplexity, have no models list. The completion endpoint only takes POST, so a GET to it
+            // answers 405 Method Not Allowed if the base url is right, and 404 if it isn't.
+            Ok(StatusCode::NOT_FOUND) => {
+                let path = match self.endpoint_style() {
+                    EndpointStyle::Completion => &self.client.config.text_completion_path,
+                    EndpointStyle::Chat | EndpointStyle::Auto => {
+                        &self.client.config.completion_path
+                    }
+                };
+                match self.client.probe(self.client.config.url(path)).await {
+                    Ok(StatusCode::METHOD_NOT_ALLOWED) => HealthStatus::Ready,
+                    probe => HealthStatus::from_probe(probe, false),
+                }
+            }
+            probe => HealthStatus::from_probe(probe, false),
+        }
+    }
+}
+
+/// Whether the error is the server not having the endpoint, rather than the request failing.
+fn is_missing_endpoint(e: &ClientError) -> bool {
+    match e {
+        ClientError::HttpStatus { status, .. } => matches!(status, 404 | 405),
+        ClientError::ApiError(e) => matches!(e.code.as_deref(), Some("404" | "405")),
+        _ => false,
+    }
+}
+
+/// Which OpenAI compatible endpoint completions are sent to. Servers such as vLLM, the llama.cpp server, LocalAI, and
+/// text-generation-webui don't all implement both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EndpointStyle {
+    /// The chat endpoint, [GenericApiConfig::completion_path], with the prompt as messages.
+    #[default]
+    Chat,
+    /// The legacy completions endpoint, [GenericApiConfig::text_completion_path]. The server applies no chat template, so the
+    /// messages are sent as a plain `User:`/`Assistant:` transcript.
+    Completion,
+    /// Tries the chat endpoint first, and falls back to the completions endpoint if the server doesn't have it.
+    /// The endpoint of the first successful request is used for the rest of the backend's requests.
+    Auto,
 }
 
 #[derive(Clone, Debug)]
 pub struct GenericApiConfig {
     pub api_config: ApiConfig,
     pub logging_config: LoggingConfig,
+    /// The path of the chat endpoint.
     pub completion_path: String,
+    /// The path of the legacy completions endpoint, used with [EndpointStyle::Completion].
+    pub text_completion_path: String,
+    pub endpoint_style: EndpointStyle,
+    /// Overrides the scheme, host and base path used for requests, e.g. `http://localhost:8000/v1` for a local server.
+    /// When set, [ApiConfig::host] and [ApiConfig::port] are ignored.
+    pub base_url: Option<String>,
 }
 
 impl Default for GenericApiConfig {
@@ -60,12 +212,18 @@ impl Default for GenericApiConfig {
                 port: None,
                 api_key: None,
                 api_key_env_var: Default::default(),
+                custom_headers: Vec::new(),
+                rate_limiter: None,
+                pool_config: Default::default(),
             },
             logging_config: LoggingConfig {
                 logger_name: "generic".to_string(),
                 ..Default::default()
             },
             completion_path: "/chat/completions".to_string(),
+            text_completion_path: "/completions".to_string(),
+            endpoint_style: EndpointStyle::default(),
+            base_url: None,
         }
     }
 }
@@ -79,6 +237,21 @@ impl GenericApiConfig {
         self.completion_path = path.into();
         self
     }
+
+    pub fn text_completion_path<S: Into<String>>(mut self, path: S) -> Self {
+        self.text_completion_path = path.into();
+        self
+    }
+
+    pub fn endpoint_style(mut self, endpoint_style: EndpointStyle) -> Self {
+        self.endpoint_style = endpoint_style;
+        self
+    }
+
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
 }
 
 impl ApiConfigTrait for GenericApiConfig {
@@ -98,14 +271,24 @@ impl ApiConfigTrait for GenericApiConfig {
     }
 
     fn url(&self, path: &str) -> String {
-        if let Some(port) = &self.api_config.port {
+        if let Some(base_url) = &self.base_url {
+            format!("{}{}", base_url.trim_end_matches('/'), path)
+        } else if let Some(port) = &self.api_config.port {
             format!("https://{}:{}{}", self.api_config.host, port, path)
         } else {
-            format!("https://{}:{}", self.api_config.host, path)
+            format!("https://{}{}", self.api_config.host, path)
         }
     }
 
     fn api_key(&self) -> &Option<Secret<String>> {
         &self.api_config.api_key
     }
+
+    fn custom_headers(&self) -> crate::Result<HeaderMap> {
+        self.api_config.custom_header_map()
+    }
+
+    fn pool_config(&self) -> &PoolConfig {
+        &self.api_config.pool_config
+    }
 }
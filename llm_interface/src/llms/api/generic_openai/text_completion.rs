@@ -0,0 +1,134 @@
+use crate::{
+    llms::api::openai::completion::{
+        ChatChoice, ChatCompletionResponseMessage, CompletionUsage, FinishReason,
+        OpenAiCompletionResponse, Role, Stop,
+    },
+    requests::completion::{error::CompletionError, request::CompletionRequest},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Ends the turn before the model starts writing the user's next message.
+const USER_TURN_STOP: &str = "\nUser:";
+
+/// A request to the legacy `/completions` endpoint, which takes the prompt as text rather than messages.
+#[derive(Clone, Serialize, Default, Debug, Deserialize, PartialEq)]
+pub struct TextCompletionRequest {
+    pub model: String,
+    /// The messages rendered as a plain transcript, since the server doesn't apply a chat template.
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logit_bias: Option<BTreeMap<String, serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Stop>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+}
+
+impl TextCompletionRequest {
+    pub fn new(req: &CompletionRequest) -> crate::Result<Self, CompletionError> {
+        let messages = req
+            .prompt
+            .api_prompt()
+            .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?
+            .get_built_prompt()
+            .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+        let mut prompt = String::new();
+        for message in &messages {
+            let (Some(role), Some(content)) = (message.get("role"), message.get("content")) else {
+                return Err(CompletionError::RequestBuilderError(
+                    "Message is missing its role or content".to_string(),
+                ));
+            };
+            match role.as_str() {
+                "system" => prompt.push_str(content),
+                "user" => prompt.push_str(&format!("User: {content}")),
+                "assistant" => prompt.push_str(&format!("Assistant: {content}")),
+                _ => {
+                    return Err(CompletionError::RequestBuilderError(format!(
+                        "Role {role} not supported"
+                    )))
+                }
+            }
+            prompt.push_str("\n\n");
+        }
+        prompt.push_str("Assistant:");
+        // Unlike the chat endpoint, a text prompt can simply end with the prefill.
+        if let Some(assistant_prefill) = &req.assistant_prefill {
+            prompt.push(' ');
+            prompt.push_str(assistant_prefill.trim_end());
+        }
+
+        // Without the turn stop the model writes the user's next message, so it's added even past OpenAI's limit of 4 stops.
+        let mut stop = req.stop_sequences.to_vec();
+        if !stop.iter().any(|s| s == USER_TURN_STOP) {
+            stop.push(USER_TURN_STOP.to_owned());
+        }
+        req.config.log_ignored_repeat_penalty("OpenAI compatible");
+        Ok(Self {
+            model: req.backend.model_id().to_owned(),
+            prompt,
+            logit_bias: req.logit_bias.as_ref().and_then(|lb| lb.get_openai()),
+            max_tokens: req.config.response_token_limit(),
+            temperature: Some(req.config.temperature),
+            frequency_penalty: req.config.frequency_penalty,
+            presence_penalty: Some(req.config.presence_penalty),
+            stop: Some(Stop::StringArray(stop)),
+            top_p: req.config.top_p,
+        })
+    }
+}
+
+/// A response from the legacy `/completions` endpoint.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct TextCompletionResponse {
+    pub id: String,
+    pub choices: Vec<TextChoice>,
+    pub created: u32,
+    pub model: String,
+    pub usage: Option<CompletionUsage>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct TextChoice {
+    pub index: u32,
+    pub text: String,
+    pub finish_reason: Option<FinishReason>,
+}
+
+impl From<TextCompletionResponse> for OpenAiCompletionResponse {
+    /// Converts to a chat response with the text as the assistant's message, so both endpoints are handled the same way.
+    fn from(res: TextCompletionResponse) -> Self {
+        OpenAiCompletionResponse {
+            id: res.id,
+            choices: res
+                .choices
+                .into_iter()
+                .map(|choice| ChatChoice {
+                    index: choice.index,
+                    message: ChatCompletionResponseMessage {
+                        content: Some(choice.text),
+                        refusal: None,
+                        role: Role::Assistant,
+                        annotations: None,
+                    },
+                    finish_reason: choice.finish_reason,
+                    logprobs: None,
+                })
+                .collect(),
+            created: res.created,
+            model: res.model,
+            usage: res.usage,
+            citations: None,
+            search_results: None,
+        }
+    }
+}
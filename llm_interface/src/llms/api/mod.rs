@@ -1,7 +1,10 @@
 pub mod anthropic;
+pub mod azure_openai;
 pub mod client;
 pub mod config;
 pub mod error;
 pub mod generic_openai;
+pub mod ollama;
 pub mod openai;
 pub mod perplexity;
+pub mod rate_limit;
@@ -0,0 +1,66 @@
+use super::{OllamaBackend, OllamaConfig, OllamaEndpoint};
+use crate::llms::{
+    api::config::{ApiConfig, LlmApiConfigTrait},
+    LlmBackend,
+};
+use llm_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use llm_models::api_model::{
+    ollama::{OllamaModelTrait, OLLAMA_DEFAULT_CTX_SIZE, OLLAMA_DEFAULT_MODEL_ID},
+    ApiLlmModel,
+};
+
+// Everything here can be implemented for any struct.
+pub struct OllamaBackendBuilder {
+    pub config: OllamaConfig,
+    pub model: ApiLlmModel,
+}
+
+impl Default for OllamaBackendBuilder {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            model: ApiLlmModel::ollama_model(OLLAMA_DEFAULT_MODEL_ID, OLLAMA_DEFAULT_CTX_SIZE),
+        }
+    }
+}
+
+impl OllamaBackendBuilder {
+    /// Sets which of Ollama's native endpoints completions are sent to. See [OllamaEndpoint].
+    ///
+    /// # Default
+    ///
+    /// [OllamaEndpoint::Chat].
+    pub fn endpoint(mut self, endpoint: OllamaEndpoint) -> Self {
+        self.config.endpoint = endpoint;
+        self
+    }
+
+    pub fn init(self) -> crate::Result<std::sync::Arc<LlmBackend>> {
+        Ok(std::sync::Arc::new(LlmBackend::Ollama(OllamaBackend::new(
+            self.config,
+            self.model,
+        )?)))
+    }
+}
+
+impl LlmApiConfigTrait for OllamaBackendBuilder {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.config.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.config.api_config
+    }
+}
+
+impl OllamaModelTrait for OllamaBackendBuilder {
+    fn model(&mut self) -> &mut ApiLlmModel {
+        &mut self.model
+    }
+}
+
+impl LoggingConfigTrait for OllamaBackendBuilder {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.config.logging_config
+    }
+}
@@ -0,0 +1,4 @@
+mod req;
+mod res;
+pub use req::{OllamaChatRequest, OllamaGenerateRequest};
+pub use res::{OllamaChatResponse, OllamaGenerateResponse};
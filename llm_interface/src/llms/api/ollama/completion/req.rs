@@ -0,0 +1,202 @@
+use super::super::OllamaConfig;
+use crate::requests::completion::{error::CompletionError, request::CompletionRequest};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Default, Debug, Deserialize, PartialEq)]
+pub struct OllamaChatRequest {
+    /// The model name, e.g. `llama3.2`.
+    pub model: String,
+
+    /// The messages of the chat. If the last message is from the assistant, the model continues it.
+    pub messages: Vec<CompletionRequestMessage>,
+
+    /// Always false. The response is returned as a single object.
+    pub stream: bool,
+
+    /// How long the model stays loaded in memory after the request. Server default is 5 minutes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+
+    /// Model parameters such as `temperature`, `num_predict`, `num_ctx`, and `stop`.
+    ///
+    /// See [the Ollama docs](https://github.com/ollama/ollama/blob/main/docs/modelfile.md#valid-parameters-and-values) for all parameters.
+    pub options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl OllamaChatRequest {
+    pub fn new(
+        req: &CompletionRequest,
+        config: &OllamaConfig,
+    ) -> crate::Result<Self, CompletionError> {
+        let mut messages = Vec::new();
+        match &req
+            .prompt
+            .api_prompt()
+            .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?
+            .get_built_prompt()
+        {
+            Ok(prompt_message) => {
+                for m in prompt_message {
+                    messages.push(CompletionRequestMessage::new(m)?);
+                }
+            }
+            Err(e) => return Err(CompletionError::RequestBuilderError(e.to_string())),
+        }
+        if let Some(assistant_prefill) = &req.assistant_prefill {
+            messages.push(CompletionRequestMessage {
+                role: "assistant".to_string(),
+                content: assistant_prefill.to_owned(),
+            });
+        }
+
+        Ok(OllamaChatRequest {
+            model: req.backend.model_id().to_owned(),
+            messages,
+            stream: false,
+            keep_alive: config.keep_alive.clone(),
+            options: request_options(req, config),
+        })
+    }
+}
+
+/// A request to `/api/generate`, which takes a single prompt and applies the model's template to it.
+#[derive(Clone, Serialize, Default, Debug, Deserialize, PartialEq)]
+pub struct OllamaGenerateRequest {
+    /// The model name, e.g. `llama3.2`.
+    pub model: String,
+
+    /// The user's message. Earlier turns of a conversation are included as a `User:`/`Assistant:` transcript.
+    pub prompt: String,
+
+    /// The system messages, which override the system prompt of the model's Modelfile.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+
+    /// Always false. The response is returned as a single object.
+    pub stream: bool,
+
+    /// How long the model stays loaded in memory after the request. Server default is 5 minutes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+
+    /// Model parameters such as `temperature`, `num_predict`, `num_ctx`, and `stop`.
+    pub options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl OllamaGenerateRequest {
+    pub fn new(
+        req: &CompletionRequest,
+        config: &OllamaConfig,
+    ) -> crate::Result<Self, CompletionError> {
+        if req.assistant_prefill.is_some() {
+            return Err(CompletionError::RequestBuilderError(
+                "Ollama's generate endpoint doesn't support an assistant prefill. Use the chat endpoint".to_string(),
+            ));
+        }
+        let messages = req
+            .prompt
+            .api_prompt()
+            .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?
+            .get_built_prompt()
+            .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+        let mut system: Vec<String> = Vec::new();
+        let mut turns: Vec<CompletionRequestMessage> = Vec::new();
+        for message in &messages {
+            let message = CompletionRequestMessage::new(message)?;
+            if message.role == "system" {
+                system.push(message.content);
+            } else {
+                turns.push(message);
+            }
+        }
+        let prompt = match &turns[..] {
+            [message] if message.role == "user" => message.content.to_owned(),
+            _ => turns
+                .iter()
+                .map(|m| match m.role.as_str() {
+                    "user" => format!("User: {}", m.content),
+                    _ => format!("Assistant: {}", m.content),
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        };
+
+        Ok(OllamaGenerateRequest {
+            model: req.backend.model_id().to_owned(),
+            prompt,
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+            stream: false,
+            keep_alive: config.keep_alive.clone(),
+            options: request_options(req, config),
+        })
+    }
+}
+
+/// The backend's `options`, with the parameters set from the request taking precedence.
+fn request_options(
+    req: &CompletionRequest,
+    config: &OllamaConfig,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut options = config.options.clone();
+    let mut set = |key: &str, value: serde_json::Value| {
+        options.insert(key.to_owned(), value);
+    };
+    set("num_ctx", req.backend.model_ctx_size().into());
+    set("temperature", req.config.temperature.into());
+    set("presence_penalty", req.config.presence_penalty.into());
+    if req.config.is_uncapped() {
+        set("num_predict", (-1).into());
+    } else if let Some(num_predict) = req.config.actual_request_tokens {
+        set("num_predict", num_predict.into());
+    }
+    if let Some(top_p) = req.config.top_p {
+        set("top_p", top_p.into());
+    }
+    if let Some(frequency_penalty) = req.config.frequency_penalty {
+        set("frequency_penalty", frequency_penalty.into());
+    }
+    if let Some(repeat_penalty) = req.config.repeat_penalty {
+        set("repeat_penalty", repeat_penalty.into());
+    }
+    if let Some(repeat_last_n) = req.config.repeat_last_n {
+        set("repeat_last_n", repeat_last_n.into());
+    }
+    if req.config.is_greedy() {
+        set("top_k", 1.into());
+    }
+    let stop = req.stop_sequences.to_vec();
+    if !stop.is_empty() {
+        set("stop", stop.into());
+    }
+    options
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CompletionRequestMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl CompletionRequestMessage {
+    pub fn new(
+        message: &std::collections::HashMap<String, String>,
+    ) -> crate::Result<Self, CompletionError> {
+        let role = message
+            .get("role")
+            .ok_or_else(|| CompletionError::RequestBuilderError("Role not found".to_string()))?;
+        let content = message
+            .get("content")
+            .ok_or_else(|| CompletionError::RequestBuilderError("Content not found".to_string()))?;
+
+        match role.as_str() {
+            "system" | "user" | "assistant" => Ok(CompletionRequestMessage {
+                role: role.to_string(),
+                content: content.to_string(),
+            }),
+            _ => Err(CompletionError::RequestBuilderError(format!(
+                "Role {} not supported",
+                role
+            ))),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::requests::completion::*;
+use serde::{Deserialize, Serialize};
+
+impl CompletionResponse {
+    pub fn new_from_ollama(
+        req: &CompletionRequest,
+        res: OllamaChatResponse,
+    ) -> Result<Self, CompletionError> {
+        let finish_reason = match res.done_reason.as_deref() {
+            Some("length") => CompletionFinishReason::StopLimit,
+            // Ollama doesn't report which stop sequence was matched, and strips it from the content.
+            _ => CompletionFinishReason::Eos,
+        };
+        let content = match &res.message {
+            Some(message) => message.content.to_owned(),
+            None => return Err(CompletionError::ReponseContentEmpty),
+        };
+
+        Ok(Self {
+            id: res.created_at.to_owned(),
+            index: None,
+            content,
+            finish_reason,
+            completion_probabilities: None,
+            truncated: false,
+            content_truncated: false,
+            thinking: None,
+            citations: None,
+            generation_settings: GenerationSettings::new_from_ollama(req, &res),
+            timing_usage: TimingUsage::new_from_ollama(&res, req.start_time),
+            token_usage: TokenUsage::new_from_ollama(&res),
+        })
+    }
+}
+
+/// The response from `/api/chat` with `stream` set to false.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct OllamaChatResponse {
+    /// The model that handled the request.
+    pub model: String,
+    /// Timestamp of the response. Ollama doesn't return a response id, so this is used as one.
+    pub created_at: String,
+    pub message: Option<OllamaResponseMessage>,
+    pub done: bool,
+    /// `stop` if the model reached a natural stopping point or a stop sequence, `length` if `num_predict` was reached.
+    pub done_reason: Option<String>,
+    /// Total time spent on the request, in nanoseconds.
+    pub total_duration: Option<u64>,
+    /// Time spent loading the model, in nanoseconds.
+    pub load_duration: Option<u64>,
+    /// Number of tokens in the prompt.
+    pub prompt_eval_count: Option<u32>,
+    /// Time spent evaluating the prompt, in nanoseconds.
+    pub prompt_eval_duration: Option<u64>,
+    /// Number of tokens in the response.
+    pub eval_count: Option<u32>,
+    /// Time spent generating the response, in nanoseconds.
+    pub eval_duration: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct OllamaResponseMessage {
+    pub role: String,
+    pub content: String,
+}
+
+/// The response from `/api/generate` with `stream` set to false.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct OllamaGenerateResponse {
+    pub model: String,
+    pub created_at: String,
+    /// The generated text.
+    pub response: String,
+    pub done: bool,
+    pub done_reason: Option<String>,
+    pub total_duration: Option<u64>,
+    pub load_duration: Option<u64>,
+    pub prompt_eval_count: Option<u32>,
+    pub prompt_eval_duration: Option<u64>,
+    pub eval_count: Option<u32>,
+    pub eval_duration: Option<u64>,
+}
+
+impl From<OllamaGenerateResponse> for OllamaChatResponse {
+    /// Converts to a chat response with the text as the assistant's message, so both endpoints are handled the same way.
+    fn from(res: OllamaGenerateResponse) -> Self {
+        OllamaChatResponse {
+            model: res.model,
+            created_at: res.created_at,
+            message: Some(OllamaResponseMessage {
+                role: "assistant".to_string(),
+                content: res.response,
+            }),
+            done: res.done,
+            done_reason: res.done_reason,
+            total_duration: res.total_duration,
+            load_duration: res.load_duration,
+            prompt_eval_count: res.prompt_eval_count,
+            prompt_eval_duration: res.prompt_eval_duration,
+            eval_count: res.eval_count,
+            eval_duration: res.eval_duration,
+        }
+    }
+}
@@ -0,0 +1,254 @@
+pub mod builder;
+pub mod completion;
+pub mod models;
+
+use super::{
+    client::ApiClient,
+    config::{ApiConfig, ApiConfigTrait, PoolConfig},
+};
+use crate::requests::completion::{
+    error::CompletionError, request::CompletionRequest, response::CompletionResponse,
+};
+use completion::{OllamaChatRequest, OllamaGenerateRequest, OllamaGenerateResponse};
+use llm_devices::logging::LoggingConfig;
+use llm_models::api_model::ApiLlmModel;
+use models::{
+    OllamaModelInfo, OllamaModelList, OllamaPullRequest, OllamaStatus, OllamaUnloadRequest,
+};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use secrecy::{ExposeSecret, Secret};
+
+pub const OLLAMA_API_HOST: &str = "localhost";
+pub const OLLAMA_API_PORT: &str = "11434";
+
+pub struct OllamaBackend {
+    pub(crate) client: ApiClient<OllamaConfig>,
+    pub model: ApiLlmModel,
+}
+
+impl OllamaBackend {
+    pub fn new(mut config: OllamaConfig, model: ApiLlmModel) -> crate::Result<Self> {
+        config.logging_config.load_logger()?;
+        // Ollama doesn't use an api key, but one may be needed for a proxy in front of it.
+        if let Ok(api_key) = config.api_config.load_api_key() {
+            config.api_config.api_key = Some(api_key);
+        }
+        Ok(Self {
+            client: ApiClient::new(config)?,
+            model,
+        })
+    }
+
+    pub(crate) async fn completion_request(
+        &self,
+        request: &CompletionRequest,
+    ) -> crate::Result<CompletionResponse, CompletionError> {
+        let res = match self.client.config.endpoint {
+            OllamaEndpoint::Chat => {
+                self.client
+                    .post(
+                        "/api/chat",
+                        OllamaChatRequest::new(request, &self.client.config)?,
+                    )
+                    .await
+            }
+            OllamaEndpoint::Generate => self
+                .client
+                .post::<_, OllamaGenerateResponse>(
+                    "/api/generate",
+                    OllamaGenerateRequest::new(request, &self.client.config)?,
+                )
+                .await
+                .map(Into::into),
+        };
+        match res {
+            Err(e) => Err(CompletionError::ClientError(e)),
+            Ok(res) => Ok(CompletionResponse::new_from_ollama(request, res)?),
+        }
+    }
+
+    /// The URL and body [Self::completion_request] would post.
+    pub(crate) fn completion_payload(
+        &self,
+        request: &CompletionRequest,
+    ) -> crate::Result<(String, serde_json::Value), CompletionError> {
+        match self.client.config.endpoint {
+            OllamaEndpoint::Chat => self.client.dry_run(
+                "/api/chat",
+                OllamaChatRequest::new(request, &self.client.config)?,
+            ),
+            OllamaEndpoint::Generate => self.client.dry_run(
+                "/api/generate",
+                OllamaGenerateRequest::new(request, &self.client.config)?,
+            ),
+        }
+        .map_err(CompletionError::ClientError)
+    }
+
+    /// Lists the models available on the Ollama server. Equivalent to `ollama list`.
+    pub async fn list_models(&self) -> crate::Result<Vec<OllamaModelInfo>> {
+        let res: OllamaModelList = self.client.get("/api/tags").await?;
+        Ok(res.models)
+    }
+
+    /// Whether the model is available on the Ollama server. Names without a tag match the `latest` tag.
+    pub async fn has_model(&self, model_id: &str) -> crate::Result<bool> {
+        let model_id = models::with_default_tag(model_id);
+        Ok(self
+            .list_models()
+            .await?
+            .iter()
+            .any(|model| models::with_default_tag(&model.name) == model_id))
+    }
+
+    /// Downloads the model to the Ollama server. Equivalent to `ollama pull`. Waits until the pull is complete.
+    pub async fn pull_model(&self, model_id: &str) -> crate::Result<()> {
+        let res: OllamaStatus = self
+            .client
+            .post(
+                "/api/pull",
+                OllamaPullRequest {
+                    model: model_id.to_owned(),
+                    stream: false,
+                },
+            )
+            .await?;
+        if res.status != "success" {
+            crate::bail!("Failed to pull {model_id}: {}", res.status);
+        }
+        Ok(())
+    }
+
+    /// Pulls the backend's model if it isn't available on the Ollama server.
+    pub async fn pull_model_if_missing(&self) -> crate::Result<()> {
+        if !self.has_model(&self.model.model_base.model_id).await? {
+            self.pull_model(&self.model.model_base.model_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Unloads the backend's model from memory immediately, rather than waiting for `keep_alive` to expire.
+    pub async fn unload_model(&self) -> crate::Result<()> {
+        let _: serde_json::Value = self
+            .client
+            .post(
+                "/api/generate",
+                OllamaUnloadRequest {
+                    model: self.model.model_base.model_id.clone(),
+                    keep_alive: 0,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Which of Ollama's native endpoints completions are sent to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OllamaEndpoint {
+    /// `/api/chat`, with the prompt as messages.
+    #[default]
+    Chat,
+    /// `/api/generate`, with the system messages as `system` and the rest of the prompt as a single `prompt`.
+    /// Doesn't support an assistant prefill.
+    Generate,
+}
+
+#[derive(Clone, Debug)]
+pub struct OllamaConfig {
+    pub api_config: ApiConfig,
+    pub logging_config: LoggingConfig,
+    pub endpoint: OllamaEndpoint,
+    /// How long the model stays loaded after a request, e.g. `"5m"`, `"1h"`, or `"-1"` to keep it loaded. Sent as `keep_alive`.
+    /// If `None`, the server's default is used.
+    pub keep_alive: Option<String>,
+    /// Extra model parameters sent in `options`, e.g. `num_gpu` or `mirostat`.
+    /// Parameters set from the request, such as `temperature`, `num_predict`, and `num_ctx`, take precedence.
+    pub options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            api_config: ApiConfig {
+                host: OLLAMA_API_HOST.to_string(),
+                port: Some(OLLAMA_API_PORT.to_string()),
+                api_key: None,
+                api_key_env_var: "OLLAMA_API_KEY".to_string(),
+                custom_headers: Vec::new(),
+                rate_limiter: None,
+                pool_config: Default::default(),
+            },
+            logging_config: LoggingConfig {
+                logger_name: "ollama".to_string(),
+                ..Default::default()
+            },
+            endpoint: OllamaEndpoint::default(),
+            keep_alive: None,
+            options: serde_json::Map::new(),
+        }
+    }
+}
+
+impl OllamaConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn endpoint(mut self, endpoint: OllamaEndpoint) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    pub fn keep_alive<S: Into<String>>(mut self, keep_alive: S) -> Self {
+        self.keep_alive = Some(keep_alive.into());
+        self
+    }
+
+    pub fn option<K: Into<String>, V: Into<serde_json::Value>>(mut self, key: K, value: V) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl ApiConfigTrait for OllamaConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = self.api_key() {
+            if let Ok(header_value) =
+                HeaderValue::from_str(&format!("Bearer {}", api_key.expose_secret()))
+            {
+                headers.insert(AUTHORIZATION, header_value);
+            } else {
+                crate::error!("Failed to create header value from authorization value");
+            }
+        }
+
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        let host = if self.api_config.host.contains("://") {
+            self.api_config.host.trim_end_matches('/').to_owned()
+        } else {
+            format!("http://{}", self.api_config.host)
+        };
+        if let Some(port) = &self.api_config.port {
+            format!("{}:{}{}", host, port, path)
+        } else {
+            format!("{}{}", host, path)
+        }
+    }
+
+    fn api_key(&self) -> &Option<Secret<String>> {
+        &self.api_config.api_key
+    }
+
+    fn custom_headers(&self) -> crate::Result<HeaderMap> {
+        self.api_config.custom_header_map()
+    }
+
+    fn pool_config(&self) -> &PoolConfig {
+        &self.api_config.pool_config
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// A model available on the Ollama server, as returned by `/api/tags`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct OllamaModelInfo {
+    /// The name used to request the model, e.g. `llama3.2:latest`.
+    pub name: String,
+    pub modified_at: String,
+    /// Size on disk in bytes.
+    pub size: u64,
+    pub digest: String,
+    #[serde(default)]
+    pub details: Option<OllamaModelDetails>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct OllamaModelDetails {
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub family: Option<String>,
+    /// e.g. `3.2B`
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    /// e.g. `Q4_K_M`
+    #[serde(default)]
+    pub quantization_level: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct OllamaModelList {
+    pub models: Vec<OllamaModelInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct OllamaPullRequest {
+    pub model: String,
+    pub stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct OllamaUnloadRequest {
+    pub model: String,
+    pub keep_alive: u8,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct OllamaStatus {
+    pub status: String,
+}
+
+/// Ollama treats a model name without a tag as the `latest` tag.
+pub(super) fn with_default_tag(model_id: &str) -> String {
+    if model_id.contains(':') {
+        model_id.to_owned()
+    } else {
+        format!("{model_id}:latest")
+    }
+}
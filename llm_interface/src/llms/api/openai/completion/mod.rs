@@ -1,4 +1,8 @@
 mod req;
 mod res;
-pub use req::OpenAiCompletionRequest;
+pub(crate) use req::Stop;
+pub use req::{is_openai_search_model, OpenAiCompletionRequest};
 pub use res::OpenAiCompletionResponse;
+pub(crate) use res::{
+    ChatChoice, ChatCompletionResponseMessage, CompletionUsage, FinishReason, Role,
+};
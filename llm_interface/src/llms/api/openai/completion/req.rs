@@ -1,6 +1,6 @@
 use crate::requests::{completion::*, stop_sequence::StopSequences};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Clone, Serialize, Default, Debug, Deserialize, PartialEq)]
 pub struct OpenAiCompletionRequest {
@@ -18,7 +18,7 @@ pub struct OpenAiCompletionRequest {
     /// The exact effect will vary per model, but values between -1 and 1 should decrease or increase likelihood of selection;
     /// values like -100 or 100 should result in a ban or exclusive selection of the relevant token.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub logit_bias: Option<HashMap<String, serde_json::Value>>, // default: null
+    pub logit_bias: Option<BTreeMap<String, serde_json::Value>>, // default: null
 
     /// Whether to return log probabilities of the output tokens or not. If true, returns the log probabilities of each output token returned in the `content` of `message`.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -57,9 +57,32 @@ pub struct OpenAiCompletionRequest {
     /// min: 0.0, max: 1.0, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
+
+    /// How many chat completion choices to generate for each input message. You are charged for the generated tokens of all choices.
+    ///
+    /// min: 1, max: 128, default: 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u8>,
+
+    /// Enables the built in web search of OpenAI's search models, e.g. `gpt-4o-search-preview`. An empty object uses the default search settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_search_options: Option<serde_json::Value>,
 }
 
+/// OpenAI compatible APIs can't continue a trailing assistant message, so the continuation is requested instead.
+pub const CONTINUE_ASSISTANT_PREFILL: &str = "Continue your previous response from exactly where it left off. Don't repeat any of it, and don't add any commentary.";
+
 impl OpenAiCompletionRequest {
+    /// Like [Self::new], with `n` set to `num_completions` to generate several completions in one request.
+    pub fn new_with_n(
+        req: &CompletionRequest,
+        num_completions: Option<u8>,
+    ) -> crate::Result<Self, CompletionError> {
+        let mut request = Self::new(req)?;
+        request.n = num_completions;
+        Ok(request)
+    }
+
     pub fn new(req: &CompletionRequest) -> crate::Result<Self, CompletionError> {
         let mut messages = Vec::new();
         match &req
@@ -75,23 +98,50 @@ impl OpenAiCompletionRequest {
             }
             Err(e) => return Err(CompletionError::RequestBuilderError(e.to_string())),
         }
+        if let Some(assistant_prefill) = &req.assistant_prefill {
+            messages.push(CompletionRequestMessage {
+                role: "assistant".to_string(),
+                content: assistant_prefill.to_owned(),
+            });
+            messages.push(CompletionRequestMessage {
+                role: "user".to_string(),
+                content: CONTINUE_ASSISTANT_PREFILL.to_string(),
+            });
+        }
+        req.config.log_ignored_repeat_penalty("OpenAI");
 
-        Ok(OpenAiCompletionRequest {
+        let mut request = OpenAiCompletionRequest {
             messages,
             model: req.backend.model_id().to_owned(),
             logit_bias: req.logit_bias.as_ref().and_then(|lb| lb.get_openai()),
             frequency_penalty: req.config.frequency_penalty,
             logprobs: None,
             top_logprobs: None,
-            max_tokens: req.config.actual_request_tokens,
+            max_tokens: req.config.response_token_limit(),
             presence_penalty: Some(req.config.presence_penalty),
             stop: Stop::new(&req.stop_sequences)?,
             temperature: Some(req.config.temperature),
             top_p: req.config.top_p,
-        })
+            n: None,
+            web_search_options: None,
+        };
+        // OpenAI's search models reject the sampling parameters.
+        if is_openai_search_model(&request.model) {
+            request.web_search_options = Some(serde_json::json!({}));
+            request.temperature = None;
+            request.top_p = None;
+            request.frequency_penalty = None;
+            request.presence_penalty = None;
+        }
+        Ok(request)
     }
 }
 
+/// OpenAI models that search the web before answering, e.g. `gpt-4o-search-preview`.
+pub fn is_openai_search_model(model_id: &str) -> bool {
+    model_id.starts_with("gpt-") && model_id.contains("-search")
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CompletionRequestMessage {
     pub role: String,
@@ -6,11 +6,49 @@ impl CompletionResponse {
         req: &CompletionRequest,
         res: OpenAiCompletionResponse,
     ) -> Result<Self, CompletionError> {
-        let choice = if res.choices.is_empty() || res.choices[0].message.content.is_none() {
+        let choice = res
+            .choices
+            .first()
+            .ok_or(CompletionError::ReponseContentEmpty)?;
+        let mut response =
+            Self::new_from_openai_choice(req, &res, choice, TokenUsage::new_from_generic(&res))?;
+        response.index = None;
+        Ok(response)
+    }
+
+    /// One response per choice of a request made with `n`. The token usage of the whole request is reported on the first response.
+    pub fn new_all_from_openai(
+        req: &CompletionRequest,
+        res: OpenAiCompletionResponse,
+    ) -> Result<Vec<Self>, CompletionError> {
+        if res.choices.is_empty() {
             return Err(CompletionError::ReponseContentEmpty);
-        } else {
-            &res.choices[0]
-        };
+        }
+        res.choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| {
+                let token_usage = if i == 0 {
+                    TokenUsage::new_from_generic(&res)
+                } else {
+                    TokenUsage::default()
+                };
+                Self::new_from_openai_choice(req, &res, choice, token_usage)
+            })
+            .collect()
+    }
+
+    fn new_from_openai_choice(
+        req: &CompletionRequest,
+        res: &OpenAiCompletionResponse,
+        choice: &ChatChoice,
+        token_usage: TokenUsage,
+    ) -> Result<Self, CompletionError> {
+        if let Some(refusal) = &choice.message.refusal {
+            return Err(CompletionError::ContentFiltered(format!(
+                "model refused: {refusal}"
+            )));
+        }
         let finish_reason = match choice.finish_reason {
             Some(FinishReason::Stop) => CompletionFinishReason::Eos,
             Some(FinishReason::Length) => CompletionFinishReason::StopLimit,
@@ -20,8 +58,8 @@ impl CompletionResponse {
                 ))
             }
             Some(FinishReason::ContentFilter) => {
-                return Err(CompletionError::StopReasonUnsupported(
-                    "FinishReason::ContentFilter is not supported".to_owned(),
+                return Err(CompletionError::ContentFiltered(
+                    "finish_reason: content_filter".to_owned(),
                 ))
             }
             Some(FinishReason::FunctionCall) => {
@@ -31,16 +69,23 @@ impl CompletionResponse {
             }
             None => CompletionFinishReason::Eos,
         };
+        let content = match &choice.message.content {
+            Some(content) => content.to_owned(),
+            None => return Err(CompletionError::ReponseContentEmpty),
+        };
         Ok(Self {
             id: res.id.to_owned(),
-            index: None,
-            content: choice.message.content.as_ref().unwrap().to_owned(),
+            index: Some(choice.index),
+            content,
             finish_reason,
             completion_probabilities: None,
             truncated: false,
-            generation_settings: GenerationSettings::new_from_openai(req, &res),
+            content_truncated: false,
+            thinking: None,
+            citations: res.sources(choice),
+            generation_settings: GenerationSettings::new_from_openai(req, res),
             timing_usage: TimingUsage::new_from_generic(req.start_time),
-            token_usage: TokenUsage::new_from_generic(&res),
+            token_usage,
         })
     }
 }
@@ -57,6 +102,58 @@ pub struct OpenAiCompletionResponse {
     /// The model used for the chat completion.
     pub model: String,
     pub usage: Option<CompletionUsage>,
+    /// The URLs of the sources the answer was based on. Only returned by Perplexity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citations: Option<Vec<String>>,
+    /// The sources the answer was based on, with their titles. Only returned by Perplexity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_results: Option<Vec<SearchResult>>,
+}
+
+impl OpenAiCompletionResponse {
+    /// Perplexity's search results, falling back to its citation URLs, or else the choice's OpenAI URL annotations.
+    pub fn sources(&self, choice: &ChatChoice) -> Option<Vec<Citation>> {
+        if let Some(search_results) = &self.search_results {
+            return Some(
+                search_results
+                    .iter()
+                    .map(|r| Citation {
+                        url: r.url.clone(),
+                        title: r.title.clone(),
+                        snippet: r.snippet.clone(),
+                    })
+                    .collect(),
+            );
+        }
+        if let Some(citations) = &self.citations {
+            return Some(citations.iter().map(Citation::new).collect());
+        }
+        let annotations = choice.message.annotations.as_ref()?;
+        let mut sources: Vec<Citation> = Vec::new();
+        for annotation in annotations {
+            if let Some(url_citation) = &annotation.url_citation {
+                if sources.iter().any(|s| s.url == url_citation.url) {
+                    continue;
+                }
+                sources.push(Citation {
+                    url: url_citation.url.clone(),
+                    title: url_citation.title.clone(),
+                    snippet: None,
+                });
+            }
+        }
+        Some(sources)
+    }
+}
+
+/// A source returned by Perplexity.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SearchResult {
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -90,8 +187,35 @@ pub struct ChatCompletionResponseMessage {
     /// The contents of the message.
     pub content: Option<String>,
 
+    /// The refusal message generated by the model, if it declined the request.
+    #[serde(default)]
+    pub refusal: Option<String>,
+
     /// The role of the author of this message.
     pub role: Role,
+
+    /// The URL citations of OpenAI's search models.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Annotation {
+    /// `url_citation` for web search results.
+    #[serde(rename = "type")]
+    pub annotation_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_citation: Option<UrlCitation>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct UrlCitation {
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// The byte range of the citation in the message content.
+    pub start_index: u32,
+    pub end_index: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
@@ -3,7 +3,7 @@ pub mod completion;
 
 use super::{
     client::ApiClient,
-    config::{ApiConfig, ApiConfigTrait},
+    config::{ApiConfig, ApiConfigTrait, PoolConfig},
 };
 use crate::requests::completion::{
     error::CompletionError, request::CompletionRequest, response::CompletionResponse,
@@ -31,7 +31,7 @@ impl OpenAiBackend {
         config.logging_config.load_logger()?;
         config.api_config.api_key = Some(config.api_config.load_api_key()?);
         Ok(Self {
-            client: ApiClient::new(config),
+            client: ApiClient::new(config)?,
             model,
         })
     }
@@ -49,6 +49,32 @@ impl OpenAiBackend {
             Ok(res) => Ok(CompletionResponse::new_from_openai(request, res)?),
         }
     }
+
+    /// The URL and body [Self::completion_request], or with `num_completions` [Self::completion_request_n], would post.
+    pub(crate) fn completion_payload(
+        &self,
+        request: &CompletionRequest,
+        num_completions: Option<u8>,
+    ) -> crate::Result<(String, serde_json::Value), CompletionError> {
+        self.client
+            .dry_run(
+                "/chat/completions",
+                OpenAiCompletionRequest::new_with_n(request, num_completions)?,
+            )
+            .map_err(CompletionError::ClientError)
+    }
+
+    pub(crate) async fn completion_request_n(
+        &self,
+        request: &CompletionRequest,
+        num_completions: u8,
+    ) -> crate::Result<Vec<CompletionResponse>, CompletionError> {
+        let req = OpenAiCompletionRequest::new_with_n(request, Some(num_completions))?;
+        match self.client.post("/chat/completions", req).await {
+            Err(e) => Err(CompletionError::ClientError(e)),
+            Ok(res) => Ok(CompletionResponse::new_all_from_openai(request, res)?),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -57,6 +83,9 @@ pub struct OpenAiConfig {
     pub logging_config: LoggingConfig,
     pub org_id: String,
     pub project_id: String,
+    /// Overrides the scheme, host and base path used for requests, e.g. `https://my-gateway.example.com/openai/v1`.
+    /// When set, [ApiConfig::host] and [ApiConfig::port] are ignored.
+    pub base_url: Option<String>,
 }
 
 impl Default for OpenAiConfig {
@@ -67,6 +96,9 @@ impl Default for OpenAiConfig {
                 port: None,
                 api_key: None,
                 api_key_env_var: "OPENAI_API_KEY".to_string(),
+                custom_headers: Vec::new(),
+                rate_limiter: None,
+                pool_config: Default::default(),
             },
             logging_config: LoggingConfig {
                 logger_name: "openai".to_string(),
@@ -74,6 +106,7 @@ impl Default for OpenAiConfig {
             },
             org_id: Default::default(),
             project_id: Default::default(),
+            base_url: None,
         }
     }
 }
@@ -94,6 +127,12 @@ impl OpenAiConfig {
         self.project_id = project_id.into();
         self
     }
+
+    /// Send requests to a different base url, such as a gateway or an OpenAI compatible proxy
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
 }
 
 impl ApiConfigTrait for OpenAiConfig {
@@ -128,10 +167,22 @@ impl ApiConfigTrait for OpenAiConfig {
     }
 
     fn url(&self, path: &str) -> String {
-        format!("https://{}{}", self.api_config.host, path)
+        if let Some(base_url) = &self.base_url {
+            format!("{}{}", base_url.trim_end_matches('/'), path)
+        } else {
+            format!("https://{}{}", self.api_config.host, path)
+        }
     }
 
     fn api_key(&self) -> &Option<Secret<String>> {
         &self.api_config.api_key
     }
+
+    fn custom_headers(&self) -> crate::Result<HeaderMap> {
+        self.api_config.custom_header_map()
+    }
+
+    fn pool_config(&self) -> &PoolConfig {
+        &self.api_config.pool_config
+    }
 }
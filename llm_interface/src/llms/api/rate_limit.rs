@@ -0,0 +1,60 @@
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+use tokio::time::Instant;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Keeps requests to an API within its requests per minute and tokens per minute limits.
+///
+/// Counts the requests and estimated tokens sent in the last minute, and makes requests that would go over either
+/// limit wait until enough of the window has passed, instead of sending them to be rejected with a 429.
+/// It's held in an `Arc` in [super::config::ApiConfig], so every clone of a backend's config shares the same budget.
+#[derive(Debug)]
+pub struct RateLimiter {
+    pub requests_per_minute: u32,
+    pub tokens_per_minute: u64,
+    sent: Mutex<VecDeque<(Instant, u64)>>,
+}
+
+impl RateLimiter {
+    /// A limit of `0` disables that limit.
+    pub fn new(requests_per_minute: u32, tokens_per_minute: u64) -> Self {
+        Self {
+            requests_per_minute,
+            tokens_per_minute,
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Waits until a request of `tokens` fits in the last minute's budget, then counts it against the budget.
+    ///
+    /// A single request larger than the whole token budget is sent once the window is empty, rather than waiting forever.
+    pub async fn acquire(&self, tokens: u64) {
+        loop {
+            let wait = {
+                let mut sent = self.sent.lock().unwrap();
+                let now = Instant::now();
+                while sent
+                    .front()
+                    .is_some_and(|(sent_at, _)| now.duration_since(*sent_at) >= WINDOW)
+                {
+                    sent.pop_front();
+                }
+                let sent_tokens: u64 = sent.iter().map(|(_, tokens)| tokens).sum();
+                let requests_fit =
+                    self.requests_per_minute == 0 || sent.len() < self.requests_per_minute as usize;
+                let tokens_fit = self.tokens_per_minute == 0
+                    || sent.is_empty()
+                    || sent_tokens + tokens <= self.tokens_per_minute;
+                if requests_fit && tokens_fit {
+                    sent.push_back((now, tokens));
+                    return;
+                }
+                // The oldest request is the next to leave the window.
+                let (oldest, _) = sent.front().expect("a full window isn't empty");
+                WINDOW.saturating_sub(now.duration_since(*oldest))
+            };
+            crate::trace!("Rate limited: waiting {wait:?} before sending the request");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
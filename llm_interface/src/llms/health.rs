@@ -0,0 +1,119 @@
+/// How long a health check waits for the server before reporting it unreachable.
+pub const HEALTH_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Whether a backend can take requests, from [super::LlmBackend::health_check].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The server responded and accepted the credentials.
+    Ready,
+    /// The server is up but not ready yet, e.g. llama.cpp is still loading the model. Retry later.
+    Loading,
+    /// The server couldn't be reached, timed out, or failed with a server error. Holds the reason.
+    Unreachable(String),
+    /// The server rejected the API key. Holds the reason.
+    AuthFailed(String),
+    /// The server is up but rejected the request for exceeding the rate limit. Retry later.
+    RateLimited,
+    /// The server responded, but not with the endpoint that was probed, e.g. the base url is wrong. Holds the reason.
+    Misconfigured(String),
+}
+
+impl HealthStatus {
+    /// Classifies the status of a response to a health check request. Only a 2xx means the server is ready. A 503 means a
+    /// local server is still loading the model, but from an API it means the service is unavailable.
+    pub(crate) fn from_status(status: reqwest::StatusCode, is_local: bool) -> Self {
+        match status.as_u16() {
+            200..=299 => HealthStatus::Ready,
+            401 | 403 => HealthStatus::AuthFailed(format!("HTTP {status}")),
+            429 => HealthStatus::RateLimited,
+            503 if is_local => HealthStatus::Loading,
+            500..=599 => HealthStatus::Unreachable(format!("HTTP {status}")),
+            _ => HealthStatus::Misconfigured(format!("HTTP {status}")),
+        }
+    }
+
+    /// Classifies the outcome of [crate::llms::api::client::ApiClient::probe].
+    pub(crate) fn from_probe(probe: Result<reqwest::StatusCode, String>, is_local: bool) -> Self {
+        match probe {
+            Ok(status) => HealthStatus::from_status(status, is_local),
+            Err(reason) => HealthStatus::Unreachable(reason),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        matches!(self, HealthStatus::Ready)
+    }
+}
+
+impl std::fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HealthStatus::Ready => write!(f, "Ready"),
+            HealthStatus::Loading => write!(f, "Loading"),
+            HealthStatus::Unreachable(reason) => write!(f, "Unreachable: {reason}"),
+            HealthStatus::AuthFailed(reason) => write!(f, "AuthFailed: {reason}"),
+            HealthStatus::RateLimited => write!(f, "RateLimited"),
+            HealthStatus::Misconfigured(reason) => write!(f, "Misconfigured: {reason}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_from_status() {
+        for is_local in [true, false] {
+            assert_eq!(
+                HealthStatus::from_status(StatusCode::OK, is_local),
+                HealthStatus::Ready
+            );
+            assert_eq!(
+                HealthStatus::from_status(StatusCode::NO_CONTENT, is_local),
+                HealthStatus::Ready
+            );
+            assert!(matches!(
+                HealthStatus::from_status(StatusCode::UNAUTHORIZED, is_local),
+                HealthStatus::AuthFailed(_)
+            ));
+            assert!(matches!(
+                HealthStatus::from_status(StatusCode::FORBIDDEN, is_local),
+                HealthStatus::AuthFailed(_)
+            ));
+            assert_eq!(
+                HealthStatus::from_status(StatusCode::TOO_MANY_REQUESTS, is_local),
+                HealthStatus::RateLimited
+            );
+            assert!(matches!(
+                HealthStatus::from_status(StatusCode::NOT_FOUND, is_local),
+                HealthStatus::Misconfigured(_)
+            ));
+            assert!(matches!(
+                HealthStatus::from_status(StatusCode::BAD_REQUEST, is_local),
+                HealthStatus::Misconfigured(_)
+            ));
+            assert!(matches!(
+                HealthStatus::from_status(StatusCode::MOVED_PERMANENTLY, is_local),
+                HealthStatus::Misconfigured(_)
+            ));
+            assert!(matches!(
+                HealthStatus::from_status(StatusCode::INTERNAL_SERVER_ERROR, is_local),
+                HealthStatus::Unreachable(_)
+            ));
+        }
+        assert_eq!(
+            HealthStatus::from_status(StatusCode::SERVICE_UNAVAILABLE, true),
+            HealthStatus::Loading
+        );
+        assert!(matches!(
+            HealthStatus::from_status(StatusCode::SERVICE_UNAVAILABLE, false),
+            HealthStatus::Unreachable(_)
+        ));
+        assert!(matches!(
+            HealthStatus::from_probe(Err("connection refused".to_string()), true),
+            HealthStatus::Unreachable(_)
+        ));
+    }
+}
@@ -1,7 +1,7 @@
-use super::{LlamaCppBackend, LlamaCppConfig};
+use super::{DrySampler, LlamaCppBackend, LlamaCppConfig};
 use crate::llms::{
     api::config::{ApiConfig, LlmApiConfigTrait},
-    local::{LlmLocalTrait, LocalLlmConfig},
+    local::{KvCacheType, LlmLocalTrait, LocalLlmConfig, RopeScaling},
     LlmBackend,
 };
 use llm_devices::logging::{LoggingConfig, LoggingConfigTrait};
@@ -19,6 +19,115 @@ pub struct LlamaCppBackendBuilder {
 }
 
 impl LlamaCppBackendBuilder {
+    /// Forces exactly `n_gpu_layers` layers of the model onto the GPU(s).
+    ///
+    /// # Arguments
+    ///
+    /// * `n_gpu_layers` - The number of layers to offload to the GPU(s). Passed to llama-server as `--n-gpu-layers`.
+    ///
+    /// # Notes
+    ///
+    /// Setting this disables the automatic VRAM based layer allocation.
+    /// Useful when the estimator is wrong for an unusual model, but it's possible to run out of VRAM if set too high.
+    ///
+    /// # Default
+    ///
+    /// If not set, the number of layers is calculated from the available VRAM.
+    pub fn n_gpu_layers(mut self, n_gpu_layers: u32) -> Self {
+        self.config.n_gpu_layers = Some(n_gpu_layers);
+        self
+    }
+
+    /// Enables llama-server's DRY (Don't Repeat Yourself) sampler for every request.
+    ///
+    /// # Arguments
+    ///
+    /// * `dry_sampler` - The DRY settings. `DrySampler::default()` uses the values recommended by the DRY author.
+    ///
+    /// # Notes
+    ///
+    /// DRY penalizes tokens that would extend a sequence already in the context, which is
+    /// very effective against repetition loops in small local models.
+    ///
+    /// # Default
+    ///
+    /// If not set, the DRY parameters are not sent and the sampler is disabled.
+    pub fn dry_sampler(mut self, dry_sampler: DrySampler) -> Self {
+        self.config.dry_sampler = Some(dry_sampler);
+        self
+    }
+
+    /// Extends the model's context past its trained length with RoPE scaling, e.g. to run an 8k model at 16k.
+    ///
+    /// # Arguments
+    ///
+    /// * `rope_scaling` - The scaling method and factor, e.g. `RopeScaling::yarn(2.0)`. Passed to llama-server as `--rope-scaling`, `--rope-scale`, and `--yarn-*`.
+    ///
+    /// # Notes
+    ///
+    /// Set the extended context with `inference_ctx_size`. It's checked against the trained context times the factor.
+    /// The settings are validated when the model loads, and a warning is logged since models not fine-tuned
+    /// on the longer context lose quality. YaRN holds up better than linear scaling past 2x.
+    ///
+    /// # Default
+    ///
+    /// If not set, the model runs at up to its trained context.
+    pub fn rope_scaling(mut self, rope_scaling: RopeScaling) -> Self {
+        self.local_config.rope_scaling = Some(rope_scaling);
+        self
+    }
+
+    /// Starts llama-server with flash attention (`--flash-attn`), which lowers the memory and time of attention at long contexts.
+    ///
+    /// # Notes
+    ///
+    /// Required for a quantized V cache. See `cache_type_v`.
+    ///
+    /// # Default
+    ///
+    /// Disabled.
+    pub fn flash_attn(mut self) -> Self {
+        self.local_config.flash_attn = true;
+        self
+    }
+
+    /// Sets the data type of the K cache, e.g. `KvCacheType::Q8_0` to fit a longer context in VRAM.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache_type_k` - The K cache type. Passed to llama-server as `--cache-type-k`.
+    ///
+    /// # Notes
+    ///
+    /// The VRAM estimate used to offload layers accounts for the smaller cache.
+    ///
+    /// # Default
+    ///
+    /// If not set, llama-server's f16 cache is used.
+    pub fn cache_type_k(mut self, cache_type_k: KvCacheType) -> Self {
+        self.local_config.cache_type_k = Some(cache_type_k);
+        self
+    }
+
+    /// Sets the data type of the V cache, e.g. `KvCacheType::Q8_0` to fit a longer context in VRAM.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache_type_v` - The V cache type. Passed to llama-server as `--cache-type-v`.
+    ///
+    /// # Notes
+    ///
+    /// llama.cpp only supports a quantized V cache with flash attention. If `flash_attn` isn't set, loading fails when
+    /// `error_on_config_issue` is set, and otherwise flash attention is enabled with a warning.
+    ///
+    /// # Default
+    ///
+    /// If not set, llama-server's f16 cache is used.
+    pub fn cache_type_v(mut self, cache_type_v: KvCacheType) -> Self {
+        self.local_config.cache_type_v = Some(cache_type_v);
+        self
+    }
+
     pub async fn init(self) -> crate::Result<std::sync::Arc<LlmBackend>> {
         Ok(std::sync::Arc::new(LlmBackend::LlamaCpp(
             LlamaCppBackend::new(self.config, self.local_config, self.llm_loader).await?,
@@ -1,4 +1,4 @@
-use crate::requests::completion::*;
+use crate::{llms::local::llama_cpp::DrySampler, requests::completion::*};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Default, Debug, Deserialize, PartialEq)]
@@ -24,8 +24,9 @@ pub struct LlamaCppCompletionRequest {
     /// The maximum number of [tokens](https://platform.openai.com/tokenizer) to generate in the chat completion.
     ///
     /// The total length of input tokens and generated tokens is limited by the model's context length. [Example Python code](https://github.com/openai/openai-cookbook/blob/main/examples/How_to_count_tokens_with_tiktoken.ipynb) for counting tokens.
+    /// -1 generates until EOS. See [crate::requests::req_components::RequestConfig::until_eos].
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub n_predict: Option<u64>,
+    pub n_predict: Option<i64>,
     /// stop: Specify a JSON array of stopping strings.
     /// These words will not be included in the completion,
     /// so make sure to add them to the prompt for the next iteration (default: []).
@@ -43,9 +44,31 @@ pub struct LlamaCppCompletionRequest {
     /// min: -2.0, max: 2.0, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
     pub presence_penalty: Option<f32>,
+    /// Penalize repeated tokens within the last `repeat_last_n` tokens. 1.0 disables. default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    /// Number of recent tokens to consider for `repeat_penalty`. 0 disables, -1 uses the context size. default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_last_n: Option<i32>,
     /// min: 0.0, max: 1.0, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
+    /// Limit the next token selection to the K most probable tokens.
+    /// Set to 1 when temperature is 0.0 to guarantee greedy decoding.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+    /// DRY sampler penalty strength. 0.0 disables. default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_multiplier: Option<f32>,
+    /// DRY sampler exponential base. default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_base: Option<f32>,
+    /// Repeated sequences up to this length are not penalized by DRY. default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_allowed_length: Option<u32>,
+    /// Strings that reset DRY sequence matching. default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_sequence_breakers: Option<Vec<String>>,
 }
 
 impl LlamaCppCompletionRequest {
@@ -55,30 +78,64 @@ impl LlamaCppCompletionRequest {
         } else {
             None
         };
-        Ok(Self {
-            prompt: req
+        let (prompt, prompt_string) = if let Some(raw_tokens) = &req.raw_tokens {
+            (raw_tokens.clone(), None)
+        } else {
+            let local_prompt = req
                 .prompt
                 .local_prompt()
-                .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?
-                .get_built_prompt_as_tokens()
-                .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?,
-            prompt_string: Some(
-                req.prompt
-                    .local_prompt()
-                    .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?
-                    .get_built_prompt()
+                .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+            (
+                local_prompt
+                    .get_built_prompt_as_tokens()
                     .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?,
-            ),
+                Some(
+                    local_prompt
+                        .get_built_prompt()
+                        .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?,
+                ),
+            )
+        };
+        let mut stop = req.stop_sequences.to_vec();
+        for banned_phrase in &req.config.banned_phrases {
+            if !banned_phrase.is_empty() && !stop.contains(banned_phrase) {
+                stop.push(banned_phrase.clone());
+            }
+        }
+        Ok(Self {
+            prompt,
+            prompt_string,
             grammar: req.grammar_string.clone(),
             cache_prompt,
             logit_bias: req.logit_bias.as_ref().and_then(|lb| lb.get_llama_cpp()),
             frequency_penalty: req.config.frequency_penalty,
             stream: None,
-            n_predict: req.config.actual_request_tokens,
+            n_predict: match req.config.response_token_limit() {
+                Some(limit) => Some(limit as i64),
+                None => Some(-1),
+            },
             presence_penalty: Some(req.config.presence_penalty),
-            stop: Some(req.stop_sequences.to_vec()),
+            repeat_penalty: req.config.repeat_penalty,
+            repeat_last_n: req.config.repeat_last_n,
+            stop: Some(stop),
             temperature: Some(req.config.temperature),
             top_p: req.config.top_p,
+            top_k: if req.config.is_greedy() {
+                Some(1)
+            } else {
+                None
+            },
+            dry_multiplier: None,
+            dry_base: None,
+            dry_allowed_length: None,
+            dry_sequence_breakers: None,
         })
     }
+
+    pub fn set_dry_sampler(&mut self, dry_sampler: &DrySampler) {
+        self.dry_multiplier = Some(dry_sampler.multiplier);
+        self.dry_base = Some(dry_sampler.base);
+        self.dry_allowed_length = Some(dry_sampler.allowed_length);
+        self.dry_sequence_breakers = Some(dry_sampler.sequence_breakers.clone());
+    }
 }
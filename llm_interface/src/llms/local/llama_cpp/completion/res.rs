@@ -33,6 +33,9 @@ impl CompletionResponse {
             finish_reason,
             completion_probabilities: None,
             truncated: res.truncated,
+            content_truncated: false,
+            thinking: None,
+            citations: None,
             generation_settings: GenerationSettings::new_from_llama(&res),
             timing_usage: TimingUsage::new_from_llama(&res, req.start_time),
             token_usage: TokenUsage::new_from_llama(&res),
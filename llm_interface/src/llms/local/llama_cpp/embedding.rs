@@ -0,0 +1,39 @@
+use crate::llms::{
+    api::{client::ApiClient, error::ClientError},
+    local::llama_cpp::LlamaCppConfig,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    input: Vec<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Requests embeddings from llama-server's OpenAI compatible endpoint. Requires the server to be started with `--embeddings`.
+/// The embeddings are returned in the same order as the inputs.
+pub(crate) async fn embedding_request<S: AsRef<str>>(
+    client: &ApiClient<LlamaCppConfig>,
+    inputs: &[S],
+) -> crate::Result<Vec<Vec<f32>>, ClientError> {
+    let request = EmbeddingRequest {
+        input: inputs.iter().map(|input| input.as_ref()).collect(),
+    };
+    let mut response: EmbeddingResponse = client.post("/v1/embeddings", request).await?;
+    response.data.sort_by_key(|data| data.index);
+    Ok(response
+        .data
+        .into_iter()
+        .map(|data| data.embedding)
+        .collect())
+}
@@ -1,20 +1,27 @@
 pub mod builder;
 pub mod completion;
+pub mod embedding;
 pub mod server;
+pub mod tokenize;
 
 use super::LocalLlmConfig;
 use crate::{
     llms::api::{
         client::ApiClient,
-        config::{ApiConfig, ApiConfigTrait},
+        config::{ApiConfig, ApiConfigTrait, PoolConfig},
+        error::ClientError,
     },
+    llms::health::HealthStatus,
     requests::completion::{
         error::CompletionError, request::CompletionRequest, response::CompletionResponse,
     },
 };
-use completion::LlamaCppCompletionRequest;
+use completion::{LlamaCppCompletionRequest, LlamaCppCompletionResponse};
 use llm_devices::logging::LoggingConfig;
-use llm_models::local_model::{gguf::GgufLoader, LocalLlmModel};
+use llm_models::{
+    local_model::{gguf::GgufLoader, LocalLlmModel},
+    tokenizer::{TokenPiece, TokenizeOptions},
+};
 use reqwest::header::{HeaderMap, AUTHORIZATION};
 use secrecy::{ExposeSecret, Secret};
 use server::LlamaCppServer;
@@ -22,10 +29,42 @@ use server::LlamaCppServer;
 pub const LLAMA_CPP_API_HOST: &str = "localhost";
 pub const LLAMA_CPP_API_PORT: &str = "8080";
 
+/// A failed [LlamaCppBackend::reload].
+pub struct ReloadError {
+    pub error: crate::Error,
+    /// The backend with the previous model started again, or `None` if it couldn't be restarted either.
+    pub restored: Option<LlamaCppBackend>,
+}
+
+impl std::fmt::Debug for ReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadError")
+            .field("error", &self.error)
+            .field("restored", &self.restored.is_some())
+            .finish()
+    }
+}
+
+impl std::fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to reload LlamaCppBackend: {}", self.error)
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
 pub struct LlamaCppBackend {
     pub model: LocalLlmModel,
     pub server: LlamaCppServer,
     pub(crate) client: ApiClient<LlamaCppConfig>,
+    /// The local config the backend was created with, before the model was loaded. Used by [LlamaCppBackend::reload].
+    local_config: LocalLlmConfig,
+    /// The loader the model was loaded with. Used to restart it if [LlamaCppBackend::reload] fails. Behind a mutex
+    /// because the loader isn't `Sync`, and the backend is shared between threads.
+    llm_loader: std::sync::Mutex<GgufLoader>,
+    server_system_prompt: std::sync::Mutex<Option<String>>,
+    /// Set once the backend starts shutting down. Completion requests are rejected after that.
+    shutting_down: std::sync::atomic::AtomicBool,
 }
 
 impl LlamaCppBackend {
@@ -38,16 +77,19 @@ impl LlamaCppBackend {
         if let Ok(api_key) = config.api_config.load_api_key() {
             config.api_config.api_key = Some(api_key);
         }
+        let initial_local_config = local_config.clone();
+        let initial_llm_loader = llm_loader.clone();
         local_config.device_config.initialize()?;
         let model = local_config.load_model(llm_loader)?;
 
         let mut server = LlamaCppServer::new(
-            local_config.device_config,
+            local_config,
             &config.api_config.host,
             &config.api_config.port,
-            local_config.inference_ctx_size,
+            config.n_gpu_layers,
+            config.embedding_mode,
         )?;
-        let client: ApiClient<LlamaCppConfig> = ApiClient::new(config);
+        let client: ApiClient<LlamaCppConfig> = ApiClient::new(config)?;
         server.start_server(&client).await?;
         println!(
             "{} with model: {}",
@@ -61,35 +103,257 @@ impl LlamaCppBackend {
             client,
             server,
             model,
+            local_config: initial_local_config,
+            llm_loader: std::sync::Mutex::new(initial_llm_loader),
+            server_system_prompt: std::sync::Mutex::new(None),
+            shutting_down: std::sync::atomic::AtomicBool::new(false),
         })
     }
 
+    /// Shuts down this backend's server and starts a new one with a different model, using the same config.
+    ///
+    /// The device config is initialized again, so the VRAM available for the new model is re-estimated after
+    /// the old model is unloaded. New completion requests to this backend are rejected as soon as the reload starts,
+    /// and requests already in-flight are given time to finish before the old server is stopped.
+    /// The server system prompt carries over.
+    ///
+    /// Both models can't be loaded at once, so if the new model fails to load or start, e.g. because of a bad path or
+    /// running out of memory, the previous model is started again and returned in [ReloadError::restored].
+    ///
+    /// # Arguments
+    ///
+    /// * `llm_loader` - The model to load, e.g. a preset or a GGUF URL.
+    /// * `embedding_mode` - Whether to start the new server in embedding mode.
+    pub async fn reload(
+        &self,
+        llm_loader: GgufLoader,
+        embedding_mode: bool,
+    ) -> crate::Result<Self, ReloadError> {
+        self.shutdown_gracefully()
+            .await
+            .map_err(|error| ReloadError {
+                error,
+                restored: None,
+            })?;
+        let mut config = self.client.config.clone();
+        config.embedding_mode = embedding_mode;
+        let error = match Self::new(config, self.local_config.clone(), llm_loader).await {
+            Ok(backend) => return Ok(self.carry_over(backend)),
+            Err(error) => error,
+        };
+
+        crate::error!(
+            "Failed to reload LlamaCppBackend: {error}. Restarting {}.",
+            self.model.model_base.model_id
+        );
+        let llm_loader = self
+            .llm_loader
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let restored = match Self::new(
+            self.client.config.clone(),
+            self.local_config.clone(),
+            llm_loader,
+        )
+        .await
+        {
+            Ok(backend) => Some(self.carry_over(backend)),
+            Err(e) => {
+                crate::error!("Failed to restart the previous model: {e}");
+                None
+            }
+        };
+        Err(ReloadError { error, restored })
+    }
+
+    fn carry_over(&self, backend: Self) -> Self {
+        *backend.server_system_prompt_guard() = self.server_system_prompt();
+        backend
+    }
+
     pub(crate) async fn completion_request(
         &self,
         request: &CompletionRequest,
     ) -> crate::Result<CompletionResponse, CompletionError> {
-        match self
-            .client
-            .post("/completion", LlamaCppCompletionRequest::new(request)?)
-            .await
-        {
+        let llama_request = self.llama_request(request, false)?;
+        match self.client.post("/completion", llama_request).await {
             Err(e) => Err(CompletionError::ClientError(e)),
             Ok(res) => Ok(CompletionResponse::new_from_llama(request, res)?),
         }
     }
 
+    /// The URL and body [Self::completion_request], or with `stream` [Self::completion_stream_request], would post.
+    pub(crate) fn completion_payload(
+        &self,
+        request: &CompletionRequest,
+        stream: bool,
+    ) -> crate::Result<(String, serde_json::Value), CompletionError> {
+        self.client
+            .dry_run("/completion", self.llama_request(request, stream)?)
+            .map_err(CompletionError::ClientError)
+    }
+
+    /// Sends the request with streaming enabled, calling `on_content` with each piece of text as it's generated.
+    /// The returned response has the stop reason, timings, and token usage, but its content is empty.
+    pub(crate) async fn completion_stream_request(
+        &self,
+        request: &CompletionRequest,
+        on_content: &mut (dyn FnMut(&str) -> crate::Result<()> + Send),
+    ) -> crate::Result<CompletionResponse, CompletionError> {
+        let llama_request = self.llama_request(request, true)?;
+        let mut final_res: Option<LlamaCppCompletionResponse> = None;
+        self.client
+            .post_stream("/completion", llama_request, |event| {
+                if let Some(content) = event.get("content").and_then(|c| c.as_str()) {
+                    if !content.is_empty() {
+                        on_content(content).map_err(|e| ClientError::GenericError {
+                            message: e.to_string(),
+                        })?;
+                    }
+                }
+                // The last event has the same fields as a non-streaming response.
+                if event.get("stop").and_then(|s| s.as_bool()) == Some(true) {
+                    final_res =
+                        Some(serde_json::from_value(event).map_err(ClientError::JSONDeserialize)?);
+                }
+                Ok(())
+            })
+            .await
+            .map_err(CompletionError::ClientError)?;
+        match final_res {
+            Some(mut res) => {
+                res.content.clear();
+                CompletionResponse::new_from_llama(request, res)
+            }
+            None => Err(CompletionError::StopReasonUnsupported(
+                "The stream ended without a final response".to_owned(),
+            )),
+        }
+    }
+
+    fn llama_request(
+        &self,
+        request: &CompletionRequest,
+        stream: bool,
+    ) -> crate::Result<LlamaCppCompletionRequest, CompletionError> {
+        if self.client.config.embedding_mode {
+            return Err(CompletionError::RequestBuilderError(
+                "LlamaCppBackend is running in embedding mode. Completion requests are disabled."
+                    .to_string(),
+            ));
+        }
+        if self.shutting_down.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(CompletionError::RequestBuilderError(
+                "LlamaCppBackend has been shut down or reloaded with another model.".to_string(),
+            ));
+        }
+        let mut llama_request = LlamaCppCompletionRequest::new(request)?;
+        if let Some(dry_sampler) = &self.client.config.dry_sampler {
+            llama_request.set_dry_sampler(dry_sampler);
+        }
+        if stream {
+            llama_request.stream = Some(true);
+        }
+        Ok(llama_request)
+    }
+
+    pub(crate) async fn health_check(&self) -> HealthStatus {
+        if self.shutting_down.load(std::sync::atomic::Ordering::SeqCst) {
+            return HealthStatus::Unreachable(
+                "LlamaCppBackend has been shut down or reloaded with another model.".to_string(),
+            );
+        }
+        self.client.health_check("/health", true).await
+    }
+
+    /// Embeds each input with the loaded model. The embeddings are returned in the same order as the inputs.
+    /// Requires the backend to be running in embedding mode.
+    pub async fn embeddings<S: AsRef<str>>(&self, inputs: &[S]) -> crate::Result<Vec<Vec<f32>>> {
+        if !self.client.config.embedding_mode {
+            crate::bail!("LlamaCppBackend is not running in embedding mode.");
+        }
+        Ok(embedding::embedding_request(&self.client, inputs).await?)
+    }
+
+    /// Tokenizes with llama-server, which uses the tokenizer embedded in the loaded GGUF.
+    pub async fn server_tokenize(
+        &self,
+        content: &str,
+        options: &TokenizeOptions,
+    ) -> crate::Result<Vec<TokenPiece>> {
+        Ok(tokenize::tokenize_request(&self.client, content, options).await?)
+    }
+
+    /// Detokenizes with llama-server, which uses the tokenizer embedded in the loaded GGUF.
+    pub async fn server_detokenize(&self, tokens: &[u32]) -> crate::Result<String> {
+        Ok(tokenize::detokenize_request(&self.client, tokens).await?)
+    }
+
+    /// Sets a system prompt that's used for every request to this backend whose prompt doesn't have its own system message.
+    ///
+    /// llama-server no longer has a global system prompt, so it's applied client side: the system message is prepended
+    /// to the request's prompt before the prompt tokens are counted, and prompt caching is enabled for the request.
+    /// Since every request then starts with the same tokens, each slot evaluates the system prompt once and reuses it
+    /// from its KV cache, so a long persona costs almost nothing per request after the first.
+    ///
+    /// A prompt with its own system message is sent as is, and doesn't benefit from the cached system prompt.
+    /// Requests with raw token prompts are never changed.
+    pub fn set_server_system_prompt<T: AsRef<str>>(&self, system_prompt: T) {
+        *self.server_system_prompt_guard() = Some(system_prompt.as_ref().to_owned());
+    }
+
+    /// Stops prepending the system prompt set with [Self::set_server_system_prompt].
+    pub fn clear_server_system_prompt(&self) {
+        *self.server_system_prompt_guard() = None;
+    }
+
+    /// The system prompt set with [Self::set_server_system_prompt], if any.
+    pub fn server_system_prompt(&self) -> Option<String> {
+        self.server_system_prompt_guard().clone()
+    }
+
+    fn server_system_prompt_guard(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.server_system_prompt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The estimated VRAM left for inference after the model loaded, whether its GPU layers were allocated automatically
+    /// or set with `n_gpu_layers`. See [llm_devices::devices::DeviceConfig::remaining_vram_bytes].
+    pub fn remaining_vram_bytes(&self) -> Option<u64> {
+        self.server.device_config.remaining_vram_bytes
+    }
+
+    /// The number of slots the server was started with, which is how many requests it can process in parallel.
+    pub async fn slot_count(&self) -> crate::Result<usize> {
+        Ok(server::slots::slot_count_request(&self.client).await?)
+    }
+
     pub(crate) fn shutdown(&self) {
         match self.server.shutdown() {
             Ok(_) => (),
             Err(e) => crate::error!("Failed to shutdown server: {}", e),
         }
     }
+
+    pub(crate) async fn shutdown_gracefully(&self) -> crate::Result<()> {
+        self.shutting_down
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        self.server.shutdown_gracefully(&self.client).await
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct LlamaCppConfig {
     pub api_config: ApiConfig,
     pub logging_config: LoggingConfig,
+    /// Forces exactly this many layers onto the GPU(s), bypassing the automatic VRAM based allocation.
+    pub n_gpu_layers: Option<u32>,
+    /// DRY sampler settings sent with every completion request. See [DrySampler].
+    pub dry_sampler: Option<DrySampler>,
+    /// Starts llama-server with `--embeddings` to serve an embedding model. Completion requests are rejected.
+    pub embedding_mode: bool,
 }
 
 impl Default for LlamaCppConfig {
@@ -100,11 +364,17 @@ impl Default for LlamaCppConfig {
                 port: Some(LLAMA_CPP_API_PORT.to_string()),
                 api_key: None,
                 api_key_env_var: "LLAMA_API_KEY".to_string(),
+                custom_headers: Vec::new(),
+                rate_limiter: None,
+                pool_config: Default::default(),
             },
             logging_config: LoggingConfig {
                 logger_name: "llama_cpp".to_string(),
                 ..Default::default()
             },
+            n_gpu_layers: None,
+            dry_sampler: None,
+            embedding_mode: false,
         }
     }
 }
@@ -115,6 +385,44 @@ impl LlamaCppConfig {
     }
 }
 
+/// Settings for llama-server's DRY (Don't Repeat Yourself) sampler.
+///
+/// DRY penalizes tokens that would extend a sequence already present in the context.
+/// The penalty for a token is `multiplier * base ^ (length - allowed_length)`, where `length`
+/// is the length of the repeated sequence it would extend.
+/// Unlike the repeat penalty it targets whole repeated phrases, which makes it very effective
+/// against looping output without hurting common tokens.
+///
+/// See: https://github.com/oobabooga/text-generation-webui/pull/5677
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrySampler {
+    /// Strength of the penalty. 0.0 disables the sampler.
+    pub multiplier: f32,
+    /// Base of the exponential penalty growth.
+    pub base: f32,
+    /// Repeated sequences up to this length are not penalized.
+    pub allowed_length: u32,
+    /// Strings that reset the repeated sequence matching, such as newlines and punctuation.
+    pub sequence_breakers: Vec<String>,
+}
+
+impl Default for DrySampler {
+    /// The values recommended by the DRY author.
+    fn default() -> Self {
+        Self {
+            multiplier: 0.8,
+            base: 1.75,
+            allowed_length: 2,
+            sequence_breakers: vec![
+                "\n".to_string(),
+                ":".to_string(),
+                "\"".to_string(),
+                "*".to_string(),
+            ],
+        }
+    }
+}
+
 impl ApiConfigTrait for LlamaCppConfig {
     fn headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
@@ -143,4 +451,12 @@ impl ApiConfigTrait for LlamaCppConfig {
     fn api_key(&self) -> &Option<Secret<String>> {
         &self.api_config.api_key
     }
+
+    fn custom_headers(&self) -> crate::Result<HeaderMap> {
+        self.api_config.custom_header_map()
+    }
+
+    fn pool_config(&self) -> &PoolConfig {
+        &self.api_config.pool_config
+    }
 }
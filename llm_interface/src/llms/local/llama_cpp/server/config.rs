@@ -49,11 +49,25 @@ impl Default for LlamaCppServerConfig {
 }
 
 impl LlamaCppServerConfig {
-    pub fn new(device_config: &DeviceConfig) -> crate::Result<Self> {
+    pub fn new(device_config: &mut DeviceConfig, n_gpu_layers: Option<u32>) -> crate::Result<Self> {
         match device_config.gpu_count() {
-            0 => Self::new_only_cpu(device_config),
-            1 => Self::new_single_gpu(device_config),
-            _ => Self::new_multiple_gpu(device_config),
+            0 => {
+                if let Some(n_gpu_layers) = n_gpu_layers {
+                    if device_config.error_on_config_issue {
+                        crate::bail!(
+                            "n_gpu_layers set to {} but no GPUs are available",
+                            n_gpu_layers
+                        );
+                    }
+                    crate::warn!(
+                        "n_gpu_layers set to {} but no GPUs are available. Falling back to CPU",
+                        n_gpu_layers
+                    );
+                }
+                Self::new_only_cpu(device_config)
+            }
+            1 => Self::new_single_gpu(device_config, n_gpu_layers),
+            _ => Self::new_multiple_gpu(device_config, n_gpu_layers),
         }
     }
 
@@ -67,9 +81,11 @@ impl LlamaCppServerConfig {
         })
     }
 
-    fn new_single_gpu(device_config: &DeviceConfig) -> crate::Result<Self> {
-        let gpu_devices = device_config.allocate_layers_to_gpus(1, 1)?;
-        let layer_count = gpu_devices.iter().map(|d| d.allocated_layers).sum();
+    fn new_single_gpu(
+        device_config: &mut DeviceConfig,
+        n_gpu_layers: Option<u32>,
+    ) -> crate::Result<Self> {
+        let layer_count = Self::gpu_layer_count(device_config, n_gpu_layers)?;
         Ok(Self {
             threads_batch: Some(ThreadsBatch::new_from_cpu_config(&device_config.cpu_config)),
             split_mode: Some(SplitMode::None),
@@ -79,9 +95,11 @@ impl LlamaCppServerConfig {
         })
     }
 
-    fn new_multiple_gpu(device_config: &DeviceConfig) -> crate::Result<Self> {
-        let gpu_devices = device_config.allocate_layers_to_gpus(1, 1)?;
-        let layer_count = gpu_devices.iter().map(|d| d.allocated_layers).sum();
+    fn new_multiple_gpu(
+        device_config: &mut DeviceConfig,
+        n_gpu_layers: Option<u32>,
+    ) -> crate::Result<Self> {
+        let layer_count = Self::gpu_layer_count(device_config, n_gpu_layers)?;
         Ok(Self {
             threads_batch: Some(ThreadsBatch::new_from_cpu_config(&device_config.cpu_config)),
             split_mode: Some(SplitMode::Layer),
@@ -91,6 +109,25 @@ impl LlamaCppServerConfig {
         })
     }
 
+    // An explicit n_gpu_layers bypasses the VRAM based allocator entirely.
+    fn gpu_layer_count(
+        device_config: &mut DeviceConfig,
+        n_gpu_layers: Option<u32>,
+    ) -> crate::Result<u64> {
+        if let Some(n_gpu_layers) = n_gpu_layers {
+            crate::info!(
+                "Using n_gpu_layers {} set by user. Skipping automatic layer allocation.",
+                n_gpu_layers
+            );
+            if let Err(e) = device_config.estimate_remaining_vram(n_gpu_layers as u64) {
+                crate::warn!("Failed to estimate the remaining VRAM: {e}");
+            }
+            return Ok(n_gpu_layers as u64);
+        }
+        let gpu_devices = device_config.allocate_layers_to_gpus(1, 1)?;
+        Ok(gpu_devices.iter().map(|d| d.allocated_layers).sum())
+    }
+
     pub(crate) fn populate_args(&self, command: &mut std::process::Command) {
         if let Some(threads) = &self.threads {
             command.args(threads.as_arg());
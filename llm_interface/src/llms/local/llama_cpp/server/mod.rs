@@ -1,39 +1,55 @@
 pub mod config;
 pub mod health;
 pub mod models;
+pub mod slots;
 pub mod status;
 
 use std::process::Command;
 
 use llm_devices::{build::get_target_directory, devices::DeviceConfig};
 
-use crate::llms::{api::client::ApiClient, local::llama_cpp::LlamaCppConfig};
+use crate::llms::{
+    api::client::ApiClient,
+    local::{llama_cpp::LlamaCppConfig, KvCacheType, LocalLlmConfig, RopeScaling},
+};
 
 use config::LlamaCppServerConfig;
+use slots::{slots_request, SlotsStatus};
 use status::{server_status, ServerStatus};
 
 const STATUS_CHECK_TIME_MS: u64 = 650;
 const STATUS_RETRY_TIMEOUT_MS: u64 = 200;
 const START_UP_CHECK_TIME_S: u64 = 180;
 const START_UP_RETRY_TIME_S: u64 = 5;
+const SHUTDOWN_IN_FLIGHT_TIMEOUT_S: u64 = 60;
+const SHUTDOWN_TERMINATE_TIMEOUT_S: u64 = 10;
+const SHUTDOWN_RETRY_TIME_MS: u64 = 100;
 
 pub struct LlamaCppServer {
     pub device_config: DeviceConfig,
     pub(crate) server_config: LlamaCppServerConfig,
-    pub server_process: Option<std::process::Child>,
+    /// The running server. Taken once the process has been shut down, so a reused PID is never killed later.
+    pub server_process: std::sync::Mutex<Option<std::process::Child>>,
     pub host: String,
     pub server_http_path: String,
     pub port: Option<String>,
     pub inference_ctx_size: u64,
+    pub rope_scaling: Option<RopeScaling>,
+    pub flash_attn: bool,
+    pub cache_type_k: Option<KvCacheType>,
+    pub cache_type_v: Option<KvCacheType>,
+    pub embedding_mode: bool,
 }
 
 impl LlamaCppServer {
     pub fn new(
-        device_config: DeviceConfig,
+        local_config: LocalLlmConfig,
         host: &str,
         port: &Option<String>,
-        inference_ctx_size: u64,
+        n_gpu_layers: Option<u32>,
+        embedding_mode: bool,
     ) -> crate::Result<Self> {
+        let mut device_config = local_config.device_config;
         let server_http_path = if let Some(port) = port {
             format!("{}:{}", &host, port)
         } else {
@@ -41,12 +57,17 @@ impl LlamaCppServer {
         };
 
         Ok(Self {
-            server_process: None,
-            server_config: LlamaCppServerConfig::new(&device_config)?,
+            server_process: std::sync::Mutex::new(None),
+            server_config: LlamaCppServerConfig::new(&mut device_config, n_gpu_layers)?,
             server_http_path,
             host: host.to_owned(),
             port: port.as_deref().map(|p| p.to_owned()),
-            inference_ctx_size,
+            inference_ctx_size: local_config.inference_ctx_size,
+            rope_scaling: local_config.rope_scaling,
+            flash_attn: local_config.flash_attn,
+            cache_type_k: local_config.cache_type_k,
+            cache_type_v: local_config.cache_type_v,
+            embedding_mode,
             device_config,
         })
     }
@@ -87,7 +108,7 @@ impl LlamaCppServer {
             None
         };
 
-        self.server_process = Some(self.start_server_backend()?);
+        *self.server_process.get_mut().unwrap() = Some(self.start_server_backend()?);
 
         match server_status(
             &self.device_config.local_model_path,
@@ -107,10 +128,8 @@ impl LlamaCppServer {
                 }
                 crate::trace!(
                     "Started LlamaCppServer with process PID: {}",
-                    self.server_process
-                        .as_ref()
+                    self.server_pid()
                         .expect("LlamaCppServer process not created")
-                        .id()
                 );
                 Ok(ServerStatus::RunningRequested)
             }
@@ -155,6 +174,50 @@ impl LlamaCppServer {
         if let Some(port) = &self.port {
             command.arg("--port").arg(port);
         }
+        if self.embedding_mode {
+            command.arg("--embeddings");
+        }
+        if self.flash_attn {
+            command.arg("--flash-attn");
+        }
+        if let Some(cache_type_k) = self.cache_type_k {
+            command.arg("--cache-type-k").arg(cache_type_k.to_string());
+        }
+        if let Some(cache_type_v) = self.cache_type_v {
+            command.arg("--cache-type-v").arg(cache_type_v.to_string());
+        }
+        if let Some(rope_scaling) = &self.rope_scaling {
+            command
+                .arg("--rope-scaling")
+                .arg(rope_scaling.method.to_string())
+                .arg("--rope-scale")
+                .arg(rope_scaling.factor.to_string());
+            if let Some(yarn_orig_ctx) = rope_scaling.yarn_orig_ctx {
+                command
+                    .arg("--yarn-orig-ctx")
+                    .arg(yarn_orig_ctx.to_string());
+            }
+            if let Some(yarn_ext_factor) = rope_scaling.yarn_ext_factor {
+                command
+                    .arg("--yarn-ext-factor")
+                    .arg(yarn_ext_factor.to_string());
+            }
+            if let Some(yarn_attn_factor) = rope_scaling.yarn_attn_factor {
+                command
+                    .arg("--yarn-attn-factor")
+                    .arg(yarn_attn_factor.to_string());
+            }
+            if let Some(yarn_beta_fast) = rope_scaling.yarn_beta_fast {
+                command
+                    .arg("--yarn-beta-fast")
+                    .arg(yarn_beta_fast.to_string());
+            }
+            if let Some(yarn_beta_slow) = rope_scaling.yarn_beta_slow {
+                command
+                    .arg("--yarn-beta-slow")
+                    .arg(yarn_beta_slow.to_string());
+            }
+        }
         crate::info!("Starting LlamaCppServer with command: {:?}", command);
         let process = command.spawn().expect("Failed to start LlamaCppServer");
 
@@ -162,17 +225,17 @@ impl LlamaCppServer {
     }
 
     pub fn shutdown(&self) -> crate::Result<()> {
-        let process = if let Some(server_process) = &self.server_process {
-            server_process
+        let pid = if let Some(pid) = self.server_pid() {
+            pid
         } else {
             crate::error!("LlamaCppServer process not started. No need to shutdown.");
             return Ok(());
         };
 
-        let pid = process.id();
         match kill_server_from_pid(pid) {
             Ok(_) => {
                 crate::info!("LlamaCppServer process with PID: {} killed", pid);
+                self.take_exited_process();
                 Ok(())
             }
             Err(e) => {
@@ -182,6 +245,119 @@ impl LlamaCppServer {
     }
 }
 
+impl LlamaCppServer {
+    /// Waits for in-flight requests to finish, terminates the server, and confirms the process has exited.
+    /// Falls back to a forced kill if the process doesn't exit in time.
+    pub(crate) async fn shutdown_gracefully(
+        &self,
+        client: &ApiClient<LlamaCppConfig>,
+    ) -> crate::Result<()> {
+        let pid = if let Some(pid) = self.server_pid() {
+            pid
+        } else {
+            crate::error!("LlamaCppServer process not started. No need to shutdown.");
+            return Ok(());
+        };
+
+        if tokio::time::timeout(
+            std::time::Duration::from_secs(SHUTDOWN_IN_FLIGHT_TIMEOUT_S),
+            wait_for_idle_slots(client),
+        )
+        .await
+        .is_err()
+        {
+            crate::warn!(
+                "LlamaCppServer requests still in-flight after {}s. Shutting down anyway.",
+                SHUTDOWN_IN_FLIGHT_TIMEOUT_S
+            );
+        }
+
+        send_kill_signal(pid, None)?;
+        if wait_for_pid_exit(pid).await? {
+            crate::info!("LlamaCppServer process with PID: {} terminated", pid);
+            self.take_exited_process();
+            return Ok(());
+        }
+        crate::warn!(
+            "LlamaCppServer process with PID: {} did not exit after {}s. Forcing kill.",
+            pid,
+            SHUTDOWN_TERMINATE_TIMEOUT_S
+        );
+        send_kill_signal(pid, Some("-9"))?;
+        if wait_for_pid_exit(pid).await? {
+            crate::info!("LlamaCppServer process with PID: {} killed", pid);
+            self.take_exited_process();
+            Ok(())
+        } else {
+            crate::bail!("Failed to kill LlamaCppServer process with PID: {}", pid)
+        }
+    }
+}
+
+impl LlamaCppServer {
+    fn server_pid(&self) -> Option<u32> {
+        self.server_process
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|process| process.id())
+    }
+
+    /// Drops the handle of a process that has exited, reaping it, so later shutdowns don't signal its PID.
+    fn take_exited_process(&self) {
+        if let Some(mut process) = self
+            .server_process
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        {
+            let _ = process.try_wait();
+        }
+    }
+}
+
+async fn wait_for_idle_slots(client: &ApiClient<LlamaCppConfig>) {
+    loop {
+        match slots_request(client).await {
+            Ok(SlotsStatus::Idle) => return,
+            Ok(SlotsStatus::Processing(count)) => {
+                crate::info!("Waiting for {} in-flight LlamaCppServer requests", count);
+            }
+            Err(e) => {
+                // The slots endpoint can be disabled, in which case there is nothing to wait on.
+                crate::trace!("Failed to get LlamaCppServer slots: {}", e);
+                return;
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(SHUTDOWN_RETRY_TIME_MS)).await;
+    }
+}
+
+fn send_kill_signal(pid: u32, signal: Option<&str>) -> crate::Result<()> {
+    let mut command = std::process::Command::new("kill");
+    if let Some(signal) = signal {
+        command.arg(signal);
+    }
+    match command.arg(pid.to_string()).status() {
+        Ok(_) => Ok(()),
+        Err(e) => crate::bail!(
+            "std::process::Command::new(\"kill\") failed to kill LlamaCppServer process: {}",
+            e
+        ),
+    }
+}
+
+async fn wait_for_pid_exit(pid: u32) -> crate::Result<bool> {
+    let start = tokio::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(SHUTDOWN_TERMINATE_TIMEOUT_S) {
+        if !server_pid_exists(pid)? {
+            return Ok(true);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(SHUTDOWN_RETRY_TIME_MS)).await;
+    }
+    Ok(false)
+}
+
 pub fn kill_server_from_model(model_id: &str) -> crate::Result<()> {
     let pid = if let Some(pid) = get_server_pid_by_model(model_id)? {
         pid
@@ -344,6 +520,7 @@ pub fn get_all_server_pids() -> crate::Result<Vec<String>> {
     }
 }
 
+/// Best-effort fallback. Drop can't run async work, so prefer shutting down gracefully.
 impl Drop for LlamaCppServer {
     fn drop(&mut self) {
         match self.shutdown() {
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use crate::llms::{
+    api::{client::ApiClient, error::ClientError},
+    local::llama_cpp::LlamaCppConfig,
+};
+
+pub enum SlotsStatus {
+    Idle,
+    Processing(usize),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Slot {
+    pub id: u64,
+    /// Newer llama-server versions report this directly.
+    #[serde(default)]
+    pub is_processing: Option<bool>,
+    /// Older llama-server versions report 0 for idle and 1 for processing.
+    #[serde(default)]
+    pub state: Option<u8>,
+}
+
+impl Slot {
+    fn is_processing(&self) -> bool {
+        self.is_processing.unwrap_or(false) || self.state.is_some_and(|state| state != 0)
+    }
+}
+
+pub(crate) async fn slots_request(
+    client: &ApiClient<LlamaCppConfig>,
+) -> crate::Result<SlotsStatus, ClientError> {
+    let slots = client.get::<Vec<Slot>>("/slots").await?;
+    crate::trace!("slots_request: {:?}", slots);
+    match slots.iter().filter(|slot| slot.is_processing()).count() {
+        0 => Ok(SlotsStatus::Idle),
+        processing => Ok(SlotsStatus::Processing(processing)),
+    }
+}
+
+pub(crate) async fn slot_count_request(
+    client: &ApiClient<LlamaCppConfig>,
+) -> crate::Result<usize, ClientError> {
+    let slots = client.get::<Vec<Slot>>("/slots").await?;
+    Ok(slots.len())
+}
@@ -0,0 +1,82 @@
+use crate::llms::{
+    api::{client::ApiClient, error::ClientError},
+    local::llama_cpp::LlamaCppConfig,
+};
+use llm_models::tokenizer::{TokenPiece, TokenizeOptions};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+struct TokenizeRequest<'a> {
+    content: &'a str,
+    add_special: bool,
+    parse_special: bool,
+    with_pieces: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenizeResponse {
+    tokens: Vec<ResponseToken>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ResponseToken {
+    Id(u32),
+    WithPiece { id: u32, piece: ResponsePiece },
+}
+
+/// Pieces that aren't valid UTF-8 on their own are returned as bytes.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ResponsePiece {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, Serialize)]
+struct DetokenizeRequest<'a> {
+    tokens: &'a [u32],
+}
+
+#[derive(Debug, Deserialize)]
+struct DetokenizeResponse {
+    content: String,
+}
+
+pub(crate) async fn tokenize_request(
+    client: &ApiClient<LlamaCppConfig>,
+    content: &str,
+    options: &TokenizeOptions,
+) -> crate::Result<Vec<TokenPiece>, ClientError> {
+    let request = TokenizeRequest {
+        content,
+        add_special: options.add_special,
+        parse_special: options.parse_special,
+        with_pieces: options.with_pieces,
+    };
+    let response: TokenizeResponse = client.post("/tokenize", request).await?;
+    Ok(response
+        .tokens
+        .into_iter()
+        .map(|token| match token {
+            ResponseToken::Id(id) => TokenPiece { id, piece: None },
+            ResponseToken::WithPiece { id, piece } => TokenPiece {
+                id,
+                piece: Some(match piece {
+                    ResponsePiece::Text(text) => text,
+                    ResponsePiece::Bytes(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+                }),
+            },
+        })
+        .collect())
+}
+
+pub(crate) async fn detokenize_request(
+    client: &ApiClient<LlamaCppConfig>,
+    tokens: &[u32],
+) -> crate::Result<String, ClientError> {
+    let response: DetokenizeResponse = client
+        .post("/detokenize", DetokenizeRequest { tokens })
+        .await?;
+    Ok(response.content)
+}
@@ -9,12 +9,21 @@ pub fn new(
     tx: tokio::sync::mpsc::Sender<Response>,
     id: usize,
 ) -> crate::Result<MistralCompletionRequest, CompletionError> {
+    // mistral.rs only exposes frequency and presence penalties.
+    request.config.log_ignored_repeat_penalty("mistral_rs");
     let sampling_params = SamplingParams {
         temperature: Some(request.config.temperature.into()),
         frequency_penalty: request.config.frequency_penalty,
         presence_penalty: Some(request.config.presence_penalty),
-        max_len: request.config.actual_request_tokens.map(|val| val as usize),
-        top_k: None,
+        max_len: request
+            .config
+            .response_token_limit()
+            .map(|val| val as usize),
+        top_k: if request.config.is_greedy() {
+            Some(1)
+        } else {
+            None
+        },
         top_p: request.config.top_p.map(|val| val as f64),
         min_p: None,
         top_n_logprobs: 0,
@@ -29,6 +29,9 @@ impl CompletionResponse {
             finish_reason,
             completion_probabilities: None,
             truncated: false,
+            content_truncated: false,
+            thinking: None,
+            citations: None,
             generation_settings: GenerationSettings::new_from_mistral(req, &res),
             timing_usage: TimingUsage::new_from_mistral(&res, req.start_time),
             token_usage: TokenUsage::new_from_mistral(&res),
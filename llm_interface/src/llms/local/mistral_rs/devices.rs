@@ -2,7 +2,7 @@ use llm_devices::devices::DeviceConfig;
 use mistralrs::{Device, DeviceLayerMapMetadata, DeviceMapMetadata};
 
 pub fn mistral_rs_device_map(
-    generic_device_map: &DeviceConfig,
+    generic_device_map: &mut DeviceConfig,
 ) -> crate::Result<(Device, DeviceMapMetadata)> {
     match generic_device_map.gpu_count() {
         0 => new_only_cpu(generic_device_map),
@@ -22,7 +22,9 @@ fn new_only_cpu(generic_device_map: &DeviceConfig) -> crate::Result<(Device, Dev
     Ok((Device::Cpu, DeviceMapMetadata::dummy()))
 }
 
-fn new_single_gpu(generic_device_map: &DeviceConfig) -> crate::Result<(Device, DeviceMapMetadata)> {
+fn new_single_gpu(
+    generic_device_map: &mut DeviceConfig,
+) -> crate::Result<(Device, DeviceMapMetadata)> {
     let _gpu_devices = generic_device_map.allocate_layers_to_gpus(0, 0)?;
     let main_gpu = generic_device_map.main_gpu()?;
     // let layer_count = gpu_devices
@@ -44,7 +46,7 @@ fn new_single_gpu(generic_device_map: &DeviceConfig) -> crate::Result<(Device, D
 }
 
 fn new_multiple_gpu(
-    generic_device_map: &DeviceConfig,
+    generic_device_map: &mut DeviceConfig,
 ) -> crate::Result<(Device, DeviceMapMetadata)> {
     let gpu_devices = generic_device_map.allocate_layers_to_gpus(0, 0)?;
     let main_gpu = generic_device_map.main_gpu()?;
@@ -24,9 +24,18 @@ pub struct MistralRsBackend {
 impl MistralRsBackend {
     pub async fn new(mut config: MistralRsConfig, llm_loader: GgufLoader) -> crate::Result<Self> {
         config.logging_config.load_logger()?;
+        if config.local_config.rope_scaling.is_some() {
+            crate::bail!("RoPE scaling is only supported by the llama.cpp backend.");
+        }
+        if config.local_config.flash_attn
+            || config.local_config.cache_type_k.is_some()
+            || config.local_config.cache_type_v.is_some()
+        {
+            crate::bail!("flash_attn and KV cache types are only supported by the llama.cpp backend.");
+        }
         config.local_config.device_config.initialize()?;
         let model = config.local_config.load_model(llm_loader)?;
-        let client = Self::init_from_gguf_local(&model, &config.local_config).await?;
+        let client = Self::init_from_gguf_local(&model, &mut config.local_config).await?;
         Ok(Self {
             client,
             config,
@@ -34,9 +43,14 @@ impl MistralRsBackend {
         })
     }
 
+    /// The estimated VRAM left for inference after the model loaded. See [llm_devices::devices::DeviceConfig::remaining_vram_bytes].
+    pub fn remaining_vram_bytes(&self) -> Option<u64> {
+        self.config.local_config.device_config.remaining_vram_bytes
+    }
+
     async fn init_from_gguf_local(
         model: &LocalLlmModel,
-        local_config: &LocalLlmConfig,
+        local_config: &mut LocalLlmConfig,
     ) -> crate::Result<std::sync::Arc<MistralRs>> {
         std::env::set_var("MISTRALRS_DEBUG", "1");
 
@@ -53,7 +67,7 @@ impl MistralRsBackend {
             MemoryGpuConfig::Utilization(0.9),
         )?);
 
-        let (device, mapper) = mistral_rs_device_map(&local_config.device_config)?;
+        let (device, mapper) = mistral_rs_device_map(&mut local_config.device_config)?;
 
         let directory = model.local_model_path.parent().and_then(|p| p.to_str()).expect("Model path must have a parent directory");
         let filename = model.local_model_path.file_name().and_then(|s| s.to_str()).expect("Model path must have a filename");
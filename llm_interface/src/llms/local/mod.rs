@@ -3,6 +3,7 @@ use llm_devices::devices::CudaConfig;
 use llm_devices::devices::DeviceConfig;
 #[cfg(target_os = "macos")]
 use llm_devices::devices::MetalConfig;
+pub use llm_models::local_model::gguf::memory::KvCacheType;
 use llm_models::local_model::{
     gguf::GgufLoader, metadata::llm::DEFAULT_CONTEXT_LENGTH, LocalLlmModel,
 };
@@ -16,6 +17,18 @@ pub mod mistral_rs;
 pub struct LocalLlmConfig {
     pub batch_size: u64,
     pub inference_ctx_size: u64,
+    /// Whether `inference_ctx_size` was set with [LlmLocalTrait::inference_ctx_size] rather than left at the default.
+    /// Only a size the user asked for fails loading when it doesn't fit the model. The default is clamped to the model's max.
+    pub(crate) inference_ctx_size_is_set: bool,
+    /// Extends the model's context past its trained length. Only supported by llama.cpp. See [RopeScaling].
+    pub rope_scaling: Option<RopeScaling>,
+    /// Starts llama-server with `--flash-attn`. Only supported by llama.cpp.
+    pub flash_attn: bool,
+    /// The K cache data type, passed to llama-server as `--cache-type-k`. Only supported by llama.cpp. `None` uses f16.
+    pub cache_type_k: Option<KvCacheType>,
+    /// The V cache data type, passed to llama-server as `--cache-type-v`. Only supported by llama.cpp. `None` uses f16.
+    /// Quantized V caches require [LocalLlmConfig::flash_attn].
+    pub cache_type_v: Option<KvCacheType>,
     pub device_config: DeviceConfig,
 }
 
@@ -24,14 +37,127 @@ impl Default for LocalLlmConfig {
         Self {
             batch_size: 512,
             inference_ctx_size: DEFAULT_CONTEXT_LENGTH,
+            inference_ctx_size_is_set: false,
+            rope_scaling: None,
+            flash_attn: false,
+            cache_type_k: None,
+            cache_type_v: None,
             device_config: DeviceConfig::default(),
         }
     }
 }
 
+/// How RoPE positions are scaled to fit a longer context. See [RopeScaling].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RopeScalingMethod {
+    /// Linear position interpolation. Cheap, but quality drops quickly past 2x without fine-tuning.
+    Linear,
+    /// YaRN, which interpolates the low frequencies and keeps the high ones. Holds up better at larger factors.
+    Yarn,
+}
+
+impl std::fmt::Display for RopeScalingMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RopeScalingMethod::Linear => write!(f, "linear"),
+            RopeScalingMethod::Yarn => write!(f, "yarn"),
+        }
+    }
+}
+
+/// RoPE scaling settings for running a model at a longer context than it was trained on, e.g. an 8k model at 16k.
+///
+/// Passed to llama-server as `--rope-scaling`, `--rope-scale`, and, for YaRN, the `--yarn-*` arguments.
+/// The usable context becomes the model's trained context times [RopeScaling::factor], and
+/// [LocalLlmConfig::inference_ctx_size] is checked against that instead of the trained context.
+///
+/// Models not fine-tuned for the extended context lose quality, so a warning is logged when scaling is applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RopeScaling {
+    pub method: RopeScalingMethod,
+    /// How many times the trained context to extend to. Must be greater than 1.0.
+    /// llama-server's `--rope-freq-scale` is the inverse of this.
+    pub factor: f32,
+    /// YaRN: the context the model was trained on. Defaults to the GGUF's context length.
+    pub yarn_orig_ctx: Option<u64>,
+    /// YaRN: the extrapolation mix factor, from 0.0 (pure interpolation) to 1.0. llama-server's default is used if `None`.
+    pub yarn_ext_factor: Option<f32>,
+    /// YaRN: scales the attention magnitude. llama-server's default is used if `None`.
+    pub yarn_attn_factor: Option<f32>,
+    /// YaRN: the high correction dimension. Must be greater than `yarn_beta_slow`. llama-server's default is used if `None`.
+    pub yarn_beta_fast: Option<f32>,
+    /// YaRN: the low correction dimension. llama-server's default is used if `None`.
+    pub yarn_beta_slow: Option<f32>,
+}
+
+impl RopeScaling {
+    /// Linear scaling by `factor`.
+    pub fn linear(factor: f32) -> Self {
+        Self {
+            method: RopeScalingMethod::Linear,
+            factor,
+            yarn_orig_ctx: None,
+            yarn_ext_factor: None,
+            yarn_attn_factor: None,
+            yarn_beta_fast: None,
+            yarn_beta_slow: None,
+        }
+    }
+
+    /// YaRN scaling by `factor`, with llama-server's defaults for the other YaRN parameters.
+    pub fn yarn(factor: f32) -> Self {
+        Self {
+            method: RopeScalingMethod::Yarn,
+            ..Self::linear(factor)
+        }
+    }
+
+    /// The context the model can use with this scaling, given the context it was trained on.
+    pub fn scaled_ctx_size(&self, trained_ctx_size: u64) -> u64 {
+        (self.yarn_orig_ctx.unwrap_or(trained_ctx_size) as f64 * self.factor as f64) as u64
+    }
+
+    /// Errors on settings llama-server would reject or that can't extend the context.
+    pub fn validate(&self) -> crate::Result<()> {
+        if !self.factor.is_finite() || self.factor <= 1.0 {
+            crate::bail!(
+                "RoPE scaling factor must be greater than 1.0. factor: {}",
+                self.factor
+            );
+        }
+        if self.method == RopeScalingMethod::Linear
+            && (self.yarn_orig_ctx.is_some()
+                || self.yarn_ext_factor.is_some()
+                || self.yarn_attn_factor.is_some()
+                || self.yarn_beta_fast.is_some()
+                || self.yarn_beta_slow.is_some())
+        {
+            crate::bail!("YaRN parameters are set, but the RoPE scaling method is linear.");
+        }
+        if let Some(ext_factor) = self.yarn_ext_factor {
+            if !(0.0..=1.0).contains(&ext_factor) {
+                crate::bail!(
+                    "yarn_ext_factor must be between 0.0 and 1.0. yarn_ext_factor: {ext_factor}"
+                );
+            }
+        }
+        if let (Some(beta_fast), Some(beta_slow)) = (self.yarn_beta_fast, self.yarn_beta_slow) {
+            if beta_fast <= beta_slow {
+                crate::bail!(
+                    "yarn_beta_fast ({beta_fast}) must be greater than yarn_beta_slow ({beta_slow})."
+                );
+            }
+        }
+        if self.yarn_orig_ctx == Some(0) {
+            crate::bail!("yarn_orig_ctx must be greater than 0.");
+        }
+        Ok(())
+    }
+}
+
 impl LocalLlmConfig {
     pub fn load_model(&mut self, mut llm_loader: GgufLoader) -> crate::Result<LocalLlmModel> {
-        let model = if llm_loader.gguf_local_loader.local_quant_file_path.is_none()
+        let mut model = if llm_loader.gguf_local_loader.local_quant_file_path.is_none()
             || llm_loader.gguf_hf_loader.hf_quant_file_url.is_none()
         {
             self.load_preset_model(llm_loader)?
@@ -39,22 +165,74 @@ impl LocalLlmConfig {
             llm_loader.load()?
         };
 
-        if self.inference_ctx_size > model.model_metadata.context_length() {
-            eprintln!("Given value for ctx_size {} is greater than the model's max {}. Using the models max.", self.inference_ctx_size, model.model_metadata.context_length());
-            self.inference_ctx_size = model.model_metadata.context_length();
+        let trained_ctx_size = model.model_metadata.context_length();
+        let model_max_ctx_size = if let Some(rope_scaling) = &self.rope_scaling {
+            rope_scaling.validate()?;
+            let scaled_ctx_size = rope_scaling.scaled_ctx_size(trained_ctx_size);
+            crate::warn!(
+                "Using {} RoPE scaling to extend the context from {trained_ctx_size} to {scaled_ctx_size} tokens. Output quality degrades for models not fine-tuned on the longer context, especially with linear scaling past 2x.",
+                rope_scaling.method
+            );
+            scaled_ctx_size
+        } else {
+            trained_ctx_size
         };
+        if self.inference_ctx_size > model_max_ctx_size {
+            if !self.inference_ctx_size_is_set {
+                crate::info!(
+                    "The default inference_ctx_size {} is greater than the model's max {model_max_ctx_size}. Using the model's max.",
+                    self.inference_ctx_size
+                );
+            } else if self.device_config.error_on_config_issue {
+                crate::bail!(
+                    "Given value for inference_ctx_size {} is greater than the model's max {model_max_ctx_size}.",
+                    self.inference_ctx_size
+                );
+            } else {
+                crate::warn!(
+                    "Given value for inference_ctx_size {} is greater than the model's max {model_max_ctx_size}. Using the model's max.",
+                    self.inference_ctx_size
+                );
+            }
+            self.inference_ctx_size = model_max_ctx_size;
+        };
+        // The server's context holds both the prompt and the response, so requests are budgeted against it rather than the model's max.
+        // A prompt that doesn't fit then fails with `RequestTokenLimitError::PromptTokensExceeds` instead of being truncated by the server.
+        model.model_base.model_ctx_size = self.inference_ctx_size;
+        model.model_base.inference_ctx_size = self.inference_ctx_size;
+
+        if self
+            .cache_type_v
+            .is_some_and(|cache_type_v| cache_type_v.is_quantized())
+            && !self.flash_attn
+        {
+            if self.device_config.error_on_config_issue {
+                crate::bail!("A quantized V cache requires flash attention.");
+            }
+            crate::warn!("A quantized V cache requires flash attention. Enabling flash_attn.");
+            self.flash_attn = true;
+        }
 
         self.device_config.layer_count = Some(model.model_metadata.layers.count_blocks());
-        self.device_config.average_layer_size_bytes = Some(
-            model
-                .model_metadata
-                .average_layer_size_bytes(self.inference_ctx_size, Some(self.batch_size))?,
-        );
+        self.device_config.average_layer_size_bytes =
+            Some(model.model_metadata.average_layer_size_bytes(
+                self.inference_ctx_size,
+                Some(self.batch_size),
+                Some(self.kv_cache_bits()),
+            )?);
         self.device_config.local_model_path = model.local_model_path.to_string_lossy().to_string();
 
         Ok(model)
     }
 
+    /// The average bits per element of the KV cache, used to estimate its size.
+    pub fn kv_cache_bits(&self) -> f64 {
+        KvCacheType::average_bits(
+            self.cache_type_k.unwrap_or_default(),
+            self.cache_type_v.unwrap_or_default(),
+        )
+    }
+
     fn load_preset_model(&mut self, mut llm_loader: GgufLoader) -> crate::Result<LocalLlmModel> {
         if llm_loader
             .gguf_preset_loader
@@ -79,6 +257,7 @@ impl LocalLlmConfig {
         llm_loader
             .gguf_preset_loader
             .preset_with_available_vram_bytes = Some(self.device_config.available_memory_bytes()?);
+        llm_loader.gguf_preset_loader.preset_with_kv_cache_bits = Some(self.kv_cache_bits());
 
         llm_loader.load()
     }
@@ -227,12 +406,19 @@ pub trait LlmLocalTrait {
     /// # Notes
     ///
     /// This value is set when the model is loaded and cannot be changed after.
-    /// If not set, a default value will be used.
+    /// If not set, a default value will be used, clamped to the model's max context length.
+    ///
+    /// If it's greater than the model's max context length, or the scaled context length with [LocalLlmConfig::rope_scaling], loading fails when `error_on_config_issue` is set.
+    /// Otherwise the model's max is used and a warning is logged.
+    ///
+    /// Requests are budgeted against this context, so a request whose prompt doesn't fit returns
+    /// `RequestTokenLimitError::PromptTokensExceeds` rather than being truncated by the server.
     fn inference_ctx_size(mut self, inference_ctx_size: u64) -> Self
     where
         Self: Sized,
     {
         self.config().inference_ctx_size = inference_ctx_size;
+        self.config().inference_ctx_size_is_set = true;
         self
     }
 
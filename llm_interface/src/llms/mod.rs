@@ -1,12 +1,19 @@
 use crate::requests::{
     completion::{
-        error::CompletionError, request::CompletionRequest, response::CompletionResponse,
+        dry_run::DryRunResult, error::CompletionError, request::CompletionRequest,
+        response::CompletionResponse,
     },
     logit_bias::LogitBias,
+    param_ranges::ParamRanges,
+    req_components::ThinkingTags,
+};
+use llm_models::{
+    api_model::ApiLlmModel,
+    tokenizer::{LlmTokenizer, TokenPiece, TokenizeOptions},
 };
-use llm_models::tokenizer::LlmTokenizer;
 use llm_prompt::{LlmPrompt, PromptTokenizer};
 pub mod api;
+pub mod health;
 #[cfg(any(feature = "llama_cpp_backend", feature = "mistral_rs_backend"))]
 pub mod local;
 
@@ -16,8 +23,21 @@ pub enum LlmBackend {
     #[cfg(feature = "mistral_rs_backend")]
     MistralRs(local::mistral_rs::MistralRsBackend),
     OpenAi(api::openai::OpenAiBackend),
+    AzureOpenAi(api::azure_openai::AzureOpenAiBackend),
     Anthropic(api::anthropic::AnthropicBackend),
     GenericApi(api::generic_openai::GenericApiBackend),
+    Ollama(api::ollama::OllamaBackend),
+}
+
+/// Which of the [CompletionRequest] methods a request is sent with, since it changes the body. See [LlmBackend::dry_run].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RequestMode {
+    /// [CompletionRequest::request]
+    Single,
+    /// [CompletionRequest::request_n] on a backend that generates them all in one request.
+    NumCompletions(u8),
+    /// [CompletionRequest::request_stream]
+    Stream,
 }
 
 impl LlmBackend {
@@ -25,14 +45,257 @@ impl LlmBackend {
         &self,
         request: &CompletionRequest,
     ) -> crate::Result<CompletionResponse, CompletionError> {
-        match self {
+        let circuit_permit = request
+            .circuit_breaker
+            .as_ref()
+            .map(|circuit_breaker| circuit_breaker.before_request())
+            .transpose()?;
+        self.wait_for_rate_limit(request, 1).await;
+        let res = match self {
             #[cfg(feature = "llama_cpp_backend")]
             LlmBackend::LlamaCpp(b) => b.completion_request(request).await,
             #[cfg(feature = "mistral_rs_backend")]
             LlmBackend::MistralRs(b) => b.completion_request(request).await,
             LlmBackend::OpenAi(b) => b.completion_request(request).await,
+            LlmBackend::AzureOpenAi(b) => b.completion_request(request).await,
             LlmBackend::Anthropic(b) => b.completion_request(request).await,
             LlmBackend::GenericApi(b) => b.completion_request(request).await,
+            LlmBackend::Ollama(b) => b.completion_request(request).await,
+        };
+        if let Some(circuit_permit) = circuit_permit {
+            circuit_permit.after_request(&res);
+        }
+        let mut res = res?;
+        if let Some(model) = self.api_model() {
+            res.token_usage.set_cost(model);
+        }
+        Ok(res)
+    }
+
+    pub(crate) fn dry_run(
+        &self,
+        request: &CompletionRequest,
+        mode: RequestMode,
+    ) -> crate::Result<DryRunResult, CompletionError> {
+        let num_completions = match mode {
+            RequestMode::NumCompletions(num_completions) => Some(num_completions),
+            _ => None,
+        };
+        let (url, body) = match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => {
+                b.completion_payload(request, mode == RequestMode::Stream)?
+            }
+            _ if mode == RequestMode::Stream => {
+                return Err(CompletionError::RequestBuilderError(format!(
+                    "The {} backend doesn't support streaming",
+                    self.backend_name()
+                )))
+            }
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(_) => {
+                return Err(CompletionError::RequestBuilderError(
+                    "The mistral.rs backend runs in process, so it has no request to dry run"
+                        .to_owned(),
+                ))
+            }
+            LlmBackend::OpenAi(b) => b.completion_payload(request, num_completions)?,
+            LlmBackend::AzureOpenAi(b) => b.completion_payload(request, num_completions)?,
+            LlmBackend::Anthropic(b) => b.completion_payload(request)?,
+            LlmBackend::GenericApi(b) => b.completion_payload(request)?,
+            LlmBackend::Ollama(b) => b.completion_payload(request)?,
+        };
+        Ok(DryRunResult {
+            backend: self.backend_name().to_owned(),
+            model_id: self.model_id().to_owned(),
+            url,
+            body,
+        })
+    }
+
+    /// Checks whether the backend can take requests, e.g. for a readiness or liveness probe. Sends one cheap request, which
+    /// isn't retried and times out after [health::HEALTH_CHECK_TIMEOUT].
+    ///
+    /// llama.cpp is checked with its `/health` endpoint, which reports [health::HealthStatus::Loading] while the model loads.
+    /// Ollama lists its models, and the other API backends request their models list, which checks the API key without
+    /// generating anything. Servers without a models list are checked with a GET to the chat endpoint, which answers 405
+    /// Method Not Allowed when the base url is right. mistral.rs runs in process, so it's always ready.
+    pub async fn health_check(&self) -> health::HealthStatus {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => b.health_check().await,
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(_) => health::HealthStatus::Ready,
+            LlmBackend::OpenAi(b) => b.client.health_check("/models", false).await,
+            LlmBackend::AzureOpenAi(b) => b.health_check().await,
+            LlmBackend::Anthropic(b) => b.client.health_check("/models", false).await,
+            LlmBackend::GenericApi(b) => b.health_check().await,
+            LlmBackend::Ollama(b) => b.client.health_check("/api/tags", false).await,
+        }
+    }
+
+    /// The thinking tags requests to the backend split responses on by default. See [crate::requests::req_components::RequestConfig::thinking_tags].
+    pub fn default_thinking_tags(&self) -> Option<ThinkingTags> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) if b.model.chat_template.has_thinking_tags() => {
+                Some(ThinkingTags::default())
+            }
+            // Perplexity's reasoning models return their thinking in the content.
+            LlmBackend::GenericApi(b)
+                if b.model.model_base.model_id.starts_with("sonar-reasoning")
+                    || b.model.model_base.model_id == "sonar-deep-research" =>
+            {
+                Some(ThinkingTags::default())
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the backend searches the web before answering and returns the sources in [CompletionResponse::citations].
+    /// True for Perplexity's sonar models and OpenAI's search models.
+    pub fn supports_search(&self) -> bool {
+        match self {
+            LlmBackend::OpenAi(b) => {
+                api::openai::completion::is_openai_search_model(&b.model.model_base.model_id)
+            }
+            LlmBackend::GenericApi(b) => b.model.model_base.model_id.starts_with("sonar"),
+            _ => false,
+        }
+    }
+
+    /// The sampling parameters the backend accepts and their ranges, e.g. for rendering sliders or validating a config before sending it.
+    pub fn param_ranges(&self) -> ParamRanges {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(_) => ParamRanges::LOCAL,
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(_) => ParamRanges::OPENAI,
+            LlmBackend::OpenAi(b)
+                if api::openai::completion::is_openai_search_model(
+                    &b.model.model_base.model_id,
+                ) =>
+            {
+                ParamRanges::NONE
+            }
+            LlmBackend::OpenAi(_) | LlmBackend::AzureOpenAi(_) | LlmBackend::GenericApi(_) => {
+                ParamRanges::OPENAI
+            }
+            LlmBackend::Anthropic(_) => ParamRanges::ANTHROPIC,
+            LlmBackend::Ollama(_) => ParamRanges::LOCAL,
+        }
+    }
+
+    /// Whether the backend can generate several completions in a single request. See [CompletionRequest::request_n].
+    pub fn supports_num_completions(&self) -> bool {
+        matches!(self, LlmBackend::OpenAi(_) | LlmBackend::AzureOpenAi(_))
+    }
+
+    /// Whether the backend can stream the response as it's generated. See [CompletionRequest::request_stream].
+    pub fn supports_streaming(&self) -> bool {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(_) => true,
+            _ => false,
+        }
+    }
+
+    #[cfg_attr(not(feature = "llama_cpp_backend"), allow(unused_variables))]
+    pub(crate) async fn completion_stream_request(
+        &self,
+        request: &CompletionRequest,
+        on_content: &mut (dyn FnMut(&str) -> crate::Result<()> + Send),
+    ) -> crate::Result<CompletionResponse, CompletionError> {
+        let circuit_permit = request
+            .circuit_breaker
+            .as_ref()
+            .map(|circuit_breaker| circuit_breaker.before_request())
+            .transpose()?;
+        let res = match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => b.completion_stream_request(request, on_content).await,
+            _ => Err(CompletionError::RequestBuilderError(format!(
+                "The {} backend doesn't support streaming",
+                self.backend_name()
+            ))),
+        };
+        if let Some(circuit_permit) = circuit_permit {
+            circuit_permit.after_request(&res);
+        }
+        res
+    }
+
+    pub(crate) async fn completion_request_n(
+        &self,
+        request: &CompletionRequest,
+        num_completions: u8,
+    ) -> crate::Result<Vec<CompletionResponse>, CompletionError> {
+        let circuit_permit = request
+            .circuit_breaker
+            .as_ref()
+            .map(|circuit_breaker| circuit_breaker.before_request())
+            .transpose()?;
+        self.wait_for_rate_limit(request, num_completions).await;
+        let responses = match self {
+            LlmBackend::OpenAi(b) => b.completion_request_n(request, num_completions).await,
+            LlmBackend::AzureOpenAi(b) => b.completion_request_n(request, num_completions).await,
+            _ => Err(CompletionError::RequestBuilderError(format!(
+                "The {} backend doesn't support multiple completions per request",
+                self.backend_name()
+            ))),
+        };
+        if let Some(circuit_permit) = circuit_permit {
+            circuit_permit.after_request(&responses);
+        }
+        let mut responses = responses?;
+        if let Some(model) = self.api_model() {
+            for res in &mut responses {
+                res.token_usage.set_cost(model);
+            }
+        }
+        Ok(responses)
+    }
+
+    /// The rate limiter of API backends configured with [api::config::LlmApiConfigTrait::rate_limit].
+    fn rate_limiter(&self) -> Option<&api::rate_limit::RateLimiter> {
+        let api_config = match self {
+            LlmBackend::OpenAi(b) => &b.client.config.api_config,
+            LlmBackend::AzureOpenAi(b) => &b.client.config.api_config,
+            LlmBackend::Anthropic(b) => &b.client.config.api_config,
+            LlmBackend::GenericApi(b) => &b.client.config.api_config,
+            LlmBackend::Ollama(b) => &b.client.config.api_config,
+            #[allow(unreachable_patterns)]
+            _ => return None,
+        };
+        api_config.rate_limiter.as_deref()
+    }
+
+    /// Waits until the request fits in the backend's rate limits, if it has any.
+    /// The tokens are estimated from the prompt plus the response token limit of each completion.
+    async fn wait_for_rate_limit(&self, request: &CompletionRequest, num_completions: u8) {
+        let Some(rate_limiter) = self.rate_limiter() else {
+            return;
+        };
+        let prompt_tokens = self.get_total_prompt_tokens(&request.prompt).unwrap_or(0);
+        let response_tokens = request.config.response_token_limit().unwrap_or(0);
+        rate_limiter
+            .acquire(prompt_tokens + response_tokens * num_completions as u64)
+            .await;
+    }
+
+    /// The model of API backends, which carries the pricing used to estimate request costs. `None` for local backends,
+    /// including Ollama, which serves models on your own hardware.
+    pub fn api_model(&self) -> Option<&ApiLlmModel> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(_) => None,
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(_) => None,
+            LlmBackend::OpenAi(b) => Some(&b.model),
+            LlmBackend::AzureOpenAi(b) => Some(&b.model),
+            LlmBackend::Anthropic(b) => Some(&b.model),
+            LlmBackend::GenericApi(b) => Some(&b.model),
+            LlmBackend::Ollama(_) => None,
         }
     }
 
@@ -81,6 +344,11 @@ impl LlmBackend {
                 Some(b.model.tokens_per_message),
                 b.model.tokens_per_name,
             ),
+            LlmBackend::AzureOpenAi(b) => LlmPrompt::new_api_prompt(
+                self.prompt_tokenizer(),
+                Some(b.model.tokens_per_message),
+                b.model.tokens_per_name,
+            ),
             LlmBackend::Anthropic(b) => LlmPrompt::new_api_prompt(
                 self.prompt_tokenizer(),
                 Some(b.model.tokens_per_message),
@@ -91,6 +359,11 @@ impl LlmBackend {
                 Some(b.model.tokens_per_message),
                 b.model.tokens_per_name,
             ),
+            LlmBackend::Ollama(b) => LlmPrompt::new_api_prompt(
+                self.prompt_tokenizer(),
+                Some(b.model.tokens_per_message),
+                b.model.tokens_per_name,
+            ),
         }
     }
 
@@ -101,8 +374,25 @@ impl LlmBackend {
             #[cfg(feature = "mistral_rs_backend")]
             LlmBackend::MistralRs(_) => prompt.get_total_prompt_tokens(),
             LlmBackend::OpenAi(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
+            LlmBackend::AzureOpenAi(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
             LlmBackend::Anthropic(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
             LlmBackend::GenericApi(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
+            LlmBackend::Ollama(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
+        }
+    }
+
+    /// A short name for the backend, used in logs and tracing spans.
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(_) => "llama_cpp",
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(_) => "mistral_rs",
+            LlmBackend::OpenAi(_) => "openai",
+            LlmBackend::AzureOpenAi(_) => "azure_openai",
+            LlmBackend::Anthropic(_) => "anthropic",
+            LlmBackend::GenericApi(_) => "generic_api",
+            LlmBackend::Ollama(_) => "ollama",
         }
     }
 
@@ -113,8 +403,10 @@ impl LlmBackend {
             #[cfg(feature = "mistral_rs_backend")]
             LlmBackend::MistralRs(b) => &b.model.model_base.model_id,
             LlmBackend::OpenAi(b) => &b.model.model_base.model_id,
+            LlmBackend::AzureOpenAi(b) => &b.model.model_base.model_id,
             LlmBackend::Anthropic(b) => &b.model.model_base.model_id,
             LlmBackend::GenericApi(b) => &b.model.model_base.model_id,
+            LlmBackend::Ollama(b) => &b.model.model_base.model_id,
         }
     }
 
@@ -125,8 +417,10 @@ impl LlmBackend {
             #[cfg(feature = "mistral_rs_backend")]
             LlmBackend::MistralRs(b) => b.model.model_base.model_ctx_size,
             LlmBackend::OpenAi(b) => b.model.model_base.model_ctx_size,
+            LlmBackend::AzureOpenAi(b) => b.model.model_base.model_ctx_size,
             LlmBackend::Anthropic(b) => b.model.model_base.model_ctx_size,
             LlmBackend::GenericApi(b) => b.model.model_base.model_ctx_size,
+            LlmBackend::Ollama(b) => b.model.model_base.model_ctx_size,
         }
     }
 
@@ -137,8 +431,22 @@ impl LlmBackend {
             #[cfg(feature = "mistral_rs_backend")]
             LlmBackend::MistralRs(b) => b.model.model_base.inference_ctx_size,
             LlmBackend::OpenAi(b) => b.model.model_base.inference_ctx_size,
+            LlmBackend::AzureOpenAi(b) => b.model.model_base.inference_ctx_size,
             LlmBackend::Anthropic(b) => b.model.model_base.inference_ctx_size,
             LlmBackend::GenericApi(b) => b.model.model_base.inference_ctx_size,
+            LlmBackend::Ollama(b) => b.model.model_base.inference_ctx_size,
+        }
+    }
+
+    /// The estimated VRAM left for the context and inference after a local model loaded. `None` for API backends,
+    /// and for local backends running without a GPU.
+    pub fn remaining_vram_bytes(&self) -> Option<u64> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => b.remaining_vram_bytes(),
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(b) => b.remaining_vram_bytes(),
+            _ => None,
         }
     }
 
@@ -149,8 +457,34 @@ impl LlmBackend {
             #[cfg(feature = "mistral_rs_backend")]
             LlmBackend::MistralRs(b) => &b.model.model_base.tokenizer,
             LlmBackend::OpenAi(b) => &b.model.model_base.tokenizer,
+            LlmBackend::AzureOpenAi(b) => &b.model.model_base.tokenizer,
             LlmBackend::Anthropic(b) => &b.model.model_base.tokenizer,
             LlmBackend::GenericApi(b) => &b.model.model_base.tokenizer,
+            LlmBackend::Ollama(b) => &b.model.model_base.tokenizer,
+        }
+    }
+
+    /// Tokenizes with the model's server when it exposes a tokenizer endpoint (llama.cpp),
+    /// which matches the loaded model exactly. Other backends use the local tokenizer from [Self::tokenizer].
+    pub async fn server_tokenize(
+        &self,
+        content: &str,
+        options: &TokenizeOptions,
+    ) -> crate::Result<Vec<TokenPiece>> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => b.server_tokenize(content, options).await,
+            _ => self.tokenizer().tokenize_with_options(content, options),
+        }
+    }
+
+    /// Detokenizes with the model's server when it exposes a detokenizer endpoint (llama.cpp).
+    /// Other backends use the local tokenizer from [Self::tokenizer].
+    pub async fn server_detokenize(&self, tokens: &[u32]) -> crate::Result<String> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => b.server_detokenize(tokens).await,
+            _ => self.tokenizer().detokenize_many(tokens),
         }
     }
 
@@ -164,10 +498,14 @@ impl LlmBackend {
                 as std::sync::Arc<dyn PromptTokenizer>,
             LlmBackend::OpenAi(b) => std::sync::Arc::clone(&b.model.model_base.tokenizer)
                 as std::sync::Arc<dyn PromptTokenizer>,
+            LlmBackend::AzureOpenAi(b) => std::sync::Arc::clone(&b.model.model_base.tokenizer)
+                as std::sync::Arc<dyn PromptTokenizer>,
             LlmBackend::Anthropic(b) => std::sync::Arc::clone(&b.model.model_base.tokenizer)
                 as std::sync::Arc<dyn PromptTokenizer>,
             LlmBackend::GenericApi(b) => std::sync::Arc::clone(&b.model.model_base.tokenizer)
                 as std::sync::Arc<dyn PromptTokenizer>,
+            LlmBackend::Ollama(b) => std::sync::Arc::clone(&b.model.model_base.tokenizer)
+                as std::sync::Arc<dyn PromptTokenizer>,
         }
     }
 
@@ -179,8 +517,10 @@ impl LlmBackend {
                 #[cfg(feature = "mistral_rs_backend")]
                 LlmBackend::MistralRs(_) => logit_bias.build_llama(self.tokenizer())?,
                 LlmBackend::OpenAi(_) => logit_bias.build_openai(self.tokenizer())?,
+                LlmBackend::AzureOpenAi(_) => logit_bias.build_openai(self.tokenizer())?,
                 LlmBackend::Anthropic(_) => unreachable!("Anthropic does not support logit bias"),
                 LlmBackend::GenericApi(_) => logit_bias.build_openai(self.tokenizer())?,
+                LlmBackend::Ollama(_) => crate::bail!("Ollama does not support logit bias"),
             };
         }
         Ok(())
@@ -229,6 +569,13 @@ impl LlmBackend {
         }
     }
 
+    pub fn azure_openai(&self) -> crate::Result<&api::azure_openai::AzureOpenAiBackend> {
+        match self {
+            LlmBackend::AzureOpenAi(b) => Ok(b),
+            _ => crate::bail!("Backend is not azure_openai"),
+        }
+    }
+
     pub fn anthropic(&self) -> crate::Result<&api::anthropic::AnthropicBackend> {
         match self {
             LlmBackend::Anthropic(b) => Ok(b),
@@ -243,6 +590,44 @@ impl LlmBackend {
         }
     }
 
+    /// The number of requests the backend can process in parallel, or `None` if there is no known limit.
+    /// For llama.cpp this is the server's slot count. If the slots endpoint is unavailable, requests are assumed to run one at a time.
+    pub async fn max_concurrent_requests(&self) -> Option<usize> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => match b.slot_count().await {
+                Ok(count) => Some(count.max(1)),
+                Err(e) => {
+                    crate::trace!("Failed to get LlamaCppServer slot count: {}", e);
+                    Some(1)
+                }
+            },
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(_) => Some(1),
+            LlmBackend::OpenAi(_) => None,
+            LlmBackend::AzureOpenAi(_) => None,
+            LlmBackend::Anthropic(_) => None,
+            LlmBackend::GenericApi(_) => None,
+            LlmBackend::Ollama(_) => None,
+        }
+    }
+
+    /// Embeds each input with the backend's model. Currently only supported by llama.cpp running an embedding model.
+    pub async fn embeddings<S: AsRef<str>>(&self, inputs: &[S]) -> crate::Result<Vec<Vec<f32>>> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => b.embeddings(inputs).await,
+            _ => crate::bail!("Embeddings are not supported by this backend"),
+        }
+    }
+
+    pub fn ollama(&self) -> crate::Result<&api::ollama::OllamaBackend> {
+        match self {
+            LlmBackend::Ollama(b) => Ok(b),
+            _ => crate::bail!("Backend is not ollama"),
+        }
+    }
+
     pub fn shutdown(&self) {
         match self {
             #[cfg(feature = "llama_cpp_backend")]
@@ -250,8 +635,25 @@ impl LlmBackend {
             #[cfg(feature = "mistral_rs_backend")]
             LlmBackend::MistralRs(_) => (),
             LlmBackend::OpenAi(_) => (),
+            LlmBackend::AzureOpenAi(_) => (),
             LlmBackend::Anthropic(_) => (),
             LlmBackend::GenericApi(_) => (),
+            LlmBackend::Ollama(_) => (),
+        }
+    }
+
+    /// Waits for in-flight requests to finish before shutting down, and confirms the server process has exited.
+    pub async fn shutdown_gracefully(&self) -> crate::Result<()> {
+        match self {
+            #[cfg(feature = "llama_cpp_backend")]
+            LlmBackend::LlamaCpp(b) => b.shutdown_gracefully().await,
+            #[cfg(feature = "mistral_rs_backend")]
+            LlmBackend::MistralRs(_) => Ok(()),
+            LlmBackend::OpenAi(_) => Ok(()),
+            LlmBackend::AzureOpenAi(_) => Ok(()),
+            LlmBackend::Anthropic(_) => Ok(()),
+            LlmBackend::GenericApi(_) => Ok(()),
+            LlmBackend::Ollama(_) => Ok(()),
         }
     }
 }
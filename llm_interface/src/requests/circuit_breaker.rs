@@ -0,0 +1,129 @@
+use crate::requests::completion::CompletionError;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Stops sending requests to a backend that keeps failing, so a down server or an API outage fails fast instead of
+/// every request waiting to time out.
+///
+/// After `failure_threshold` consecutive failures the circuit opens, and requests fail immediately with
+/// [CompletionError::CircuitOpen] until `cooldown` has passed. Then it's half-open: a single trial request is sent.
+/// If it succeeds the circuit closes, and if it fails the circuit opens for another cooldown.
+///
+/// Only failures to get a response from the backend count, i.e. a [CompletionError::ClientError] that
+/// [crate::llms::api::error::ClientError::is_backend_failure], and [CompletionError::LocalClientError]. A rejected request,
+/// e.g. a 400 or a bad API key, doesn't, so one caller's bad requests can't open the circuit for everyone. Any response,
+/// even one that's retried, shows the backend is up and closes the circuit. Other errors don't change it.
+///
+/// Set it on a [super::completion::CompletionRequest] with [super::completion::CompletionRequest::circuit_breaker].
+/// Requests cloned from a base request share it.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Debug, Default)]
+struct CircuitState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(CircuitState::default()),
+        }
+    }
+
+    /// True while requests are being short-circuited, including while a half-open trial request is in flight.
+    pub fn is_open(&self) -> bool {
+        self.state.lock().unwrap().opened_at.is_some()
+    }
+
+    /// Returns an error if the circuit is open. Once the cooldown has passed, lets a single trial request through.
+    ///
+    /// The returned permit records the outcome with [CircuitPermit::after_request]. If it's dropped first, e.g. because
+    /// the request timed out or was cancelled, a trial request gives up its turn so the next request can be the trial.
+    pub(crate) fn before_request(&self) -> crate::Result<CircuitPermit<'_>, CompletionError> {
+        let mut state = self.state.lock().unwrap();
+        let Some(opened_at) = state.opened_at else {
+            return Ok(CircuitPermit::new(self, false));
+        };
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.cooldown || state.trial_in_flight {
+            return Err(CompletionError::CircuitOpen {
+                retry_in: self.cooldown.saturating_sub(elapsed),
+            });
+        }
+        crate::info!("Circuit half-open. Sending a trial request.");
+        state.trial_in_flight = true;
+        Ok(CircuitPermit::new(self, true))
+    }
+
+    fn record<T>(&self, res: &crate::Result<T, CompletionError>, is_trial: bool) {
+        let mut state = self.state.lock().unwrap();
+        if is_trial {
+            state.trial_in_flight = false;
+        }
+        match res {
+            Err(CompletionError::ClientError(e)) if !e.is_backend_failure() => (),
+            Err(CompletionError::ClientError(_) | CompletionError::LocalClientError(_)) => {
+                state.consecutive_failures += 1;
+                if state.opened_at.is_some() || state.consecutive_failures >= self.failure_threshold
+                {
+                    crate::warn!(
+                        "Circuit opened after {} consecutive failures. Short-circuiting requests for {:?}.",
+                        state.consecutive_failures,
+                        self.cooldown
+                    );
+                    state.opened_at = Some(Instant::now());
+                }
+            }
+            Ok(_) => {
+                if state.opened_at.is_some() {
+                    crate::info!("Circuit closed. The backend has recovered.");
+                }
+                *state = CircuitState::default();
+            }
+            Err(_) => (),
+        }
+    }
+}
+
+/// A request let through by [CircuitBreaker::before_request].
+pub(crate) struct CircuitPermit<'a> {
+    circuit_breaker: &'a CircuitBreaker,
+    is_trial: bool,
+    recorded: bool,
+}
+
+impl<'a> CircuitPermit<'a> {
+    fn new(circuit_breaker: &'a CircuitBreaker, is_trial: bool) -> Self {
+        Self {
+            circuit_breaker,
+            is_trial,
+            recorded: false,
+        }
+    }
+
+    /// Records the outcome of the request.
+    pub(crate) fn after_request<T>(mut self, res: &crate::Result<T, CompletionError>) {
+        self.recorded = true;
+        self.circuit_breaker.record(res, self.is_trial);
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if self.is_trial && !self.recorded {
+            crate::info!("Circuit trial request was dropped. The next request will be the trial.");
+            self.circuit_breaker.state.lock().unwrap().trial_in_flight = false;
+        }
+    }
+}
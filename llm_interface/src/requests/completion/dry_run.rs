@@ -0,0 +1,41 @@
+/// The request a [super::CompletionRequest] would have sent, from [super::CompletionRequest::dry_run_request].
+///
+/// Headers aren't included, since they carry the API key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DryRunResult {
+    pub backend: String,
+    pub model_id: String,
+    /// The URL the request would have been posted to.
+    pub url: String,
+    /// The JSON body in the backend's own format, e.g. OpenAI's `messages` or llama.cpp's `prompt`.
+    pub body: serde_json::Value,
+}
+
+impl DryRunResult {
+    /// The URL and body as pretty printed JSON with sorted keys, for snapshot tests with e.g. `insta`.
+    ///
+    /// Building the same request with the same inputs gives the same string, so a changed snapshot means the prompt,
+    /// grammar, logit bias, or another part of the request changed.
+    pub fn to_snapshot(&self) -> String {
+        let snapshot = serde_json::json!({
+            "url": self.url,
+            "body": self.body,
+        });
+        serde_json::to_string_pretty(&snapshot).expect("A JSON value always serializes")
+    }
+}
+
+impl std::fmt::Display for DryRunResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "DryRunResult:")?;
+        writeln!(f, "    backend: {}", self.backend)?;
+        writeln!(f, "    model_id: {}", self.model_id)?;
+        writeln!(f, "    url: {}", self.url)?;
+        writeln!(
+            f,
+            "    body: {}",
+            serde_json::to_string_pretty(&self.body).unwrap_or_default()
+        )
+    }
+}
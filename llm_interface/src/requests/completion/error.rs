@@ -11,6 +11,20 @@ pub enum CompletionError {
     RequestTokenLimitError(#[from] llm_prompt::RequestTokenLimitError),
     #[error("StopReasonUnsupported: {0}")]
     StopReasonUnsupported(String),
+    /// The model refused the request, or the provider's content filter stopped the response. The content isn't an answer, so it isn't returned.
+    #[error("ContentFiltered: {0}")]
+    ContentFiltered(String),
+    /// The backend's [crate::requests::circuit_breaker::CircuitBreaker] is open after repeated failures, so the request wasn't sent.
+    #[error("CircuitOpen: The backend is failing, so requests are paused. Retry in {retry_in:?}.")]
+    CircuitOpen { retry_in: std::time::Duration },
+    /// [super::CompletionRequest::dry_run] is set, so the request was built but not sent. Workflows stop at their first
+    /// request and return this in their error, so set it on a workflow's base request to snapshot what it sends.
+    #[error("DryRun: The request wasn't sent. {0}")]
+    DryRun(Box<super::DryRunResult>),
+    #[error(
+        "EmptyResponse: Response content was empty or whitespace after {retry_count} attempts"
+    )]
+    EmptyResponse { retry_count: u8 },
     #[error("ExceededRetryCount")]
     ExceededRetryCount {
         message: String,
@@ -29,4 +43,6 @@ pub enum CompletionError {
         "NonMatchingStopSequence: One of the sequences is required, but response's stop sequence was: {0}."
     )]
     NonMatchingStopSequence(String),
+    #[error("BannedPhrase: Response contained the banned phrase: {0}.")]
+    BannedPhrase(String),
 }
@@ -1,8 +1,12 @@
+pub mod dry_run;
 pub mod error;
 pub mod request;
 pub mod response;
 
-pub use super::res_components::{GenerationSettings, TimingUsage, TokenUsage};
+pub use super::res_components::{
+    AggregateUsage, Citation, GenerationSettings, TimingUsage, TokenUsage,
+};
+pub use dry_run::DryRunResult;
 pub use error::CompletionError;
 pub use request::CompletionRequest;
 pub use response::{CompletionFinishReason, CompletionResponse};
@@ -1,12 +1,18 @@
-use super::{error::CompletionError, response::CompletionResponse};
+use super::{dry_run::DryRunResult, error::CompletionError, response::CompletionResponse};
 use crate::{
-    llms::LlmBackend,
+    llms::{LlmBackend, RequestMode},
     requests::{
-        completion::response::CompletionFinishReason, logit_bias::LogitBias,
-        req_components::RequestConfig, stop_sequence::StopSequences,
+        circuit_breaker::CircuitBreaker,
+        completion::response::CompletionFinishReason,
+        logit_bias::LogitBias,
+        observer::RequestObserver,
+        req_components::{RequestConfig, EMPTY_RESPONSE_TEMPERATURE_STEP},
+        res_components::AggregateUsage,
+        stop_sequence::StopSequences,
     },
 };
 use llm_prompt::LlmPrompt;
+use tracing::Instrument;
 
 pub struct CompletionRequest {
     pub start_time: std::time::Instant,
@@ -14,63 +20,473 @@ pub struct CompletionRequest {
     pub grammar_string: Option<String>,
     pub logit_bias: Option<LogitBias>,
     pub prompt: LlmPrompt,
+    /// Token ids sent as the prompt in place of the built prompt. See [CompletionRequest::set_raw_tokens].
+    pub raw_tokens: Option<Vec<u32>>,
+    /// Partial assistant text the response continues from. See [CompletionRequest::set_assistant_prefill].
+    pub assistant_prefill: Option<String>,
     pub config: RequestConfig,
     pub backend: std::sync::Arc<LlmBackend>,
     pub llm_interface_errors: Vec<CompletionError>,
+    /// Totals of every response received since the request was created or reset, including responses that were retried.
+    /// Workflows use this to report the usage of all their internal requests.
+    pub usage: AggregateUsage,
+    /// Called for each request sent to the backend. See [RequestObserver].
+    pub observer: Option<std::sync::Arc<dyn RequestObserver>>,
+    /// Short-circuits requests while the backend keeps failing. See [CircuitBreaker].
+    pub circuit_breaker: Option<std::sync::Arc<CircuitBreaker>>,
+    /// Builds the request without sending it. See [CompletionRequest::dry_run].
+    pub dry_run: bool,
+    /// The server system prompt message prepended for the last request, and the `cache_prompt` it replaced.
+    /// Removed before the next request so the caller's prompt isn't changed for good.
+    #[cfg(feature = "llama_cpp_backend")]
+    applied_server_system_prompt: Option<(std::sync::Arc<llm_prompt::PromptMessage>, bool)>,
 }
 
 impl Clone for CompletionRequest {
     fn clone(&self) -> Self {
+        let prompt = self.prompt.clone();
+        // The prompt's messages are copied, so the copy of the prepended message is the one to remove later.
+        #[cfg(feature = "llama_cpp_backend")]
+        let applied_server_system_prompt = self
+            .applied_server_system_prompt
+            .as_ref()
+            .and_then(|(_, cache_prompt)| Some((prompt.message(0).ok()?, *cache_prompt)));
         Self {
             start_time: self.start_time,
             stop_sequences: self.stop_sequences.clone(),
             grammar_string: self.grammar_string.clone(),
             logit_bias: self.logit_bias.clone(),
-            prompt: self.prompt.clone(),
+            prompt,
+            raw_tokens: self.raw_tokens.clone(),
+            assistant_prefill: self.assistant_prefill.clone(),
             config: self.config.clone(),
             backend: std::sync::Arc::clone(&self.backend),
             llm_interface_errors: Vec::new(),
+            usage: AggregateUsage::default(),
+            observer: self.observer.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            dry_run: self.dry_run,
+            #[cfg(feature = "llama_cpp_backend")]
+            applied_server_system_prompt,
         }
     }
 }
 
 impl CompletionRequest {
     pub fn new(backend: std::sync::Arc<LlmBackend>) -> CompletionRequest {
+        let mut config = RequestConfig::new(backend.model_ctx_size(), backend.inference_ctx_size());
+        config.thinking_tags = backend.default_thinking_tags();
         CompletionRequest {
             start_time: std::time::Instant::now(),
             stop_sequences: Default::default(),
             logit_bias: None,
-            config: RequestConfig::new(backend.model_ctx_size(), backend.inference_ctx_size()),
+            config,
             prompt: backend.new_prompt(),
+            raw_tokens: None,
+            assistant_prefill: None,
             grammar_string: None,
             backend: std::sync::Arc::clone(&backend),
             llm_interface_errors: Vec::new(),
+            usage: AggregateUsage::default(),
+            observer: None,
+            circuit_breaker: None,
+            dry_run: false,
+            #[cfg(feature = "llama_cpp_backend")]
+            applied_server_system_prompt: None,
         }
     }
 
+    /// Creates a request that sends an already built prompt, e.g. a multi-turn prompt put together separately with the prompt API.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - Made for this backend with [LlmBackend::new_prompt]. Local and API backends build prompts differently,
+    ///   so a prompt made for another kind of backend fails when the request is sent.
+    pub fn from_prompt(
+        backend: std::sync::Arc<LlmBackend>,
+        prompt: LlmPrompt,
+    ) -> CompletionRequest {
+        let mut req = Self::new(backend);
+        req.prompt = prompt;
+        req
+    }
+
     pub fn reset_completion_request(&mut self) {
         self.prompt.reset_prompt();
+        #[cfg(feature = "llama_cpp_backend")]
+        if let Some((_, cache_prompt)) = self.applied_server_system_prompt.take() {
+            self.config.cache_prompt = cache_prompt;
+        }
+        self.raw_tokens = None;
+        if self.assistant_prefill.take().is_some() {
+            self.prompt.clear_generation_prefix();
+        }
         self.stop_sequences.sequences.clear();
         self.grammar_string = None;
         self.logit_bias = None;
+        self.usage = AggregateUsage::default();
+    }
+
+    /// Sends the given token ids as the prompt, bypassing the chat template.
+    ///
+    /// When set, `prompt` is not built and the tokens are sent to the server unmodified.
+    /// llama.cpp performs no manipulation when sending just tokens, so this gives byte-exact
+    /// control for callers that manage their own formatting and special tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The prompt as token ids from the model's tokenizer.
+    ///
+    /// # Notes
+    ///
+    /// Only supported by the llama.cpp backend. Other backends return a
+    /// [CompletionError::RequestBuilderError].
+    pub fn set_raw_tokens(&mut self, tokens: Vec<u32>) -> &mut Self {
+        self.raw_tokens = Some(tokens);
+        self
     }
 
+    /// Has the LLM continue a partially written assistant turn rather than starting a new one.
+    ///
+    /// The response contains only the continuation, not the prefill itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefill` - The start of the assistant's response.
+    ///
+    /// # Notes
+    ///
+    /// * Local backends set the prefill as the prompt's generation prefix, replacing any existing one.
+    /// * Anthropic sends it as a trailing assistant message, which the API continues natively. Trailing whitespace is trimmed as the API requires.
+    /// * OpenAI compatible APIs don't support prefill, so it's sent as an assistant message followed by an instruction to continue it.
+    /// * Ollama's chat endpoint continues a trailing assistant message. Its generate endpoint returns a [CompletionError::RequestBuilderError].
+    pub fn set_assistant_prefill<T: AsRef<str>>(&mut self, prefill: T) -> &mut Self {
+        self.assistant_prefill = Some(prefill.as_ref().to_owned());
+        self
+    }
+
+    /// Sets a [RequestObserver] that's called for each request sent to the backend.
+    pub fn observer(&mut self, observer: std::sync::Arc<dyn RequestObserver>) -> &mut Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Sets a [CircuitBreaker] that fails requests fast with [CompletionError::CircuitOpen] while the backend keeps failing.
+    /// Share the `Arc` between requests so they trip the same circuit.
+    pub fn circuit_breaker(
+        &mut self,
+        circuit_breaker: std::sync::Arc<CircuitBreaker>,
+    ) -> &mut Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// When set, requests are built but not sent. [CompletionRequest::request], [CompletionRequest::request_n] and
+    /// [CompletionRequest::request_stream] fail with [CompletionError::DryRun], which holds the body that would have been
+    /// sent, e.g. to check what a workflow sends or to snapshot test prompts. Use [CompletionRequest::dry_run_request] to
+    /// get the body directly.
+    ///
+    /// Not supported by the mistral.rs backend, which runs in process.
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Builds the request as [CompletionRequest::request] would, and returns the URL and JSON body instead of sending it.
+    pub fn dry_run_request(&mut self) -> crate::Result<DryRunResult, CompletionError> {
+        self.prepare_request()?;
+        self.backend.dry_run(self, RequestMode::Single)
+    }
+
+    /// Fails with [CompletionError::DryRun] if [CompletionRequest::dry_run] is set. Called after the request is prepared,
+    /// with the mode it's about to be sent with.
+    fn check_dry_run(&self, mode: RequestMode) -> crate::Result<(), CompletionError> {
+        if self.dry_run {
+            return Err(CompletionError::DryRun(Box::new(
+                self.backend.dry_run(self, mode)?,
+            )));
+        }
+        Ok(())
+    }
+
+    /// Sends the request, retrying as configured. Runs in an `llm_interface.completion` tracing span, with an
+    /// `llm_interface.completion.attempt` span for each request sent to the backend. See `docs/tracing.md`.
     pub async fn request(&mut self) -> crate::Result<CompletionResponse, CompletionError> {
+        let span = tracing::info_span!(
+            "llm_interface.completion",
+            backend = self.backend.backend_name(),
+            model = self.backend.model_id(),
+            temperature = self.config.temperature,
+            attempts = tracing::field::Empty,
+            prompt_tokens = tracing::field::Empty,
+            completion_tokens = tracing::field::Empty,
+            finish_reason = tracing::field::Empty,
+        );
+        let res = self.request_inner().instrument(span.clone()).await;
+        if let Ok(res) = &res {
+            span.record("prompt_tokens", res.token_usage.prompt_tokens);
+            span.record("completion_tokens", res.token_usage.completion_tokens);
+            span.record("finish_reason", res.finish_reason.to_string());
+        }
+        res
+    }
+
+    /// Sends the request once, calling `on_content` with each piece of text as it's generated, e.g. to write a long response to disk.
+    ///
+    /// The returned response has the stop reason, timings, and token usage, but its content is empty, since the text was
+    /// already passed to `on_content`. Nothing is retried and thinking blocks aren't split off, because the text is handed over as it arrives.
+    /// An error from `on_content` stops the request.
+    ///
+    /// Only supported by the llama.cpp backend. Check with [LlmBackend::supports_streaming].
+    pub async fn request_stream<F>(
+        &mut self,
+        mut on_content: F,
+    ) -> crate::Result<CompletionResponse, CompletionError>
+    where
+        F: FnMut(&str) -> crate::Result<()> + Send,
+    {
+        let span = tracing::info_span!(
+            "llm_interface.completion_stream",
+            backend = self.backend.backend_name(),
+            model = self.backend.model_id(),
+        );
+        self.prepare_request()?;
+        self.check_dry_run(RequestMode::Stream)?;
+        tracing::info!("{}", self);
+        if let Some(observer) = &self.observer {
+            observer.on_request(self);
+        }
+        self.usage.attempt_count += 1;
+        let res = self
+            .backend
+            .completion_stream_request(self, &mut on_content)
+            .instrument(span)
+            .await;
+        match res {
+            Ok(res) => {
+                tracing::info!("{}", res);
+                if let Some(observer) = &self.observer {
+                    observer.on_response(self, &res);
+                }
+                self.usage.add_response(&res);
+                Ok(res)
+            }
+            Err(e) => {
+                tracing::warn!(?e);
+                if let Some(observer) = &self.observer {
+                    observer.on_error(self, &e);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Sends the request for `num_completions` independent completions of the same prompt, e.g. for self-consistency sampling.
+    ///
+    /// OpenAI and Azure OpenAI generate them in a single request with the `n` parameter. Completions that come back empty or
+    /// with a banned phrase are replaced by individually retried requests. Requests that require a stop sequence, and all
+    /// other backends, emulate it by sending `num_completions` requests concurrently. The llama.cpp server handles these in
+    /// parallel when it has more than one slot.
+    ///
+    /// Fails if any of the completions fails after retrying.
+    pub async fn request_n(
+        &mut self,
+        num_completions: u8,
+    ) -> crate::Result<Vec<CompletionResponse>, CompletionError> {
+        if num_completions <= 1 {
+            return Ok(vec![self.request().await?]);
+        }
+        self.llm_interface_errors.clear();
+        let mut responses =
+            if self.backend.supports_num_completions() && !self.stop_sequences.required {
+                let span = tracing::info_span!(
+                    "llm_interface.completion",
+                    backend = self.backend.backend_name(),
+                    model = self.backend.model_id(),
+                    temperature = self.config.temperature,
+                    num_completions,
+                );
+                self.request_n_native(num_completions)
+                    .instrument(span)
+                    .await?
+            } else {
+                Vec::new()
+            };
+
+        let mut requests: Vec<CompletionRequest> = (responses.len()..num_completions as usize)
+            .map(|_| self.clone())
+            .collect();
+        let results =
+            futures::future::join_all(requests.iter_mut().map(|request| request.request())).await;
+        let mut error = None;
+        for (request, res) in requests.iter_mut().zip(results) {
+            self.usage.merge(&request.usage);
+            self.llm_interface_errors
+                .append(&mut request.llm_interface_errors);
+            match res {
+                Ok(res) => responses.push(res),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = error {
+            return Err(e);
+        }
+        for (i, res) in responses.iter_mut().enumerate() {
+            res.index = Some(i as u32);
+        }
+        Ok(responses)
+    }
+
+    /// Returns the usable completions of a single request with `n` set. Empty if the request failed with an error that can be retried.
+    async fn request_n_native(
+        &mut self,
+        num_completions: u8,
+    ) -> crate::Result<Vec<CompletionResponse>, CompletionError> {
+        self.prepare_request()?;
+        self.check_dry_run(RequestMode::NumCompletions(num_completions))?;
+        tracing::info!("{}", self);
+        if let Some(observer) = &self.observer {
+            observer.on_request(self);
+        }
+        self.usage.attempt_count += 1;
+        let responses = match self
+            .backend
+            .completion_request_n(self, num_completions)
+            .await
+        {
+            Err(e) => {
+                tracing::warn!(?e);
+                if let Some(observer) = &self.observer {
+                    observer.on_error(self, &e);
+                }
+                match e {
+                    CompletionError::RequestBuilderError { .. }
+                    | CompletionError::StopReasonUnsupported { .. }
+                    | CompletionError::ContentFiltered { .. }
+                    | CompletionError::CircuitOpen { .. }
+                    | CompletionError::ClientError { .. } => return Err(e),
+                    _ => (),
+                }
+                self.llm_interface_errors.push(e);
+                return Ok(Vec::new());
+            }
+            Ok(responses) => responses,
+        };
+        // Only the first response carries the request's token usage.
+        if let Some(res) = responses.first() {
+            self.usage.add_response(res);
+        }
+        let mut usable = Vec::new();
+        for mut res in responses {
+            self.split_thinking(&mut res);
+            tracing::info!("{}", res);
+            if let Some(observer) = &self.observer {
+                observer.on_response(self, &res);
+            }
+            if let Some(banned_phrase) = self.config.find_banned_phrase(&res.content) {
+                let llm_interface_error = CompletionError::BannedPhrase(banned_phrase.to_owned());
+                tracing::warn!(?llm_interface_error);
+                self.llm_interface_errors.push(llm_interface_error);
+            } else if !res.content.trim().is_empty() {
+                usable.push(res);
+            }
+        }
+        Ok(usable)
+    }
+
+    async fn request_inner(&mut self) -> crate::Result<CompletionResponse, CompletionError> {
+        let total_prompt_tokens = self.prepare_request()?;
+        self.check_dry_run(RequestMode::Single)?;
+
+        // Retrying an empty response or a banned phrase raises the temperature, so it's restored afterwards.
+        let temperature = self.config.temperature;
+        let res = self.request_with_retries(total_prompt_tokens).await;
+        self.config.temperature = temperature;
+        res
+    }
+
+    /// Builds the logit bias, applies the prefill and sets the max tokens for the prompt. Returns the prompt's token count.
+    fn prepare_request(&mut self) -> crate::Result<u64, CompletionError> {
         self.llm_interface_errors.clear();
         self.start_time = std::time::Instant::now();
+        self.config
+            .check_param_ranges(&self.backend.param_ranges())?;
         self.backend
             .build_logit_bias(&mut self.logit_bias)
             .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+        #[cfg(feature = "llama_cpp_backend")]
+        self.apply_server_system_prompt()?;
+        if let Some(assistant_prefill) = &self.assistant_prefill {
+            // Only applies to local prompts. API backends add the prefill when building their requests.
+            self.prompt.set_generation_prefix(assistant_prefill);
+        }
 
-        let total_prompt_tokens = self
-            .backend
-            .get_total_prompt_tokens(&self.prompt)
-            .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+        let total_prompt_tokens = if let Some(raw_tokens) = &self.raw_tokens {
+            match *self.backend {
+                #[cfg(feature = "llama_cpp_backend")]
+                LlmBackend::LlamaCpp(_) => (),
+                _ => {
+                    return Err(CompletionError::RequestBuilderError(
+                        "Raw token prompts are only supported by the llama.cpp backend".to_string(),
+                    ))
+                }
+            }
+            raw_tokens.len() as u64
+        } else {
+            self.backend
+                .get_total_prompt_tokens(&self.prompt)
+                .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?
+        };
 
         self.config
             .set_max_tokens_for_request(total_prompt_tokens)
             .map_err(CompletionError::RequestTokenLimitError)?;
+        Ok(total_prompt_tokens)
+    }
 
+    /// Prepends the llama.cpp backend's server system prompt if the prompt doesn't have a system message.
+    /// See [crate::llms::local::llama_cpp::LlamaCppBackend::set_server_system_prompt].
+    ///
+    /// The message prepended for the previous request is removed first, so sending the request again uses the server's
+    /// current system prompt, or none if it was cleared.
+    #[cfg(feature = "llama_cpp_backend")]
+    fn apply_server_system_prompt(&mut self) -> crate::Result<(), CompletionError> {
+        if let Some((message, cache_prompt)) = self.applied_server_system_prompt.take() {
+            if self
+                .prompt
+                .message(0)
+                .is_ok_and(|first| std::sync::Arc::ptr_eq(&first, &message))
+            {
+                self.prompt
+                    .remove_message(0)
+                    .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+            }
+            self.config.cache_prompt = cache_prompt;
+        }
+        let LlmBackend::LlamaCpp(backend) = &*self.backend else {
+            return Ok(());
+        };
+        if self.raw_tokens.is_some() || self.prompt.has_system_message() {
+            return Ok(());
+        }
+        if let Some(system_prompt) = backend.server_system_prompt() {
+            let message = self
+                .prompt
+                .prepend_system_message()
+                .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+            message.set_content(system_prompt);
+            self.applied_server_system_prompt = Some((message, self.config.cache_prompt));
+            self.config.cache_prompt = true;
+        }
+        Ok(())
+    }
+
+    async fn request_with_retries(
+        &mut self,
+        total_prompt_tokens: u64,
+    ) -> crate::Result<CompletionResponse, CompletionError> {
         let mut retry_count: u8 = 0;
 
         loop {
@@ -84,13 +500,34 @@ impl CompletionRequest {
                 return Err(llm_interface_error);
             }
             tracing::info!("{}", self);
-            match self.backend.completion_request(self).await {
+            if let Some(observer) = &self.observer {
+                observer.on_request(self);
+            }
+            self.usage.attempt_count += 1;
+            // Recorded before sending, so attempts that fail are counted too.
+            tracing::Span::current().record("attempts", retry_count + 1);
+            let attempt_span = tracing::info_span!(
+                "llm_interface.completion.attempt",
+                attempt = retry_count + 1,
+                temperature = self.config.temperature,
+            );
+            match self
+                .backend
+                .completion_request(self)
+                .instrument(attempt_span)
+                .await
+            {
                 Err(e) => {
                     tracing::warn!(?e);
+                    if let Some(observer) = &self.observer {
+                        observer.on_error(self, &e);
+                    }
                     retry_count += 1;
                     match e {
                         CompletionError::RequestBuilderError { .. }
                         | CompletionError::StopReasonUnsupported { .. }
+                        | CompletionError::ContentFiltered { .. }
+                        | CompletionError::CircuitOpen { .. }
                         | CompletionError::ClientError { .. } => {
                             return Err(e);
                         }
@@ -100,8 +537,50 @@ impl CompletionRequest {
                     self.llm_interface_errors.push(e);
                     continue;
                 }
-                Ok(res) => {
+                Ok(mut res) => {
+                    self.split_thinking(&mut res);
                     tracing::info!("{}", res);
+                    if let Some(observer) = &self.observer {
+                        observer.on_response(self, &res);
+                    }
+                    self.usage.add_response(&res);
+                    let banned_phrase = match &res.finish_reason {
+                        // llama.cpp stops on banned phrases, which leaves them out of the content.
+                        CompletionFinishReason::NonMatchingStoppingSequence(Some(s))
+                            if self.config.banned_phrases.contains(s) =>
+                        {
+                            Some(s.clone())
+                        }
+                        _ => self
+                            .config
+                            .find_banned_phrase(&res.content)
+                            .map(|s| s.to_owned()),
+                    };
+                    if let Some(banned_phrase) = banned_phrase {
+                        let llm_interface_error = CompletionError::BannedPhrase(banned_phrase);
+                        tracing::warn!(?llm_interface_error);
+                        self.llm_interface_errors.push(llm_interface_error);
+                        retry_count += 1;
+                        self.config.temperature =
+                            (self.config.temperature + EMPTY_RESPONSE_TEMPERATURE_STEP).min(2.0);
+                        continue;
+                    }
+                    if res.is_empty() {
+                        if !self.config.retry_on_empty {
+                            return Ok(res);
+                        }
+                        retry_count += 1;
+                        let llm_interface_error = CompletionError::EmptyResponse { retry_count };
+                        if retry_count >= self.config.retry_after_fail_n_times {
+                            tracing::error!(?llm_interface_error);
+                            return Err(llm_interface_error);
+                        }
+                        tracing::warn!(?llm_interface_error);
+                        self.llm_interface_errors.push(llm_interface_error);
+                        self.config.temperature =
+                            (self.config.temperature + EMPTY_RESPONSE_TEMPERATURE_STEP).min(2.0);
+                        continue;
+                    }
                     if self.stop_sequences.required {
                         if matches!(
                             res.finish_reason,
@@ -151,6 +630,13 @@ impl CompletionRequest {
         }
     }
 
+    /// Moves the thinking block out of the response's content. See [RequestConfig::thinking_tags].
+    fn split_thinking(&self, res: &mut CompletionResponse) {
+        if let Some(thinking_tags) = &self.config.thinking_tags {
+            res.split_thinking(thinking_tags);
+        }
+    }
+
     pub fn set_base_req_stop_sequences(
         &mut self,
         stop_word_done: &Option<String>,
@@ -182,7 +668,14 @@ impl std::fmt::Display for CompletionRequest {
         writeln!(f)?;
         writeln!(f, "CompletionRequest:")?;
 
-        writeln!(f, "  prompt: {}", self.prompt)?;
+        if let Some(raw_tokens) = &self.raw_tokens {
+            writeln!(f, "  raw_tokens: {} tokens", raw_tokens.len())?;
+        } else {
+            writeln!(f, "  prompt: {}", self.prompt)?;
+        }
+        if let Some(assistant_prefill) = &self.assistant_prefill {
+            writeln!(f, "  assistant_prefill: {:?}", assistant_prefill)?;
+        }
         writeln!(f, "  stop_sequences: {:?}", self.stop_sequences.to_vec())?;
         if let Some(logit_bias) = &self.logit_bias {
             writeln!(f, "  logit_bias: {}", logit_bias)?;
@@ -1,5 +1,8 @@
 use crate::requests::{
-    res_components::{GenerationSettings, InferenceProbabilities, TimingUsage, TokenUsage},
+    req_components::ThinkingTags,
+    res_components::{
+        Citation, GenerationSettings, InferenceProbabilities, TimingUsage, TokenUsage,
+    },
     stop_sequence::StoppingSequence,
 };
 
@@ -8,22 +11,68 @@ pub struct CompletionResponse {
     pub id: String,
     /// If batched, the index of the choice in the list of choices.
     pub index: Option<u32>,
-    /// The generated completion.
+    /// The generated completion. With [crate::requests::req_components::RequestConfig::thinking_tags], the answer after the thinking block.
     pub content: String,
+    /// The thinking block of a reasoning model, without its tags. See [crate::requests::req_components::RequestConfig::thinking_tags].
+    pub thinking: Option<String>,
+    /// The sources a search backed answer was based on, in the order they're referenced as `[1]`, `[2]`, etc. in the content,
+    /// with their titles and snippets where the backend returns them.
+    /// Returned by Perplexity and OpenAI's search models. `None` for other backends.
+    pub citations: Option<Vec<Citation>>,
     pub finish_reason: CompletionFinishReason,
     pub completion_probabilities: Option<Vec<InferenceProbabilities>>,
     /// True if the context size was exceeded during generation, i.e. the number of tokens provided in the prompt (tokens_evaluated) plus tokens generated (tokens predicted) exceeded the context size (n_ctx)
     pub truncated: bool,
+    /// True if the content was cut after generation to fit a requested word or character count.
+    pub content_truncated: bool,
     pub generation_settings: GenerationSettings,
     pub timing_usage: TimingUsage,
     pub token_usage: TokenUsage,
 }
 
+impl CompletionResponse {
+    /// Moves the thinking block out of [CompletionResponse::content] into [CompletionResponse::thinking].
+    /// Does nothing if the content has neither tag.
+    pub fn split_thinking(&mut self, thinking_tags: &ThinkingTags) {
+        let (thinking, content) = thinking_tags.split(&self.content);
+        if thinking.is_some() {
+            self.thinking = thinking;
+            self.content = content;
+        }
+    }
+
+    /// True if the content is empty or only whitespace, and the response didn't stop on a [StoppingSequence::NoResult] stop sequence.
+    /// That stop word is itself the answer, e.g. a primitive's "no result" word, and llama.cpp leaves it out of the content.
+    /// A response that's only any other stop word has no answer. See [crate::requests::req_components::RequestConfig::retry_on_empty].
+    pub fn is_empty(&self) -> bool {
+        self.content.trim().is_empty()
+            && !matches!(
+                self.finish_reason,
+                CompletionFinishReason::MatchingStoppingSequence(StoppingSequence::NoResult(_))
+            )
+    }
+
+    /// The estimated cost of the request in US dollars, from the prompt and completion token usage and the model's pricing.
+    /// `None` for local backends.
+    pub fn estimated_cost_usd(&self) -> Option<f32> {
+        self.token_usage.dollar_cost
+    }
+}
+
 impl std::fmt::Display for CompletionResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f)?;
         writeln!(f, "CompletionResponse:")?;
+        if let Some(thinking) = &self.thinking {
+            writeln!(f, "    thinking: {:?}", thinking)?;
+        }
         writeln!(f, "    content: {:?}", self.content)?;
+        if let Some(citations) = &self.citations {
+            writeln!(f, "    citations:")?;
+            for citation in citations {
+                writeln!(f, "        {citation}")?;
+            }
+        }
         writeln!(f, "    finish_reason: {}", self.finish_reason)?;
         write!(f, "    generation_settings: {}", self.generation_settings)?;
         write!(f, "    timing_usage: {}", self.timing_usage)?;
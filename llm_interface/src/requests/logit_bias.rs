@@ -2,7 +2,10 @@ use llm_models::tokenizer::LlmTokenizer;
 
 use crate::requests::req_components::RequestConfigTrait;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 #[derive(Clone, Default)]
 pub struct LogitBias {
@@ -12,6 +15,7 @@ pub struct LogitBias {
     from_token_ids: FromTokenIds,
     from_chars: FromChars,
     from_words: FromWords,
+    from_word_variants: FromWordVariants,
     from_texts: FromTexts,
 }
 
@@ -44,6 +48,13 @@ impl LogitBias {
         self
     }
 
+    /// Biases a word in its common surface forms: as given, lowercase, capitalized, and uppercase, each with and without a leading space.
+    pub fn add_word(&mut self, word: &str, bias: f32) -> &mut Self {
+        self.from_word_variants.add_word(word, bias);
+        self.clear_built();
+        self
+    }
+
     pub fn add_from_text(&mut self, text: &str, bias: f32) -> &mut Self {
         self.from_texts.add_text(text, bias);
         self.clear_built();
@@ -54,6 +65,7 @@ impl LogitBias {
         self.from_token_ids.clear();
         self.from_chars.clear();
         self.from_words.clear();
+        self.from_word_variants.clear();
         self.from_texts.clear();
         self.clear_built();
         self
@@ -85,7 +97,7 @@ impl LogitBias {
         Ok(())
     }
 
-    pub(crate) fn get_openai(&self) -> Option<HashMap<String, serde_json::Value>> {
+    pub(crate) fn get_openai(&self) -> Option<BTreeMap<String, serde_json::Value>> {
         self.built_openai_bias.get()
     }
 
@@ -97,29 +109,31 @@ impl LogitBias {
         if self.from_token_ids.is_none()
             && self.from_chars.is_none()
             && self.from_words.is_none()
+            && self.from_word_variants.is_none()
             && self.from_texts.is_none()
         {
             return Ok(());
         }
-        let validated_logit_bias = self.from_token_ids.get(tokenizer)?;
+        let key = self.cache_key(tokenizer);
+        let validated_logit_bias = match cached_logit_bias(&key, tokenizer) {
+            Some(logit_bias) => logit_bias,
+            None => {
+                // Token ids are merged last, so an id given directly overrides the bias of the same token from a string.
+                let logit_bias = Self::merge_logit_biases(vec![
+                    &self.from_chars.get(tokenizer)?,
+                    &self.from_words.get(tokenizer)?,
+                    &self.from_word_variants.get(tokenizer)?,
+                    &self.from_texts.get(tokenizer)?,
+                    &self.from_token_ids.get(tokenizer)?,
+                ]);
+                cache_logit_bias(key, tokenizer, &logit_bias);
+                logit_bias
+            }
+        };
         self.from_token_ids.clear();
-
-        let validated_logit_bias = Self::merge_logit_biases(vec![
-            &validated_logit_bias,
-            &self.from_chars.get(tokenizer)?,
-        ]);
         self.from_chars.clear();
-
-        let validated_logit_bias = Self::merge_logit_biases(vec![
-            &validated_logit_bias,
-            &self.from_words.get(tokenizer)?,
-        ]);
         self.from_words.clear();
-
-        let validated_logit_bias = Self::merge_logit_biases(vec![
-            &validated_logit_bias,
-            &self.from_texts.get(tokenizer)?,
-        ]);
+        self.from_word_variants.clear();
         self.from_texts.clear();
 
         if !validated_logit_bias.is_empty() {
@@ -129,6 +143,19 @@ impl LogitBias {
         Ok(())
     }
 
+    /// Identifies the bias inputs and the tokenizer they're tokenized with. Entries are sorted so equal inputs give equal keys.
+    fn cache_key(&self, tokenizer: &Arc<LlmTokenizer>) -> String {
+        format!(
+            "{:p}|{:?}|{:?}|{:?}|{:?}|{:?}",
+            Arc::as_ptr(tokenizer),
+            sorted_entries(self.from_token_ids.token_ids.as_ref()),
+            sorted_entries(self.from_chars.chars.as_ref()),
+            sorted_entries(self.from_words.words.as_ref()),
+            sorted_entries(self.from_word_variants.words.as_ref()),
+            sorted_entries(self.from_texts.texts.as_ref()),
+        )
+    }
+
     fn clear_built(&mut self) -> &mut Self {
         self.base_logit_bias = None;
         self.built_llama_cpp_bias.clear();
@@ -177,6 +204,40 @@ impl LogitBias {
     }
 }
 
+const LOGIT_BIAS_CACHE_CAPACITY: usize = 256;
+
+type LogitBiasCache = HashMap<String, (std::sync::Weak<LlmTokenizer>, HashMap<u32, f32>)>;
+
+/// Token id biases built from the `add_*` inputs, shared across requests so repeated runs with the same bias skip tokenizing.
+/// Each entry holds a weak reference to its tokenizer, so an entry can't be returned for a different tokenizer that reuses the address.
+static LOGIT_BIAS_CACHE: std::sync::LazyLock<std::sync::Mutex<LogitBiasCache>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
+
+fn cached_logit_bias(key: &str, tokenizer: &Arc<LlmTokenizer>) -> Option<HashMap<u32, f32>> {
+    let cache = LOGIT_BIAS_CACHE.lock().unwrap();
+    let (cached_tokenizer, logit_bias) = cache.get(key)?;
+    match cached_tokenizer.upgrade() {
+        Some(cached_tokenizer) if Arc::ptr_eq(&cached_tokenizer, tokenizer) => {
+            Some(logit_bias.clone())
+        }
+        _ => None,
+    }
+}
+
+fn cache_logit_bias(key: String, tokenizer: &Arc<LlmTokenizer>, logit_bias: &HashMap<u32, f32>) {
+    let mut cache = LOGIT_BIAS_CACHE.lock().unwrap();
+    if cache.len() >= LOGIT_BIAS_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(key, (Arc::downgrade(tokenizer), logit_bias.clone()));
+}
+
+fn sorted_entries<K: Ord, V>(entries: Option<&HashMap<K, V>>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = entries.into_iter().flatten().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
 #[derive(Clone, Default)]
 struct FromTokenIds {
     pub token_ids: Option<HashMap<u32, f32>>,
@@ -191,10 +252,17 @@ impl FromTokenIds {
         self.token_ids = None;
     }
 
+    /// Token ids are used as given, without detokenizing them, so ids of partial characters or whitespace are allowed.
     fn get(&self, tokenizer: &Arc<LlmTokenizer>) -> crate::Result<HashMap<u32, f32>> {
         if let Some(token_ids) = &self.token_ids {
             for token_id in token_ids.keys() {
-                tokenizer.try_from_single_token_id(*token_id)?;
+                if *token_id >= tokenizer.vocab_size {
+                    return Err(crate::anyhow!(
+                        "logit_bias token id {} is outside the model's vocabulary of {} tokens",
+                        token_id,
+                        tokenizer.vocab_size
+                    ));
+                }
             }
             Ok(token_ids.clone())
         } else {
@@ -312,6 +380,82 @@ impl FromWords {
     }
 }
 
+#[derive(Clone, Default)]
+struct FromWordVariants {
+    pub words: Option<HashMap<String, f32>>,
+}
+
+impl FromWordVariants {
+    fn is_none(&self) -> bool {
+        self.words.is_none()
+    }
+
+    fn clear(&mut self) {
+        self.words = None;
+    }
+
+    fn get(&self, tokenizer: &Arc<LlmTokenizer>) -> crate::Result<HashMap<u32, f32>> {
+        if let Some(words) = &self.words {
+            let mut token_logit_bias: HashMap<u32, f32> = HashMap::new();
+            for (word_maybe, bias) in words {
+                let word = word_maybe.trim();
+                if word.is_empty() {
+                    return Err(crate::anyhow!(
+                        "logit_bias contains an empty word. Given word: {}",
+                        word_maybe
+                    ));
+                } else if word.split_ascii_whitespace().count() > 1 {
+                    return Err(crate::anyhow!(
+                        "logit_bias contains a word seperated by whitespace. Given word: {}",
+                        word_maybe
+                    ));
+                }
+                for variant in Self::surface_forms(word) {
+                    // Most tokenizers merge a leading space into the word's first token, so " word" and "word" tokenize differently.
+                    for id in tokenizer.tokenize(&variant) {
+                        if id == tokenizer.white_space_token_id {
+                            continue;
+                        }
+                        token_logit_bias.insert(id, *bias);
+                    }
+                }
+            }
+            Ok(token_logit_bias)
+        } else {
+            Ok(HashMap::new())
+        }
+    }
+
+    fn surface_forms(word: &str) -> Vec<String> {
+        let mut chars = word.chars();
+        let capitalized = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+        let mut forms: Vec<String> = vec![];
+        for form in [
+            word.to_owned(),
+            word.to_lowercase(),
+            capitalized,
+            word.to_uppercase(),
+        ] {
+            for form in [form.clone(), format!(" {form}")] {
+                if !forms.contains(&form) {
+                    forms.push(form);
+                }
+            }
+        }
+        forms
+    }
+
+    fn add_word(&mut self, word: &str, bias: f32) {
+        self.words
+            .get_or_insert_with(HashMap::new)
+            .entry(word.to_owned())
+            .or_insert(bias);
+    }
+}
+
 #[derive(Clone, Default)]
 struct FromTexts {
     pub texts: Option<HashMap<String, f32>>,
@@ -354,7 +498,8 @@ impl FromTexts {
 
 #[derive(Clone, Default)]
 pub struct OpenAiLogitBias {
-    pub built_logit_bias: Option<HashMap<String, serde_json::Value>>,
+    /// Ordered so the request body is the same each time it's built.
+    pub built_logit_bias: Option<BTreeMap<String, serde_json::Value>>,
 }
 
 impl OpenAiLogitBias {
@@ -367,22 +512,24 @@ impl OpenAiLogitBias {
     }
 
     fn build(&mut self, logit_bias: &HashMap<u32, f32>) {
-        let mut openai_logit_bias: HashMap<String, serde_json::Value> = HashMap::new();
+        let mut openai_logit_bias: BTreeMap<String, serde_json::Value> = BTreeMap::new();
         for (token_id, value) in logit_bias {
             openai_logit_bias.insert(
                 token_id.to_string(),
                 serde_json::Value::Number(serde_json::Number::from(value.ceil() as i32)),
             );
         }
+        self.built_logit_bias = Some(openai_logit_bias);
     }
 
-    fn get(&self) -> Option<HashMap<String, serde_json::Value>> {
+    fn get(&self) -> Option<BTreeMap<String, serde_json::Value>> {
         self.built_logit_bias.clone()
     }
 }
 
 #[derive(Clone, Default)]
 pub struct LlamaCppLogitBias {
+    /// Sorted by token id, so the request body is the same each time it's built.
     pub built_logit_bias: Option<Vec<Vec<serde_json::Value>>>,
 }
 
@@ -397,7 +544,7 @@ impl LlamaCppLogitBias {
 
     fn build(&mut self, logit_bias: &HashMap<u32, f32>) {
         let mut llama_logit_bias: Vec<Vec<serde_json::Value>> = Vec::new();
-        for (token_id, value) in logit_bias {
+        for (token_id, value) in sorted_entries(Some(logit_bias)) {
             llama_logit_bias.push(vec![
                 serde_json::Value::Number(serde_json::Number::from(*token_id)),
                 serde_json::Value::Number(
@@ -416,7 +563,11 @@ impl LlamaCppLogitBias {
 impl std::fmt::Display for LogitBias {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f)?;
-        writeln!(f, "LogitBias: {:?}", self.base_logit_bias)?;
+        writeln!(
+            f,
+            "LogitBias: {:?}",
+            sorted_entries(self.base_logit_bias.as_ref())
+        )?;
         Ok(())
     }
 }
@@ -431,7 +582,9 @@ pub trait LogitBiasTrait: RequestConfigTrait {
         self.lb_mut().as_mut().unwrap()
     }
 
-    /// Adds a logit bias for a specific token ID. In the case you have your own tokenizer or other situations where you have token IDs.
+    /// Adds a logit bias for a specific token ID. In the case you have your own tokenizer or other situations where you have token IDs,
+    /// e.g. ids from OpenAI's tiktoken tokenizer. The id is sent as is, without tokenizing, and overrides any bias for the same token
+    /// from a char, word, or text. Building the request errors if the id is outside the model's vocabulary.
     ///
     /// # Arguments
     ///
@@ -476,6 +629,20 @@ pub trait LogitBiasTrait: RequestConfigTrait {
         self
     }
 
+    /// Adds a logit bias for a word in all its common surface forms: as given, lowercase, capitalized, and uppercase, each with and without a leading space.
+    /// Use this rather than `add_logit_bias_from_word` to reliably steer a word, since tokenizers usually give " however" and "However" different tokens than "however".
+    /// Every token of every form is biased, so tokens shared with other words are affected too.
+    /// Errors if the word is empty or contains whitespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - The word.
+    /// * `bias` - The bias value.
+    fn add_logit_bias_word(&mut self, word: &str, bias: f32) -> &mut Self {
+        self.logit_bias().add_word(word, bias);
+        self
+    }
+
     /// Adds a logit bias for a specific text. Splits the text into tokens and applies the bias to each token. It does not add the logit bias value to the whitespace token.
     ///
     /// # Arguments
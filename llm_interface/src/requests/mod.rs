@@ -1,6 +1,9 @@
+pub mod circuit_breaker;
 pub mod completion;
 // pub mod constraints;
 pub mod logit_bias;
+pub mod observer;
+pub mod param_ranges;
 pub mod req_components;
 pub mod res_components;
 pub mod stop_sequence;
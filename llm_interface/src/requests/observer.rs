@@ -0,0 +1,18 @@
+use crate::requests::completion::{CompletionError, CompletionRequest, CompletionResponse};
+
+/// Callbacks for each request sent to the backend, for metrics, tracing, or logging without changing the crate.
+///
+/// Set it on a [CompletionRequest] with [CompletionRequest::observer]. The observer is kept when the request is reset
+/// or cloned, so it sees every request a workflow sends, including retries and each step of a multi-step workflow.
+/// All methods do nothing by default, so only the needed ones have to be implemented.
+pub trait RequestObserver: Send + Sync {
+    /// Called before each request is sent to the backend.
+    fn on_request(&self, _req: &CompletionRequest) {}
+
+    /// Called after each response from the backend, before the response is validated.
+    /// A response can still be retried, for example if it's empty or contains a banned phrase.
+    fn on_response(&self, _req: &CompletionRequest, _res: &CompletionResponse) {}
+
+    /// Called when the backend returns an error.
+    fn on_error(&self, _req: &CompletionRequest, _err: &CompletionError) {}
+}
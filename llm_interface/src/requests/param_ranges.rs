@@ -0,0 +1,81 @@
+/// The inclusive range a backend accepts for a sampling parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParamRange {
+    pub const fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, value: f32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// The value moved to the nearest end of the range if it's outside of it.
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+impl std::fmt::Display for ParamRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} to {}", self.min, self.max)
+    }
+}
+
+const TEMPERATURE: ParamRange = ParamRange::new(0.0, 2.0);
+const TOP_P: ParamRange = ParamRange::new(0.0, 1.0);
+const OPENAI_PENALTY: ParamRange = ParamRange::new(-2.0, 2.0);
+const REPEAT_PENALTY: ParamRange = ParamRange::new(0.0, 2.0);
+
+/// The sampling parameters a backend accepts, and their ranges, as set on [super::req_components::RequestConfig].
+/// `None` if the backend ignores the parameter.
+///
+/// Ranges are in the crate's units, not the provider's. Temperature is always `0.0` to `2.0`, and backends with a
+/// narrower native range are sent a scaled value, e.g. Anthropic is sent half the temperature.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamRanges {
+    pub temperature: Option<ParamRange>,
+    pub top_p: Option<ParamRange>,
+    pub frequency_penalty: Option<ParamRange>,
+    pub presence_penalty: Option<ParamRange>,
+    pub repeat_penalty: Option<ParamRange>,
+}
+
+impl ParamRanges {
+    /// Temperature, top_p, and OpenAI's frequency and presence penalties.
+    pub(crate) const OPENAI: Self = Self {
+        temperature: Some(TEMPERATURE),
+        top_p: Some(TOP_P),
+        frequency_penalty: Some(OPENAI_PENALTY),
+        presence_penalty: Some(OPENAI_PENALTY),
+        repeat_penalty: None,
+    };
+
+    /// [ParamRanges::OPENAI] plus the llama.cpp style repeat penalty.
+    pub(crate) const LOCAL: Self = Self {
+        repeat_penalty: Some(REPEAT_PENALTY),
+        ..Self::OPENAI
+    };
+
+    /// Anthropic has no penalties.
+    pub(crate) const ANTHROPIC: Self = Self {
+        temperature: Some(TEMPERATURE),
+        top_p: Some(TOP_P),
+        frequency_penalty: None,
+        presence_penalty: None,
+        repeat_penalty: None,
+    };
+
+    /// OpenAI's search models reject all sampling parameters.
+    pub(crate) const NONE: Self = Self {
+        temperature: None,
+        top_p: None,
+        frequency_penalty: None,
+        presence_penalty: None,
+        repeat_penalty: None,
+    };
+}
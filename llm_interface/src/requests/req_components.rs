@@ -1,4 +1,10 @@
-use llm_prompt::{check_and_get_max_tokens, MaxTokenState, RequestTokenLimitError};
+use crate::requests::{
+    completion::CompletionError,
+    param_ranges::{ParamRange, ParamRanges},
+};
+use llm_prompt::{
+    check_and_get_max_tokens, MaxTokenState, RequestTokenLimitError, DEFAULT_SAFETY_TOKENS,
+};
 
 #[derive(Clone)]
 pub struct RequestConfig {
@@ -32,13 +38,28 @@ pub struct RequestConfig {
     ///
     /// If `None`, the system will use a default or calculated value based on [RequestConfig::model_ctx_size] or [RequestConfig::inference_ctx_size].
     pub requested_response_tokens: Option<u64>,
+    /// Lets the model generate until it stops on its own, instead of up to a computed token limit.
+    ///
+    /// - For llama.cpp, `n_predict` is sent as -1. For Ollama, `num_predict` is sent as -1.
+    /// - For OpenAI API-compatible LLMs, `max_tokens` is omitted.
+    /// - Anthropic requires a limit, so it's sent the rest of the context window. mistral.rs is sent no limit.
+    ///
+    /// The prompt is still checked against the context window before the request is sent.
+    /// A model that never emits EOS can run on until the context window fills, or indefinitely if llama.cpp shifts its context,
+    /// so set [RequestConfig::requested_response_tokens] as a hard cap for unattended runs. When it's set, it's sent as the limit.
+    ///
+    /// Set with [RequestConfigTrait::until_eos]. Defaults to false.
+    pub until_eos: bool,
     /// A small safety margin to prevent exceeding model limits.
     ///
     /// This is a count of tokens subtracted from the total available tokens to help ensure
     /// that the model doesn't unexpectedly exceed its token limit.
     /// This prevents issues that might arise from slight discrepancies in token counting or unexpected model behavior.
+    /// Raise it for models where the prompt template overhead is underestimated and requests near the context limit fail.
     ///
-    /// Defaults to 10 tokens.
+    /// Supported LLMs: All
+    ///
+    /// Defaults to [`DEFAULT_SAFETY_TOKENS`] (10 tokens).
     pub safety_tokens: u64,
     /// Final adjusted token count for model output.
     ///
@@ -53,6 +74,8 @@ pub struct RequestConfig {
     /// response. It accepts values between 0.0 and 2.0:
     /// - Higher values (e.g., 0.8) increase randomness, leading to more diverse and creative outputs.
     /// - Lower values (e.g., 0.2) decrease randomness, resulting in more focused and deterministic responses.
+    /// - A value of 0.0 selects greedy decoding on every backend. On local backends the same prompt gives the
+    ///   same response; API backends may still vary slightly between runs.
     ///
     /// Note: It's generally recommended to adjust either this parameter or `top_p`, but not both simultaneously.
     ///
@@ -101,6 +124,30 @@ pub struct RequestConfig {
     ///
     /// Defaults to `0.0` (no presence penalty applied).
     pub presence_penalty: f32,
+    /// Multiplicative penalty applied to tokens that appeared in the last [RequestConfig::repeat_last_n] tokens.
+    ///
+    /// This is llama.cpp's repetition penalty and differs from `frequency_penalty` and
+    /// `presence_penalty`, which subtract a fixed amount from a token's logit based on the whole
+    /// generated text. `repeat_penalty` instead divides the logit of any token seen within the
+    /// recent window, regardless of how many times it appeared:
+    /// - Values above 1.0 discourage repetition. 1.1 to 1.3 is typical for small local models.
+    /// - A value of 1.0 disables the penalty.
+    ///
+    /// Useful for breaking the repetition loops that small local models tend to fall into.
+    ///
+    /// Supported LLMs: llama_cpp. Other backends ignore it and log at debug level.
+    ///
+    /// Defaults to `None` (uses the server default).
+    pub repeat_penalty: Option<f32>,
+    /// The number of most recent tokens considered by [RequestConfig::repeat_penalty].
+    ///
+    /// - A value of 0 disables the penalty.
+    /// - A value of -1 uses the full context size.
+    ///
+    /// Supported LLMs: llama_cpp. Other backends ignore it and log at debug level.
+    ///
+    /// Defaults to `None` (uses the server default).
+    pub repeat_last_n: Option<i32>,
     /// Controls diversity via nucleus sampling.
     ///
     /// Top-p sampling (also called nucleus sampling) is an alternative to temperature-based sampling.
@@ -147,23 +194,238 @@ pub struct RequestConfig {
     ///
     /// Defaults to `false`.
     pub cache_prompt: bool,
+    /// Retry the request when the response content is empty or only whitespace.
+    ///
+    /// Small models with aggressive stop sequences occasionally return nothing.
+    /// When set to `true`, the request is retried with the temperature raised by
+    /// [`EMPTY_RESPONSE_TEMPERATURE_STEP`] on each attempt, up to [RequestConfig::retry_after_fail_n_times].
+    /// The original temperature is restored once the request completes.
+    /// If retries are exhausted, [crate::requests::completion::CompletionError::EmptyResponse] is returned. When this is `false`,
+    /// the empty response is returned as is.
+    ///
+    /// A response that's only a stop word is empty, since llama.cpp leaves the stop word out of the content, unless it's a
+    /// [crate::requests::stop_sequence::StoppingSequence::NoResult] stop word, which is the answer. See [crate::requests::completion::CompletionResponse::is_empty].
+    ///
+    /// Supported LLMs: All
+    ///
+    /// Defaults to `true`.
+    pub retry_on_empty: bool,
+    /// Phrases that must never appear in the response.
+    ///
+    /// Logit bias acts on single tokens, so it can't ban a phrase without also banning its parts.
+    /// Instead, a response containing a banned phrase is discarded and the request is retried with the
+    /// temperature raised by [`EMPTY_RESPONSE_TEMPERATURE_STEP`] on each attempt, up to [RequestConfig::retry_after_fail_n_times].
+    /// If retries are exhausted, [crate::requests::completion::CompletionError::ExceededRetryCount] is returned.
+    /// Phrases are matched case-insensitively.
+    ///
+    /// For llama_cpp, the phrases are also sent as stop strings, so generation stops as soon as one is produced
+    /// rather than running to completion. Other backends check the finished response.
+    ///
+    /// Supported LLMs: All
+    ///
+    /// Defaults to empty.
+    pub banned_phrases: Vec<String>,
+    /// Tags delimiting the thinking block reasoning models write before their answer, e.g. `<think>` and `</think>`.
+    ///
+    /// When set, the thinking block is removed from [crate::requests::completion::CompletionResponse::content]
+    /// and returned as [crate::requests::completion::CompletionResponse::thinking], so banned phrases,
+    /// empty response retries, and workflow parsing only see the answer. A response that opens a thinking block
+    /// without closing it, e.g. because the token limit ran out, has no answer and is treated as empty.
+    ///
+    /// Stop sequences are matched by the backend during generation, so one that appears inside the thinking block still ends the response.
+    /// That response has no answer, so it's retried like an empty one instead of being returned with a stop sequence match.
+    ///
+    /// Supported LLMs: All
+    ///
+    /// Defaults to `<think>` and `</think>` for llama_cpp models whose chat template handles a thinking block, and for
+    /// Perplexity's reasoning models. Otherwise `None`, which keeps the thinking block in the content.
+    pub thinking_tags: Option<ThinkingTags>,
+    /// How sampling parameters outside the backend's accepted range are handled. See [ParamRanges].
+    ///
+    /// Every request checks temperature, top_p, and the penalties against the backend's ranges before it's sent.
+    /// - When `false`, an out of range value is clamped to the nearest accepted value and a warning is logged.
+    ///   The clamped value is kept in the config.
+    /// - When `true`, an out of range value is a [CompletionError::RequestBuilderError], and so is setting
+    ///   `top_p` or a penalty the backend doesn't support, which would otherwise be ignored.
+    ///
+    /// Ranges are in the crate's units, so the same config means the same thing on every backend. Temperature is
+    /// always `0.0` to `2.0`, and Anthropic, whose native range is `0.0` to `1.0`, is sent half the value.
+    ///
+    /// Supported LLMs: All
+    ///
+    /// Defaults to `false`.
+    pub strict_params: bool,
+}
+
+/// The tags around a reasoning model's thinking block. See [RequestConfig::thinking_tags].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThinkingTags {
+    pub open: String,
+    pub close: String,
+}
+
+impl Default for ThinkingTags {
+    /// The `<think>` and `</think>` tags used by DeepSeek-R1, QwQ, Qwen3, and most other open reasoning models.
+    fn default() -> Self {
+        Self::new("<think>", "</think>")
+    }
+}
+
+impl ThinkingTags {
+    pub fn new<T: AsRef<str>>(open: T, close: T) -> Self {
+        Self {
+            open: open.as_ref().to_owned(),
+            close: close.as_ref().to_owned(),
+        }
+    }
+
+    /// Splits `content` into the thinking block and the answer that follows it.
+    ///
+    /// Chat templates often open the thinking block in the prompt, so a closing tag without an opening tag
+    /// treats everything before it as thinking. Returns `None` for the thinking if `content` has no tags.
+    /// Text before and after a block is joined with the whitespace around the block.
+    pub fn split(&self, content: &str) -> (Option<String>, String) {
+        let open = (!self.open.is_empty())
+            .then(|| content.find(&self.open))
+            .flatten();
+        let close = (!self.close.is_empty())
+            .then(|| content.find(&self.close))
+            .flatten();
+        let (before, thinking, after) = match (open, close) {
+            (Some(open), Some(close)) if open < close => (
+                &content[..open],
+                &content[open + self.open.len()..close],
+                &content[close + self.close.len()..],
+            ),
+            (_, Some(close)) => ("", &content[..close], &content[close + self.close.len()..]),
+            (Some(open), None) => (&content[..open], &content[open + self.open.len()..], ""),
+            (None, None) => return (None, content.to_owned()),
+        };
+        let (before_text, after_text) = (before.trim_end(), after.trim_start());
+        // Text on both sides of the block keeps the whitespace that separated them, so words aren't run together.
+        let answer = if before_text.is_empty() || after_text.is_empty() {
+            format!("{before_text}{after_text}")
+        } else {
+            let separator = match &before[before_text.len()..] {
+                "" => &after[..after.len() - after_text.len()],
+                trailing => trailing,
+            };
+            format!("{before_text}{separator}{after_text}")
+        };
+        (Some(thinking.trim().to_owned()), answer)
+    }
 }
 
+/// The temperature increase applied on each retry after an empty response. See [RequestConfig::retry_on_empty].
+pub const EMPTY_RESPONSE_TEMPERATURE_STEP: f32 = 0.1;
+
 impl RequestConfig {
     pub fn new(model_ctx_size: u64, inference_ctx_size: u64) -> Self {
         Self {
             model_ctx_size,
             inference_ctx_size,
             requested_response_tokens: None,
+            until_eos: false,
             actual_request_tokens: None,
             frequency_penalty: None,
             presence_penalty: 0.0,
+            repeat_penalty: None,
+            repeat_last_n: None,
             temperature: 1.0,
             top_p: None,
-            safety_tokens: 10,
+            safety_tokens: DEFAULT_SAFETY_TOKENS,
             retry_after_fail_n_times: 3,
             increase_limit_on_fail: false,
             cache_prompt: false,
+            retry_on_empty: true,
+            banned_phrases: Vec::new(),
+            thinking_tags: None,
+            strict_params: false,
+        }
+    }
+
+    /// Returns true if the request should use greedy decoding. See [RequestConfig::temperature].
+    pub fn is_greedy(&self) -> bool {
+        self.temperature == 0.0
+    }
+
+    /// Returns the first of [RequestConfig::banned_phrases] found in `content`.
+    pub fn find_banned_phrase(&self, content: &str) -> Option<&str> {
+        let content = content.to_lowercase();
+        self.banned_phrases
+            .iter()
+            .find(|phrase| !phrase.is_empty() && content.contains(&phrase.to_lowercase()))
+            .map(|phrase| phrase.as_str())
+    }
+
+    /// Checks the sampling parameters against `ranges`, clamping or erroring on out of range values as set by [RequestConfig::strict_params].
+    pub fn check_param_ranges(
+        &mut self,
+        ranges: &ParamRanges,
+    ) -> crate::Result<(), CompletionError> {
+        let strict = self.strict_params;
+        let check = |name: &str,
+                     value: f32,
+                     range: Option<ParamRange>|
+         -> crate::Result<f32, CompletionError> {
+            let Some(range) = range else {
+                if strict {
+                    return Err(CompletionError::RequestBuilderError(format!(
+                        "{name} is not supported by this backend"
+                    )));
+                }
+                return Ok(value);
+            };
+            if range.contains(value) {
+                Ok(value)
+            } else if strict {
+                Err(CompletionError::RequestBuilderError(format!(
+                    "{name} of {value} is outside this backend's range of {range}"
+                )))
+            } else {
+                let clamped = range.clamp(value);
+                crate::warn!(
+                    "{name} of {value} is outside this backend's range of {range}. Using {clamped}."
+                );
+                Ok(clamped)
+            }
+        };
+        if ranges.temperature.is_some() {
+            self.temperature = check("temperature", self.temperature, ranges.temperature)?;
+        }
+        if let Some(top_p) = self.top_p {
+            self.top_p = Some(check("top_p", top_p, ranges.top_p)?);
+        }
+        if let Some(frequency_penalty) = self.frequency_penalty {
+            self.frequency_penalty = Some(check(
+                "frequency_penalty",
+                frequency_penalty,
+                ranges.frequency_penalty,
+            )?);
+        }
+        if self.presence_penalty != 0.0 {
+            self.presence_penalty = check(
+                "presence_penalty",
+                self.presence_penalty,
+                ranges.presence_penalty,
+            )?;
+        }
+        if let Some(repeat_penalty) = self.repeat_penalty {
+            self.repeat_penalty = Some(check(
+                "repeat_penalty",
+                repeat_penalty,
+                ranges.repeat_penalty,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Logs at debug level when repeat penalty settings are set for a backend that ignores them.
+    pub(crate) fn log_ignored_repeat_penalty(&self, backend: &str) {
+        if self.repeat_penalty.is_some() || self.repeat_last_n.is_some() {
+            crate::debug!(
+                "repeat_penalty and repeat_last_n are not supported by {backend} and will be ignored"
+            );
         }
     }
 
@@ -179,12 +441,26 @@ impl RequestConfig {
             self.requested_response_tokens,
         )?;
         self.actual_request_tokens = Some(actual_request_tokens);
-        if self.requested_response_tokens.is_none() {
+        if self.requested_response_tokens.is_none() && !self.until_eos {
             self.requested_response_tokens = Some(actual_request_tokens);
         }
         Ok(())
     }
 
+    /// Returns true if the request is sent without a token limit. See [RequestConfig::until_eos].
+    pub fn is_uncapped(&self) -> bool {
+        self.until_eos && self.requested_response_tokens.is_none()
+    }
+
+    /// The token limit to send to backends that accept no limit, or `None` for no limit. See [RequestConfig::until_eos].
+    pub(crate) fn response_token_limit(&self) -> Option<u64> {
+        if self.is_uncapped() {
+            None
+        } else {
+            self.actual_request_tokens
+        }
+    }
+
     pub const DEFAULT_INCREASE_FACTOR: f32 = 1.33;
     pub fn increase_token_limit(
         &mut self,
@@ -197,6 +473,18 @@ impl RequestConfig {
         if self.actual_request_tokens.is_none() || self.requested_response_tokens.is_none() {
             self.set_max_tokens_for_request(total_prompt_tokens)?; // To ensure both token sets are set
         }
+        if self.is_uncapped() {
+            // There's no limit to raise; the response already ran to the end of the context window.
+            let state = MaxTokenState {
+                actual_request: self.actual_request_tokens.unwrap_or_default(),
+                requested_response: self.actual_request_tokens.unwrap_or_default(),
+            };
+            crate::error!("Increase limit failed. The request has no token limit to increase.");
+            return Err(RequestTokenLimitError::TokenLimitIncreaseError {
+                initial_state: state.clone(),
+                new_state: state,
+            });
+        }
 
         let initial_state = MaxTokenState {
             actual_request: self
@@ -253,6 +541,21 @@ pub trait RequestConfigTrait {
         self
     }
 
+    /// Generates until the model stops on its own, removing any limit set with [RequestConfigTrait::max_tokens].
+    /// Call [RequestConfigTrait::max_tokens] afterwards to keep a hard cap. See [RequestConfig::until_eos].
+    fn until_eos(&mut self) -> &mut Self {
+        self.config().until_eos = true;
+        self.config().requested_response_tokens = None;
+        self
+    }
+
+    /// Sets the value of [RequestConfig::safety_tokens]. The margin is subtracted from the response's share of the
+    /// context twice, so a request fails with [RequestTokenLimitError::SafetyTokensExceeds] if it's half the context or more.
+    fn safety_tokens(&mut self, safety_tokens: u64) -> &mut Self {
+        self.config().safety_tokens = safety_tokens;
+        self
+    }
+
     /// Sets the value of [RequestConfig::frequency_penalty].
     fn frequency_penalty(&mut self, frequency_penalty: f32) -> &mut Self {
         self.config().frequency_penalty = Some(frequency_penalty);
@@ -268,6 +571,18 @@ pub trait RequestConfigTrait {
         self
     }
 
+    /// Sets the value of [RequestConfig::repeat_penalty].
+    fn repeat_penalty(&mut self, repeat_penalty: f32) -> &mut Self {
+        self.config().repeat_penalty = Some(repeat_penalty);
+        self
+    }
+
+    /// Sets the value of [RequestConfig::repeat_last_n].
+    fn repeat_last_n(&mut self, repeat_last_n: i32) -> &mut Self {
+        self.config().repeat_last_n = Some(repeat_last_n);
+        self
+    }
+
     /// Sets the value of [RequestConfig::temperature].
     fn temperature(&mut self, temperature: f32) -> &mut Self {
         match temperature {
@@ -300,6 +615,30 @@ pub trait RequestConfigTrait {
         self.config().cache_prompt = cache_prompt;
         self
     }
+
+    /// Sets the value of [RequestConfig::retry_on_empty].
+    fn retry_on_empty(&mut self, retry_on_empty: bool) -> &mut Self {
+        self.config().retry_on_empty = retry_on_empty;
+        self
+    }
+
+    /// Sets the value of [RequestConfig::banned_phrases], replacing any previously banned phrases.
+    fn ban_phrases(&mut self, phrases: Vec<String>) -> &mut Self {
+        self.config().banned_phrases = phrases;
+        self
+    }
+
+    /// Sets the value of [RequestConfig::strict_params].
+    fn strict_params(&mut self, strict_params: bool) -> &mut Self {
+        self.config().strict_params = strict_params;
+        self
+    }
+
+    /// Sets [RequestConfig::thinking_tags], e.g. `thinking_tags("<think>", "</think>")`.
+    fn thinking_tags<T: AsRef<str>>(&mut self, open: T, close: T) -> &mut Self {
+        self.config().thinking_tags = Some(ThinkingTags::new(open, close));
+        self
+    }
 }
 
 impl std::fmt::Display for RequestConfig {
@@ -312,15 +651,20 @@ impl std::fmt::Display for RequestConfig {
             "    requested_response_tokens: {:?}",
             self.requested_response_tokens
         )?;
+        writeln!(f, "    until_eos: {:?}", self.until_eos)?;
         writeln!(
             f,
             "    actual_request_tokens: {:?}",
             self.actual_request_tokens
         )?;
+        writeln!(f, "    safety_tokens: {:?}", self.safety_tokens)?;
         writeln!(f, "    frequency_penalty: {:?}", self.frequency_penalty)?;
         writeln!(f, "    presence_penalty: {:?}", self.presence_penalty)?;
+        writeln!(f, "    repeat_penalty: {:?}", self.repeat_penalty)?;
+        writeln!(f, "    repeat_last_n: {:?}", self.repeat_last_n)?;
         writeln!(f, "    temperature: {:?}", self.temperature)?;
         writeln!(f, "    top_p: {:?}", self.top_p)?;
+        writeln!(f, "    strict_params: {:?}", self.strict_params)?;
         writeln!(
             f,
             "    retry_after_fail_n_times: {:?}",
@@ -331,6 +675,9 @@ impl std::fmt::Display for RequestConfig {
             "    increase_limit_on_fail: {:?}",
             self.increase_limit_on_fail
         )?;
-        writeln!(f, "    cache_prompt: {:?}", self.cache_prompt)
+        writeln!(f, "    cache_prompt: {:?}", self.cache_prompt)?;
+        writeln!(f, "    retry_on_empty: {:?}", self.retry_on_empty)?;
+        writeln!(f, "    banned_phrases: {:?}", self.banned_phrases)?;
+        writeln!(f, "    thinking_tags: {:?}", self.thinking_tags)
     }
 }
@@ -1,10 +1,11 @@
-use super::completion::request::CompletionRequest;
+use super::completion::{request::CompletionRequest, response::CompletionResponse};
 use crate::llms::api::{
-    anthropic::completion::AnthropicCompletionResponse,
+    anthropic::completion::AnthropicCompletionResponse, ollama::completion::OllamaChatResponse,
     openai::completion::OpenAiCompletionResponse,
 };
 #[cfg(feature = "llama_cpp_backend")]
 use crate::llms::local::llama_cpp::completion::LlamaCppCompletionResponse;
+use llm_models::api_model::ApiLlmModel;
 #[cfg(feature = "mistral_rs_backend")]
 use mistralrs::CompletionResponse as MistralCompletionResponse;
 
@@ -25,6 +26,34 @@ pub struct TopProbabilities {
     pub prob: f32,
 }
 
+/// A source a search backed answer was based on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    pub url: String,
+    pub title: Option<String>,
+    /// An excerpt of the source. Only Perplexity returns snippets.
+    pub snippet: Option<String>,
+}
+
+impl Citation {
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        Self {
+            url: url.into(),
+            title: None,
+            snippet: None,
+        }
+    }
+}
+
+impl std::fmt::Display for Citation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.title {
+            Some(title) => write!(f, "{title} ({})", self.url),
+            None => write!(f, "{}", self.url),
+        }
+    }
+}
+
 /// The settings used to generate the completion.
 pub struct GenerationSettings {
     /// The model used
@@ -72,7 +101,7 @@ impl GenerationSettings {
             temperature: req.config.temperature,
             top_p: req.config.top_p,
             n_choices: 1,
-            n_predict: req.config.actual_request_tokens.map(|x| x as i32),
+            n_predict: req.config.response_token_limit().map(|x| x as i32),
             n_ctx: req.config.inference_ctx_size,
             logit_bias: vec![].into(),
             grammar: None,
@@ -93,7 +122,7 @@ impl GenerationSettings {
             temperature: req.config.temperature,
             top_p: req.config.top_p,
             n_choices: 1,
-            n_predict: req.config.actual_request_tokens.map(|x| x as i32),
+            n_predict: req.config.response_token_limit().map(|x| x as i32),
             n_ctx: req.config.inference_ctx_size,
             logit_bias: None,
             grammar: None,
@@ -126,6 +155,27 @@ impl GenerationSettings {
                 .collect(),
         }
     }
+
+    pub fn new_from_ollama(req: &CompletionRequest, res: &OllamaChatResponse) -> Self {
+        Self {
+            model: res.model.to_owned(),
+            frequency_penalty: req.config.frequency_penalty,
+            presence_penalty: req.config.presence_penalty,
+            temperature: req.config.temperature,
+            top_p: req.config.top_p,
+            n_choices: 1,
+            n_predict: req.config.response_token_limit().map(|x| x as i32),
+            n_ctx: req.backend.model_ctx_size(),
+            logit_bias: None,
+            grammar: None,
+            stop_sequences: req
+                .stop_sequences
+                .sequences
+                .iter()
+                .map(|x| x.as_str().to_owned())
+                .collect(),
+        }
+    }
 }
 
 impl std::fmt::Display for GenerationSettings {
@@ -225,6 +275,30 @@ impl TimingUsage {
             generation_tok_per_sec: None,
         }
     }
+
+    pub fn new_from_ollama(res: &OllamaChatResponse, start_time: std::time::Instant) -> Self {
+        let per_ms = |count: Option<u32>, duration_ns: Option<u64>| match (count, duration_ns) {
+            (Some(count), Some(duration_ns)) if duration_ns > 0 => {
+                Some(count as f32 / (duration_ns as f32 / 1_000_000.0))
+            }
+            _ => None,
+        };
+        let prompt_tok_per_ms = per_ms(res.prompt_eval_count, res.prompt_eval_duration);
+        let generation_tok_per_ms = per_ms(res.eval_count, res.eval_duration);
+        Self {
+            total_time: start_time.elapsed(),
+            start_time,
+            end_time: std::time::Instant::now(),
+            prompt_processing_t: res
+                .prompt_eval_duration
+                .map(std::time::Duration::from_nanos),
+            generation_t: res.eval_duration.map(std::time::Duration::from_nanos),
+            prompt_tok_per_ms,
+            prompt_tok_per_sec: prompt_tok_per_ms.map(|x| x * 1000.0),
+            generation_tok_per_ms,
+            generation_tok_per_sec: generation_tok_per_ms.map(|x| x * 1000.0),
+        }
+    }
 }
 
 impl std::fmt::Display for TimingUsage {
@@ -249,6 +323,7 @@ impl std::fmt::Display for TimingUsage {
 }
 
 /// Token statistics for the completion request.
+#[derive(Default)]
 pub struct TokenUsage {
     /// Number of tokens from the prompt which could be re-used from previous completion (n_past)
     pub tokens_cached: Option<u32>,
@@ -258,13 +333,21 @@ pub struct TokenUsage {
     pub completion_tokens: u32,
     /// Total number of tokens used in the request (prompt + completion).
     pub total_tokens: u32,
-    /// Dollar cost of the request.
+    /// Estimated dollar cost of the request. `None` for local backends and Ollama. See [TokenUsage::set_cost].
     pub dollar_cost: Option<f32>,
-    /// Cents cost of the request.
+    /// Estimated cents cost of the request. `None` for local backends and Ollama.
     pub cents_cost: Option<f32>,
 }
 
 impl TokenUsage {
+    /// Estimates the cost of the request from the model's price per million tokens.
+    /// The model's prices can be overridden with `model_pricing` on the backend builder.
+    pub fn set_cost(&mut self, model: &ApiLlmModel) {
+        let dollar_cost = model.estimated_cost_usd(self.prompt_tokens, self.completion_tokens);
+        self.dollar_cost = Some(dollar_cost);
+        self.cents_cost = Some(dollar_cost * 100.0);
+    }
+
     #[cfg(feature = "llama_cpp_backend")]
     pub fn new_from_llama(res: &LlamaCppCompletionResponse) -> Self {
         Self {
@@ -320,6 +403,69 @@ impl TokenUsage {
             cents_cost: None,
         }
     }
+
+    pub fn new_from_ollama(res: &OllamaChatResponse) -> Self {
+        let prompt_tokens = res.prompt_eval_count.unwrap_or(0);
+        let completion_tokens = res.eval_count.unwrap_or(0);
+        Self {
+            tokens_cached: None,
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            dollar_cost: None,
+            cents_cost: None,
+        }
+    }
+}
+
+/// Token, cost, and time totals across multiple requests.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AggregateUsage {
+    /// Number of responses received, including responses that were retried.
+    pub request_count: u32,
+    /// Number of requests sent to the backend, including attempts that failed without a response.
+    pub attempt_count: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Estimated dollar cost of all requests. Zero for local backends.
+    pub dollar_cost: f32,
+    /// Total time spent waiting on requests.
+    pub request_time: std::time::Duration,
+}
+
+impl AggregateUsage {
+    pub fn add_response(&mut self, res: &CompletionResponse) {
+        self.request_count += 1;
+        self.prompt_tokens += res.token_usage.prompt_tokens as u64;
+        self.completion_tokens += res.token_usage.completion_tokens as u64;
+        self.total_tokens += res.token_usage.total_tokens as u64;
+        self.dollar_cost += res.token_usage.dollar_cost.unwrap_or(0.0);
+        self.request_time += res.timing_usage.total_time;
+    }
+
+    pub fn merge(&mut self, other: &AggregateUsage) {
+        self.request_count += other.request_count;
+        self.attempt_count += other.attempt_count;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.dollar_cost += other.dollar_cost;
+        self.request_time += other.request_time;
+    }
+}
+
+impl std::fmt::Display for AggregateUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "    request_count: {:?}", self.request_count)?;
+        writeln!(f, "    attempt_count: {:?}", self.attempt_count)?;
+        writeln!(f, "    prompt_tokens: {:?}", self.prompt_tokens)?;
+        writeln!(f, "    completion_tokens: {:?}", self.completion_tokens)?;
+        writeln!(f, "    total_tokens: {:?}", self.total_tokens)?;
+        writeln!(f, "    dollar_cost: {:?}", self.dollar_cost)?;
+        writeln!(f, "    request_time: {:?}", self.request_time)
+    }
 }
 
 impl std::fmt::Display for TokenUsage {
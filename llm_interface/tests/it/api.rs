@@ -13,6 +13,7 @@ async fn test_perplexity() {
 
     let res = req.request().await.unwrap();
     println!("{res}");
+    assert!(res.citations.is_some());
 }
 
 #[tokio::test]
@@ -42,3 +43,1204 @@ async fn test_anthropic() {
     let res = req.request().await.unwrap();
     println!("{res}");
 }
+
+#[tokio::test]
+#[serial]
+#[ignore]
+async fn test_ollama() {
+    let backend = LlmInterface::ollama().init().unwrap();
+    let ollama = backend.ollama().unwrap();
+    ollama.pull_model_if_missing().await.unwrap();
+    for model in ollama.list_models().await.unwrap() {
+        println!("{}: {} bytes", model.name, model.size);
+    }
+
+    let mut req = CompletionRequest::new(backend.clone());
+    req.prompt
+        .add_user_message()
+        .unwrap()
+        .set_content("Hello, world!");
+
+    let res = req.request().await.unwrap();
+    println!("{res}");
+}
+
+#[test]
+fn test_perplexity_citations() {
+    use llm_interface::llms::api::openai::completion::OpenAiCompletionResponse;
+
+    let res: OpenAiCompletionResponse = serde_json::from_str(
+        r#"{
+            "id": "3c90c3cc-0d44-4b50-8888-8dd25736052a",
+            "model": "sonar",
+            "object": "chat.completion",
+            "created": 1724369245,
+            "citations": [
+                "https://www.rust-lang.org/",
+                "https://doc.rust-lang.org/book/"
+            ],
+            "choices": [
+                {
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {
+                        "role": "assistant",
+                        "content": "Rust is a systems programming language [1][2]."
+                    }
+                }
+            ],
+            "usage": {
+                "prompt_tokens": 14,
+                "completion_tokens": 12,
+                "total_tokens": 26
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        res.citations.as_deref(),
+        Some(
+            &[
+                "https://www.rust-lang.org/".to_owned(),
+                "https://doc.rust-lang.org/book/".to_owned()
+            ][..]
+        )
+    );
+}
+
+#[test]
+fn test_search_sources() {
+    use llm_interface::{
+        llms::api::openai::completion::OpenAiCompletionResponse, requests::completion::Citation,
+    };
+
+    let perplexity: OpenAiCompletionResponse = serde_json::from_str(
+        r#"{
+            "id": "3c90c3cc-0d44-4b50-8888-8dd25736052a",
+            "model": "sonar",
+            "created": 1724369245,
+            "citations": ["https://www.rust-lang.org/"],
+            "search_results": [
+                {
+                    "title": "Rust Programming Language",
+                    "url": "https://www.rust-lang.org/",
+                    "date": "2025-01-01",
+                    "snippet": "A language empowering everyone."
+                }
+            ],
+            "choices": [
+                {
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": { "role": "assistant", "content": "Rust is fast [1]." }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        perplexity.sources(&perplexity.choices[0]),
+        Some(vec![Citation {
+            url: "https://www.rust-lang.org/".to_owned(),
+            title: Some("Rust Programming Language".to_owned()),
+            snippet: Some("A language empowering everyone.".to_owned()),
+        }])
+    );
+
+    let openai: OpenAiCompletionResponse = serde_json::from_str(
+        r#"{
+            "id": "chatcmpl-123",
+            "model": "gpt-4o-search-preview",
+            "created": 1741294021,
+            "choices": [
+                {
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {
+                        "role": "assistant",
+                        "content": "Rust 1.85 shipped the 2024 edition ([blog.rust-lang.org](https://blog.rust-lang.org/)).",
+                        "annotations": [
+                            {
+                                "type": "url_citation",
+                                "url_citation": {
+                                    "start_index": 36,
+                                    "end_index": 87,
+                                    "title": "Announcing Rust 1.85.0",
+                                    "url": "https://blog.rust-lang.org/"
+                                }
+                            },
+                            {
+                                "type": "url_citation",
+                                "url_citation": {
+                                    "start_index": 36,
+                                    "end_index": 87,
+                                    "title": "Announcing Rust 1.85.0",
+                                    "url": "https://blog.rust-lang.org/"
+                                }
+                            }
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        openai.sources(&openai.choices[0]),
+        Some(vec![Citation {
+            url: "https://blog.rust-lang.org/".to_owned(),
+            title: Some("Announcing Rust 1.85.0".to_owned()),
+            snippet: None,
+        }])
+    );
+}
+
+#[test]
+fn test_until_eos() {
+    use llm_interface::requests::req_components::RequestConfig;
+
+    let mut config = RequestConfig::new(4096, 4096);
+    config.until_eos = true;
+    config.set_max_tokens_for_request(100).unwrap();
+    assert!(config.is_uncapped());
+    assert_eq!(config.requested_response_tokens, None);
+    // There's no limit to raise when the response runs to the end of the context window.
+    assert!(config.increase_token_limit(100, None).is_err());
+
+    // A prompt that doesn't fit the context window is still rejected.
+    assert!(config.set_max_tokens_for_request(5000).is_err());
+
+    // A requested limit is kept as a hard cap.
+    config.requested_response_tokens = Some(50);
+    config.set_max_tokens_for_request(100).unwrap();
+    assert!(!config.is_uncapped());
+}
+
+#[tokio::test]
+async fn test_rate_limiter() {
+    use llm_interface::llms::api::rate_limit::RateLimiter;
+    use std::{sync::Arc, time::Duration};
+    use tokio::time::timeout;
+
+    let limiter = Arc::new(RateLimiter::new(2, 1000));
+    let shared = Arc::clone(&limiter);
+    timeout(Duration::from_secs(1), limiter.acquire(100))
+        .await
+        .unwrap();
+    timeout(Duration::from_secs(1), shared.acquire(100))
+        .await
+        .unwrap();
+    // The third request in the same minute waits, whichever handle sends it.
+    assert!(timeout(Duration::from_millis(50), limiter.acquire(100))
+        .await
+        .is_err());
+
+    // Too many tokens waits too, even with requests to spare.
+    let limiter = RateLimiter::new(0, 1000);
+    timeout(Duration::from_secs(1), limiter.acquire(900))
+        .await
+        .unwrap();
+    assert!(timeout(Duration::from_millis(50), limiter.acquire(200))
+        .await
+        .is_err());
+    timeout(Duration::from_secs(1), limiter.acquire(100))
+        .await
+        .unwrap();
+
+    // A request larger than the whole budget isn't held forever once the window is empty.
+    let limiter = RateLimiter::new(0, 10);
+    timeout(Duration::from_secs(1), limiter.acquire(500))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_circuit_breaker() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait,
+        requests::{circuit_breaker::CircuitBreaker, completion::CompletionError},
+    };
+    use std::{sync::Arc, time::Duration};
+
+    // Nothing listens on the discard port, so every request fails to connect.
+    let backend = LlmInterface::openai()
+        .base_url("http://127.0.0.1:9/v1")
+        .with_api_key("test")
+        .init()
+        .unwrap();
+    let circuit_breaker = Arc::new(CircuitBreaker::new(2, Duration::from_secs(60)));
+    let mut req = CompletionRequest::new(backend);
+    req.circuit_breaker(Arc::clone(&circuit_breaker));
+    req.prompt
+        .add_user_message()
+        .unwrap()
+        .set_content("Hello, world!");
+
+    for _ in 0..2 {
+        assert!(matches!(
+            req.request().await,
+            Err(CompletionError::ClientError(_))
+        ));
+    }
+    assert!(circuit_breaker.is_open());
+    assert!(matches!(
+        req.clone().request().await,
+        Err(CompletionError::CircuitOpen { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_ignores_rejected_requests() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait,
+        requests::{circuit_breaker::CircuitBreaker, completion::CompletionError},
+    };
+    use std::{sync::Arc, time::Duration};
+
+    // The server rejects every request as invalid.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            read_request(&mut socket).await;
+            let body =
+                r#"{"error": {"message": "Invalid request", "type": "invalid_request_error"}}"#;
+            write_response(&mut socket, "400 Bad Request", body).await;
+        }
+    });
+    let backend = LlmInterface::openai()
+        .base_url(&base_url)
+        .with_api_key("test")
+        .init()
+        .unwrap();
+    let circuit_breaker = Arc::new(CircuitBreaker::new(1, Duration::from_secs(60)));
+    let mut req = CompletionRequest::new(backend);
+    req.circuit_breaker(Arc::clone(&circuit_breaker));
+    req.prompt
+        .add_user_message()
+        .unwrap()
+        .set_content("Hello, world!");
+
+    for _ in 0..2 {
+        match req.request().await {
+            Err(CompletionError::ClientError(e)) => assert!(!e.is_backend_failure(), "{e}"),
+            Err(e) => panic!("Expected a client error, got {e}"),
+            Ok(res) => panic!("Expected a client error, got {res}"),
+        }
+    }
+    assert!(!circuit_breaker.is_open());
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_dropped_trial() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait,
+        requests::{circuit_breaker::CircuitBreaker, completion::CompletionError},
+    };
+    use std::{sync::Arc, time::Duration};
+
+    let circuit_breaker = Arc::new(CircuitBreaker::new(1, Duration::from_millis(50)));
+    let request = |base_url: &str| {
+        let backend = LlmInterface::openai()
+            .base_url(base_url)
+            .with_api_key("test")
+            .init()
+            .unwrap();
+        let mut req = CompletionRequest::new(backend);
+        req.circuit_breaker(Arc::clone(&circuit_breaker));
+        req.prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("Hello, world!");
+        req
+    };
+
+    // Nothing listens on the discard port, so the request fails to connect and opens the circuit.
+    let mut refused = request("http://127.0.0.1:9/v1");
+    assert!(matches!(
+        refused.request().await,
+        Err(CompletionError::ClientError(_))
+    ));
+    assert!(circuit_breaker.is_open());
+
+    // The listener never accepts, so the trial request hangs until it's dropped by the timeout.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut hanging = request(&format!("http://{}/v1", listener.local_addr().unwrap()));
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), hanging.request())
+            .await
+            .is_err()
+    );
+
+    // The dropped trial doesn't leave the circuit stuck: the next request is let through as the trial.
+    assert!(matches!(
+        refused.request().await,
+        Err(CompletionError::ClientError(_))
+    ));
+}
+
+#[test]
+fn test_param_ranges() {
+    use llm_interface::llms::api::config::LlmApiConfigTrait;
+
+    let openai = LlmInterface::openai().with_api_key("test").init().unwrap();
+    let ranges = openai.param_ranges();
+    assert!(ranges.temperature.unwrap().contains(1.8));
+    assert!(!ranges.top_p.unwrap().contains(1.5));
+    assert_eq!(ranges.presence_penalty.unwrap().min, -2.0);
+    assert_eq!(ranges.repeat_penalty, None);
+
+    // Anthropic is sent half the temperature, so it takes the same range in the crate's units.
+    let anthropic = LlmInterface::anthropic()
+        .with_api_key("test")
+        .init()
+        .unwrap();
+    let ranges = anthropic.param_ranges();
+    assert_eq!(ranges.temperature.unwrap().max, 2.0);
+    assert_eq!(ranges.frequency_penalty, None);
+    assert_eq!(ranges.presence_penalty, None);
+}
+
+#[test]
+fn test_strict_params() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait,
+        requests::{completion::CompletionError, req_components::RequestConfig},
+    };
+
+    let anthropic = LlmInterface::anthropic()
+        .with_api_key("test")
+        .init()
+        .unwrap();
+    let ranges = anthropic.param_ranges();
+
+    // Out of range values are clamped by default.
+    let mut config = RequestConfig::new(4096, 4096);
+    config.top_p = Some(1.5);
+    config.frequency_penalty = Some(0.5);
+    config.check_param_ranges(&ranges).unwrap();
+    assert_eq!(config.top_p, Some(1.0));
+    // Unsupported parameters are left for the backend to ignore.
+    assert_eq!(config.frequency_penalty, Some(0.5));
+
+    let mut config = RequestConfig::new(4096, 4096);
+    config.strict_params = true;
+    config.top_p = Some(1.5);
+    assert!(matches!(
+        config.check_param_ranges(&ranges),
+        Err(CompletionError::RequestBuilderError(_))
+    ));
+    config.top_p = Some(0.9);
+    config.check_param_ranges(&ranges).unwrap();
+    config.frequency_penalty = Some(0.5);
+    assert!(config.check_param_ranges(&ranges).is_err());
+}
+
+#[test]
+fn test_openai_logit_bias_token_ids() {
+    use llm_interface::{llms::api::config::LlmApiConfigTrait, requests::logit_bias::LogitBias};
+
+    let openai = LlmInterface::openai().with_api_key("test").init().unwrap();
+    let word_token = openai.tokenizer().try_into_single_token("hello").unwrap();
+    let mut logit_bias = LogitBias::new();
+    logit_bias
+        .add_from_word("hello", -50.0)
+        .add_token_id(word_token, 10.0)
+        // Ids are checked against the vocabulary size, not detokenized.
+        .add_token_id(200, -100.0);
+    let mut logit_bias = Some(logit_bias);
+    openai.build_logit_bias(&mut logit_bias).unwrap();
+    let built = logit_bias
+        .unwrap()
+        .built_openai_bias
+        .built_logit_bias
+        .unwrap();
+    assert_eq!(built.len(), 2);
+    // The id given directly overrides the bias from the word.
+    assert_eq!(built[&word_token.to_string()], 10);
+    assert_eq!(built["200"], -100);
+
+    let mut logit_bias = LogitBias::new();
+    logit_bias.add_token_id(openai.tokenizer().vocab_size, 10.0);
+    assert!(openai.build_logit_bias(&mut Some(logit_bias)).is_err());
+}
+
+#[tokio::test]
+async fn test_dry_run() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait, requests::completion::CompletionError,
+    };
+
+    let backend = LlmInterface::anthropic()
+        .with_api_key("test")
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt
+        .add_system_message()
+        .unwrap()
+        .set_content("Be brief.");
+    req.prompt
+        .add_user_message()
+        .unwrap()
+        .set_content("Hello, world!");
+
+    let dry_run = req.dry_run_request().unwrap();
+    assert_eq!(dry_run.url, "https://api.anthropic.com/v1/messages");
+    assert_eq!(dry_run.body["model"], dry_run.model_id.as_str());
+    assert_eq!(dry_run.body["system"], "Be brief.");
+    assert_eq!(dry_run.body["messages"][0]["content"], "Hello, world!");
+
+    // The request fails before it's sent, with the same body.
+    req.dry_run(true);
+    match req.request().await {
+        Err(CompletionError::DryRun(res)) => assert_eq!(*res, dry_run),
+        Err(e) => panic!("Expected a dry run, got {e}"),
+        Ok(res) => panic!("Expected a dry run, got {res}"),
+    }
+}
+
+#[tokio::test]
+async fn test_dry_run_matches_request_mode() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait, requests::completion::CompletionError,
+    };
+
+    let backend = LlmInterface::openai().with_api_key("test").init().unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt
+        .add_user_message()
+        .unwrap()
+        .set_content("Hello, world!");
+    assert!(req.dry_run_request().unwrap().body.get("n").is_none());
+
+    // request_n sends a single request with n set, so the dry run has it too.
+    req.dry_run(true);
+    match req.request_n(3).await {
+        Err(CompletionError::DryRun(res)) => assert_eq!(res.body["n"], 3),
+        Err(e) => panic!("Expected a dry run, got {e}"),
+        Ok(_) => panic!("Expected a dry run"),
+    }
+
+    // Streaming isn't supported, so there's nothing it would send.
+    match req.request_stream(|_| Ok(())).await {
+        Err(CompletionError::RequestBuilderError(_)) => (),
+        Err(e) => panic!("Expected a request builder error, got {e}"),
+        Ok(res) => panic!("Expected an error, got {res}"),
+    }
+}
+
+#[test]
+fn test_dry_run_snapshot_is_deterministic() {
+    use llm_interface::llms::api::config::LlmApiConfigTrait;
+
+    let backend = LlmInterface::openai().with_api_key("test").init().unwrap();
+    let snapshot = |words: &[&str]| {
+        let mut req = CompletionRequest::new(backend.clone());
+        req.prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("Name a color.");
+        let mut logit_bias = llm_interface::requests::logit_bias::LogitBias::new();
+        for word in words {
+            logit_bias.add_word(word, -20.0);
+        }
+        req.logit_bias = Some(logit_bias);
+        req.dry_run_request().unwrap().to_snapshot()
+    };
+
+    let words = ["red", "green", "blue", "yellow", "purple"];
+    let first = snapshot(&words);
+    assert!(first.contains("logit_bias"));
+    let mut reversed = words;
+    reversed.reverse();
+    for _ in 0..5 {
+        assert_eq!(snapshot(&reversed), first);
+    }
+}
+
+#[test]
+fn test_pool_config() {
+    use llm_interface::llms::api::config::LlmApiConfigTrait;
+
+    let builder = LlmInterface::openai()
+        .with_api_key("test")
+        .pool_max_idle_per_host(4)
+        .pool_idle_timeout(None)
+        .tcp_keepalive(Some(std::time::Duration::from_secs(30)));
+    let pool_config = &builder.api_config().pool_config;
+    assert_eq!(pool_config.max_idle_per_host, Some(4));
+    assert_eq!(pool_config.idle_timeout, None);
+    assert_eq!(
+        pool_config.tcp_keepalive,
+        Some(std::time::Duration::from_secs(30))
+    );
+    assert!(!pool_config.http2_prior_knowledge);
+    builder.init().unwrap();
+}
+
+/// Serves the completions endpoint and 404s everything else, like a server without the chat endpoint. The models list
+/// answers 401 unless the API key is `good-key`, and 429 for `limited-key`. Under `/nomodels` there's no models list, and
+/// the chat endpoint answers 405 like a server that only takes POST. Returns the base url and the paths requested.
+async fn completions_only_server() -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let paths = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let requested = paths.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+            let path = request.split_whitespace().nth(1).unwrap().to_owned();
+            let (status, body) = if path == "/v1/completions" {
+                (
+                    "200 OK",
+                    r#"{"id":"cmpl-1","created":0,"model":"test-model","choices":[{"index":0,"text":" Hi there!","finish_reason":"stop"}],"usage":{"prompt_tokens":5,"completion_tokens":3,"total_tokens":8}}"#,
+                )
+            } else if path == "/v1/models" && request.contains("Bearer limited-key") {
+                (
+                    "429 Too Many Requests",
+                    r#"{"error":{"message":"Rate limit exceeded"}}"#,
+                )
+            } else if path == "/v1/models" && !request.contains("Bearer good-key") {
+                (
+                    "401 Unauthorized",
+                    r#"{"error":{"message":"Invalid API key"}}"#,
+                )
+            } else if path == "/v1/models" {
+                ("200 OK", r#"{"object":"list","data":[]}"#)
+            } else if path == "/nomodels/chat/completions" {
+                (
+                    "405 Method Not Allowed",
+                    r#"{"detail":"Method Not Allowed"}"#,
+                )
+            } else {
+                ("404 Not Found", r#"{"detail":"Not Found"}"#)
+            };
+            requested.lock().unwrap().push(path);
+            write_response(&mut socket, status, body).await;
+        }
+    });
+    (base_url, paths)
+}
+
+/// Reads a request's headers and body, which is at most a few kilobytes.
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    use tokio::io::AsyncReadExt;
+
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request);
+        if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+            let content_length = headers
+                .lines()
+                .find_map(|line| {
+                    line.to_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|v| v.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if body.len() >= content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&request).into_owned()
+}
+
+async fn write_response(socket: &mut tokio::net::TcpStream, status: &str, body: &str) {
+    use tokio::io::AsyncWriteExt;
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    socket.write_all(response.as_bytes()).await.unwrap();
+}
+
+/// Answers each chat request with the next of `replies`, repeating the last one once they run out. `None` answers with
+/// no choices, which fails the attempt. Returns the base url and the bodies of the requests.
+async fn scripted_chat_server(
+    replies: Vec<Option<&'static str>>,
+) -> (
+    String,
+    std::sync::Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let bodies = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let requested = bodies.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+            let body: serde_json::Value =
+                serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap();
+            let reply = {
+                let mut requested = requested.lock().unwrap();
+                requested.push(body);
+                replies[(requested.len() - 1).min(replies.len() - 1)]
+            };
+            let choices = match reply {
+                Some(reply) => serde_json::json!([{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": reply},
+                    "finish_reason": "stop"
+                }]),
+                None => serde_json::json!([]),
+            };
+            let response = serde_json::json!({
+                "id": "chatcmpl-1",
+                "created": 0,
+                "model": "test-model",
+                "choices": choices,
+                "usage": {"prompt_tokens": 5, "completion_tokens": 5, "total_tokens": 10}
+            });
+            write_response(&mut socket, "200 OK", &response.to_string()).await;
+        }
+    });
+    (base_url, bodies)
+}
+
+/// Answers every request with `response`. Returns the base url and the paths of the requests.
+async fn fixed_response_server(
+    response: serde_json::Value,
+) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let paths = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let requested = paths.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+            let path = request.split_whitespace().nth(1).unwrap().to_owned();
+            requested.lock().unwrap().push(path);
+            write_response(&mut socket, "200 OK", &response.to_string()).await;
+        }
+    });
+    (base_url, paths)
+}
+
+/// Answers Ollama's `/api/chat` and `/api/generate` with a fixed reply. Returns the host and port, and the paths and bodies of the requests.
+async fn ollama_server() -> (
+    String,
+    String,
+    std::sync::Arc<std::sync::Mutex<Vec<(String, serde_json::Value)>>>,
+) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let requested = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+            let path = request.split_whitespace().nth(1).unwrap().to_owned();
+            let body: serde_json::Value =
+                serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap();
+            requested.lock().unwrap().push((path.clone(), body));
+            let (status, response) = match path.as_str() {
+                "/api/chat" => (
+                    "200 OK",
+                    serde_json::json!({
+                        "model": "llama3.2",
+                        "created_at": "2024-01-01T00:00:00Z",
+                        "message": {"role": "assistant", "content": "Hi there!"},
+                        "done": true,
+                        "done_reason": "stop",
+                        "prompt_eval_count": 5,
+                        "eval_count": 3
+                    }),
+                ),
+                "/api/generate" => (
+                    "200 OK",
+                    serde_json::json!({
+                        "model": "llama3.2",
+                        "created_at": "2024-01-01T00:00:00Z",
+                        "response": "Generated!",
+                        "done": true,
+                        "done_reason": "length",
+                        "prompt_eval_count": 5,
+                        "eval_count": 3
+                    }),
+                ),
+                _ => ("404 Not Found", serde_json::json!({"error": "not found"})),
+            };
+            write_response(&mut socket, status, &response.to_string()).await;
+        }
+    });
+    (addr.ip().to_string(), addr.port().to_string(), requests)
+}
+
+#[tokio::test]
+async fn test_ollama_has_no_cost() {
+    use llm_interface::llms::api::config::LlmApiConfigTrait;
+
+    let (host, port, requests) = ollama_server().await;
+    let backend = LlmInterface::ollama()
+        .with_api_host(host)
+        .with_api_port(port)
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    let res = req.request().await.unwrap();
+    assert_eq!(res.content, "Hi there!");
+    assert_eq!(res.token_usage.prompt_tokens, 5);
+    assert_eq!(res.token_usage.dollar_cost, None);
+    assert_eq!(res.token_usage.cents_cost, None);
+    assert_eq!(requests.lock().unwrap()[0].0, "/api/chat");
+}
+
+#[tokio::test]
+async fn test_ollama_generate_endpoint() {
+    use llm_interface::{
+        llms::api::{config::LlmApiConfigTrait, ollama::OllamaEndpoint},
+        requests::completion::CompletionFinishReason,
+    };
+
+    let (host, port, requests) = ollama_server().await;
+    let backend = LlmInterface::ollama()
+        .with_api_host(host)
+        .with_api_port(port)
+        .endpoint(OllamaEndpoint::Generate)
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend.clone());
+    req.prompt
+        .add_system_message()
+        .unwrap()
+        .set_content("Be brief.");
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    let res = req.request().await.unwrap();
+    assert_eq!(res.content, "Generated!");
+    assert!(matches!(
+        res.finish_reason,
+        CompletionFinishReason::StopLimit
+    ));
+    assert_eq!(res.token_usage.completion_tokens, 3);
+    {
+        let requests = requests.lock().unwrap();
+        let (path, body) = &requests[0];
+        assert_eq!(path, "/api/generate");
+        assert_eq!(body["prompt"], "Hello!");
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(body["stream"], false);
+        assert!(body.get("messages").is_none());
+    }
+
+    // The generate endpoint can't continue an assistant message.
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    req.set_assistant_prefill("Sure,");
+    assert!(req.request().await.is_err());
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_openai_content_filtered() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait, requests::completion::CompletionError,
+    };
+
+    let openai_response = |message: serde_json::Value, finish_reason: &str| {
+        serde_json::json!({
+            "id": "chatcmpl-1",
+            "created": 0,
+            "model": "gpt-4o-mini",
+            "choices": [{"index": 0, "message": message, "finish_reason": finish_reason}],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 5, "total_tokens": 10}
+        })
+    };
+    for (response, reason) in [
+        (
+            openai_response(
+                serde_json::json!({"role": "assistant", "content": null, "refusal": "I can't help with that."}),
+                "stop",
+            ),
+            "I can't help with that.",
+        ),
+        (
+            openai_response(
+                serde_json::json!({"role": "assistant", "content": ""}),
+                "content_filter",
+            ),
+            "content_filter",
+        ),
+    ] {
+        let (base_url, paths) = fixed_response_server(response).await;
+        let backend = LlmInterface::openai()
+            .with_api_key("test")
+            .base_url(&base_url)
+            .init()
+            .unwrap();
+        let mut req = CompletionRequest::new(backend);
+        req.prompt.add_user_message().unwrap().set_content("Hello!");
+        // A filtered response is returned without retrying.
+        match req.request().await {
+            Err(CompletionError::ContentFiltered(message)) => {
+                assert!(message.contains(reason), "{message}")
+            }
+            Err(e) => panic!("expected ContentFiltered, got {e}"),
+            Ok(res) => panic!("expected ContentFiltered, got {res}"),
+        }
+        assert_eq!(*paths.lock().unwrap(), vec!["/v1/chat/completions"]);
+    }
+}
+
+#[tokio::test]
+async fn test_anthropic_content_filtered() {
+    use llm_interface::{
+        llms::api::config::LlmApiConfigTrait, requests::completion::CompletionError,
+    };
+
+    let (base_url, paths) = fixed_response_server(serde_json::json!({
+        "id": "msg_1",
+        "type": "message",
+        "role": "assistant",
+        "content": [],
+        "model": "claude-3-5-sonnet-20240620",
+        "stop_reason": "refusal",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 5, "output_tokens": 0}
+    }))
+    .await;
+    let backend = LlmInterface::anthropic()
+        .with_api_key("test")
+        .base_url(&base_url)
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    match req.request().await {
+        Err(CompletionError::ContentFiltered(message)) => {
+            assert!(message.contains("refusal"), "{message}")
+        }
+        Err(e) => panic!("expected ContentFiltered, got {e}"),
+        Ok(res) => panic!("expected ContentFiltered, got {res}"),
+    }
+    assert_eq!(*paths.lock().unwrap(), vec!["/v1/messages"]);
+}
+
+#[tokio::test]
+async fn test_generic_api_endpoint_style() {
+    use llm_interface::llms::api::generic_openai::EndpointStyle;
+
+    assert!(LlmInterface::generic_api()
+        .model_id("test-model")
+        .init()
+        .is_err());
+    assert!(LlmInterface::generic_api()
+        .base_url("http://localhost:8000/v1")
+        .init()
+        .is_err());
+
+    let (base_url, paths) = completions_only_server().await;
+    let backend = LlmInterface::generic_api()
+        .base_url(&base_url)
+        .model_id("test-model")
+        .endpoint_style(EndpointStyle::Auto)
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend.clone());
+    req.prompt
+        .add_system_message()
+        .unwrap()
+        .set_content("Be brief.");
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+
+    // Until a request succeeds, a dry run shows the chat request.
+    assert!(req
+        .dry_run_request()
+        .unwrap()
+        .url
+        .ends_with("/v1/chat/completions"));
+
+    let res = req.request().await.unwrap();
+    assert_eq!(res.content, "Hi there!");
+    assert_eq!(
+        *paths.lock().unwrap(),
+        vec!["/v1/chat/completions", "/v1/completions"]
+    );
+
+    // The fallback is remembered.
+    let dry_run = req.dry_run_request().unwrap();
+    assert_eq!(dry_run.url, format!("{base_url}/completions"));
+    assert_eq!(
+        dry_run.body["prompt"],
+        "Be brief.\n\nUser: Hello!\n\nAssistant:"
+    );
+    req.request().await.unwrap();
+    assert_eq!(paths.lock().unwrap().len(), 3);
+
+    // The turn stop is kept when the caller already has 4 stops.
+    for stop_word in ["one", "two", "three", "four"] {
+        req.stop_sequences.set_stop_word_done(stop_word);
+    }
+    let stop = req.dry_run_request().unwrap().body["stop"].clone();
+    assert_eq!(
+        stop,
+        serde_json::json!(["one", "two", "three", "four", "\nUser:"])
+    );
+}
+
+#[tokio::test]
+async fn test_health_check() {
+    use llm_interface::llms::{api::config::LlmApiConfigTrait, health::HealthStatus};
+
+    let (base_url, paths) = completions_only_server().await;
+    let backend = |base_url: &str, api_key: &str| {
+        LlmInterface::generic_api()
+            .base_url(base_url)
+            .model_id("test-model")
+            .with_api_key(api_key)
+            .init()
+            .unwrap()
+    };
+    let bad_key = backend(&base_url, "bad-key");
+    assert!(matches!(
+        bad_key.health_check().await,
+        HealthStatus::AuthFailed(_)
+    ));
+
+    let good_key = backend(&base_url, "good-key");
+    assert_eq!(good_key.health_check().await, HealthStatus::Ready);
+    let limited_key = backend(&base_url, "limited-key");
+    assert_eq!(limited_key.health_check().await, HealthStatus::RateLimited);
+    assert_eq!(
+        *paths.lock().unwrap(),
+        vec!["/v1/models", "/v1/models", "/v1/models"]
+    );
+    paths.lock().unwrap().clear();
+
+    // A server without the models list is up if the chat endpoint exists.
+    let server = base_url.trim_end_matches("/v1");
+    let no_models = backend(&format!("{server}/nomodels"), "good-key");
+    assert_eq!(no_models.health_check().await, HealthStatus::Ready);
+    // A wrong base url 404s both.
+    let wrong_url = backend(&format!("{server}/wrong"), "good-key");
+    let status = wrong_url.health_check().await;
+    assert!(matches!(status, HealthStatus::Misconfigured(_)), "{status}");
+    assert_eq!(
+        *paths.lock().unwrap(),
+        vec![
+            "/nomodels/models",
+            "/nomodels/chat/completions",
+            "/wrong/models",
+            "/wrong/chat/completions"
+        ]
+    );
+
+    // Nothing listens on the port once the listener is dropped.
+    let port = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let offline = backend(&format!("http://127.0.0.1:{port}/v1"), "good-key");
+    let status = offline.health_check().await;
+    assert!(matches!(status, HealthStatus::Unreachable(_)), "{status}");
+    assert!(!status.is_ready());
+}
+
+#[tokio::test]
+async fn test_custom_headers() {
+    use llm_interface::llms::api::config::LlmApiConfigTrait;
+
+    let backend = |name: &str, value: &str| {
+        LlmInterface::generic_api()
+            .base_url("http://localhost:8000/v1")
+            .model_id("test-model")
+            .header(name, value)
+            .init()
+    };
+    assert!(backend("X-Routing-Key", "team-a").is_ok());
+
+    let err = backend("X Routing Key", "team-a")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("X Routing Key"), "{err}");
+    let err = backend("X-Routing-Key", "team-a\n")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("X-Routing-Key"), "{err}");
+}
+
+#[tokio::test]
+async fn test_banned_phrase_retries() {
+    use llm_interface::requests::completion::CompletionError;
+
+    let mut config = llm_interface::requests::req_components::RequestConfig::new(4096, 4096);
+    config.banned_phrases = vec!["".to_owned(), "Red Fox".to_owned(), "fox".to_owned()];
+    assert_eq!(config.find_banned_phrase("a RED fox"), Some("Red Fox"));
+    assert_eq!(config.find_banned_phrase("a brown FOX"), Some("fox"));
+    assert_eq!(config.find_banned_phrase("a brown dog"), None);
+
+    let (base_url, bodies) = scripted_chat_server(vec![
+        Some("The quick Brown Fox jumps."),
+        Some("The quick red fox jumps."),
+    ])
+    .await;
+    let backend = LlmInterface::generic_api()
+        .base_url(&base_url)
+        .model_id("test-model")
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend.clone());
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    req.config.temperature = 0.5;
+    req.config.banned_phrases = vec!["brown fox".to_owned()];
+
+    // The banned phrase is matched case-insensitively, and the retry raises the temperature.
+    let res = req.request().await.unwrap();
+    assert_eq!(res.content, "The quick red fox jumps.");
+    let temperatures: Vec<f64> = bodies
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|body| body["temperature"].as_f64().unwrap())
+        .collect();
+    assert_eq!(temperatures.len(), 2);
+    assert!(temperatures[1] > temperatures[0], "{temperatures:?}");
+    // The temperature is restored after the request.
+    assert_eq!(req.config.temperature, 0.5);
+
+    // The retries run out if every response has the banned phrase.
+    let (base_url, bodies) = scripted_chat_server(vec![Some("A brown fox.")]).await;
+    let backend = LlmInterface::generic_api()
+        .base_url(&base_url)
+        .model_id("test-model")
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    req.config.retry_after_fail_n_times = 3;
+    req.config.banned_phrases = vec!["brown fox".to_owned()];
+    match req.request().await {
+        Err(CompletionError::ExceededRetryCount { errors, .. }) => {
+            assert_eq!(errors.len(), 3);
+            assert!(errors
+                .iter()
+                .all(|e| matches!(e, CompletionError::BannedPhrase(p) if p == "brown fox")));
+        }
+        Err(e) => panic!("expected ExceededRetryCount, got {e}"),
+        Ok(res) => panic!("expected ExceededRetryCount, got {res}"),
+    }
+    assert_eq!(bodies.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_empty_response_retries() {
+    use llm_interface::requests::completion::CompletionError;
+
+    let (base_url, bodies) = scripted_chat_server(vec![Some(" "), Some("Hi there!")]).await;
+    let backend = LlmInterface::generic_api()
+        .base_url(&base_url)
+        .model_id("test-model")
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    let res = req.request().await.unwrap();
+    assert_eq!(res.content, "Hi there!");
+    assert_eq!(bodies.lock().unwrap().len(), 2);
+    assert!(matches!(
+        req.llm_interface_errors[..],
+        [CompletionError::EmptyResponse { retry_count: 1 }]
+    ));
+
+    // Without retrying, the empty response is returned.
+    let (base_url, bodies) = scripted_chat_server(vec![Some(" ")]).await;
+    let backend = LlmInterface::generic_api()
+        .base_url(&base_url)
+        .model_id("test-model")
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    req.config.retry_on_empty = false;
+    let res = req.request().await.unwrap();
+    assert!(res.content.trim().is_empty());
+    assert_eq!(bodies.lock().unwrap().len(), 1);
+}
+
+/// Collects the values recorded to the `attempts` field of any span.
+struct AttemptsLayer(std::sync::Arc<std::sync::Mutex<Vec<u64>>>);
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for AttemptsLayer {
+    fn on_record(
+        &self,
+        _span: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        values.record(&mut AttemptsVisitor(&self.0));
+    }
+}
+
+struct AttemptsVisitor<'a>(&'a std::sync::Mutex<Vec<u64>>);
+
+impl tracing::field::Visit for AttemptsVisitor<'_> {
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        if field.name() == "attempts" {
+            self.0.lock().unwrap().push(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &tracing::field::Field, _value: &dyn std::fmt::Debug) {}
+}
+
+#[tokio::test]
+async fn test_attempts_span_field() {
+    use llm_devices::logging::LoggingConfigTrait;
+    use llm_interface::requests::completion::CompletionError;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let attempts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let _guard = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(AttemptsLayer(attempts.clone())),
+    );
+
+    // Failed attempts are counted along with the one that succeeds.
+    let (base_url, _) = scripted_chat_server(vec![None, None, Some("Hi there!")]).await;
+    let backend = LlmInterface::generic_api()
+        .base_url(&base_url)
+        .model_id("test-model")
+        // The logger would replace the subscriber for the thread.
+        .logging_enabled(false)
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    req.request().await.unwrap();
+    assert_eq!(*attempts.lock().unwrap(), vec![1, 2, 3]);
+    assert_eq!(req.usage.request_count, 1);
+    assert_eq!(req.usage.attempt_count, 3);
+    attempts.lock().unwrap().clear();
+
+    // And recorded when the retries run out.
+    let (base_url, _) = scripted_chat_server(vec![None]).await;
+    let backend = LlmInterface::generic_api()
+        .base_url(&base_url)
+        .model_id("test-model")
+        .logging_enabled(false)
+        .init()
+        .unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    req.config.retry_after_fail_n_times = 2;
+    assert!(matches!(
+        req.request().await,
+        Err(CompletionError::ExceededRetryCount { .. })
+    ));
+    assert_eq!(attempts.lock().unwrap().last(), Some(&2));
+}
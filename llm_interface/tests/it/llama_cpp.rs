@@ -9,6 +9,13 @@ use llm_interface::llms::local::LlmLocalTrait;
 use llm_interface::{requests::completion::CompletionRequest, LlmInterface};
 use serial_test::serial;
 
+#[test]
+fn test_backend_is_send_sync() {
+    // The backend is shared between tasks behind an Arc.
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<llm_interface::llms::LlmBackend>();
+}
+
 #[tokio::test]
 #[serial]
 async fn test_dropping_server() {
@@ -193,3 +200,71 @@ async fn test_metal() {
     let res = req.request().await.unwrap();
     println!("{res}");
 }
+
+#[tokio::test]
+#[serial]
+async fn test_raw_tokens() {
+    let backend = LlmInterface::llama_cpp().init().await.unwrap();
+    let mut req = CompletionRequest::new(backend);
+    req.prompt
+        .add_user_message()
+        .unwrap()
+        .set_content("Hello, world!");
+    let tokens = req
+        .prompt
+        .local_prompt()
+        .unwrap()
+        .get_built_prompt_as_tokens()
+        .unwrap();
+    req.prompt.reset_prompt();
+    req.set_raw_tokens(tokens);
+
+    let res = req.request().await.unwrap();
+    println!("{res}");
+    assert!(!res.content.is_empty());
+}
+
+#[test]
+fn test_rope_scaling() {
+    use llm_interface::llms::local::RopeScaling;
+
+    let linear = RopeScaling::linear(2.0);
+    linear.validate().unwrap();
+    assert_eq!(linear.scaled_ctx_size(8192), 16384);
+
+    let mut yarn = RopeScaling::yarn(4.0);
+    yarn.yarn_orig_ctx = Some(4096);
+    yarn.yarn_beta_fast = Some(32.0);
+    yarn.yarn_beta_slow = Some(1.0);
+    yarn.validate().unwrap();
+    assert_eq!(yarn.scaled_ctx_size(8192), 16384);
+
+    assert!(RopeScaling::linear(1.0).validate().is_err());
+    yarn.yarn_beta_fast = Some(0.5);
+    assert!(yarn.validate().is_err());
+    let mut linear_with_yarn_params = RopeScaling::linear(2.0);
+    linear_with_yarn_params.yarn_ext_factor = Some(1.0);
+    assert!(linear_with_yarn_params.validate().is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_server_system_prompt_sent_twice() {
+    let backend = LlmInterface::llama_cpp().init().await.unwrap();
+    let llama_cpp = backend.llama_cpp().unwrap();
+    llama_cpp.set_server_system_prompt("You are a pirate.");
+    let mut req = CompletionRequest::new(backend.clone());
+    req.prompt.add_user_message().unwrap().set_content("Hello!");
+    req.config.requested_response_tokens = Some(8);
+
+    req.request().await.unwrap();
+    req.request().await.unwrap();
+    assert_eq!(req.prompt.message_count(), 2);
+    assert!(req.config.cache_prompt);
+
+    // A cleared server system prompt is no longer sent, and the caller's prompt is back to how it was.
+    llama_cpp.clear_server_system_prompt();
+    req.request().await.unwrap();
+    assert_eq!(req.prompt.message_count(), 1);
+    assert!(!req.config.cache_prompt);
+}
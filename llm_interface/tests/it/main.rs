@@ -2,3 +2,4 @@ mod api;
 mod llama_cpp;
 #[cfg(feature = "mistral_rs_backend")]
 mod mistral_rs;
+mod thinking;
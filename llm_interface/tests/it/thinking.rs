@@ -0,0 +1,193 @@
+use llm_interface::{
+    llms::local::llama_cpp::completion::LlamaCppCompletionResponse,
+    requests::{
+        completion::{
+            CompletionFinishReason, CompletionResponse, GenerationSettings, TimingUsage, TokenUsage,
+        },
+        req_components::ThinkingTags,
+        stop_sequence::StoppingSequence,
+    },
+};
+
+#[test]
+fn test_split_thinking() {
+    let tags = ThinkingTags::new("<think>", "</think>");
+    assert_eq!(
+        tags.split("<think>\nThe sky scatters blue light.\n</think>\n\nYes."),
+        (
+            Some("The sky scatters blue light.".to_owned()),
+            "Yes.".to_owned()
+        )
+    );
+    // The chat template opened the thinking block in the prompt.
+    assert_eq!(
+        tags.split("Blue light scatters.</think> Yes."),
+        (Some("Blue light scatters.".to_owned()), "Yes.".to_owned())
+    );
+    // The token limit ran out before the thinking block closed.
+    assert_eq!(
+        tags.split("<think>Blue light"),
+        (Some("Blue light".to_owned()), "".to_owned())
+    );
+    assert_eq!(tags.split("Yes."), (None, "Yes.".to_owned()));
+    // Text on both sides of the block stays separated.
+    assert_eq!(
+        tags.split("Hello <think>x</think> world"),
+        (Some("x".to_owned()), "Hello world".to_owned())
+    );
+    assert_eq!(
+        tags.split("Hello<think>x</think>\n\nworld"),
+        (Some("x".to_owned()), "Hello\n\nworld".to_owned())
+    );
+    assert_eq!(
+        tags.split("Hello<think>x</think>world"),
+        (Some("x".to_owned()), "Helloworld".to_owned())
+    );
+
+    let tags = ThinkingTags::new("[THINK]", "[/THINK]");
+    assert_eq!(
+        tags.split("[THINK]hmm[/THINK]No."),
+        (Some("hmm".to_owned()), "No.".to_owned())
+    );
+}
+
+#[test]
+fn test_split_thinking_llama_cpp_response() {
+    // A llama-server /completion response from a DeepSeek-R1 distill. Its chat template opens the thinking block in the prompt.
+    let res: LlamaCppCompletionResponse = serde_json::from_str(
+        r#"{
+            "content": "Okay, the user asks whether the sky is blue. Air scatters short wavelengths more, so yes.\n</think>\n\nYes, the sky is blue.",
+            "model": "DeepSeek-R1-Distill-Qwen-7B-Q4_K_M.gguf",
+            "prompt": [151646, 151644, 3872, 279, 12884, 6303, 30, 151645, 151648, 198],
+            "generation_settings": {
+                "n_ctx": 4096,
+                "frequency_penalty": 0.0,
+                "presence_penalty": 0.0,
+                "temperature": 0.6,
+                "top_p": 0.95,
+                "n_predict": 512,
+                "logit_bias": [],
+                "grammar": "",
+                "stop": []
+            },
+            "timings": {
+                "predicted_ms": 912.4,
+                "prompt_per_token_ms": 4.1,
+                "predicted_per_token_ms": 28.5,
+                "prompt_ms": 41.0,
+                "prompt_per_second": 243.9,
+                "predicted_n": 32,
+                "prompt_n": 10,
+                "predicted_per_second": 35.1
+            },
+            "stop": true,
+            "stopped_eos": true,
+            "stopped_limit": false,
+            "stopped_word": false,
+            "stopping_word": "",
+            "tokens_cached": 42,
+            "tokens_evaluated": 10,
+            "truncated": false
+        }"#,
+    )
+    .unwrap();
+    let mut response = CompletionResponse {
+        id: "llama_cpp".to_owned(),
+        index: None,
+        content: res.content.clone(),
+        thinking: None,
+        citations: None,
+        finish_reason: CompletionFinishReason::Eos,
+        completion_probabilities: None,
+        truncated: res.truncated,
+        content_truncated: false,
+        generation_settings: GenerationSettings::new_from_llama(&res),
+        timing_usage: TimingUsage::new_from_llama(&res, std::time::Instant::now()),
+        token_usage: TokenUsage::new_from_llama(&res),
+    };
+    response.split_thinking(&ThinkingTags::default());
+    assert_eq!(response.content, "Yes, the sky is blue.");
+    assert_eq!(
+        response.thinking.as_deref(),
+        Some("Okay, the user asks whether the sky is blue. Air scatters short wavelengths more, so yes.")
+    );
+
+    // Content without thinking tags is left as is.
+    response.split_thinking(&ThinkingTags::default());
+    assert_eq!(response.content, "Yes, the sky is blue.");
+    assert!(response.thinking.is_some());
+}
+
+#[test]
+fn test_stop_word_only_response() {
+    // A llama-server /completion response that stopped on a stop word as soon as it started. The stop word isn't in the content.
+    let res: LlamaCppCompletionResponse = serde_json::from_str(
+        r#"{
+            "content": "",
+            "model": "Llama-3.2-1B-Instruct-Q8_0.gguf",
+            "prompt": [128000, 9906, 0],
+            "generation_settings": {
+                "n_ctx": 4096,
+                "frequency_penalty": 0.0,
+                "presence_penalty": 0.0,
+                "temperature": 0.0,
+                "top_p": 1.0,
+                "n_predict": 64,
+                "logit_bias": [],
+                "grammar": "",
+                "stop": ["done", "null"]
+            },
+            "timings": {
+                "predicted_ms": 28.5,
+                "prompt_per_token_ms": 4.1,
+                "predicted_per_token_ms": 28.5,
+                "prompt_ms": 12.3,
+                "prompt_per_second": 243.9,
+                "predicted_n": 1,
+                "prompt_n": 3,
+                "predicted_per_second": 35.1
+            },
+            "stop": true,
+            "stopped_eos": false,
+            "stopped_limit": false,
+            "stopped_word": true,
+            "stopping_word": "null",
+            "tokens_cached": 4,
+            "tokens_evaluated": 3,
+            "truncated": false
+        }"#,
+    )
+    .unwrap();
+    let response = |finish_reason: CompletionFinishReason| CompletionResponse {
+        id: "llama_cpp".to_owned(),
+        index: None,
+        content: res.content.clone(),
+        thinking: None,
+        citations: None,
+        finish_reason,
+        completion_probabilities: None,
+        truncated: res.truncated,
+        content_truncated: false,
+        generation_settings: GenerationSettings::new_from_llama(&res),
+        timing_usage: TimingUsage::new_from_llama(&res, std::time::Instant::now()),
+        token_usage: TokenUsage::new_from_llama(&res),
+    };
+
+    // The "no result" stop word is the answer.
+    assert!(!response(CompletionFinishReason::MatchingStoppingSequence(
+        StoppingSequence::NoResult("null".to_owned())
+    ))
+    .is_empty());
+    // Any other stop word leaves the response without an answer, so it's retried.
+    assert!(response(CompletionFinishReason::MatchingStoppingSequence(
+        StoppingSequence::InferenceDone("done".to_owned())
+    ))
+    .is_empty());
+    assert!(
+        response(CompletionFinishReason::NonMatchingStoppingSequence(Some(
+            "\n".to_owned()
+        )))
+        .is_empty()
+    );
+    assert!(response(CompletionFinishReason::Eos).is_empty());
+}
@@ -98,6 +98,18 @@ pub fn model_tokenizer(_model_id: &str) -> Arc<LlmTokenizer> {
 pub trait AnthropicModelTrait: Sized {
     fn model(&mut self) -> &mut ApiLlmModel;
 
+    /// Overrides the model's price per million tokens, used to estimate the cost of each request.
+    /// Prices change, so the built in prices may be out of date.
+    /// Must be set after the model, as setting the model resets its prices.
+    fn model_pricing(mut self, cost_per_m_in_tokens: f32, cost_per_m_out_tokens: f32) -> Self
+    where
+        Self: Sized,
+    {
+        self.model().cost_per_m_in_tokens = cost_per_m_in_tokens;
+        self.model().cost_per_m_out_tokens = cost_per_m_out_tokens;
+        self
+    }
+
     /// Set the model using the model_id string.
     fn model_id_str(mut self, model_id: &str) -> Self
     where
@@ -1,6 +1,7 @@
 use super::LlmModelBase;
 
 pub mod anthropic;
+pub mod ollama;
 pub mod openai;
 pub mod perplexity;
 
@@ -13,6 +14,36 @@ pub struct ApiLlmModel {
     pub tokens_per_name: Option<i32>,
 }
 
+impl ApiLlmModel {
+    /// The estimated cost in US dollars of a request, from [ApiLlmModel::cost_per_m_in_tokens] and [ApiLlmModel::cost_per_m_out_tokens].
+    pub fn estimated_cost_usd(&self, prompt_tokens: u32, completion_tokens: u32) -> f32 {
+        (prompt_tokens as f32 * self.cost_per_m_in_tokens
+            + completion_tokens as f32 * self.cost_per_m_out_tokens)
+            / 1_000_000.0
+    }
+}
+
+impl ApiLlmModel {
+    /// A model served by a self-hosted OpenAI compatible server, e.g. vLLM or LocalAI. The server may serve any model,
+    /// so the tokenizer used for counting prompt tokens is an approximation, and the model is free.
+    pub fn openai_compatible_model(model_id: &str, model_ctx_size: u64) -> ApiLlmModel {
+        let model_id = model_id.to_string();
+        let tokenizer = perplexity::model_tokenizer(&model_id);
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size,
+                inference_ctx_size: model_ctx_size,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 0.0,
+            cost_per_m_out_tokens: 0.0,
+            tokens_per_message: 3,
+            tokens_per_name: None,
+        }
+    }
+}
+
 impl Default for ApiLlmModel {
     fn default() -> Self {
         Self::gpt_4_o_mini()
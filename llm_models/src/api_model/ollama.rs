@@ -0,0 +1,38 @@
+use super::ApiLlmModel;
+
+/// Ollama's default model when none is set.
+pub const OLLAMA_DEFAULT_MODEL_ID: &str = "llama3.2";
+/// The context size sent to Ollama as `num_ctx` unless set. Ollama's own default is small, so this is set explicitly.
+pub const OLLAMA_DEFAULT_CTX_SIZE: u64 = 8192;
+
+impl ApiLlmModel {
+    /// A model served by Ollama. Ollama serves arbitrary models, so the tokenizer used for counting
+    /// prompt tokens is an approximation.
+    pub fn ollama_model(model_id: &str, model_ctx_size: u64) -> ApiLlmModel {
+        Self::openai_compatible_model(model_id, model_ctx_size)
+    }
+}
+
+pub trait OllamaModelTrait: Sized {
+    fn model(&mut self) -> &mut ApiLlmModel;
+
+    /// Set the model using the model name as shown by `ollama list`, e.g. `llama3.2:3b`.
+    fn model_id_str(mut self, model_id: &str) -> Self
+    where
+        Self: Sized,
+    {
+        let model_ctx_size = self.model().model_base.model_ctx_size;
+        *self.model() = ApiLlmModel::ollama_model(model_id, model_ctx_size);
+        self
+    }
+
+    /// Set the context size. This is sent to Ollama as the `num_ctx` option, so the server and the client agree on it.
+    fn model_ctx_size(mut self, model_ctx_size: u64) -> Self
+    where
+        Self: Sized,
+    {
+        self.model().model_base.model_ctx_size = model_ctx_size;
+        self.model().model_base.inference_ctx_size = model_ctx_size;
+        self
+    }
+}
@@ -11,6 +11,8 @@ impl ApiLlmModel {
             "gpt-4o" => Self::gpt_4_o(),
             "gpt-3.5-turbo" => Self::gpt_3_5_turbo(),
             "gpt-4o-mini" => Self::gpt_3_5_turbo(),
+            "gpt-4o-search-preview" => Self::gpt_4_o_search_preview(),
+            "gpt-4o-mini-search-preview" => Self::gpt_4_o_mini_search_preview(),
             _ => panic!("Model ID ({model_id}) not found for ApiLlmModel"),
         }
     }
@@ -150,6 +152,42 @@ impl ApiLlmModel {
             tokens_per_name: Some(-1),
         }
     }
+
+    /// gpt-4o with built in web search. The response's citations are returned as URL annotations.
+    pub fn gpt_4_o_search_preview() -> ApiLlmModel {
+        let model_id = "gpt-4o-search-preview".to_string();
+        let tokenizer = model_tokenizer("gpt-4o");
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size: 128000,
+                inference_ctx_size: 16384,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 2.50,
+            cost_per_m_out_tokens: 10.00,
+            tokens_per_message: 3,
+            tokens_per_name: Some(1),
+        }
+    }
+
+    /// gpt-4o-mini with built in web search. The response's citations are returned as URL annotations.
+    pub fn gpt_4_o_mini_search_preview() -> ApiLlmModel {
+        let model_id = "gpt-4o-mini-search-preview".to_string();
+        let tokenizer = model_tokenizer("gpt-4o-mini");
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size: 128000,
+                inference_ctx_size: 16384,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 0.15,
+            cost_per_m_out_tokens: 0.60,
+            tokens_per_message: 3,
+            tokens_per_name: Some(1),
+        }
+    }
 }
 
 fn model_tokenizer(model_id: &str) -> Arc<LlmTokenizer> {
@@ -162,6 +200,18 @@ fn model_tokenizer(model_id: &str) -> Arc<LlmTokenizer> {
 pub trait OpenAiModelTrait {
     fn model(&mut self) -> &mut ApiLlmModel;
 
+    /// Overrides the model's price per million tokens, used to estimate the cost of each request.
+    /// Prices change, so the built in prices may be out of date.
+    /// Must be set after the model, as setting the model resets its prices.
+    fn model_pricing(mut self, cost_per_m_in_tokens: f32, cost_per_m_out_tokens: f32) -> Self
+    where
+        Self: Sized,
+    {
+        self.model().cost_per_m_in_tokens = cost_per_m_in_tokens;
+        self.model().cost_per_m_out_tokens = cost_per_m_out_tokens;
+        self
+    }
+
     /// Set the model using the model_id string.
     fn model_id_str(mut self, model_id: &str) -> Self
     where
@@ -216,6 +266,24 @@ pub trait OpenAiModelTrait {
         self
     }
 
+    /// Use gpt-4o-search-preview as the model for the OpenAI client. Searches the web before answering.
+    fn gpt_4_o_search_preview(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        *self.model() = ApiLlmModel::gpt_4_o_search_preview();
+        self
+    }
+
+    /// Use gpt-4o-mini-search-preview as the model for the OpenAI client. Searches the web before answering.
+    fn gpt_4_o_mini_search_preview(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        *self.model() = ApiLlmModel::gpt_4_o_mini_search_preview();
+        self
+    }
+
     fn o1_preview<T: Into<Option<bool>>>(mut self) -> Self
     where
         Self: Sized,
@@ -4,7 +4,17 @@ use std::sync::Arc;
 
 impl ApiLlmModel {
     pub fn perplexity_model_from_model_id(model_id: &str) -> ApiLlmModel {
-        if model_id.starts_with("llama-3.1-sonar-small") {
+        if model_id == "sonar" {
+            Self::sonar()
+        } else if model_id == "sonar-pro" {
+            Self::sonar_pro()
+        } else if model_id == "sonar-reasoning" {
+            Self::sonar_reasoning()
+        } else if model_id == "sonar-reasoning-pro" {
+            Self::sonar_reasoning_pro()
+        } else if model_id == "sonar-deep-research" {
+            Self::sonar_deep_research()
+        } else if model_id.starts_with("llama-3.1-sonar-small") {
             Self::sonar_small()
         } else if model_id.starts_with("llama-3.1-sonar-large") {
             Self::sonar_large()
@@ -21,6 +31,97 @@ impl ApiLlmModel {
         }
     }
 
+    /// Lightweight search model.
+    pub fn sonar() -> ApiLlmModel {
+        let model_id = "sonar".to_string();
+        let tokenizer = model_tokenizer(&model_id);
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size: 127072,
+                inference_ctx_size: 8192,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 1.0,
+            cost_per_m_out_tokens: 1.0,
+            tokens_per_message: 3,
+            tokens_per_name: None,
+        }
+    }
+
+    /// Search model for complex queries, with about twice as many citations as `sonar`.
+    pub fn sonar_pro() -> ApiLlmModel {
+        let model_id = "sonar-pro".to_string();
+        let tokenizer = model_tokenizer(&model_id);
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size: 200000,
+                inference_ctx_size: 8192,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 3.0,
+            cost_per_m_out_tokens: 15.0,
+            tokens_per_message: 3,
+            tokens_per_name: None,
+        }
+    }
+
+    /// Search model that reasons in a `<think>` block before answering.
+    pub fn sonar_reasoning() -> ApiLlmModel {
+        let model_id = "sonar-reasoning".to_string();
+        let tokenizer = model_tokenizer(&model_id);
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size: 127072,
+                inference_ctx_size: 8192,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 1.0,
+            cost_per_m_out_tokens: 5.0,
+            tokens_per_message: 3,
+            tokens_per_name: None,
+        }
+    }
+
+    /// Search model with stronger reasoning in a `<think>` block before answering.
+    pub fn sonar_reasoning_pro() -> ApiLlmModel {
+        let model_id = "sonar-reasoning-pro".to_string();
+        let tokenizer = model_tokenizer(&model_id);
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size: 127072,
+                inference_ctx_size: 8192,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 2.0,
+            cost_per_m_out_tokens: 8.0,
+            tokens_per_message: 3,
+            tokens_per_name: None,
+        }
+    }
+
+    /// Runs many searches and writes a long report. Slow, and billed extra per search.
+    pub fn sonar_deep_research() -> ApiLlmModel {
+        let model_id = "sonar-deep-research".to_string();
+        let tokenizer = model_tokenizer(&model_id);
+        ApiLlmModel {
+            model_base: LlmModelBase {
+                model_id,
+                model_ctx_size: 127072,
+                inference_ctx_size: 8192,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 2.0,
+            cost_per_m_out_tokens: 8.0,
+            tokens_per_message: 3,
+            tokens_per_name: None,
+        }
+    }
+
+    /// Legacy Llama 3.1 model, retired by Perplexity in favor of `sonar`.
     pub fn sonar_small() -> ApiLlmModel {
         let model_id = "llama-3.1-sonar-small-128k-online".to_string();
         let tokenizer = model_tokenizer(&model_id);
@@ -38,6 +139,7 @@ impl ApiLlmModel {
         }
     }
 
+    /// Legacy Llama 3.1 model, retired by Perplexity in favor of `sonar_pro`.
     pub fn sonar_large() -> ApiLlmModel {
         let model_id = "llama-3.1-sonar-large-128k-online".to_string();
         let tokenizer = model_tokenizer(&model_id);
@@ -55,6 +157,7 @@ impl ApiLlmModel {
         }
     }
 
+    /// Legacy Llama 3.1 model, retired by Perplexity in favor of `sonar_pro`.
     pub fn sonar_huge() -> ApiLlmModel {
         let model_id = "llama-3.1-sonar-huge-128k-online".to_string();
         let tokenizer = model_tokenizer(&model_id);
@@ -83,6 +186,18 @@ pub fn model_tokenizer(_model_id: &str) -> Arc<LlmTokenizer> {
 pub trait PerplexityModelTrait: Sized {
     fn model(&mut self) -> &mut ApiLlmModel;
 
+    /// Overrides the model's price per million tokens, used to estimate the cost of each request.
+    /// Prices change, so the built in prices may be out of date.
+    /// Must be set after the model, as setting the model resets its prices.
+    fn model_pricing(mut self, cost_per_m_in_tokens: f32, cost_per_m_out_tokens: f32) -> Self
+    where
+        Self: Sized,
+    {
+        self.model().cost_per_m_in_tokens = cost_per_m_in_tokens;
+        self.model().cost_per_m_out_tokens = cost_per_m_out_tokens;
+        self
+    }
+
     /// Set the model using the model_id string.
     fn model_id_str(mut self, model_id: &str) -> Self
     where
@@ -92,6 +207,46 @@ pub trait PerplexityModelTrait: Sized {
         self
     }
 
+    fn sonar(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        *self.model() = ApiLlmModel::sonar();
+        self
+    }
+
+    fn sonar_pro(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        *self.model() = ApiLlmModel::sonar_pro();
+        self
+    }
+
+    fn sonar_reasoning(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        *self.model() = ApiLlmModel::sonar_reasoning();
+        self
+    }
+
+    fn sonar_reasoning_pro(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        *self.model() = ApiLlmModel::sonar_reasoning_pro();
+        self
+    }
+
+    fn sonar_deep_research(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        *self.model() = ApiLlmModel::sonar_deep_research();
+        self
+    }
+
     fn sonar_small(mut self) -> Self
     where
         Self: Sized,
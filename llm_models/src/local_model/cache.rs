@@ -0,0 +1,134 @@
+//! Lists the GGUF models that are already downloaded, without contacting Hugging Face.
+use super::{
+    gguf::tools::gguf_file::GgufFile, hf_loader::HuggingFaceLoader, metadata::general::FileType,
+};
+use std::path::{Path, PathBuf};
+
+/// A GGUF model found in the cache, with metadata read from the file header.
+#[derive(Debug, Clone)]
+pub struct LocalModelInfo {
+    /// The Hugging Face repo the file was downloaded from, e.g. `bartowski/Meta-Llama-3.1-8B-Instruct-GGUF`.
+    /// `None` if the file isn't in a Hugging Face cache layout.
+    pub repo_id: Option<String>,
+    pub file_name: String,
+    pub local_model_path: PathBuf,
+    /// Size on disk in bytes.
+    pub file_size_bytes: u64,
+    pub architecture: Option<String>,
+    /// `general.name` from the header.
+    pub name: Option<String>,
+    /// The quantization type, e.g. `Q4_K_M`. `None` if the header doesn't specify it.
+    pub quantization: Option<String>,
+    /// The total parameter count, summed from the tensors in the file.
+    pub parameter_count: u64,
+    /// `general.size_label` from the header, e.g. `8B`.
+    pub size_label: Option<String>,
+    /// The context length the model was trained with.
+    pub context_length: Option<u64>,
+}
+
+/// Lists the GGUF models in the Hugging Face cache. The cache is at `$HF_HOME/hub`, or `~/.cache/huggingface/hub` if `HF_HOME` isn't set.
+pub fn list_local_models() -> crate::Result<Vec<LocalModelInfo>> {
+    list_local_models_in(HuggingFaceLoader::cache_dir())
+}
+
+/// Lists the GGUF models in `cache_dir` and its subdirectories.
+/// Files that are corrupt or only partially downloaded are skipped with a warning.
+pub fn list_local_models_in<P: AsRef<Path>>(cache_dir: P) -> crate::Result<Vec<LocalModelInfo>> {
+    let cache_dir = cache_dir.as_ref();
+    let mut gguf_paths = vec![];
+    if cache_dir.exists() {
+        find_gguf_files(cache_dir, &mut gguf_paths)?;
+    }
+
+    let mut models: Vec<LocalModelInfo> = vec![];
+    let mut seen_paths = vec![];
+    for path in gguf_paths {
+        // Files in different snapshots of a repo can link to the same blob.
+        let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if seen_paths.contains(&canonical_path) {
+            continue;
+        }
+        seen_paths.push(canonical_path);
+        match LocalModelInfo::from_gguf_path(&path) {
+            Ok(model) => models.push(model),
+            Err(e) => crate::warn!("Skipping GGUF file {}: {}", path.display(), e),
+        }
+    }
+    models.sort_by(|a, b| (&a.repo_id, &a.file_name).cmp(&(&b.repo_id, &b.file_name)));
+    Ok(models)
+}
+
+impl LocalModelInfo {
+    pub fn from_gguf_path(path: &Path) -> crate::Result<Self> {
+        let file_size_bytes = std::fs::metadata(path)?.len();
+        let mut reader = std::fs::File::open(path)?;
+        let gguf = GgufFile::read(&mut reader)?;
+
+        let expected_size = gguf
+            .tensors
+            .iter()
+            .map(|tensor| gguf.tensor_data_offset + tensor.offset + tensor.size())
+            .max()
+            .unwrap_or(gguf.tensor_data_offset);
+        if file_size_bytes < expected_size {
+            crate::bail!(
+                "file is {file_size_bytes} bytes but its tensors need {expected_size} bytes. It may be partially downloaded."
+            );
+        }
+
+        let architecture: Option<String> = gguf.get_value("general.architecture")?;
+        let context_length: Option<u64> = match &architecture {
+            Some(architecture) => gguf.get_value(&format!("{architecture}.context_length"))?,
+            None => None,
+        };
+        let file_type: Option<u32> = gguf.get_value("general.file_type")?;
+        Ok(Self {
+            repo_id: repo_id_from_cache_path(path),
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            local_model_path: path.to_path_buf(),
+            file_size_bytes,
+            architecture,
+            name: gguf.get_value("general.name")?,
+            quantization: file_type
+                .and_then(|file_type| FileType::from_u32(file_type).ok())
+                .map(|file_type| file_type.label().to_owned()),
+            parameter_count: gguf.tensors.iter().map(|tensor| tensor.parameters()).sum(),
+            size_label: gguf.get_value("general.size_label")?,
+            context_length,
+        })
+    }
+}
+
+fn find_gguf_files(dir: &Path, gguf_paths: &mut Vec<PathBuf>) -> crate::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        // The cache's blobs are reached through the snapshot links, which have the real file names.
+        if path.file_name().is_some_and(|name| name == "blobs") {
+            continue;
+        }
+        if path.is_dir() {
+            find_gguf_files(&path, gguf_paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gguf"))
+        {
+            gguf_paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Hugging Face caches repos in directories named `models--{owner}--{name}`.
+fn repo_id_from_cache_path(path: &Path) -> Option<String> {
+    path.ancestors().find_map(|ancestor| {
+        ancestor
+            .file_name()?
+            .to_str()?
+            .strip_prefix("models--")
+            .map(|repo| repo.replacen("--", "/", 1))
+    })
+}
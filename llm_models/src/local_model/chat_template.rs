@@ -20,6 +20,12 @@ impl LlmChatTemplate {
         let file = std::fs::File::open(tokenizer_config_local_path)?;
         let reader = std::io::BufReader::new(file);
         let mut chat_template: LlmChatTemplate = serde_json::from_reader(reader)?;
+        chat_template.validate_chat_template().with_context(|| {
+            format!(
+                "Invalid chat template in {}",
+                tokenizer_config_local_path.display()
+            )
+        })?;
         chat_template.set_generation_prefix()?;
         Ok(chat_template)
     }
@@ -66,10 +72,48 @@ impl LlmChatTemplate {
             unk_token,
             base_generation_prefix: None,
         };
+        chat_template
+            .validate_chat_template()
+            .context("Invalid chat template in GGUF metadata")?;
         chat_template.set_generation_prefix()?;
         Ok(chat_template)
     }
 
+    /// Embedding models don't have a chat template. This placeholder only carries the special tokens,
+    /// and the model can't be used to build chat prompts.
+    pub fn new_for_embedding_model(tokenizer: &TokenizerMetadata) -> Self {
+        let token = |id: Option<u32>| {
+            let ggml = tokenizer.ggml.as_ref()?;
+            ggml.tokens.get(id? as usize).map(ToString::to_string)
+        };
+        let ggml = tokenizer.ggml.as_ref();
+        LlmChatTemplate {
+            chat_template: String::new(),
+            bos_token: token(ggml.map(|ggml| ggml.bos_token_id)),
+            eos_token: token(ggml.map(|ggml| ggml.eos_token_id)).unwrap_or_default(),
+            unk_token: token(ggml.and_then(|ggml| ggml.unknown_token_id)),
+            base_generation_prefix: None,
+        }
+    }
+
+    /// Whether the template handles a `<think>` block, as the templates of reasoning models like DeepSeek-R1, QwQ,
+    /// and Qwen3 do, e.g. to strip the thinking from earlier turns.
+    pub fn has_thinking_tags(&self) -> bool {
+        self.chat_template.contains("</think>")
+    }
+
+    /// Checks that the chat template compiles and renders a basic conversation.
+    /// Called when the template is loaded so broken templates fail before the first request.
+    pub fn validate_chat_template(&self) -> crate::Result<()> {
+        llm_prompt::validate_chat_template(
+            &self.chat_template,
+            self.bos_token.as_deref(),
+            &self.eos_token,
+            self.unk_token.as_deref(),
+        )?;
+        Ok(())
+    }
+
     fn set_generation_prefix(&mut self) -> crate::Result<()> {
         let user_message_1 = HashMap::from([
             ("role".to_string(), "user".to_string()),
@@ -84,22 +128,22 @@ impl LlmChatTemplate {
         ]);
 
         let message_1 = llm_prompt::apply_chat_template(
-            &vec![user_message_1.clone()],
+            std::slice::from_ref(&user_message_1),
             &self.chat_template,
             self.bos_token.as_deref(),
             &self.eos_token,
             self.unk_token.as_deref(),
-        );
+        )?;
         let message_1 = message_1
             .trim_end_matches(self.eos_token.as_str())
             .to_owned();
         let message_2 = llm_prompt::apply_chat_template(
-            &vec![user_message_1, assistant_message_1],
+            &[user_message_1, assistant_message_1],
             &self.chat_template,
             self.bos_token.as_deref(),
             &self.eos_token,
             self.unk_token.as_deref(),
-        );
+        )?;
 
         // Find the point where the outputs start to differ
         let diff_index = message_1
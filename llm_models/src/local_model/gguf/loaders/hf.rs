@@ -1,11 +1,20 @@
+use super::{
+    hf_repo::{fallback_ctx_memory_size, list_repo_gguf_files, select_repo_quant},
+    preset::DEFAULT_PRESET_CONTEXT_LENGTH,
+};
 use crate::{
-    local_model::{hf_loader::HuggingFaceLoader, metadata::LocalLlmMetadata, LocalLlmModel},
+    local_model::{
+        hf_loader::HuggingFaceLoader,
+        metadata::{config_json::ConfigJson, LocalLlmMetadata},
+        LocalLlmModel,
+    },
     LlmModelBase,
 };
 
 #[derive(Default, Clone)]
 pub struct GgufHfLoader {
     pub hf_quant_file_url: Option<String>,
+    pub hf_repo_id: Option<String>,
     pub hf_config_repo_id: Option<String>,
     pub hf_tokenizer_repo_id: Option<String>,
     pub hf_tokenizer_config_repo_id: Option<String>,
@@ -20,11 +29,83 @@ impl GgufHfLoader {
             crate::bail!("local_quant_file_path must be set")
         };
 
-        let (model_id, repo_id, gguf_model_filename) =
-            HuggingFaceLoader::parse_full_model_url(&hf_quant_file_url);
+        let url = HuggingFaceLoader::parse_gguf_url(&hf_quant_file_url)?;
+        self.load_model_file(
+            hf_loader,
+            url.model_id,
+            url.repo_id,
+            url.revision,
+            url.file_name,
+        )
+    }
 
+    /// Lists the quant files in `hf_repo_id` and loads the one picked by [select_repo_quant].
+    ///
+    /// Picking by `available_vram_bytes` leaves room for a context of `max_ctx_size` tokens, or
+    /// [DEFAULT_PRESET_CONTEXT_LENGTH], as estimated from the model's config.json. It's loaded from `hf_config_repo_id`,
+    /// or from the GGUF repo itself, with a KV cache of `kv_cache_bits`. If neither has one, a fifth of the VRAM is left for the context.
+    /// See [fallback_ctx_memory_size].
+    pub fn load_from_repo(
+        &mut self,
+        hf_loader: &HuggingFaceLoader,
+        available_vram_bytes: Option<u64>,
+        max_ctx_size: Option<u64>,
+        quantization_level: Option<u8>,
+        kv_cache_bits: Option<f64>,
+    ) -> crate::Result<LocalLlmModel> {
+        let repo_id = if let Some(hf_repo_id) = self.hf_repo_id.as_ref() {
+            hf_repo_id.to_owned()
+        } else {
+            crate::bail!("hf_repo_id must be set")
+        };
+        let files = list_repo_gguf_files(hf_loader, &repo_id)?;
+        let ctx_memory_size_bytes = match available_vram_bytes {
+            Some(available_vram_bytes) if quantization_level.is_none() => {
+                let config_repo_id = self.hf_config_repo_id.as_deref().unwrap_or(&repo_id);
+                match hf_loader
+                    .load_file("config.json", config_repo_id)
+                    .and_then(|path| ConfigJson::from_local_path(&path))
+                {
+                    Ok(config_json) => {
+                        let ctx_size = max_ctx_size
+                            .unwrap_or(DEFAULT_PRESET_CONTEXT_LENGTH)
+                            .min(config_json.context_length);
+                        config_json.estimate_context_size(ctx_size, kv_cache_bits)
+                    }
+                    Err(e) => {
+                        crate::warn!(
+                            "Failed to load config.json from {config_repo_id} to estimate the context size: {e}. \
+                            Leaving a fifth of the VRAM for the context. Set hf_config_repo_id to the model's original repo for a better estimate."
+                        );
+                        fallback_ctx_memory_size(available_vram_bytes)
+                    }
+                }
+            }
+            _ => 0,
+        };
+        let file = select_repo_quant(
+            &files,
+            available_vram_bytes,
+            ctx_memory_size_bytes,
+            quantization_level,
+        )
+        .map_err(|e| crate::anyhow!("Failed to pick a quant from {repo_id}: {e}"))?;
+        crate::info!("Selected {} from {}", file.file_name, repo_id);
+        let model_id = repo_id.rsplit('/').next().unwrap_or(&repo_id).to_owned();
+        let file_name = file.file_name.clone();
+        self.load_model_file(hf_loader, model_id, repo_id, "main".to_owned(), file_name)
+    }
+
+    fn load_model_file(
+        &self,
+        hf_loader: &HuggingFaceLoader,
+        model_id: String,
+        repo_id: String,
+        revision: String,
+        gguf_model_filename: String,
+    ) -> crate::Result<LocalLlmModel> {
         let local_model_path = HuggingFaceLoader::canonicalize_local_path(
-            hf_loader.load_file(gguf_model_filename, repo_id)?,
+            hf_loader.load_file_at_revision(gguf_model_filename, repo_id, revision)?,
         )?;
 
         let local_tokenizer_path = if let Some(hf_tokenizer_repo_id) = &self.hf_tokenizer_repo_id {
@@ -0,0 +1,180 @@
+//! Picks a quant file from a Hugging Face GGUF repo, for loading a model from just its repo id.
+use crate::local_model::hf_loader::HuggingFaceLoader;
+
+/// Quant labels that can be auto-selected, and their approximate bits per weight.
+/// Within the same bits, the labels are in order of preference.
+const QUANT_LABELS: &[(&str, u8)] = &[
+    ("Q8_0", 8),
+    ("Q6_K_L", 6),
+    ("Q6_K", 6),
+    ("Q5_K_M", 5),
+    ("Q5_K_L", 5),
+    ("Q5_K_S", 5),
+    ("Q5_1", 5),
+    ("Q5_0", 5),
+    ("Q4_K_M", 4),
+    ("Q4_K_L", 4),
+    ("Q4_K_S", 4),
+    ("IQ4_XS", 4),
+    ("IQ4_NL", 4),
+    ("Q4_1", 4),
+    ("Q4_0", 4),
+    ("Q3_K_L", 3),
+    ("Q3_K_M", 3),
+    ("Q3_K_XL", 3),
+    ("Q3_K_S", 3),
+    ("IQ3_M", 3),
+    ("IQ3_XS", 3),
+    ("IQ3_XXS", 3),
+    ("Q2_K_L", 2),
+    ("Q2_K", 2),
+    ("IQ2_M", 2),
+    ("IQ2_S", 2),
+    ("IQ2_XS", 2),
+    ("IQ2_XXS", 2),
+    ("IQ1_M", 1),
+    ("IQ1_S", 1),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HfRepoGgufFile {
+    pub file_name: String,
+    /// The quant type from the file name, e.g. `Q4_K_M`.
+    pub quant_label: String,
+    pub q_bits: u8,
+    /// `None` if Hugging Face didn't report the size.
+    pub size_bytes: Option<u64>,
+}
+
+impl HfRepoGgufFile {
+    /// Returns `None` for files that aren't a single-file GGUF quant, such as split files, mmproj files, and F16 files.
+    pub fn from_file_name(file_name: &str, size_bytes: Option<u64>) -> Option<Self> {
+        let upper = file_name.to_uppercase();
+        let stem = upper.strip_suffix(".GGUF")?;
+        if stem.contains("MMPROJ") || stem.contains("-OF-0") {
+            return None;
+        }
+        QUANT_LABELS.iter().find_map(|(label, q_bits)| {
+            let prefix = stem.strip_suffix(label)?;
+            if prefix.is_empty() || prefix.ends_with(['.', '-', '_']) {
+                Some(Self {
+                    file_name: file_name.to_owned(),
+                    quant_label: label.to_string(),
+                    q_bits: *q_bits,
+                    size_bytes,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    fn preference(&self) -> usize {
+        QUANT_LABELS
+            .iter()
+            .position(|(label, _)| *label == self.quant_label)
+            .unwrap_or(usize::MAX)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RepoInfoWithBlobs {
+    siblings: Vec<SiblingWithBlob>,
+}
+
+#[derive(serde::Deserialize)]
+struct SiblingWithBlob {
+    rfilename: String,
+    size: Option<u64>,
+}
+
+/// Lists the GGUF quant files in a Hugging Face repo, with their sizes.
+pub fn list_repo_gguf_files(
+    hf_loader: &HuggingFaceLoader,
+    repo_id: &str,
+) -> crate::Result<Vec<HfRepoGgufFile>> {
+    let repo_info: RepoInfoWithBlobs = hf_loader
+        .hf_api()
+        .model(repo_id.to_owned())
+        .info_request()
+        .query("blobs", "true")
+        .call()
+        .map_err(|e| crate::anyhow!("Failed to list files in {repo_id}: {e}"))?
+        .into_json()?;
+    Ok(repo_info
+        .siblings
+        .iter()
+        .filter_map(|sibling| HfRepoGgufFile::from_file_name(&sibling.rfilename, sibling.size))
+        .collect())
+}
+
+/// The share of VRAM left for the context when it can't be estimated, because the repo has no config.json.
+/// GGUF-only repos often don't include one, and the GGUF header isn't available until a quant is picked.
+const FALLBACK_CTX_MEMORY_DIVISOR: u64 = 5;
+
+/// A rough estimate of the context's memory for [select_repo_quant] when the model's config.json can't be loaded:
+/// a fifth of `available_vram_bytes`.
+pub fn fallback_ctx_memory_size(available_vram_bytes: u64) -> u64 {
+    available_vram_bytes / FALLBACK_CTX_MEMORY_DIVISOR
+}
+
+/// Picks the quant to load from a repo's GGUF files.
+///
+/// * With `quantization_level`, picks a quant with exactly that many bits.
+/// * With `available_vram_bytes`, picks the largest quant whose file fits in the VRAM left after `ctx_memory_size_bytes`,
+///   the estimated size of the KV cache and buffers for the context. See [crate::local_model::gguf::memory::estimate_context_size].
+/// * Otherwise picks the largest quant up to 8 bits.
+///
+/// Errors with the list of available quants if none match.
+pub fn select_repo_quant(
+    files: &[HfRepoGgufFile],
+    available_vram_bytes: Option<u64>,
+    ctx_memory_size_bytes: u64,
+    quantization_level: Option<u8>,
+) -> crate::Result<&HfRepoGgufFile> {
+    let mut candidates: Vec<&HfRepoGgufFile> = files.iter().collect();
+    candidates.sort_by_key(|file| (std::cmp::Reverse(file.q_bits), file.preference()));
+
+    let selected = if let Some(q_bits) = quantization_level {
+        candidates.into_iter().find(|file| file.q_bits == q_bits)
+    } else if let Some(available_vram_bytes) = available_vram_bytes {
+        let model_vram_bytes = available_vram_bytes.saturating_sub(ctx_memory_size_bytes);
+        candidates
+            .into_iter()
+            .find(|file| file.size_bytes.is_some_and(|size| size <= model_vram_bytes))
+    } else {
+        candidates.into_iter().next()
+    };
+
+    match selected {
+        Some(file) => Ok(file),
+        None => {
+            let available: Vec<String> = files
+                .iter()
+                .map(|file| match file.size_bytes {
+                    Some(size) => format!(
+                        "{} ({:.2} GB)",
+                        file.quant_label,
+                        size as f64 / 1024.0 / 1024.0 / 1024.0
+                    ),
+                    None => file.quant_label.clone(),
+                })
+                .collect();
+            if let Some(q_bits) = quantization_level {
+                crate::bail!(
+                    "No {q_bits} bit quant found. Available quants: [{}]",
+                    available.join(", ")
+                )
+            } else if let Some(available_vram_bytes) = available_vram_bytes {
+                crate::bail!(
+                    "No quant fits in {:.2} GB of VRAM with {:.2} GB for the context. Available quants: [{}]",
+                    available_vram_bytes as f64 / 1024.0 / 1024.0 / 1024.0,
+                    ctx_memory_size_bytes as f64 / 1024.0 / 1024.0 / 1024.0,
+                    available.join(", ")
+                )
+            } else {
+                crate::bail!("No GGUF quant files found.")
+            }
+        }
+    }
+}
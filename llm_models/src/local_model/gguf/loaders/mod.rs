@@ -1,3 +1,4 @@
 pub mod hf;
+pub mod hf_repo;
 pub mod local;
 pub mod preset;
@@ -4,7 +4,7 @@ use crate::local_model::{
     },
     hf_loader::HuggingFaceLoader,
     metadata::LocalLlmMetadata,
-    LocalLlmModel,
+    LlmChatTemplate, LocalLlmModel,
 };
 pub(crate) const DEFAULT_PRESET_CONTEXT_LENGTH: u64 = 4096;
 
@@ -15,6 +15,8 @@ pub struct GgufPresetLoader {
     pub preset_with_available_vram_bytes: Option<u64>,
     pub preset_with_max_ctx_size: Option<u64>,
     pub preset_with_quantization_level: Option<u8>,
+    /// The average bits per element of the KV cache the model will run with, used to estimate the context's size when picking a quant.
+    pub preset_with_kv_cache_bits: Option<f64>,
 }
 
 impl Default for GgufPresetLoader {
@@ -25,6 +27,7 @@ impl Default for GgufPresetLoader {
             preset_with_available_vram_bytes: None,
             preset_with_max_ctx_size: None,
             preset_with_quantization_level: None,
+            preset_with_kv_cache_bits: None,
         }
     }
 }
@@ -50,16 +53,22 @@ impl GgufPresetLoader {
                     &model_metadata,
                 )?,
             },
-            chat_template: load_chat_template(
-                &Some(self.llm_preset.load_tokenizer_config(hf_loader)?),
-                &model_metadata,
-            )?,
+            chat_template: if self.llm_preset.is_embedding_model() {
+                LlmChatTemplate::new_for_embedding_model(&model_metadata.tokenizer)
+            } else {
+                load_chat_template(
+                    &Some(self.llm_preset.load_tokenizer_config(hf_loader)?),
+                    &model_metadata,
+                )?
+            },
             model_metadata,
             local_model_path,
         })
     }
 
-    fn select_quant(&mut self) -> crate::Result<String> {
+    /// Picks the preset's quant file name for `preset_with_quantization_level`, or the largest that fits in the available VRAM
+    /// alongside a context of `preset_with_max_ctx_size` tokens with a KV cache of `preset_with_kv_cache_bits`.
+    pub fn select_quant(&mut self) -> crate::Result<String> {
         let config_json = self.llm_preset.config_json()?;
 
         let ctx_size = if let Some(preset_with_max_ctx_size) = self.preset_with_max_ctx_size {
@@ -93,7 +102,8 @@ impl GgufPresetLoader {
                 );
             }
         } else {
-            let ctx_memory_size_bytes = config_json.estimate_context_size(ctx_size as u64);
+            let ctx_memory_size_bytes =
+                config_json.estimate_context_size(ctx_size, self.preset_with_kv_cache_bits);
 
             let initial_q_bits = estimate_quantization_level(
                 self.llm_preset.number_of_parameters(),
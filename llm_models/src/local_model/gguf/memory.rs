@@ -50,6 +50,7 @@ pub(crate) fn estimate_model_size(params: f64, dtype: GgmlDType) -> f64 {
 
 // // This is converted from https://github.com/pandora-s-git/LLMVRAMCalculator/blob/main/LLMVRAMCalculator/LLMVRAMCalculator.py
 // also see https://gist.github.com/jrruethe/8974d2c8b4ece242a071d1a1526aa763
+/// `kv_cache_bits` is the average bits per element of the K and V caches. See [KvCacheType::average_bits]. Defaults to 16 (f16).
 pub fn estimate_context_size(
     ctx_size: u64,
     embedding_length: u64,
@@ -57,6 +58,7 @@ pub fn estimate_context_size(
     head_count_kv: u64,
     block_count: u64,
     batch_size: Option<u64>,
+    kv_cache_bits: Option<f64>,
 ) -> u64 {
     let batch_size = batch_size.unwrap_or(512);
     // Input buffer
@@ -67,17 +69,74 @@ pub fn estimate_context_size(
     let compute_buffer =
         (ctx_size as f64 / 1024f64 * 2f64 + 0.75) * head_count as f64 * 1024f64 * 1024f64;
     // Key-value cache
-    let cache_bit = 16;
+    let cache_bit = kv_cache_bits.unwrap_or(16.0);
     let gqa = head_count / head_count_kv;
     let n_embd_gqa = embedding_length / gqa;
     let n_elements = n_embd_gqa * (block_count * ctx_size);
     let size = 2 * n_elements;
-    let kv_cache = size as f64 * (cache_bit as f64 / 8f64);
+    let kv_cache = size as f64 * (cache_bit / 8f64);
 
     let context_bytes = input_buffer + kv_cache + compute_buffer;
     context_bytes as u64
 }
 
+/// The data type of llama.cpp's KV cache, set with `--cache-type-k` and `--cache-type-v`.
+/// Quantized types shrink the cache, which is most of the context's memory at long context sizes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KvCacheType {
+    F32,
+    #[default]
+    F16,
+    Bf16,
+    Q8_0,
+    Q5_1,
+    Q5_0,
+    Q4_1,
+    Q4_0,
+    Iq4Nl,
+}
+
+impl KvCacheType {
+    /// Bits per element, including the block scales of the quantized types.
+    pub fn bits_per_element(&self) -> f64 {
+        match self {
+            Self::F32 => 32.0,
+            Self::F16 | Self::Bf16 => 16.0,
+            Self::Q8_0 => GgmlDType::Q8_0.bits_per_weight(),
+            Self::Q5_1 => GgmlDType::Q5_1.bits_per_weight(),
+            Self::Q5_0 => GgmlDType::Q5_0.bits_per_weight(),
+            Self::Q4_1 => GgmlDType::Q4_1.bits_per_weight(),
+            Self::Q4_0 | Self::Iq4Nl => GgmlDType::Q4_0.bits_per_weight(),
+        }
+    }
+
+    /// The average bits per element of a cache with these K and V types.
+    pub fn average_bits(cache_type_k: Self, cache_type_v: Self) -> f64 {
+        (cache_type_k.bits_per_element() + cache_type_v.bits_per_element()) / 2.0
+    }
+
+    pub fn is_quantized(&self) -> bool {
+        !matches!(self, Self::F32 | Self::F16 | Self::Bf16)
+    }
+}
+
+impl std::fmt::Display for KvCacheType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::F32 => "f32",
+            Self::F16 => "f16",
+            Self::Bf16 => "bf16",
+            Self::Q8_0 => "q8_0",
+            Self::Q5_1 => "q5_1",
+            Self::Q5_0 => "q5_0",
+            Self::Q4_1 => "q4_1",
+            Self::Q4_0 => "q4_0",
+            Self::Iq4Nl => "iq4_nl",
+        };
+        write!(f, "{name}")
+    }
+}
+
 // pub fn estimate_context_size(
 //     ctx_size: u64,
 //     embedding_length: u64,
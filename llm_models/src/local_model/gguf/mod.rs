@@ -26,11 +26,26 @@ impl GgufLoader {
 
     pub fn load(&mut self) -> crate::Result<LocalLlmModel> {
         if self.gguf_local_loader.local_quant_file_path.is_some() {
-            return self.gguf_local_loader.load();
+            self.gguf_local_loader.load()
         } else if self.gguf_hf_loader.hf_quant_file_url.is_some() {
-            return self.gguf_hf_loader.load(&self.hf_loader);
+            self.gguf_hf_loader.load(&self.hf_loader)
+        } else if self.gguf_hf_loader.hf_repo_id.is_some() {
+            let preset_loader = &self.gguf_preset_loader;
+            let available_vram_bytes =
+                preset_loader
+                    .preset_with_available_vram_bytes
+                    .or(preset_loader
+                        .preset_with_available_vram_gb
+                        .map(|gb| gb as u64 * 1024 * 1024 * 1024));
+            self.gguf_hf_loader.load_from_repo(
+                &self.hf_loader,
+                available_vram_bytes,
+                preset_loader.preset_with_max_ctx_size,
+                preset_loader.preset_with_quantization_level,
+                preset_loader.preset_with_kv_cache_bits,
+            )
         } else {
-            return self.gguf_preset_loader.load(&self.hf_loader);
+            self.gguf_preset_loader.load(&self.hf_loader)
         }
     }
 }
@@ -89,20 +104,29 @@ pub(crate) fn load_tokenizer(
     }
 }
 
+/// Prefers the `tokenizer.chat_template` embedded in the GGUF header, and falls back to the
+/// `tokenizer_config.json` sidecar when the GGUF doesn't have one or it fails to load.
 pub(crate) fn load_chat_template(
     local_tokenizer_config_path: &Option<std::path::PathBuf>,
     model_metadata: &LocalLlmMetadata,
 ) -> crate::Result<LlmChatTemplate> {
-    if let Some(local_tokenizer_config_path) = local_tokenizer_config_path {
-        match LlmChatTemplate::from_local_path(local_tokenizer_config_path) {
-            Ok(chat_template) => Ok(chat_template),
+    if model_metadata.tokenizer.chat_template.is_some() {
+        match LlmChatTemplate::from_gguf_tokenizer(&model_metadata.tokenizer) {
+            Ok(chat_template) => return Ok(chat_template),
             Err(e) => {
-                crate::warn!("Failed to load chat template from local path: {}", e);
-                LlmChatTemplate::from_gguf_tokenizer(&model_metadata.tokenizer)
+                if local_tokenizer_config_path.is_none() {
+                    return Err(e);
+                }
+                crate::warn!("Failed to load chat template from GGUF metadata: {}", e);
             }
         }
+    }
+    if let Some(local_tokenizer_config_path) = local_tokenizer_config_path {
+        LlmChatTemplate::from_local_path(local_tokenizer_config_path)
     } else {
-        LlmChatTemplate::from_gguf_tokenizer(&model_metadata.tokenizer)
+        crate::bail!(
+            "No chat template found in GGUF metadata and no tokenizer_config.json provided"
+        )
     }
 }
 
@@ -148,7 +172,7 @@ pub trait GgufLoaderTrait {
     }
 
     /// Sets the local path to the tokenizer_config.json file.
-    /// Optional because this can be loaded from the GGUF file.
+    /// Only used for the chat template if the GGUF file doesn't embed one.
     fn local_tokenizer_config_path<P: AsRef<std::path::Path>>(
         &mut self,
         local_tokenizer_config_path: P,
@@ -167,6 +191,16 @@ pub trait GgufLoaderTrait {
         self
     }
 
+    /// Sets the Hugging Face repo id of a GGUF repo, e.g. 'bartowski/Meta-Llama-3.1-8B-Instruct-GGUF'.
+    /// The quant file is picked from the files in the repo: the largest quant that fits in the available VRAM along with
+    /// a context of `preset_with_max_ctx_size`, or the level set with `preset_with_quantization_level`. Split and F16 files
+    /// are not considered. Picking by VRAM estimates the context's size from the model's config.json, from `hf_config_repo_id`
+    /// or the GGUF repo, or leaves a fifth of the VRAM for it if neither has one.
+    fn hf_repo_id<S: Into<String>>(&mut self, hf_repo_id: S) -> &mut Self {
+        self.gguf_loader().gguf_hf_loader.hf_repo_id = Some(hf_repo_id.into());
+        self
+    }
+
     /// Sets the Hugging Face repo id to the model config.json file.
     /// Optional because this can be loaded from the GGUF file.
     fn hf_config_repo_id<S: Into<String>>(&mut self, hf_config_repo_id: S) -> &mut Self {
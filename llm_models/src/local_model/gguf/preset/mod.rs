@@ -29,6 +29,9 @@ pub struct LlmPresetData {
     pub f_name_for_q_bits: QuantizationConfig,
     pub tokenizer_preset_data: TokenizerPresetData,
     pub tokenizer_config_preset_data: TokenizerConfigPresetData,
+    /// Embedding models have no chat template and are served in embedding mode.
+    #[serde(default)]
+    pub embedding_model: bool,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -164,6 +167,10 @@ macro_rules! generate_models {
 
             }
 
+            pub fn is_embedding_model(&self) -> bool {
+                self.get_data().embedding_model
+            }
+
             pub fn number_of_parameters(&self) -> f64 {
                 self.get_data().number_of_parameters as f64 * 1_000_000_000.0
             }
@@ -247,21 +254,29 @@ generate_models!(
         Llama3_1_8bInstruct => "llama/llama3_1_8b_instruct",
         Llama3_2_3bInstruct => "llama/llama3_2_3b_instruct",
         Llama3_2_1bInstruct => "llama/llama3_2_1b_instruct",
+        Llama3_3_70bInstruct => "llama/llama3_3_70b_instruct",
         Mistral7bInstructV0_3 => "mistral/mistral7b_instruct_v0_3",
         Mixtral8x7bInstructV0_1 => "mistral/mixtral8x7b_instruct_v0_1",
         MistralNemoInstruct2407 => "mistral/mistral_nemo_instruct_2407",
         MistralSmallInstruct2409 => "mistral/mistral_small_instruct_2409",
+        MistralSmall24bInstruct2501 => "mistral/mistral_small_24b_instruct_2501",
         Phi3Medium4kInstruct => "phi/phi3_medium4k_instruct",
         Phi3Mini4kInstruct => "phi/phi3_mini4k_instruct",
         Phi3_5MiniInstruct => "phi/phi3_5_mini_instruct",
+        Phi4 => "phi/phi4",
         Granite3_8bInstruct => "granite/granite3_8b_instruct",
         Granite3_2bInstruct => "granite/granite3_2b_instruct",
         Qwen2_5_32bInstruct => "qwen/qwen2_5_32b_instruct",
         Qwen2_5_14bInstruct => "qwen/qwen2_5_14b_instruct",
         Qwen2_5_7bInstruct => "qwen/qwen2_5_7b_instruct",
         Qwen2_5_3bInstruct => "qwen/qwen2_5_3b_instruct",
+        Qwen3_8b => "qwen/qwen3_8b",
+        Gemma3_12bInstruct => "google/gemma3_12b_instruct",
+        Exaone3_5_7_8bInstruct => "lgai/exaone3_5_7_8b_instruct",
         Llama3_1_70bNemotronInstruct => "nvidia/llama3_1_70b_nemotron_instruct",
         MistralNemoMinitron8bInstruct => "nvidia/mistral_nemo_minitron_8b_instruct",
         StableLm2_12bChat => "stabilityai/stablelm_2_12b_chat",
+        NomicEmbedTextV1_5 => "nomic/nomic_embed_text_v1_5",
+        BgeLargeEnV1_5 => "baai/bge_large_en_v1_5",
     }
 );
@@ -379,6 +379,66 @@ impl Value {
         }
     }
 
+    /// Any integer value that fits in a u64.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::U8(v) => Some(*v as u64),
+            Self::U16(v) => Some(*v as u64),
+            Self::U32(v) => Some(*v as u64),
+            Self::U64(v) => Some(*v),
+            Self::I8(v) => u64::try_from(*v).ok(),
+            Self::I16(v) => u64::try_from(*v).ok(),
+            Self::I32(v) => u64::try_from(*v).ok(),
+            Self::I64(v) => u64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Any integer value that fits in an i64.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::U8(v) => Some(*v as i64),
+            Self::U16(v) => Some(*v as i64),
+            Self::U32(v) => Some(*v as i64),
+            Self::U64(v) => i64::try_from(*v).ok(),
+            Self::I8(v) => Some(*v as i64),
+            Self::I16(v) => Some(*v as i64),
+            Self::I32(v) => Some(*v as i64),
+            Self::I64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Any float or integer value as an f64.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::F32(v) => Some(*v as f64),
+            Self::F64(v) => Some(*v),
+            v => v.as_i64().map(|v| v as f64),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
     fn read<R: std::io::Read>(
         reader: &mut R,
         value_type: ValueType,
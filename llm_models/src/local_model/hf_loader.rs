@@ -1,7 +1,10 @@
 //! Downloads to Path: "/root/.cache/huggingface/hub/
 use anyhow::{anyhow, Result};
 use dotenvy::dotenv;
-use hf_hub::api::sync::{Api, ApiBuilder};
+use hf_hub::{
+    api::sync::{Api, ApiBuilder},
+    Repo, RepoType,
+};
 use std::{cell::OnceCell, path::PathBuf};
 
 const DEFAULT_ENV_VAR: &str = "HUGGING_FACE_TOKEN";
@@ -28,6 +31,16 @@ impl HuggingFaceLoader {
         }
     }
 
+    /// The Hugging Face cache directory models are downloaded to. `$HF_HOME/hub`, or `~/.cache/huggingface/hub` if `HF_HOME` isn't set.
+    pub fn cache_dir() -> PathBuf {
+        hf_hub::Cache::default().path().to_owned()
+    }
+
+    /// Lists the GGUF models already downloaded to the cache. See [crate::local_model::cache::list_local_models].
+    pub fn list_local_models(&self) -> Result<Vec<crate::local_model::cache::LocalModelInfo>> {
+        crate::local_model::cache::list_local_models()
+    }
+
     pub fn hf_api(&self) -> &Api {
         self.hf_api.get_or_init(|| {
             ApiBuilder::new()
@@ -69,6 +82,23 @@ impl HuggingFaceLoader {
             .map_err(|e| anyhow!(e))
     }
 
+    /// Like [Self::load_file], but from `revision`, e.g. a branch, tag, or commit hash, rather than `main`.
+    pub fn load_file_at_revision<T: AsRef<str>, S: Into<String>>(
+        &self,
+        file_name: T,
+        repo_id: S,
+        revision: S,
+    ) -> Result<PathBuf> {
+        self.hf_api()
+            .repo(Repo::with_revision(
+                repo_id.into(),
+                RepoType::Model,
+                revision.into(),
+            ))
+            .get(file_name.as_ref())
+            .map_err(|e| anyhow!(e))
+    }
+
     pub fn load_model_safe_tensors<S: Into<String>>(&self, repo_id: S) -> Result<Vec<PathBuf>> {
         let repo_id = repo_id.into();
         let mut safe_tensor_filenames = vec![];
@@ -96,20 +126,49 @@ impl HuggingFaceLoader {
         local_path.canonicalize().map_err(|e| anyhow!(e))
     }
 
-    pub fn parse_full_model_url(model_url: &str) -> (String, String, String) {
-        if !model_url.starts_with("https://huggingface.co") {
-            panic!("URL does not start with https://huggingface.co\n Format should be like: https://huggingface.co/TheBloke/zephyr-7B-alpha-GGUF/blob/main/zephyr-7b-alpha.Q8_0.gguf");
-        } else if !model_url.ends_with(".gguf") {
-            panic!("URL does not end with .gguf\n Format should be like: https://huggingface.co/TheBloke/zephyr-7B-alpha-GGUF/blob/main/zephyr-7b-alpha.Q8_0.gguf");
+    /// Checks that the URL points to a GGUF file in a Hugging Face repo, like:
+    /// 'https://huggingface.co/TheBloke/zephyr-7B-alpha-GGUF/blob/main/zephyr-7b-alpha.Q8_0.gguf'
+    ///
+    /// Accepts the URLs [Self::parse_gguf_url] can parse, so any revision and files in subdirectories of the repo.
+    pub fn validate_gguf_url(model_url: &str) -> Result<()> {
+        Self::parse_gguf_url(model_url).map(|_| ())
+    }
+
+    /// Splits a Hugging Face GGUF file URL into the repo, revision, and path of the file in the repo, e.g.
+    /// 'https://huggingface.co/TheBloke/zephyr-7B-alpha-GGUF/blob/main/zephyr-7b-alpha.Q8_0.gguf'
+    /// or 'https://huggingface.co/unsloth/Qwen3-8B-GGUF/resolve/v1.0/Q8_0/Qwen3-8B-Q8_0.gguf'.
+    pub fn parse_gguf_url(model_url: &str) -> Result<HfFileUrl> {
+        let path = if let Some(path) = model_url.strip_prefix("https://huggingface.co/") {
+            path
         } else {
-            let parts: Vec<&str> = model_url.split('/').collect();
-            if parts.len() < 5 {
-                panic!("URL does not have enough parts\n Format should be like: https://huggingface.co/TheBloke/zephyr-7B-alpha-GGUF/blob/main/zephyr-7b-alpha.Q8_0.gguf");
-            }
-            let model_id = parts[4].to_string();
-            let repo_id = format!("{}/{}", parts[3], parts[4]);
-            let gguf_model_filename = parts.last().unwrap_or(&"").to_string();
-            (model_id, repo_id, gguf_model_filename)
+            anyhow::bail!("URL does not start with https://huggingface.co/: {model_url}");
+        };
+        if !model_url.ends_with(".gguf") {
+            anyhow::bail!("URL does not point to a .gguf file: {model_url}");
+        }
+        let parts: Vec<&str> = path.split('/').collect();
+        if parts.len() < 5
+            || parts.iter().any(|part| part.is_empty())
+            || !matches!(parts[2], "blob" | "resolve")
+        {
+            anyhow::bail!(
+                "URL is not a Hugging Face file URL. Format should be like: https://huggingface.co/TheBloke/zephyr-7B-alpha-GGUF/blob/main/zephyr-7b-alpha.Q8_0.gguf"
+            );
+        }
+        Ok(HfFileUrl {
+            model_id: parts[1].to_string(),
+            repo_id: format!("{}/{}", parts[0], parts[1]),
+            // Revisions with slashes, e.g. `refs/pr/1`, are percent-encoded in the URL.
+            revision: parts[3].replace("%2F", "/").replace("%2f", "/"),
+            file_name: parts[4..].join("/"),
+        })
+    }
+
+    /// Returns the model id, repo id, and path of the file in the repo. See [Self::parse_gguf_url].
+    pub fn parse_full_model_url(model_url: &str) -> (String, String, String) {
+        match Self::parse_gguf_url(model_url) {
+            Ok(url) => (url.model_id, url.repo_id, url.file_name),
+            Err(e) => panic!("{e}"),
         }
     }
 
@@ -135,6 +194,17 @@ impl HuggingFaceLoader {
     }
 }
 
+/// A GGUF file URL split by [HuggingFaceLoader::parse_gguf_url].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HfFileUrl {
+    pub model_id: String,
+    pub repo_id: String,
+    /// The branch, tag, or commit hash the file is at.
+    pub revision: String,
+    /// The path of the file in the repo, including any subdirectories.
+    pub file_name: String,
+}
+
 impl HfTokenTrait for HuggingFaceLoader {
     fn hf_token_mut(&mut self) -> &mut Option<String> {
         &mut self.hf_token
@@ -6,6 +6,7 @@ use std::{fs::File, io::BufReader};
 pub struct ConfigJson {
     #[serde(alias = "max_position_embeddings")]
     #[serde(alias = "n_ctx")]
+    #[serde(alias = "n_positions")]
     pub context_length: u64,
     #[serde(alias = "hidden_size")]
     #[serde(alias = "n_embd")]
@@ -39,14 +40,16 @@ impl ConfigJson {
     }
 
     // This is converted from https://github.com/pandora-s-git/LLMVRAMCalculator/blob/main/LLMVRAMCalculator/LLMVRAMCalculator.py
-    pub fn estimate_context_size(&self, ctx_size: u64) -> u64 {
+    /// `kv_cache_bits` is the average bits per element of the KV cache. Defaults to 16 (f16).
+    pub fn estimate_context_size(&self, ctx_size: u64, kv_cache_bits: Option<f64>) -> u64 {
         estimate_context_size(
             ctx_size,
             self.embedding_length,
             self.head_count,
-            self.head_count_kv.unwrap(),
+            self.head_count_kv.unwrap_or(self.head_count),
             self.block_count,
             None,
+            kv_cache_bits,
         )
     }
 }
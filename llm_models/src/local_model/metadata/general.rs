@@ -86,6 +86,29 @@ impl FileType {
         }
     }
 
+    /// The name llama.cpp uses for the quantization type, e.g. `Q4_K_M`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::AllF32 => "F32",
+            Self::MostlyF16 => "F16",
+            Self::MostlyQ4_0 => "Q4_0",
+            Self::MostlyQ4_1 => "Q4_1",
+            Self::MostlyQ4_1SomeF16 => "Q4_1_SOME_F16",
+            Self::MostlyQ8_0 => "Q8_0",
+            Self::MostlyQ5_0 => "Q5_0",
+            Self::MostlyQ5_1 => "Q5_1",
+            Self::MostlyQ2K => "Q2_K",
+            Self::MostlyQ3KS => "Q3_K_S",
+            Self::MostlyQ3KM => "Q3_K_M",
+            Self::MostlyQ3KL => "Q3_K_L",
+            Self::MostlyQ4KS => "Q4_K_S",
+            Self::MostlyQ4KM => "Q4_K_M",
+            Self::MostlyQ5KS => "Q5_K_S",
+            Self::MostlyQ5KM => "Q5_K_M",
+            Self::MostlyQ6K => "Q6_K",
+        }
+    }
+
     pub fn to_ggml_d_type(&self) -> GgmlDType {
         match self {
             Self::AllF32 => GgmlDType::F32,
@@ -84,7 +84,12 @@ impl LlamaMetadata {
     }
 
     // This is converted from https://github.com/pandora-s-git/LLMVRAMCalculator/blob/main/LLMVRAMCalculator/LLMVRAMCalculator.py
-    pub fn estimate_context_size(&self, ctx_size: u64, batch_size: Option<u64>) -> u64 {
+    pub fn estimate_context_size(
+        &self,
+        ctx_size: u64,
+        batch_size: Option<u64>,
+        kv_cache_bits: Option<f64>,
+    ) -> u64 {
         estimate_context_size(
             ctx_size,
             self.embedding_length,
@@ -94,6 +99,7 @@ impl LlamaMetadata {
                 .unwrap_or(self.attention.head_count),
             self.block_count,
             batch_size,
+            kv_cache_bits,
         )
     }
 }
@@ -14,16 +14,20 @@ impl Architecture {
     ) -> crate::Result<Self> {
         let arch: String = gguf.get_value("general.architecture")?;
         match arch.as_str() {
-            "llama" | "phi3" | "qwen2" | "granite" | "stablelm" => {
-                Ok(Self::Llama(LlamaMetadata::from_gguf(gguf)?))
-            }
+            "llama" | "phi3" | "qwen2" | "qwen3" | "gemma3" | "exaone" | "granite" | "stablelm"
+            | "bert" | "nomic-bert" => Ok(Self::Llama(LlamaMetadata::from_gguf(gguf)?)),
             _ => crate::bail!("Unknown architecture: {}", arch),
         }
     }
 
-    pub fn estimate_context_size(&self, ctx_size: u64, batch_size: Option<u64>) -> u64 {
+    pub fn estimate_context_size(
+        &self,
+        ctx_size: u64,
+        batch_size: Option<u64>,
+        kv_cache_bits: Option<f64>,
+    ) -> u64 {
         match self {
-            Self::Llama(llama) => llama.estimate_context_size(ctx_size, batch_size),
+            Self::Llama(llama) => llama.estimate_context_size(ctx_size, batch_size, kv_cache_bits),
         }
     }
 
@@ -2,9 +2,11 @@ pub mod config_json;
 pub mod general;
 pub mod llm;
 pub mod tokenizer;
+pub use super::gguf::tools::gguf_file::Value as GgufValue;
 use super::gguf::tools::{gguf_file::GgufFile, gguf_layers::GgufLayers};
 use general::GeneralMetadata;
 use llm::Architecture;
+use std::collections::HashMap;
 use tokenizer::TokenizerMetadata;
 
 pub struct LocalLlmMetadata {
@@ -33,18 +35,26 @@ impl LocalLlmMetadata {
         Ok(self.gguf_file.size())
     }
 
-    pub fn estimate_context_size(&self, ctx_size: u64, batch_size: Option<u64>) -> u64 {
-        self.llm.estimate_context_size(ctx_size, batch_size)
+    /// `kv_cache_bits` is the average bits per element of the KV cache. See [crate::local_model::gguf::memory::KvCacheType::average_bits].
+    pub fn estimate_context_size(
+        &self,
+        ctx_size: u64,
+        batch_size: Option<u64>,
+        kv_cache_bits: Option<f64>,
+    ) -> u64 {
+        self.llm
+            .estimate_context_size(ctx_size, batch_size, kv_cache_bits)
     }
 
     pub fn average_layer_size_bytes(
         &self,
         ctx_size: u64,
         batch_size: Option<u64>,
+        kv_cache_bits: Option<f64>,
     ) -> crate::Result<u64> {
         let total_layers_size = self.layers.total_size_blocks_bytes();
         let block_count = self.layers.count_blocks();
-        let context_size = self.estimate_context_size(ctx_size, batch_size);
+        let context_size = self.estimate_context_size(ctx_size, batch_size, kv_cache_bits);
         let total_size = total_layers_size + context_size;
         Ok(total_size / block_count)
     }
@@ -52,6 +62,44 @@ impl LocalLlmMetadata {
     pub fn context_length(&self) -> u64 {
         self.llm.context_length()
     }
+
+    /// Any key from the GGUF header, e.g. `llama.rope.freq_base`. `None` if the key is not present.
+    pub fn gguf_metadata_value(&self, key: &str) -> Option<GgufValue> {
+        self.gguf_file.metadata.get(key).cloned()
+    }
+
+    /// Every key in the GGUF header. This includes the tokenizer vocabulary, so it can be large;
+    /// use `gguf_file.metadata` to borrow it instead.
+    pub fn all_metadata(&self) -> HashMap<String, GgufValue> {
+        self.gguf_file.metadata.clone()
+    }
+
+    /// `None` if the key is missing or isn't an integer that fits in a u64.
+    pub fn gguf_metadata_u64(&self, key: &str) -> Option<u64> {
+        self.gguf_file.metadata.get(key)?.as_u64()
+    }
+
+    /// `None` if the key is missing or isn't an integer that fits in an i64.
+    pub fn gguf_metadata_i64(&self, key: &str) -> Option<i64> {
+        self.gguf_file.metadata.get(key)?.as_i64()
+    }
+
+    /// `None` if the key is missing or isn't a number.
+    pub fn gguf_metadata_f64(&self, key: &str) -> Option<f64> {
+        self.gguf_file.metadata.get(key)?.as_f64()
+    }
+
+    pub fn gguf_metadata_bool(&self, key: &str) -> Option<bool> {
+        self.gguf_file.metadata.get(key)?.as_bool()
+    }
+
+    pub fn gguf_metadata_string(&self, key: &str) -> Option<&str> {
+        self.gguf_file.metadata.get(key)?.as_str()
+    }
+
+    pub fn gguf_metadata_array(&self, key: &str) -> Option<&[GgufValue]> {
+        self.gguf_file.metadata.get(key)?.as_array()
+    }
 }
 
 impl std::fmt::Debug for LocalLlmMetadata {
@@ -26,6 +26,7 @@ pub enum GgmlTokenizerModel {
     Replit,
     Gpt2,
     Rwkv,
+    Bert,
 }
 
 impl GgmlTokenizerModel {
@@ -35,6 +36,7 @@ impl GgmlTokenizerModel {
             "replit" => Self::Replit,
             "gpt2" => Self::Gpt2,
             "rwkv" => Self::Rwkv,
+            "bert" => Self::Bert,
             _ => crate::bail!("Unknown GGML tokenizer model: {}", s),
         })
     }
@@ -45,6 +47,7 @@ impl GgmlTokenizerModel {
             Self::Replit => "replit",
             Self::Gpt2 => "gpt2",
             Self::Rwkv => "rwkv",
+            Self::Bert => "bert",
         }
     }
 }
@@ -1,6 +1,8 @@
 use gguf::GgufLoader;
-use metadata::LocalLlmMetadata;
+use metadata::{GgufValue, LocalLlmMetadata};
+use std::collections::HashMap;
 
+pub mod cache;
 pub mod chat_template;
 pub mod gguf;
 pub mod hf_loader;
@@ -18,6 +20,18 @@ pub struct LocalLlmModel {
     pub chat_template: LlmChatTemplate,
 }
 
+impl LocalLlmModel {
+    /// Any key from the GGUF header. See [LocalLlmMetadata] for typed accessors.
+    pub fn gguf_metadata_value(&self, key: &str) -> Option<GgufValue> {
+        self.model_metadata.gguf_metadata_value(key)
+    }
+
+    /// Every key in the GGUF header.
+    pub fn all_metadata(&self) -> HashMap<String, GgufValue> {
+        self.model_metadata.all_metadata()
+    }
+}
+
 impl Default for LocalLlmModel {
     fn default() -> Self {
         let mut loader = GgufLoader::default();
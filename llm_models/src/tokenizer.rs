@@ -2,7 +2,11 @@ use super::local_model::hf_loader::{HfTokenTrait, HuggingFaceLoader};
 use anyhow::{anyhow, Result};
 use llm_prompt::PromptTokenizer;
 use std::{fmt, path::PathBuf};
-use tiktoken_rs::{get_bpe_from_model, CoreBPE};
+use tiktoken_rs::{
+    get_bpe_from_model,
+    tokenizer::{get_tokenizer, Tokenizer},
+    CoreBPE,
+};
 use tokenizers::Tokenizer as HFTokenizer;
 
 pub enum TokenizerBackend {
@@ -23,44 +27,100 @@ impl fmt::Debug for TokenizerBackend {
     }
 }
 
+/// Options for [LlmTokenizer::tokenize_with_options]. The defaults match llama-server's `/tokenize` endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenizeOptions {
+    /// Adds the special tokens the model adds to a prompt, like BOS.
+    pub add_special: bool,
+    /// Parses special tokens written in the text, like `<|eot_id|>`, into their ids.
+    /// If false, they're tokenized as plain text.
+    pub parse_special: bool,
+    /// Returns the text of each token alongside its id.
+    pub with_pieces: bool,
+}
+
+impl Default for TokenizeOptions {
+    fn default() -> Self {
+        Self {
+            add_special: false,
+            parse_special: true,
+            with_pieces: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenPiece {
+    pub id: u32,
+    /// The text of the token. `None` unless [TokenizeOptions::with_pieces] is set.
+    pub piece: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct LlmTokenizer {
     pub tokenizer: TokenizerBackend,
     pub tokenizer_path: Option<PathBuf>,
     pub with_special_tokens: bool,
     pub white_space_token_id: u32,
+    /// The number of token ids, including special tokens. Every valid token id is less than it.
+    pub vocab_size: u32,
+    /// Unique to this instance, for [PromptTokenizer::tokenizer_id].
+    id: u64,
+}
+
+/// Ids for [LlmTokenizer::id]. Unlike an address, an id is never reused by a later tokenizer.
+static NEXT_TOKENIZER_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+fn next_tokenizer_id() -> u64 {
+    NEXT_TOKENIZER_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
 }
 
 impl LlmTokenizer {
     pub fn new_tiktoken<T: AsRef<str>>(model_id: T) -> Result<Self> {
         let tokenizer = get_bpe_from_model(model_id.as_ref())?;
         let white_space_token_id = u32::try_from(tokenizer.encode_ordinary(" ").remove(0))?;
+        // CoreBPE doesn't expose its vocabulary, so these are the sizes of tiktoken's encodings, counting special tokens.
+        let vocab_size = match get_tokenizer(model_id.as_ref()) {
+            Some(Tokenizer::O200kBase) => 200_019,
+            Some(Tokenizer::Cl100kBase) => 100_277,
+            Some(Tokenizer::P50kEdit) => 50_284,
+            Some(Tokenizer::P50kBase) => 50_281,
+            Some(Tokenizer::R50kBase | Tokenizer::Gpt2) | None => 50_257,
+        };
         Ok(Self {
             tokenizer: TokenizerBackend::Tiktoken(tokenizer),
             tokenizer_path: None,
             with_special_tokens: false,
             white_space_token_id,
+            vocab_size,
+            id: next_tokenizer_id(),
         })
     }
 
     pub fn new_from_tokenizer(tokenizer: HFTokenizer) -> Result<Self> {
         let white_space_token_id = tokenizer.encode(" ", false).unwrap().get_ids()[0];
+        let vocab_size = u32::try_from(tokenizer.get_vocab_size(true))?;
         Ok(Self {
             tokenizer: TokenizerBackend::HuggingFacesTokenizer(tokenizer),
             tokenizer_path: None,
             with_special_tokens: false,
             white_space_token_id,
+            vocab_size,
+            id: next_tokenizer_id(),
         })
     }
 
     pub fn new_from_tokenizer_json(local_path: &PathBuf) -> Result<Self> {
         let tokenizer = HFTokenizer::from_file(local_path).map_err(|e| anyhow!(e))?;
         let white_space_token_id = tokenizer.encode(" ", false).unwrap().get_ids()[0];
+        let vocab_size = u32::try_from(tokenizer.get_vocab_size(true))?;
         Ok(Self {
             tokenizer: TokenizerBackend::HuggingFacesTokenizer(tokenizer),
             tokenizer_path: Some(local_path.clone()),
             with_special_tokens: false,
             white_space_token_id,
+            vocab_size,
+            id: next_tokenizer_id(),
         })
     }
 
@@ -78,6 +138,59 @@ impl LlmTokenizer {
         self.encode(str.as_ref())
     }
 
+    /// Tokenizes with explicit control over special tokens. See [TokenizeOptions].
+    ///
+    /// For Hugging Face tokenizers, `parse_special: false` tokenizes from a copy of the tokenizer,
+    /// so it's slower than [Self::tokenize].
+    pub fn tokenize_with_options<T: AsRef<str>>(
+        &self,
+        str: T,
+        options: &TokenizeOptions,
+    ) -> Result<Vec<TokenPiece>> {
+        let str = str.as_ref();
+        let ids: Vec<u32> = match &self.tokenizer {
+            TokenizerBackend::HuggingFacesTokenizer(tokenizer) => {
+                let encoding = if options.parse_special {
+                    tokenizer.encode(str, options.add_special)
+                } else {
+                    let mut tokenizer = tokenizer.clone();
+                    tokenizer.set_encode_special_tokens(true);
+                    tokenizer.encode(str, options.add_special)
+                };
+                encoding.map_err(|e| anyhow!(e))?.get_ids().to_vec()
+            }
+            // Tiktoken models don't add special tokens to the prompt.
+            TokenizerBackend::Tiktoken(tokenizer) => {
+                let tokens = if options.parse_special {
+                    tokenizer.encode_with_special_tokens(str)
+                } else {
+                    tokenizer.encode_ordinary(str)
+                };
+                tokens.into_iter().map(|x| x as u32).collect()
+            }
+        };
+        Ok(ids
+            .into_iter()
+            .map(|id| TokenPiece {
+                id,
+                piece: if options.with_pieces {
+                    self.decode_piece(id)
+                } else {
+                    None
+                },
+            })
+            .collect())
+    }
+
+    fn decode_piece(&self, id: u32) -> Option<String> {
+        match &self.tokenizer {
+            TokenizerBackend::HuggingFacesTokenizer(tokenizer) => {
+                tokenizer.decode(&[id], false).ok()
+            }
+            TokenizerBackend::Tiktoken(tokenizer) => tokenizer.decode(vec![id as usize]).ok(),
+        }
+    }
+
     pub fn detokenize_one(&self, token: u32) -> Result<String> {
         self.decode(&[token])
     }
@@ -234,4 +347,9 @@ impl PromptTokenizer for LlmTokenizer {
     fn count_tokens(&self, str: &str) -> u32 {
         self.count_tokens(str)
     }
+
+    fn tokenizer_id(&self) -> Option<u64> {
+        // with_special_tokens can be changed after loading, and changes the tokens.
+        Some(self.id << 1 | self.with_special_tokens as u64)
+    }
 }
@@ -0,0 +1,11 @@
+use llm_models::api_model::ApiLlmModel;
+
+#[test]
+fn test_estimated_cost_usd() {
+    let mut model = ApiLlmModel::gpt_4();
+    assert!((model.estimated_cost_usd(1_000, 500) - 0.06).abs() < 1e-6);
+
+    model.cost_per_m_in_tokens = 1.0;
+    model.cost_per_m_out_tokens = 2.0;
+    assert!((model.estimated_cost_usd(1_000_000, 1_000_000) - 3.0).abs() < 1e-6);
+}
@@ -0,0 +1,75 @@
+use llm_models::local_model::cache::list_local_models_in;
+use std::path::Path;
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+// A GGUF v3 file with a single 4x2 F32 tensor.
+fn synthetic_gguf() -> Vec<u8> {
+    let mut bytes = vec![];
+    bytes.extend_from_slice(&0x46554747u32.to_le_bytes());
+    bytes.extend_from_slice(&3u32.to_le_bytes());
+    // Tensor count, then metadata kv count.
+    bytes.extend_from_slice(&1u64.to_le_bytes());
+    bytes.extend_from_slice(&3u64.to_le_bytes());
+
+    write_string(&mut bytes, "general.architecture");
+    bytes.extend_from_slice(&8u32.to_le_bytes());
+    write_string(&mut bytes, "llama");
+    write_string(&mut bytes, "general.file_type");
+    bytes.extend_from_slice(&4u32.to_le_bytes());
+    bytes.extend_from_slice(&15u32.to_le_bytes());
+    write_string(&mut bytes, "llama.context_length");
+    bytes.extend_from_slice(&4u32.to_le_bytes());
+    bytes.extend_from_slice(&4096u32.to_le_bytes());
+
+    write_string(&mut bytes, "token_embd.weight");
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&4u64.to_le_bytes());
+    bytes.extend_from_slice(&2u64.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&0u64.to_le_bytes());
+
+    while bytes.len() % 32 != 0 {
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(&[0u8; 4 * 2 * 4]);
+    bytes
+}
+
+fn write_file(path: &Path, bytes: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn test_list_local_models_in() {
+    let cache_dir = std::env::temp_dir().join(format!("llm_models_cache_{}", std::process::id()));
+    let snapshot_dir = cache_dir.join("models--org--repo-GGUF/snapshots/abc123");
+    let gguf = synthetic_gguf();
+    write_file(&snapshot_dir.join("model-Q4_K_M.gguf"), &gguf);
+    write_file(&snapshot_dir.join("corrupt.gguf"), b"not a gguf file");
+    write_file(&snapshot_dir.join("partial.gguf"), &gguf[..gguf.len() - 8]);
+    write_file(&snapshot_dir.join("README.md"), b"readme");
+
+    let models = list_local_models_in(&cache_dir).unwrap();
+    std::fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert_eq!(models.len(), 1);
+    let model = &models[0];
+    assert_eq!(model.repo_id.as_deref(), Some("org/repo-GGUF"));
+    assert_eq!(model.file_name, "model-Q4_K_M.gguf");
+    assert_eq!(model.file_size_bytes, gguf.len() as u64);
+    assert_eq!(model.architecture.as_deref(), Some("llama"));
+    assert_eq!(model.quantization.as_deref(), Some("Q4_K_M"));
+    assert_eq!(model.parameter_count, 8);
+    assert_eq!(model.context_length, Some(4096));
+}
+
+#[test]
+fn test_list_local_models_in_missing_dir() {
+    let models = list_local_models_in("/nonexistent/llm_models_cache").unwrap();
+    assert!(models.is_empty());
+}
@@ -1,4 +1,11 @@
-use llm_models::local_model::{gguf::GgufLoader, GgufLoaderTrait};
+use llm_models::local_model::{
+    gguf::{
+        loaders::hf_repo::{fallback_ctx_memory_size, select_repo_quant, HfRepoGgufFile},
+        GgufLoader,
+    },
+    hf_loader::HuggingFaceLoader,
+    GgufLoaderTrait,
+};
 
 #[test]
 fn load_local_basic() {
@@ -41,3 +48,123 @@ fn load_hf_with_config() {
 
     println!("{:#?}", model);
 }
+
+#[test]
+fn load_hf_repo_id() {
+    let model = GgufLoader::default()
+        .hf_repo_id("bartowski/Meta-Llama-3.1-8B-Instruct-GGUF")
+        .load()
+        .unwrap();
+
+    println!("{:#?}", model);
+}
+
+#[test]
+fn hf_repo_quant_selection() {
+    const GB: u64 = 1024 * 1024 * 1024;
+    let files: Vec<HfRepoGgufFile> = [
+        ("Meta-Llama-3.1-8B-Instruct-Q8_0.gguf", 8 * GB),
+        ("Meta-Llama-3.1-8B-Instruct-Q6_K.gguf", 6 * GB),
+        ("Meta-Llama-3.1-8B-Instruct-Q4_K_S.gguf", 4 * GB),
+        ("Meta-Llama-3.1-8B-Instruct-Q4_K_M.gguf", 5 * GB),
+        ("Meta-Llama-3.1-8B-Instruct-IQ2_M.gguf", 3 * GB),
+        ("Meta-Llama-3.1-8B-Instruct-f16.gguf", 16 * GB),
+        (
+            "Meta-Llama-3.1-8B-Instruct-Q8_0-00001-of-00002.gguf",
+            4 * GB,
+        ),
+        ("README.md", 0),
+    ]
+    .iter()
+    .filter_map(|(file_name, size)| HfRepoGgufFile::from_file_name(file_name, Some(*size)))
+    .collect();
+    assert_eq!(files.len(), 5);
+
+    let pick_with_ctx = |vram_gb: Option<u64>, ctx_gb: u64, q_bits: Option<u8>| {
+        select_repo_quant(&files, vram_gb.map(|gb| gb * GB), ctx_gb * GB, q_bits)
+            .map(|file| file.quant_label.clone())
+    };
+    let pick = |vram_gb: Option<u64>, q_bits: Option<u8>| pick_with_ctx(vram_gb, 0, q_bits);
+    assert_eq!(pick(None, None).unwrap(), "Q8_0");
+    assert_eq!(pick(Some(7), None).unwrap(), "Q6_K");
+    assert_eq!(pick(Some(5), None).unwrap(), "Q4_K_M");
+    assert_eq!(pick(None, Some(4)).unwrap(), "Q4_K_M");
+    assert_eq!(pick(Some(3), None).unwrap(), "IQ2_M");
+    let error = pick(Some(2), None).unwrap_err().to_string();
+    assert!(error.contains("Q4_K_M"), "{error}");
+    assert!(pick(None, Some(3)).is_err());
+
+    // The context takes up VRAM too.
+    assert_eq!(pick_with_ctx(Some(8), 1, None).unwrap(), "Q6_K");
+    assert_eq!(pick_with_ctx(Some(7), 2, None).unwrap(), "Q4_K_M");
+    assert!(pick_with_ctx(Some(3), 1, None).is_err());
+
+    // Without a config.json, a fifth of the VRAM is left for the context.
+    assert_eq!(fallback_ctx_memory_size(10 * GB), 2 * GB);
+    let pick_without_config = |vram_gb: u64| {
+        select_repo_quant(
+            &files,
+            Some(vram_gb * GB),
+            fallback_ctx_memory_size(vram_gb * GB),
+            None,
+        )
+        .map(|file| file.quant_label.clone())
+    };
+    assert_eq!(pick_without_config(10).unwrap(), "Q8_0");
+    assert_eq!(pick_without_config(7).unwrap(), "Q4_K_M");
+}
+
+#[test]
+fn validate_gguf_url() {
+    assert!(HuggingFaceLoader::validate_gguf_url("https://huggingface.co/bartowski/Meta-Llama-3.1-8B-Instruct-GGUF/blob/main/Meta-Llama-3.1-8B-Instruct-Q8_0.gguf").is_ok());
+    assert!(HuggingFaceLoader::validate_gguf_url("https://huggingface.co/bartowski/Meta-Llama-3.1-8B-Instruct-GGUF/resolve/main/Meta-Llama-3.1-8B-Instruct-Q8_0.gguf").is_ok());
+    assert!(HuggingFaceLoader::validate_gguf_url(
+        "https://huggingface.co/bartowski/Meta-Llama-3.1-8B-Instruct-GGUF/blob/main/README.md"
+    )
+    .is_err());
+    assert!(HuggingFaceLoader::validate_gguf_url(
+        "https://example.com/bartowski/Meta-Llama-3.1-8B-Instruct-GGUF/blob/main/model.gguf"
+    )
+    .is_err());
+    assert!(
+        HuggingFaceLoader::validate_gguf_url("https://huggingface.co/bartowski/model.gguf")
+            .is_err()
+    );
+}
+
+#[test]
+fn parse_gguf_url() {
+    let url = HuggingFaceLoader::parse_gguf_url("https://huggingface.co/bartowski/Meta-Llama-3.1-8B-Instruct-GGUF/blob/main/Meta-Llama-3.1-8B-Instruct-Q8_0.gguf").unwrap();
+    assert_eq!(url.model_id, "Meta-Llama-3.1-8B-Instruct-GGUF");
+    assert_eq!(url.repo_id, "bartowski/Meta-Llama-3.1-8B-Instruct-GGUF");
+    assert_eq!(url.revision, "main");
+    assert_eq!(url.file_name, "Meta-Llama-3.1-8B-Instruct-Q8_0.gguf");
+
+    // A tagged revision, and a file in a subdirectory of the repo.
+    let model_url =
+        "https://huggingface.co/unsloth/Qwen3-8B-GGUF/resolve/v1.0/Q8_0/Qwen3-8B-Q8_0.gguf";
+    assert!(HuggingFaceLoader::validate_gguf_url(model_url).is_ok());
+    let url = HuggingFaceLoader::parse_gguf_url(model_url).unwrap();
+    assert_eq!(url.repo_id, "unsloth/Qwen3-8B-GGUF");
+    assert_eq!(url.revision, "v1.0");
+    assert_eq!(url.file_name, "Q8_0/Qwen3-8B-Q8_0.gguf");
+    assert_eq!(
+        HuggingFaceLoader::parse_full_model_url(model_url),
+        (
+            "Qwen3-8B-GGUF".to_owned(),
+            "unsloth/Qwen3-8B-GGUF".to_owned(),
+            "Q8_0/Qwen3-8B-Q8_0.gguf".to_owned()
+        )
+    );
+
+    let url = HuggingFaceLoader::parse_gguf_url(
+        "https://huggingface.co/unsloth/Qwen3-8B-GGUF/blob/refs%2Fpr%2F3/Qwen3-8B-Q8_0.gguf",
+    )
+    .unwrap();
+    assert_eq!(url.revision, "refs/pr/3");
+
+    assert!(HuggingFaceLoader::validate_gguf_url(
+        "https://huggingface.co/unsloth/Qwen3-8B-GGUF/blob/main//Qwen3-8B-Q8_0.gguf"
+    )
+    .is_err());
+}
@@ -1,3 +1,6 @@
+mod api_model;
+mod cache;
 mod custom;
 mod metadata;
 mod preset;
+mod tokenizer;
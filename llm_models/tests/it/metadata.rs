@@ -1,4 +1,4 @@
-use llm_models::local_model::{gguf::GgufLoader, GgufPresetTrait};
+use llm_models::local_model::{gguf::GgufLoader, metadata::GgufValue, GgufPresetTrait};
 
 #[test]
 fn test_base_generation_prefix() {
@@ -33,6 +33,72 @@ fn test_base_generation_prefix() {
         model.chat_template.base_generation_prefix.as_deref()
     );
 
+    let model = GgufLoader::default()
+        .llama3_3_70b_instruct()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model.chat_template.base_generation_prefix);
+    assert_eq!(
+        Some("<|start_header_id|>assistant<|end_header_id|>\n\n"),
+        model.chat_template.base_generation_prefix.as_deref()
+    );
+
+    let model = GgufLoader::default()
+        .mistral_small24b_instruct2501()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model.chat_template.base_generation_prefix);
+    assert_eq!(
+        Some(""),
+        model.chat_template.base_generation_prefix.as_deref()
+    );
+
+    let model = GgufLoader::default()
+        .phi4()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model.chat_template.base_generation_prefix);
+    assert_eq!(
+        Some("<|im_start|>assistant<|im_sep|>"),
+        model.chat_template.base_generation_prefix.as_deref()
+    );
+
+    let model = GgufLoader::default()
+        .qwen3_8b()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model.chat_template.base_generation_prefix);
+    assert_eq!(
+        Some("<|im_start|>assistant\n"),
+        model.chat_template.base_generation_prefix.as_deref()
+    );
+
+    let model = GgufLoader::default()
+        .gemma3_12b_instruct()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model.chat_template.base_generation_prefix);
+    assert_eq!(
+        Some("<start_of_turn>model\n"),
+        model.chat_template.base_generation_prefix.as_deref()
+    );
+
+    let model = GgufLoader::default()
+        .exaone3_5_7_8b_instruct()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model.chat_template.base_generation_prefix);
+    assert_eq!(
+        Some("[|assistant|]"),
+        model.chat_template.base_generation_prefix.as_deref()
+    );
+
     // let model = GgufLoader::default()
     //     .super_nova_medius13b()
     //     .preset_with_available_vram_gb(48)
@@ -44,3 +110,60 @@ fn test_base_generation_prefix() {
     //     model.chat_template.base_generation_prefix.as_deref()
     // );
 }
+
+#[test]
+fn test_gguf_value_accessors() {
+    assert_eq!(GgufValue::U32(4096).as_u64(), Some(4096));
+    assert_eq!(GgufValue::I32(-1).as_u64(), None);
+    assert_eq!(GgufValue::I32(-1).as_i64(), Some(-1));
+    assert_eq!(GgufValue::U64(u64::MAX).as_i64(), None);
+    assert_eq!(GgufValue::F32(0.5).as_f64(), Some(0.5));
+    assert_eq!(GgufValue::U8(2).as_f64(), Some(2.0));
+    assert_eq!(GgufValue::Bool(true).as_bool(), Some(true));
+    assert_eq!(
+        GgufValue::String("llama".to_string()).as_str(),
+        Some("llama")
+    );
+    assert_eq!(GgufValue::String("llama".to_string()).as_u64(), None);
+    let array = GgufValue::Array(vec![GgufValue::U32(1), GgufValue::U32(2)]);
+    assert_eq!(array.as_array().map(|values| values.len()), Some(2));
+}
+
+#[test]
+fn test_kv_cache_type_context_size() {
+    use llm_models::local_model::gguf::memory::{estimate_context_size, KvCacheType};
+
+    assert_eq!(KvCacheType::Q8_0.bits_per_element(), 8.5);
+    assert_eq!(
+        KvCacheType::average_bits(KvCacheType::F16, KvCacheType::Q4_0),
+        10.25
+    );
+    // Llama 3.1 8B at 32k.
+    let estimate =
+        |kv_cache_bits| estimate_context_size(32768, 4096, 32, 8, 32, None, kv_cache_bits);
+    let f16 = estimate(None);
+    let q8_0 = estimate(Some(KvCacheType::Q8_0.bits_per_element()));
+    assert_eq!(f16, estimate(Some(16.0)));
+    // The f16 cache is 4 GiB, so q8_0 saves a little under 2 GiB.
+    let saved = f16 - q8_0;
+    assert!(saved > 1_800_000_000 && saved < 2_147_483_648, "{saved}");
+}
+
+#[test]
+fn test_has_thinking_tags() {
+    let template = |chat_template: &str| llm_models::local_model::chat_template::LlmChatTemplate {
+        chat_template: chat_template.to_owned(),
+        bos_token: None,
+        eos_token: "<|im_end|>".to_owned(),
+        unk_token: None,
+        base_generation_prefix: None,
+    };
+    assert!(template(
+        "{% for message in messages %}{% set content = message['content'].split('</think>')[-1] %}{{ content }}{% endfor %}"
+    )
+    .has_thinking_tags());
+    assert!(
+        !template("{% for message in messages %}{{ message['content'] }}{% endfor %}")
+            .has_thinking_tags()
+    );
+}
@@ -104,6 +104,51 @@ fn load_from_q_level() {
     println!("{:#?}", model);
 }
 
+#[test]
+fn load_recent_presets() {
+    let model = GgufLoader::default()
+        .llama3_3_70b_instruct()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model);
+
+    let model = GgufLoader::default()
+        .mistral_small24b_instruct2501()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model);
+
+    let model = GgufLoader::default()
+        .phi4()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model);
+
+    let model = GgufLoader::default()
+        .qwen3_8b()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model);
+
+    let model = GgufLoader::default()
+        .gemma3_12b_instruct()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model);
+
+    let model = GgufLoader::default()
+        .exaone3_5_7_8b_instruct()
+        .preset_with_available_vram_gb(48)
+        .load()
+        .unwrap();
+    println!("{:#?}", model);
+}
+
 #[test]
 fn models_macros_test() {
     let variants = vec![
@@ -127,15 +172,57 @@ fn models_macros_test() {
         LlmPreset::Qwen2_5_7bInstruct,
         LlmPreset::Qwen2_5_3bInstruct,
         LlmPreset::StableLm2_12bChat,
+        LlmPreset::Llama3_3_70bInstruct,
+        LlmPreset::MistralSmall24bInstruct2501,
+        LlmPreset::Phi4,
+        LlmPreset::Qwen3_8b,
+        LlmPreset::Gemma3_12bInstruct,
+        LlmPreset::Exaone3_5_7_8bInstruct,
+        LlmPreset::NomicEmbedTextV1_5,
+        LlmPreset::BgeLargeEnV1_5,
     ];
     for variant in variants {
         println!("{:#?}", variant.model_id());
         println!("{:#?}", variant.gguf_repo_id());
         println!("{:#?}", variant.config_json());
         println!("{:#?}", variant.number_of_parameters());
+        println!("{:#?}", variant.is_embedding_model());
         for i in 1..=8 {
             println!("{:#?}", variant.f_name_for_q_bits(i));
         }
         println!("{:#?}", variant.get_data());
     }
 }
+
+#[test]
+fn embedding_presets() {
+    assert!(LlmPreset::NomicEmbedTextV1_5.is_embedding_model());
+    assert!(LlmPreset::BgeLargeEnV1_5.is_embedding_model());
+    assert!(!LlmPreset::Llama3_1_8bInstruct.is_embedding_model());
+}
+
+#[test]
+fn select_quant_with_kv_cache_bits() {
+    use llm_models::local_model::gguf::{loaders::preset::GgufPresetLoader, memory::KvCacheType};
+
+    let select = |kv_cache_bits: Option<f64>| {
+        GgufPresetLoader {
+            llm_preset: LlmPreset::Llama3_1_8bInstruct,
+            preset_with_available_vram_bytes: Some(12 * 1024 * 1024 * 1024),
+            preset_with_max_ctx_size: Some(32768),
+            preset_with_kv_cache_bits: kv_cache_bits,
+            ..Default::default()
+        }
+        .select_quant()
+        .unwrap()
+    };
+    let q4_0_cache = KvCacheType::average_bits(KvCacheType::Q4_0, KvCacheType::Q4_0);
+    // At 32k tokens an f16 cache leaves room for Q5, while a Q4_0 cache shrinks the context enough for Q8.
+    let preset = LlmPreset::Llama3_1_8bInstruct;
+    assert_eq!(select(None), preset.f_name_for_q_bits(5).unwrap());
+    assert_eq!(select(Some(16.0)), preset.f_name_for_q_bits(5).unwrap());
+    assert_eq!(
+        select(Some(q4_0_cache)),
+        preset.f_name_for_q_bits(8).unwrap()
+    );
+}
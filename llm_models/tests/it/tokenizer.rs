@@ -0,0 +1,29 @@
+use llm_models::tokenizer::{LlmTokenizer, TokenizeOptions};
+
+#[test]
+fn test_tokenize_with_options() {
+    let tokenizer = LlmTokenizer::new_tiktoken("gpt-4").unwrap();
+    let text = "Hello<|endoftext|>";
+
+    let parsed = tokenizer
+        .tokenize_with_options(text, &TokenizeOptions::default())
+        .unwrap();
+    assert!(parsed.iter().all(|token| token.piece.is_none()));
+
+    let unparsed = tokenizer
+        .tokenize_with_options(
+            text,
+            &TokenizeOptions {
+                parse_special: false,
+                with_pieces: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(unparsed.len() > parsed.len());
+    let pieces: String = unparsed
+        .iter()
+        .map(|token| token.piece.clone().unwrap())
+        .collect();
+    assert_eq!(pieces, text);
+}
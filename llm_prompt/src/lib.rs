@@ -7,8 +7,12 @@ mod variants;
 pub use concatenator::{TextConcatenator, TextConcatenatorTrait};
 pub use prompt_message::{PromptMessage, PromptMessageType, PromptMessages};
 pub use prompt_tokenizer::PromptTokenizer;
-pub use token_count::{check_and_get_max_tokens, MaxTokenState, RequestTokenLimitError};
-pub use variants::{apply_chat_template, ApiPrompt, LocalPrompt};
+pub use token_count::{
+    check_and_get_max_tokens, MaxTokenState, RequestTokenLimitError, DEFAULT_SAFETY_TOKENS,
+};
+pub use variants::{
+    apply_chat_template, validate_chat_template, ApiPrompt, ChatTemplateError, LocalPrompt,
+};
 
 pub(crate) use anyhow::{bail, Error, Result};
 use serde::Serialize;
@@ -28,6 +32,8 @@ pub struct LlmPrompt {
     pub messages: PromptMessages,
     pub concatenator: TextConcatenator,
     pub built_prompt_messages: Mutex<Option<Vec<HashMap<String, String>>>>,
+    /// Text added to the end of the system message when the prompt is built. See [LlmPrompt::set_summary].
+    pub summary: Mutex<Option<String>>,
 }
 
 impl LlmPrompt {
@@ -121,6 +127,42 @@ impl LlmPrompt {
         Ok(self.last_message())
     }
 
+    /// Adds a system message before the existing messages.
+    ///
+    /// Useful for applying a default system message to a prompt that was built without one.
+    /// Returns an error if the prompt already starts with a system message.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the newly created message for setting content, or an error if validation fails.
+    pub fn prepend_system_message(&self) -> Result<Arc<PromptMessage>, crate::Error> {
+        let message = {
+            let mut messages = self.messages();
+
+            if let Some(first) = messages.first() {
+                if first.message_type == PromptMessageType::System {
+                    crate::bail!("Prompt already starts with a system message.");
+                }
+            }
+
+            let message = Arc::new(PromptMessage::new(
+                PromptMessageType::System,
+                &self.concatenator,
+            ));
+            messages.insert(0, message.clone());
+            message
+        };
+        self.clear_built_prompt();
+        Ok(message)
+    }
+
+    /// Returns true if the prompt starts with a system message.
+    pub fn has_system_message(&self) -> bool {
+        self.messages()
+            .first()
+            .is_some_and(|first| first.message_type == PromptMessageType::System)
+    }
+
     /// Adds a user message to the prompt.
     ///
     /// Cannot add a user message directly after another user message.
@@ -196,6 +238,27 @@ impl LlmPrompt {
         };
     }
 
+    /// Sets text added to the end of the system message when the prompt is built, e.g. a summary of turns removed
+    /// from a long conversation. If the prompt has no system message, it's sent as one.
+    ///
+    /// It's kept apart from the messages, so it's also added to a system message prepended at request time,
+    /// like the llama.cpp server system prompt, and replacing the system message doesn't lose it.
+    ///
+    /// # Arguments
+    ///
+    /// * `summary` - The text to add. It's separated from the system message by a blank line.
+    pub fn set_summary<T: AsRef<str>>(&self, summary: T) {
+        *self.summary() = Some(summary.as_ref().to_owned());
+        self.clear_built_prompt();
+    }
+
+    /// Removes the text set with [LlmPrompt::set_summary].
+    pub fn clear_summary(&self) {
+        if self.summary().take().is_some() {
+            self.clear_built_prompt();
+        }
+    }
+
     /// Clears any previously set generation prefix.
     pub fn clear_generation_prefix(&self) {
         self.clear_built_prompt();
@@ -204,14 +267,172 @@ impl LlmPrompt {
         };
     }
 
-    /// Resets the prompt, clearing all messages and built state.
+    /// Replaces the content of the message at `index`, e.g. when a user edits a prior turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the message in the prompt
+    /// * `content` - The new content of the message
+    ///
+    /// # Returns
+    ///
+    /// A reference to the edited message, or an error if the index is out of range or the content is empty.
+    pub fn edit_message<T: AsRef<str>>(
+        &self,
+        index: usize,
+        content: T,
+    ) -> Result<Arc<PromptMessage>, crate::Error> {
+        if content.as_ref().is_empty() {
+            crate::bail!("Cannot edit message {index} to have empty content.");
+        }
+        let message = self.message(index)?;
+        message.set_content(content);
+        self.clear_built_prompt();
+        Ok(message)
+    }
+
+    /// Removes the message at `index`.
+    ///
+    /// The remaining messages must still follow the turn ordering rules, so removing a single
+    /// user or assistant message from the middle of a conversation is an error. Use
+    /// [LlmPrompt::truncate_to] to drop a turn and everything after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the message in the prompt
+    ///
+    /// # Returns
+    ///
+    /// The removed message, or an error if the index is out of range or the removal would break the turn ordering.
+    /// The prompt is unchanged on error.
+    pub fn remove_message(&self, index: usize) -> Result<Arc<PromptMessage>, crate::Error> {
+        let message = {
+            let mut messages = self.messages();
+            if index >= messages.len() {
+                crate::bail!(
+                    "Message index {index} out of range for prompt with {} messages.",
+                    messages.len()
+                );
+            }
+            let mut remaining = messages.clone();
+            let message = remaining.remove(index);
+            validate_message_order(&remaining)?;
+            *messages = remaining;
+            message
+        };
+        self.clear_built_prompt();
+        Ok(message)
+    }
+
+    /// Keeps the first `len` messages and removes the rest, e.g. to regenerate a response
+    /// from an earlier turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of messages to keep
+    ///
+    /// # Returns
+    ///
+    /// An error if `len` is greater than the number of messages.
+    pub fn truncate_to(&self, len: usize) -> Result<(), crate::Error> {
+        {
+            let mut messages = self.messages();
+            if len > messages.len() {
+                crate::bail!(
+                    "Cannot truncate prompt with {} messages to {len} messages.",
+                    messages.len()
+                );
+            }
+            messages.truncate(len);
+        }
+        self.clear_built_prompt();
+        Ok(())
+    }
+
+    /// Drops the oldest user and assistant turns until the prompt fits in `max_tokens`, for keeping a
+    /// long conversation within the model's context.
+    ///
+    /// Turns are dropped in user/assistant pairs, so the history still starts with a user message
+    /// after the system message, which is never dropped. An assistant message directly after the system
+    /// message is dropped with the first pair. The last turn is always kept.
+    ///
+    /// If the prompt ends with a user message, the built prompt's total tokens are checked. If it
+    /// ends with an assistant message, e.g. before adding the next user message, the tokens are
+    /// counted like [LlmPrompt::count_conversation_tokens] with an empty next message.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_tokens` - The token budget for the prompt
+    ///
+    /// # Returns
+    ///
+    /// The number of user/assistant pairs dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prompt fails to build, or if it's still over the budget after dropping
+    /// every turn but the last. The turns dropped before the error stay dropped.
+    pub fn trim_to_token_budget(&self, max_tokens: u64) -> Result<usize, crate::Error> {
+        let mut dropped = 0;
+        loop {
+            let total_tokens = self.total_conversation_tokens()?;
+            if total_tokens <= max_tokens {
+                return Ok(dropped);
+            }
+            if self.drop_oldest_turns(1) == 0 {
+                crate::bail!(
+                    "Prompt is {total_tokens} tokens after dropping {dropped} turns, over the budget of {max_tokens} tokens."
+                );
+            }
+            dropped += 1;
+        }
+    }
+
+    /// Removes up to `count` of the oldest user/assistant pairs after the system message.
+    /// The system message and the last turn are never removed. An assistant message directly after the
+    /// system message, e.g. a greeting, is removed with the first pair. See [LlmPrompt::trim_to_token_budget].
+    ///
+    /// # Returns
+    ///
+    /// The number of pairs removed.
+    pub fn drop_oldest_turns(&self, count: usize) -> usize {
+        let dropped = {
+            let mut messages = self.messages();
+            let first_turn = match messages.first() {
+                Some(first) if first.message_type == PromptMessageType::System => 1,
+                _ => 0,
+            };
+            // Dropping the pair after a leading assistant message would leave the history starting with an assistant turn.
+            let leading_assistant = match messages.get(first_turn) {
+                Some(message) if message.message_type == PromptMessageType::Assistant => 1,
+                _ => 0,
+            };
+            let droppable = messages
+                .len()
+                .saturating_sub(first_turn + leading_assistant + 1)
+                / 2;
+            let dropped = count.min(droppable);
+            if dropped > 0 {
+                messages.drain(first_turn..first_turn + leading_assistant + dropped * 2);
+            }
+            dropped
+        };
+        if dropped > 0 {
+            self.clear_built_prompt();
+        }
+        dropped
+    }
+
+    /// Resets the prompt, clearing all messages, the summary, and built state.
     pub fn reset_prompt(&self) {
         self.messages().clear();
+        *self.summary() = None;
         self.clear_built_prompt();
     }
 
     /// Clears any built prompt state, forcing a rebuild on next access.
     pub fn clear_built_prompt(&self) {
+        *self.built_prompt_messages() = None;
         if let Some(api_prompt) = &self.api_prompt {
             api_prompt.clear_built_prompt();
         };
@@ -223,12 +444,30 @@ impl LlmPrompt {
     // Getter methods
     //
 
+    /// Returns the number of messages in the prompt.
+    pub fn message_count(&self) -> usize {
+        self.messages().len()
+    }
+
+    /// Returns the message at `index`, or an error if the index is out of range.
+    pub fn message(&self, index: usize) -> Result<Arc<PromptMessage>, crate::Error> {
+        let messages = self.messages();
+        match messages.get(index) {
+            Some(message) => Ok(message.clone()),
+            None => crate::bail!(
+                "Message index {index} out of range for prompt with {} messages.",
+                messages.len()
+            ),
+        }
+    }
+
     /// Gets and builds the local prompt if this is prompt has one.
     ///
     /// # Returns
     ///
     /// A reference to the `LocalPrompt` if present, otherwise returns an error
     pub fn local_prompt(&self) -> Result<&LocalPrompt, crate::Error> {
+        self.clear_built_if_messages_changed();
         if let Some(local_prompt) = &self.local_prompt {
             if local_prompt.get_built_prompt().is_err() {
                 self.precheck_build()?;
@@ -246,6 +485,7 @@ impl LlmPrompt {
     ///
     /// A reference to the `ApiPrompt` if present, otherwise returns an error
     pub fn api_prompt(&self) -> Result<&ApiPrompt, crate::Error> {
+        self.clear_built_if_messages_changed();
         if let Some(api_prompt) = &self.api_prompt {
             if api_prompt.get_built_prompt().is_err() {
                 self.precheck_build()?;
@@ -275,21 +515,88 @@ impl LlmPrompt {
     /// - The build process fails
     /// - The built messages are unexpectedly None after building
     pub fn get_built_prompt_messages(&self) -> Result<Vec<HashMap<String, String>>, crate::Error> {
-        let built_prompt_messages = self.built_prompt_messages();
-
-        if let Some(built_prompt_messages) = &*built_prompt_messages {
+        self.clear_built_if_messages_changed();
+        if let Some(built_prompt_messages) = &*self.built_prompt_messages() {
             return Ok(built_prompt_messages.clone());
         };
 
         self.precheck_build()?;
         self.build_prompt()?;
-        if let Some(built_prompt_messages) = &*built_prompt_messages {
+        if let Some(built_prompt_messages) = &*self.built_prompt_messages() {
             Ok(built_prompt_messages.clone())
         } else {
             crate::bail!("built_prompt_messages is None after building!");
         }
     }
 
+    /// Projects the total prompt tokens if a user message of `next_message_tokens` tokens were added.
+    ///
+    /// Unlike [LocalPrompt::get_total_prompt_tokens] and [ApiPrompt::get_total_prompt_tokens], this
+    /// includes the chat template's per-turn overhead for the hypothetical message, and for local
+    /// prompts, the generation prefix the model is prompted with. Useful for deciding when to
+    /// summarize or evict history before the context limit is hit, rather than at request time.
+    /// The prompt itself is not modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `next_message_tokens` - The length of the next user message in tokens
+    ///
+    /// # Returns
+    ///
+    /// The projected total token count of the prompt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a user message can't be added next, i.e. the last message is a user message,
+    /// or if the prompt fails to build.
+    pub fn count_conversation_tokens(&self, next_message_tokens: u64) -> Result<u64, crate::Error> {
+        // A placeholder is used because messages with empty content are skipped when building.
+        const PLACEHOLDER: &str = "placeholder";
+        let projected = self.clone();
+        projected.add_user_message()?.set_content(PLACEHOLDER);
+
+        let (total_prompt_tokens, placeholder_tokens) =
+            if let Some(local_prompt) = &projected.local_prompt {
+                if !local_prompt.has_generation_prefix() {
+                    local_prompt.set_generation_prefix("");
+                }
+                let local_prompt = projected.local_prompt()?;
+                (
+                    local_prompt.get_total_prompt_tokens()?,
+                    local_prompt.count_tokens(PLACEHOLDER),
+                )
+            } else if projected.api_prompt.is_some() {
+                let api_prompt = projected.api_prompt()?;
+                (
+                    api_prompt.get_total_prompt_tokens()?,
+                    api_prompt.count_tokens(PLACEHOLDER),
+                )
+            } else {
+                crate::bail!("LlmPrompt has neither a LocalPrompt or ApiPrompt");
+            };
+
+        Ok(total_prompt_tokens.saturating_sub(placeholder_tokens) + next_message_tokens)
+    }
+
+    /// The total tokens of the prompt, or if it doesn't end with a user message, of the prompt plus an empty user message.
+    /// This is the count [LlmPrompt::trim_to_token_budget] checks against its budget.
+    pub fn total_conversation_tokens(&self) -> Result<u64, crate::Error> {
+        let ends_with_user = self
+            .messages()
+            .last()
+            .is_some_and(|last| last.message_type == PromptMessageType::User);
+        if !ends_with_user {
+            return self.count_conversation_tokens(0);
+        }
+        if self.local_prompt.is_some() {
+            self.local_prompt()?.get_total_prompt_tokens()
+        } else if self.api_prompt.is_some() {
+            self.api_prompt()?.get_total_prompt_tokens()
+        } else {
+            crate::bail!("LlmPrompt has neither a LocalPrompt or ApiPrompt");
+        }
+    }
+
     // Builder methods
     //
 
@@ -312,6 +619,7 @@ impl LlmPrompt {
     fn build_prompt(&self) -> crate::Result<()> {
         let messages = self.messages();
         let mut built_prompt_messages: Vec<HashMap<String, String>> = Vec::new();
+        let mut content_tokens: Vec<u32> = Vec::new();
         let mut last_message_type = None;
 
         for (i, message) in messages.iter().enumerate() {
@@ -333,7 +641,7 @@ impl LlmPrompt {
                 match (last_message_type, message_type) {
                     (Some(PromptMessageType::User), PromptMessageType::Assistant) => {},
                     (Some(PromptMessageType::Assistant), PromptMessageType::User) => {},
-                    (Some(PromptMessageType::System), PromptMessageType::User) => {},
+                    (Some(PromptMessageType::System), PromptMessageType::User | PromptMessageType::Assistant) => {},
                     _ => panic!("Messages must alternate between User and Assistant after the first message (which can be System)."),
                 }
             }
@@ -344,12 +652,48 @@ impl LlmPrompt {
                     ("role".to_string(), message.message_type.as_str().to_owned()),
                     ("content".to_string(), built_message_string.to_owned()),
                 ]));
+                if let Some(api_prompt) = &self.api_prompt {
+                    content_tokens.push(message.count_tokens_for_built(
+                        built_message_string,
+                        api_prompt.tokenizer().as_ref(),
+                    ));
+                }
             } else {
                 eprintln!("message.built_content is empty and skipped");
                 continue;
                 // This should be an error? Unless we're just building to display?
             }
         }
+        drop(messages);
+
+        if let Some(summary) = &*self.summary() {
+            let is_system = |message: &HashMap<String, String>| {
+                message.get("role").map(String::as_str) == Some(PromptMessageType::System.as_str())
+            };
+            let content = match built_prompt_messages.first() {
+                Some(first) if is_system(first) => {
+                    format!("{}\n\n{summary}", first["content"])
+                }
+                _ => {
+                    built_prompt_messages.insert(
+                        0,
+                        HashMap::from([(
+                            "role".to_string(),
+                            PromptMessageType::System.as_str().to_owned(),
+                        )]),
+                    );
+                    content_tokens.insert(0, 0);
+                    summary.to_owned()
+                }
+            };
+            if let Some(api_prompt) = &self.api_prompt {
+                content_tokens[0] = prompt_tokenizer::cached_count_tokens(
+                    api_prompt.tokenizer().as_ref(),
+                    &content,
+                );
+            }
+            built_prompt_messages[0].insert("content".to_string(), content);
+        }
 
         *self.built_prompt_messages.lock().unwrap_or_else(|e| {
             panic!(
@@ -359,15 +703,28 @@ impl LlmPrompt {
         }) = Some(built_prompt_messages.clone());
 
         if let Some(api_prompt) = &self.api_prompt {
-            api_prompt.build_prompt(&built_prompt_messages);
+            api_prompt.build_prompt(&built_prompt_messages, &content_tokens);
         };
         if let Some(local_prompt) = &self.local_prompt {
-            local_prompt.build_prompt(&built_prompt_messages);
+            local_prompt.build_prompt(&built_prompt_messages)?;
         };
 
         Ok(())
     }
 
+    /// Clears the built prompt if the content of any message changed since it was built,
+    /// e.g. with [PromptMessage::append_content] on a message of an already built prompt.
+    fn clear_built_if_messages_changed(&self) {
+        let mut changed = false;
+        for message in self.messages().iter() {
+            // Take every flag, so a change that's already been handled isn't seen again on the next build.
+            changed |= message.take_changed();
+        }
+        if changed {
+            self.clear_built_prompt();
+        }
+    }
+
     // Helper methods
     //
 
@@ -382,6 +739,12 @@ impl LlmPrompt {
             .clone()
     }
 
+    fn summary(&self) -> MutexGuard<'_, Option<String>> {
+        self.summary
+            .lock()
+            .unwrap_or_else(|e| panic!("LlmPrompt Error - summary not available: {:?}", e))
+    }
+
     fn built_prompt_messages(&self) -> MutexGuard<'_, Option<Vec<HashMap<String, String>>>> {
         self.built_prompt_messages.lock().unwrap_or_else(|e| {
             panic!(
@@ -392,6 +755,30 @@ impl LlmPrompt {
     }
 }
 
+/// Checks the turn ordering rules the `add_*_message` methods enforce: a system message can only be first,
+/// the first message must be a system or user message, and user and assistant messages must alternate after it.
+/// Either can follow the system message, e.g. an assistant greeting.
+fn validate_message_order(messages: &[Arc<PromptMessage>]) -> Result<(), crate::Error> {
+    let mut last_message_type: Option<&PromptMessageType> = None;
+    for (i, message) in messages.iter().enumerate() {
+        match (last_message_type, &message.message_type) {
+            (None, PromptMessageType::System | PromptMessageType::User)
+            | (
+                Some(PromptMessageType::System),
+                PromptMessageType::User | PromptMessageType::Assistant,
+            )
+            | (Some(PromptMessageType::Assistant), PromptMessageType::User)
+            | (Some(PromptMessageType::User), PromptMessageType::Assistant) => (),
+            (_, message_type) => crate::bail!(
+                "Message {i} ({}) breaks the turn ordering. A system message can only be first, an assistant message can't be first, and user and assistant messages must alternate.",
+                message_type.as_str()
+            ),
+        }
+        last_message_type = Some(&message.message_type);
+    }
+    Ok(())
+}
+
 impl Default for LlmPrompt {
     fn default() -> Self {
         Self {
@@ -400,6 +787,7 @@ impl Default for LlmPrompt {
             messages: PromptMessages::default(),
             concatenator: TextConcatenator::default(),
             built_prompt_messages: Mutex::new(None),
+            summary: Mutex::new(None),
         }
     }
 }
@@ -412,6 +800,7 @@ impl Clone for LlmPrompt {
             messages: self.messages.clone(),
             concatenator: self.concatenator.clone(),
             built_prompt_messages: self.built_prompt_messages().clone().into(),
+            summary: self.summary().clone().into(),
         }
     }
 }
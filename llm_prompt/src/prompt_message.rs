@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use super::TextConcatenator;
-use std::sync::{Arc, Mutex, MutexGuard};
+use super::{
+    prompt_tokenizer::{cached_count_tokens, cached_tokenize},
+    PromptTokenizer, TextConcatenator,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, MutexGuard,
+};
 
 /// Represents the type of message in a prompt sequence.
 ///
@@ -74,12 +80,22 @@ impl std::fmt::Display for PromptMessages {
 /// Represents a single message with its content, type, and concatenation rules.
 /// Maintains thread-safe interior mutability for content manipulation and
 /// provides methods for building and accessing the message content.
+///
+/// The content is kept as a list of fragments joined with the concatenator's separator.
+/// Changing it only discards the built message, which is rebuilt when it's next read,
+/// so a message can be assembled from many fragments without rebuilding after each one.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PromptMessage {
     pub content: Mutex<Vec<String>>,
     pub built_prompt_message: Mutex<Option<String>>,
     pub message_type: PromptMessageType,
     pub concatenator: TextConcatenator,
+    /// Token ids supplied with [PromptMessage::set_tokens]. Cleared whenever the content changes.
+    #[serde(skip)]
+    supplied_tokens: Mutex<Option<Vec<u32>>>,
+    /// Set when the content changes, so the [crate::LlmPrompt] holding the message knows to rebuild.
+    #[serde(skip)]
+    changed: AtomicBool,
 }
 
 impl PromptMessage {
@@ -89,6 +105,8 @@ impl PromptMessage {
             built_prompt_message: None.into(),
             message_type,
             concatenator: concatenator.clone(),
+            supplied_tokens: None.into(),
+            changed: AtomicBool::new(false),
         }
     }
 
@@ -98,7 +116,7 @@ impl PromptMessage {
     /// Sets the primary content of the message, replacing any existing content.
     ///
     /// If the provided content is empty, the message remains unchanged. Otherwise,
-    /// replaces all existing content with the new content and rebuilds the message.
+    /// replaces all existing content with the new content.
     ///
     /// # Arguments
     ///
@@ -113,13 +131,9 @@ impl PromptMessage {
         }
 
         let mut content_guard = self.content();
-        let should_update = content_guard
-            .first()
-            .map_or(true, |first| first != content.as_ref());
-
-        if should_update {
+        if *content_guard != [content.as_ref()] {
             *content_guard = vec![content.as_ref().to_owned()];
-            self.build(content_guard);
+            self.clear_built();
         }
 
         self
@@ -128,8 +142,8 @@ impl PromptMessage {
     /// Adds content to the beginning of the message.
     ///
     /// If the provided content is empty, the message remains unchanged. Otherwise,
-    /// inserts the new content at the start of the existing content and rebuilds
-    /// the message.
+    /// inserts the new content at the start of the existing content, joined to it
+    /// with the message's concatenator.
     ///
     /// # Arguments
     ///
@@ -143,23 +157,17 @@ impl PromptMessage {
             return self;
         }
 
-        let mut content_guard = self.content();
-        let should_update = content_guard
-            .first()
-            .map_or(true, |first| first != content.as_ref());
-
-        if should_update {
-            content_guard.insert(0, content.as_ref().to_owned());
-            self.build(content_guard);
-        }
-
+        self.content().insert(0, content.as_ref().to_owned());
+        self.clear_built();
         self
     }
 
     /// Adds content to the end of the message.
     ///
     /// If the provided content is empty, the message remains unchanged. Otherwise,
-    /// adds the new content after existing content and rebuilds the message.
+    /// adds the new content after the existing content, joined to it with the
+    /// message's concatenator. Each call adds a fragment, so it can be called in a
+    /// loop, e.g. for each document or streamed piece.
     ///
     /// # Arguments
     ///
@@ -173,16 +181,44 @@ impl PromptMessage {
             return self;
         }
 
-        let mut content_guard = self.content();
-        let should_update = content_guard
-            .last()
-            .map_or(true, |last| last != content.as_ref());
+        self.content().push(content.as_ref().to_owned());
+        self.clear_built();
+        self
+    }
 
-        if should_update {
-            content_guard.push(content.as_ref().to_owned());
-            self.build(content_guard);
+    /// Removes all content from the message.
+    ///
+    /// A message without content is skipped when the prompt is built, so set new content
+    /// before sending the prompt.
+    ///
+    /// # Returns
+    ///
+    /// A reference to self for method chaining
+    pub fn clear_content(&self) -> &Self {
+        let mut content_guard = self.content();
+        if !content_guard.is_empty() {
+            content_guard.clear();
+            self.clear_built();
         }
+        self
+    }
 
+    /// Supplies the token ids of the current content, e.g. from tokenizing the same supporting material once for a batch of
+    /// workflow runs. They're used for the message's token count and an API prompt's total instead of running the tokenizer,
+    /// until the content changes. Local prompts tokenize the whole rendered prompt, so they don't use them.
+    ///
+    /// The ids aren't checked, so they must come from the tokenizer of the backend the message is sent to.
+    /// Set the content first, since changing it discards the ids.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token ids of the built message
+    ///
+    /// # Returns
+    ///
+    /// A reference to self for method chaining
+    pub fn set_tokens(&self, tokens: Vec<u32>) -> &Self {
+        *self.supplied_tokens() = Some(tokens);
         self
     }
 
@@ -200,30 +236,73 @@ impl PromptMessage {
     ///
     /// # Errors
     ///
-    /// Returns an error if the message has not been built yet.
+    /// Returns an error if the message has no content.
     pub fn get_built_prompt_message(&self) -> Result<String, crate::Error> {
         match &*self.built_prompt_message() {
             Some(prompt) => Ok(prompt.clone()),
             None => crate::bail!(
-                " PromptMessage Error - built_prompt_string not available - message not built"
+                " PromptMessage Error - built_prompt_string not available - message has no content"
             ),
         }
     }
 
+    /// Counts the tokens of the built message with `tokenizer`.
+    ///
+    /// The count is memoized by content for tokenizers with a [PromptTokenizer::tokenizer_id], so any message with the same
+    /// content, e.g. the same supporting material in each workflow run, is only tokenized once.
+    /// Returns 0 if the message has no content.
+    pub fn count_tokens(&self, tokenizer: &dyn PromptTokenizer) -> u32 {
+        match &*self.built_prompt_message() {
+            Some(built_prompt_message) => {
+                self.count_tokens_for_built(built_prompt_message, tokenizer)
+            }
+            None => 0,
+        }
+    }
+
+    /// Tokenizes the built message with `tokenizer`, memoized like [PromptMessage::count_tokens].
+    /// Returns no tokens if the message has no content.
+    pub fn tokenize(&self, tokenizer: &dyn PromptTokenizer) -> Vec<u32> {
+        let built_prompt_message = match &*self.built_prompt_message() {
+            Some(built_prompt_message) => built_prompt_message.clone(),
+            None => return Vec::new(),
+        };
+        if let Some(tokens) = &*self.supplied_tokens() {
+            return tokens.clone();
+        }
+        cached_tokenize(tokenizer, &built_prompt_message)
+    }
+
     // Builder methods
     //
 
-    fn build(&self, content_guard: MutexGuard<'_, Vec<String>>) {
-        let mut built_prompt_message = String::new();
-
-        for c in content_guard.iter() {
-            if !built_prompt_message.is_empty() {
-                built_prompt_message.push_str(self.concatenator.as_str());
-            }
-            built_prompt_message.push_str(c.as_str());
+    /// Counts the tokens of `built_prompt_message`, which the caller has already locked.
+    pub(crate) fn count_tokens_for_built(
+        &self,
+        built_prompt_message: &str,
+        tokenizer: &dyn PromptTokenizer,
+    ) -> u32 {
+        if let Some(tokens) = &*self.supplied_tokens() {
+            return tokens.len() as u32;
         }
+        cached_count_tokens(tokenizer, built_prompt_message)
+    }
+
+    /// Discards the built message and its tokens after the content changes.
+    fn clear_built(&self) {
+        *self.built_prompt_message.lock().unwrap_or_else(|e| {
+            panic!(
+                "PromptMessage Error - built_prompt_message not available: {:?}",
+                e
+            )
+        }) = None;
+        *self.supplied_tokens() = None;
+        self.changed.store(true, Ordering::SeqCst);
+    }
 
-        *self.built_prompt_message() = Some(built_prompt_message);
+    /// Returns true if the content changed since the last call, and resets the flag.
+    pub(crate) fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::SeqCst)
     }
 
     // Helper methods
@@ -235,23 +314,41 @@ impl PromptMessage {
             .unwrap_or_else(|e| panic!("PromptMessage Error - content not available: {:?}", e))
     }
 
+    fn supplied_tokens(&self) -> MutexGuard<'_, Option<Vec<u32>>> {
+        self.supplied_tokens.lock().unwrap_or_else(|e| {
+            panic!(
+                "PromptMessage Error - supplied_tokens not available: {:?}",
+                e
+            )
+        })
+    }
+
+    /// Locks the built message, building it from the content first if it was cleared.
     pub(crate) fn built_prompt_message(&self) -> MutexGuard<'_, Option<String>> {
-        self.built_prompt_message.lock().unwrap_or_else(|e| {
+        let content_guard = self.content();
+        let mut built_prompt_message = self.built_prompt_message.lock().unwrap_or_else(|e| {
             panic!(
                 "PromptMessage Error - built_prompt_message not available: {:?}",
                 e
             )
-        })
+        });
+        if built_prompt_message.is_none() && !content_guard.is_empty() {
+            *built_prompt_message = Some(content_guard.join(self.concatenator.as_str()));
+        }
+        built_prompt_message
     }
 }
 
 impl Clone for PromptMessage {
     fn clone(&self) -> Self {
+        let built_prompt_message = self.built_prompt_message().clone();
         Self {
             content: self.content().clone().into(),
-            built_prompt_message: self.built_prompt_message().clone().into(),
+            built_prompt_message: built_prompt_message.into(),
             message_type: self.message_type.clone(),
             concatenator: self.concatenator.clone(),
+            supplied_tokens: self.supplied_tokens().clone().into(),
+            changed: AtomicBool::new(self.changed.load(Ordering::SeqCst)),
         }
     }
 }
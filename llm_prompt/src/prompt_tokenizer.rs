@@ -1,4 +1,8 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, LazyLock, Mutex},
+};
 
 /// A trait for tokenizers that can be used with the prompt management system.
 ///
@@ -35,6 +39,16 @@ pub trait PromptTokenizer: Send + Sync {
     ///
     /// The number of tokens in the input text
     fn count_tokens(&self, input: &str) -> u32;
+
+    /// Identifies the tokenizer, so the tokens of a text can be memoized and reused by any prompt or message with the same
+    /// text. Tokenizers that return the same id must tokenize identically.
+    ///
+    /// # Returns
+    ///
+    /// `None`, the default, to tokenize every time
+    fn tokenizer_id(&self) -> Option<u64> {
+        None
+    }
 }
 
 impl PromptTokenizer for Arc<dyn PromptTokenizer> {
@@ -45,4 +59,77 @@ impl PromptTokenizer for Arc<dyn PromptTokenizer> {
     fn count_tokens(&self, input: &str) -> u32 {
         (**self).count_tokens(input)
     }
+
+    fn tokenizer_id(&self) -> Option<u64> {
+        (**self).tokenizer_id()
+    }
+}
+
+const TOKEN_CACHE_CAPACITY: usize = 256;
+
+type TokenCacheKey = (u64, usize, u64);
+
+/// Tokens keyed by [PromptTokenizer::tokenizer_id] and the length and hash of the text.
+/// Workflows build new messages and prompts on every run, so this is what lets repeated runs over the same supporting
+/// material skip tokenizing it again.
+static TOKEN_CACHE: LazyLock<Mutex<HashMap<TokenCacheKey, CachedTokens>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone)]
+struct CachedTokens {
+    count: u32,
+    ids: Option<Vec<u32>>,
+}
+
+fn token_cache_key(tokenizer_id: u64, text: &str) -> TokenCacheKey {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    (tokenizer_id, text.len(), hasher.finish())
+}
+
+fn cache_tokens(key: TokenCacheKey, tokens: CachedTokens) {
+    let mut cache = TOKEN_CACHE.lock().unwrap();
+    if cache.len() >= TOKEN_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(key, tokens);
+}
+
+/// Counts the tokens of `text`, memoized for tokenizers with a [PromptTokenizer::tokenizer_id].
+pub(crate) fn cached_count_tokens(tokenizer: &dyn PromptTokenizer, text: &str) -> u32 {
+    let Some(tokenizer_id) = tokenizer.tokenizer_id() else {
+        return tokenizer.count_tokens(text);
+    };
+    let key = token_cache_key(tokenizer_id, text);
+    if let Some(cached) = TOKEN_CACHE.lock().unwrap().get(&key) {
+        return cached.count;
+    }
+    let count = tokenizer.count_tokens(text);
+    cache_tokens(key, CachedTokens { count, ids: None });
+    count
+}
+
+/// Tokenizes `text`, memoized like [cached_count_tokens].
+pub(crate) fn cached_tokenize(tokenizer: &dyn PromptTokenizer, text: &str) -> Vec<u32> {
+    let Some(tokenizer_id) = tokenizer.tokenizer_id() else {
+        return tokenizer.tokenize(text);
+    };
+    let key = token_cache_key(tokenizer_id, text);
+    if let Some(ids) = TOKEN_CACHE
+        .lock()
+        .unwrap()
+        .get(&key)
+        .and_then(|cached| cached.ids.clone())
+    {
+        return ids;
+    }
+    let ids = tokenizer.tokenize(text);
+    cache_tokens(
+        key,
+        CachedTokens {
+            count: ids.len() as u32,
+            ids: Some(ids.clone()),
+        },
+    );
+    ids
 }
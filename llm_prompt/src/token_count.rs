@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::PromptTokenizer;
+use crate::{prompt_tokenizer::cached_count_tokens, PromptTokenizer};
 use thiserror::Error;
 
 pub const DEFAULT_SAFETY_TOKENS: u64 = 10;
@@ -69,38 +69,51 @@ fn available_tokens(
     safety_tokens: Option<u64>,
 ) -> Result<u64, RequestTokenLimitError> {
     let safety_tokens = safety_tokens.unwrap_or(DEFAULT_SAFETY_TOKENS);
+    // The margin is taken from the response's share of the context twice, so a margin of half the context leaves nothing.
+    let margin = safety_tokens.saturating_mul(2);
+    let response_ctx_size = inference_ctx_size.map_or(ctx_size, |size| size.min(ctx_size));
+    if margin >= response_ctx_size {
+        return Err(RequestTokenLimitError::SafetyTokensExceeds {
+            safety_tokens,
+            ctx_size: response_ctx_size,
+        });
+    }
 
-    if total_prompt_tokens as u64 >= ctx_size - safety_tokens {
+    let prompt_ctx_size = ctx_size - safety_tokens;
+    if total_prompt_tokens >= prompt_ctx_size {
         return Err(RequestTokenLimitError::PromptTokensExceeds {
             total_prompt_tokens,
-            ctx_size: ctx_size - safety_tokens,
+            ctx_size: prompt_ctx_size,
         });
     }
 
-    let available_tokens = if let Some(inference_ctx_size) = inference_ctx_size {
-        std::cmp::min(ctx_size - total_prompt_tokens, inference_ctx_size) - safety_tokens
-    } else {
-        ctx_size - total_prompt_tokens - safety_tokens
-    };
-    if available_tokens == 0 {
-        panic!("available_tokens == 0. This should never happen.",);
+    match std::cmp::min(ctx_size - total_prompt_tokens, response_ctx_size).checked_sub(margin) {
+        Some(available_tokens) if available_tokens > 0 => Ok(available_tokens),
+        _ => Err(RequestTokenLimitError::PromptTokensExceeds {
+            total_prompt_tokens,
+            ctx_size: prompt_ctx_size,
+        }),
     }
-    Ok(available_tokens - safety_tokens)
 }
 
+/// `content_tokens` holds the token count of each message's content, in order, as memoized by [crate::PromptMessage::count_tokens].
 pub(crate) fn total_prompt_tokens_openai_format(
-    built_prompt_messages: &Vec<std::collections::HashMap<String, String>>,
+    built_prompt_messages: &[std::collections::HashMap<String, String>],
+    content_tokens: &[u32],
     tokens_per_message: Option<u32>,
     tokens_per_name: Option<i32>,
     tokenizer: &Arc<dyn PromptTokenizer>,
 ) -> u64 {
     let tokens_per_message = tokens_per_message.unwrap_or(0);
     let mut num_tokens: u64 = 0;
-    for message in built_prompt_messages {
+    for (i, message) in built_prompt_messages.iter().enumerate() {
         num_tokens += tokens_per_message as u64;
 
         for (key, value) in message.iter() {
-            num_tokens += tokenizer.count_tokens(value) as u64;
+            num_tokens += match content_tokens.get(i) {
+                Some(content_tokens) if key == "content" => *content_tokens as u64,
+                _ => cached_count_tokens(tokenizer.as_ref(), value) as u64,
+            };
             if let Some(tokens_per_name) = tokens_per_name {
                 if key == "name" {
                     if tokens_per_name < 0 {
@@ -130,6 +143,10 @@ pub enum RequestTokenLimitError {
         total_prompt_tokens: u64,
         ctx_size: u64,
     },
+    #[error(
+        "safety_tokens ({safety_tokens}) leaves no room for a response in ctx_size ({ctx_size})"
+    )]
+    SafetyTokensExceeds { safety_tokens: u64, ctx_size: u64 },
     #[error("GenericPromptError: {e}")]
     GenericPromptError { e: String },
     #[error("PromptTokensNotSet: Prompt tokens not set.")]
@@ -96,20 +96,33 @@ impl ApiPrompt {
     // Builder methods
     //
 
-    pub(crate) fn build_prompt(&self, built_prompt_messages: &Vec<HashMap<String, String>>) {
+    pub(crate) fn build_prompt(
+        &self,
+        built_prompt_messages: &[HashMap<String, String>],
+        content_tokens: &[u32],
+    ) {
         *self.total_prompt_tokens() = Some(total_prompt_tokens_openai_format(
-            &built_prompt_messages,
+            built_prompt_messages,
+            content_tokens,
             self.tokens_per_message,
             self.tokens_per_name,
             &self.tokenizer,
         ));
 
-        *self.built_prompt_messages() = Some(built_prompt_messages.clone());
+        *self.built_prompt_messages() = Some(built_prompt_messages.to_vec());
     }
 
     // Helper methods
     //
 
+    pub(crate) fn count_tokens(&self, input: &str) -> u64 {
+        self.tokenizer.count_tokens(input) as u64
+    }
+
+    pub(crate) fn tokenizer(&self) -> &Arc<dyn PromptTokenizer> {
+        &self.tokenizer
+    }
+
     fn built_prompt_messages(&self) -> MutexGuard<'_, Option<Vec<HashMap<String, String>>>> {
         self.built_prompt_messages.lock().unwrap_or_else(|e| {
             panic!(
@@ -1,10 +1,31 @@
-use crate::PromptTokenizer;
+use crate::{prompt_tokenizer::cached_tokenize, PromptTokenizer};
 use minijinja::value::{from_args, Value, ValueKind};
 use minijinja::{context, Environment, Error, ErrorKind};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::sync::{Arc, MutexGuard};
+use thiserror::Error as ThisError;
+
+/// The name the chat template is registered under in the template environment.
+const CHAT_TEMPLATE_NAME: &str = "chat_template";
+
+/// Errors returned when a chat template fails to compile or render.
+///
+/// The template is only a string here, so the caller adds where it came from, as the model loaders do.
+#[derive(ThisError, Debug)]
+pub enum ChatTemplateError {
+    #[error("Chat template failed to compile: {source:#}")]
+    Compile { source: minijinja::Error },
+    #[error(
+        "Chat template failed to render message at index {message_index} (role: {role}): {source:#}"
+    )]
+    Render {
+        message_index: usize,
+        role: String,
+        source: minijinja::Error,
+    },
+}
 
 /// A prompt formatter for local LLMs that use chat templates.
 ///
@@ -147,17 +168,36 @@ impl LocalPrompt {
         }
     }
 
+    /// Checks that the chat template compiles and renders a basic conversation.
+    ///
+    /// See [validate_chat_template] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [ChatTemplateError] if the template fails to compile or render.
+    pub fn validate_chat_template(&self) -> Result<(), ChatTemplateError> {
+        validate_chat_template(
+            &self.chat_template,
+            self.bos_token.as_deref(),
+            &self.eos_token,
+            self.unk_token.as_deref(),
+        )
+    }
+
     // Builder methods
     //
 
-    pub(crate) fn build_prompt(&self, built_prompt_messages: &Vec<HashMap<String, String>>) {
+    pub(crate) fn build_prompt(
+        &self,
+        built_prompt_messages: &[HashMap<String, String>],
+    ) -> Result<(), ChatTemplateError> {
         let mut built_prompt_string = apply_chat_template(
             built_prompt_messages,
             &self.chat_template,
             self.bos_token.as_deref(),
             &self.eos_token,
             self.unk_token.as_deref(),
-        );
+        )?;
 
         {
             if let Some(generation_prefix) = &*self.generation_prefix() {
@@ -168,15 +208,24 @@ impl LocalPrompt {
             }
         }
 
-        let built_prompt_as_tokens = self.tokenizer.tokenize(&built_prompt_string);
+        let built_prompt_as_tokens = cached_tokenize(self.tokenizer.as_ref(), &built_prompt_string);
         *self.total_prompt_tokens() = Some(built_prompt_as_tokens.len() as u64);
         *self.built_prompt_as_tokens() = Some(built_prompt_as_tokens);
         *self.built_prompt_string() = Some(built_prompt_string);
+        Ok(())
     }
 
     // Helper methods
     //
 
+    pub(crate) fn has_generation_prefix(&self) -> bool {
+        self.generation_prefix().is_some()
+    }
+
+    pub(crate) fn count_tokens(&self, input: &str) -> u64 {
+        self.tokenizer.count_tokens(input) as u64
+    }
+
     fn generation_prefix(&self) -> MutexGuard<'_, Option<String>> {
         self.generation_prefix.lock().unwrap_or_else(|e| {
             panic!(
@@ -266,18 +315,24 @@ impl std::fmt::Display for LocalPrompt {
 /// # Returns
 ///
 /// The formatted message as a String.
+///
+/// # Errors
+///
+/// Returns [ChatTemplateError::Compile] if the template is not valid jinja, or
+/// [ChatTemplateError::Render] with the index and role of the first message that
+/// fails to render.
 pub fn apply_chat_template(
-    messages: &Vec<HashMap<String, String>>,
+    messages: &[HashMap<String, String>],
     chat_template: &str,
     bos_token: Option<&str>,
     eos_token: &str,
     unk_token: Option<&str>,
-) -> String {
+) -> Result<String, ChatTemplateError> {
     let mut env = Environment::new();
     env.set_lstrip_blocks(true);
     env.set_trim_blocks(true);
-    env.add_template("chat_template", chat_template)
-        .expect("Failed to add template");
+    env.add_template(CHAT_TEMPLATE_NAME, chat_template)
+        .map_err(|source| ChatTemplateError::Compile { source })?;
     env.add_function("raise_exception", raise_exception);
 
     env.set_unknown_method_callback(|state, value, method, args| match (value.kind(), method) {
@@ -296,20 +351,75 @@ pub fn apply_chat_template(
     });
 
     let tmpl = env
-        .get_template("chat_template")
-        .expect("Failed to get template");
+        .get_template(CHAT_TEMPLATE_NAME)
+        .map_err(|source| ChatTemplateError::Compile { source })?;
 
     let unk_token = unk_token.unwrap_or("");
     let bos_token = bos_token.unwrap_or("");
 
-    tmpl.render(context! {
-        messages => messages,
-        add_generation_prompt => false,
-        bos_token => bos_token,
-        eos_token => eos_token,
-        unk_token => unk_token,
+    let render = |messages: &[HashMap<String, String>]| {
+        tmpl.render(context! {
+            messages => messages,
+            add_generation_prompt => false,
+            bos_token => bos_token,
+            eos_token => eos_token,
+            unk_token => unk_token,
+        })
+    };
+
+    render(messages).map_err(|source| {
+        // The render error doesn't say which message caused it, so find the
+        // shortest conversation that still fails.
+        let message_index = (0..messages.len())
+            .find(|i| render(&messages[..=*i]).is_err())
+            .unwrap_or(messages.len().saturating_sub(1));
+        let role = messages
+            .get(message_index)
+            .and_then(|m| m.get("role"))
+            .map_or("unknown", |r| r.as_str())
+            .to_owned();
+        ChatTemplateError::Render {
+            message_index,
+            role,
+            source,
+        }
+    })
+}
+
+/// Checks that a chat template compiles and renders a basic user/assistant/user
+/// conversation.
+///
+/// Intended to be called when a model is loaded so that broken templates are caught
+/// before the first request is built.
+///
+/// # Arguments
+///
+/// * `chat_template` - The chat template as a String.
+///
+/// # Errors
+///
+/// Returns a [ChatTemplateError] if the template fails to compile or render.
+pub fn validate_chat_template(
+    chat_template: &str,
+    bos_token: Option<&str>,
+    eos_token: &str,
+    unk_token: Option<&str>,
+) -> Result<(), ChatTemplateError> {
+    let messages: Vec<HashMap<String, String>> = [
+        ("user", "validate_user_message_1"),
+        ("assistant", "validate_assistant_message_1"),
+        ("user", "validate_user_message_2"),
+    ]
+    .iter()
+    .map(|(role, content)| {
+        HashMap::from([
+            ("role".to_string(), role.to_string()),
+            ("content".to_string(), content.to_string()),
+        ])
     })
-    .expect("Failed to render template without system prompt")
+    .collect();
+    apply_chat_template(&messages, chat_template, bos_token, eos_token, unk_token)?;
+    Ok(())
 }
 
 /// This exists specifically for the minijinja template engine to raise an exception.
@@ -2,5 +2,5 @@ mod api_prompt;
 mod local_prompt;
 
 pub use api_prompt::ApiPrompt;
-pub use local_prompt::apply_chat_template;
 pub use local_prompt::LocalPrompt;
+pub use local_prompt::{apply_chat_template, validate_chat_template, ChatTemplateError};
@@ -44,3 +44,400 @@ fn test_api() -> crate::Result<()> {
     assert_eq!(36, token_count);
     Ok(())
 }
+
+#[test]
+fn test_api_count_conversation_tokens() -> crate::Result<()> {
+    let model = ApiLlmModel::gpt_3_5_turbo();
+    let prompt = LlmPrompt::new_api_prompt(
+        model.model_base.tokenizer.clone(),
+        Some(model.tokens_per_message),
+        model.tokens_per_name,
+    );
+
+    prompt.add_system_message()?.set_content(SYSTEM_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_1);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+
+    let next_message_tokens = model.model_base.tokenizer.count_tokens(USER_PROMPT_2) as u64;
+    let projected = prompt.count_conversation_tokens(next_message_tokens)?;
+
+    prompt.add_user_message()?.set_content(USER_PROMPT_2);
+    assert_eq!(projected, prompt.api_prompt()?.get_total_prompt_tokens()?);
+    assert!(prompt
+        .count_conversation_tokens(next_message_tokens)
+        .is_err());
+    Ok(())
+}
+
+/// Counts whitespace separated words, and how many times it was called.
+struct CountingTokenizer {
+    id: u64,
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+impl CountingTokenizer {
+    /// Each test uses its own `id`, since the token cache is shared by the whole process.
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            calls: Default::default(),
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+impl llm_prompt::PromptTokenizer for CountingTokenizer {
+    fn tokenize(&self, input: &str) -> Vec<u32> {
+        self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        (0..input.split_whitespace().count() as u32).collect()
+    }
+    fn count_tokens(&self, input: &str) -> u32 {
+        self.tokenize(input).len() as u32
+    }
+    fn tokenizer_id(&self) -> Option<u64> {
+        Some(self.id)
+    }
+}
+
+#[test]
+fn test_prompt_message_token_cache() -> crate::Result<()> {
+    use llm_prompt::{PromptMessage, PromptMessageType, TextConcatenator};
+
+    let tokenizer = CountingTokenizer::new(1_001);
+    let message = PromptMessage::new(PromptMessageType::User, &TextConcatenator::default());
+    assert_eq!(message.count_tokens(&tokenizer), 0);
+
+    message.set_content("one two three");
+    assert_eq!(message.count_tokens(&tokenizer), 3);
+    assert_eq!(message.count_tokens(&tokenizer), 3);
+    assert_eq!(message.clone().count_tokens(&tokenizer), 3);
+    assert_eq!(tokenizer.calls(), 1);
+
+    message.append_content("four");
+    assert_eq!(message.count_tokens(&tokenizer), 4);
+    assert_eq!(message.tokenize(&tokenizer), vec![0, 1, 2, 3]);
+    assert_eq!(message.tokenize(&tokenizer), vec![0, 1, 2, 3]);
+    assert_eq!(tokenizer.calls(), 3);
+
+    // A new message with the same content reuses the tokens.
+    let other_message = PromptMessage::new(PromptMessageType::User, &TextConcatenator::default());
+    other_message
+        .set_content("one two three")
+        .append_content("four");
+    assert_eq!(other_message.tokenize(&tokenizer), vec![0, 1, 2, 3]);
+    assert_eq!(tokenizer.calls(), 3);
+
+    // But not with another tokenizer.
+    let other_tokenizer = CountingTokenizer::new(1_002);
+    assert_eq!(other_message.count_tokens(&other_tokenizer), 4);
+    assert_eq!(other_tokenizer.calls(), 1);
+
+    // Supplied tokens are used with any tokenizer, until the content changes.
+    message.set_tokens(vec![7, 8]);
+    assert_eq!(message.count_tokens(&other_tokenizer), 2);
+    assert_eq!(message.tokenize(&other_tokenizer), vec![7, 8]);
+    assert_eq!(other_tokenizer.calls(), 1);
+    message.set_content("five");
+    assert_eq!(message.count_tokens(&other_tokenizer), 1);
+    assert_eq!(other_tokenizer.calls(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_prompt_token_cache_across_runs() -> crate::Result<()> {
+    let tokenizer = std::sync::Arc::new(CountingTokenizer::new(1_003));
+    let supporting_material = "a long document that every run shares";
+    // Each run builds a new prompt, as a workflow does.
+    let run = || -> crate::Result<u64> {
+        let prompt = LlmPrompt::new_api_prompt(tokenizer.clone(), Some(3), None);
+        prompt.add_system_message()?.set_content("Be brief.");
+        prompt
+            .add_user_message()?
+            .set_content(format!("Summarize this.\n\n{supporting_material}"));
+        prompt.api_prompt()?.get_total_prompt_tokens()
+    };
+
+    let first = run()?;
+    let calls = tokenizer.calls();
+    assert!(calls > 0);
+    assert_eq!(run()?, first);
+    assert_eq!(tokenizer.calls(), calls);
+    Ok(())
+}
+
+#[test]
+fn test_prepend_system_message() -> crate::Result<()> {
+    let model = ApiLlmModel::gpt_3_5_turbo();
+    let prompt = LlmPrompt::new_api_prompt(
+        model.model_base.tokenizer.clone(),
+        Some(model.tokens_per_message),
+        model.tokens_per_name,
+    );
+    prompt.add_user_message()?.set_content(USER_PROMPT_1);
+    // Build before prepending to check the built prompt is cleared.
+    assert_eq!(prompt.api_prompt()?.get_built_prompt()?.len(), 1);
+    assert!(!prompt.has_system_message());
+
+    prompt
+        .prepend_system_message()?
+        .set_content(SYSTEM_PROMPT_1);
+    assert!(prompt.has_system_message());
+    let built = prompt.api_prompt()?.get_built_prompt()?;
+    assert_eq!(built.len(), 2);
+    assert_eq!(built[0]["role"], "system");
+    assert_eq!(built[0]["content"], SYSTEM_PROMPT_1);
+    assert_eq!(built[1]["content"], USER_PROMPT_1);
+
+    assert!(prompt.prepend_system_message().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_summary() -> crate::Result<()> {
+    let model = ApiLlmModel::gpt_3_5_turbo();
+    let prompt = LlmPrompt::new_api_prompt(
+        model.model_base.tokenizer.clone(),
+        Some(model.tokens_per_message),
+        model.tokens_per_name,
+    );
+    prompt.add_user_message()?.set_content(USER_PROMPT_1);
+    let tokens_without_summary = prompt.api_prompt()?.get_total_prompt_tokens()?;
+
+    // Without a system message, the summary is sent as one.
+    prompt.set_summary("Summary.");
+    let built = prompt.api_prompt()?.get_built_prompt()?;
+    assert_eq!(built.len(), 2);
+    assert_eq!(built[0]["role"], "system");
+    assert_eq!(built[0]["content"], "Summary.");
+    assert!(prompt.api_prompt()?.get_total_prompt_tokens()? > tokens_without_summary);
+
+    // A system message prepended later, like the server system prompt, gets the summary, and keeps it after
+    // being removed and prepended again.
+    prompt
+        .prepend_system_message()?
+        .set_content(SYSTEM_PROMPT_1);
+    prompt.remove_message(0)?;
+    prompt
+        .prepend_system_message()?
+        .set_content(SYSTEM_PROMPT_1);
+    let built = prompt.api_prompt()?.get_built_prompt()?;
+    assert_eq!(built.len(), 2);
+    assert_eq!(
+        built[0]["content"],
+        format!("{SYSTEM_PROMPT_1}\n\nSummary.")
+    );
+
+    prompt.clear_summary();
+    assert_eq!(
+        prompt.api_prompt()?.get_built_prompt()?[0]["content"],
+        SYSTEM_PROMPT_1
+    );
+    Ok(())
+}
+
+#[test]
+fn test_append_content() -> crate::Result<()> {
+    use llm_prompt::{PromptMessage, PromptMessageType, TextConcatenator};
+
+    let model = ApiLlmModel::gpt_3_5_turbo();
+    let prompt = LlmPrompt::new_api_prompt(
+        model.model_base.tokenizer.clone(),
+        Some(model.tokens_per_message),
+        model.tokens_per_name,
+    );
+    let message = prompt.add_user_message()?;
+    message.set_content("one");
+    let tokens = prompt.api_prompt()?.get_total_prompt_tokens()?;
+
+    // Repeated fragments are all kept, and the already built prompt is rebuilt.
+    for piece in ["two", "two", "three"] {
+        message.append_content(piece);
+    }
+    message.prepend_content("zero");
+    assert_eq!(
+        message.get_built_prompt_message()?,
+        "zero\none\ntwo\ntwo\nthree"
+    );
+    assert_eq!(
+        prompt.api_prompt()?.get_built_prompt()?[0]["content"],
+        "zero\none\ntwo\ntwo\nthree"
+    );
+    assert_eq!(
+        prompt.get_built_prompt_messages()?[0]["content"],
+        "zero\none\ntwo\ntwo\nthree"
+    );
+    assert!(prompt.api_prompt()?.get_total_prompt_tokens()? > tokens);
+
+    message.set_content("one");
+    assert_eq!(
+        prompt.api_prompt()?.get_built_prompt()?[0]["content"],
+        "one"
+    );
+    assert_eq!(prompt.api_prompt()?.get_total_prompt_tokens()?, tokens);
+
+    let message = PromptMessage::new(PromptMessageType::User, &TextConcatenator::Space);
+    message.append_content("Hello,").append_content("world!");
+    assert_eq!(message.get_built_prompt_message()?, "Hello, world!");
+    Ok(())
+}
+
+#[test]
+fn test_edit_messages() -> crate::Result<()> {
+    let model = ApiLlmModel::gpt_3_5_turbo();
+    let prompt = LlmPrompt::new_api_prompt(
+        model.model_base.tokenizer.clone(),
+        Some(model.tokens_per_message),
+        model.tokens_per_name,
+    );
+    prompt.add_system_message()?.set_content(SYSTEM_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_1);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_2);
+    assert_eq!(prompt.api_prompt()?.get_built_prompt()?.len(), 4);
+
+    prompt.edit_message(1, "Edited")?;
+    assert_eq!(
+        prompt.api_prompt()?.get_built_prompt()?[1]["content"],
+        "Edited"
+    );
+    assert!(prompt.edit_message(1, "").is_err());
+    assert!(prompt.edit_message(4, "Out of range").is_err());
+
+    // Removing a middle turn would put two user messages next to each other.
+    assert!(prompt.remove_message(2).is_err());
+    assert_eq!(prompt.message_count(), 4);
+    assert!(prompt.remove_message(3).is_ok());
+    assert!(prompt.remove_message(2).is_ok());
+    // Removing the system message leaves the user message first.
+    assert!(prompt.remove_message(0).is_ok());
+    assert_eq!(prompt.api_prompt()?.get_built_prompt()?.len(), 1);
+
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_2);
+    // Removing the first user message would leave the assistant message first.
+    assert!(prompt.remove_message(0).is_err());
+
+    assert!(prompt.truncate_to(4).is_err());
+    prompt.truncate_to(1)?;
+    assert_eq!(prompt.api_prompt()?.get_built_prompt()?.len(), 1);
+    assert!(prompt.add_user_message().is_err());
+    prompt.message(0)?.clear_content();
+    assert!(prompt.message(0)?.get_built_prompt_message().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_remove_message_after_assistant_greeting() -> crate::Result<()> {
+    let model = ApiLlmModel::gpt_3_5_turbo();
+    let prompt = LlmPrompt::new_api_prompt(
+        model.model_base.tokenizer.clone(),
+        Some(model.tokens_per_message),
+        model.tokens_per_name,
+    );
+    // An assistant greeting can follow the system message.
+    prompt.add_system_message()?.set_content(SYSTEM_PROMPT_1);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_1);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+
+    assert!(prompt.remove_message(3).is_ok());
+    assert_eq!(prompt.message_count(), 3);
+    // Removing the greeting leaves the user message after the system message.
+    let built_prompt = prompt.api_prompt()?.get_built_prompt()?;
+    assert_eq!(built_prompt.len(), 3);
+    assert_eq!(built_prompt[1]["role"], "assistant");
+    assert!(prompt.remove_message(1).is_ok());
+    assert_eq!(prompt.api_prompt()?.get_built_prompt()?.len(), 2);
+    // Removing the system message would leave the greeting first.
+    prompt.truncate_to(0)?;
+    prompt.add_system_message()?.set_content(SYSTEM_PROMPT_1);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    assert!(prompt.remove_message(0).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_trim_to_token_budget() -> crate::Result<()> {
+    use llm_prompt::PromptMessageType;
+
+    let model = ApiLlmModel::gpt_3_5_turbo();
+    let prompt = LlmPrompt::new_api_prompt(
+        model.model_base.tokenizer.clone(),
+        Some(model.tokens_per_message),
+        model.tokens_per_name,
+    );
+    prompt.add_system_message()?.set_content(SYSTEM_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_1);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_2);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_2);
+    let total_tokens = prompt.api_prompt()?.get_total_prompt_tokens()?;
+
+    assert_eq!(prompt.trim_to_token_budget(total_tokens)?, 0);
+    assert_eq!(prompt.trim_to_token_budget(total_tokens - 1)?, 1);
+    assert_eq!(prompt.message_count(), 4);
+    assert_eq!(prompt.trim_to_token_budget(total_tokens - 1)?, 0);
+
+    // Only the system message and the last turn are kept.
+    assert!(prompt.trim_to_token_budget(1).is_err());
+    let built_prompt = prompt.api_prompt()?.get_built_prompt()?;
+    assert_eq!(built_prompt.len(), 2);
+    assert_eq!(built_prompt[0]["role"], "system");
+    assert_eq!(built_prompt[1]["content"], USER_PROMPT_2);
+
+    // A prompt ending with an assistant message is trimmed the same way.
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_2);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    let total_tokens = prompt.count_conversation_tokens(0)?;
+    assert_eq!(prompt.trim_to_token_budget(total_tokens - 1)?, 1);
+    assert_eq!(prompt.message_count(), 3);
+    assert_eq!(prompt.message(1)?.message_type, PromptMessageType::User);
+
+    // The last turn is kept.
+    assert_eq!(prompt.drop_oldest_turns(5), 0);
+
+    // An assistant greeting after the system message is dropped with the first pair.
+    prompt.truncate_to(1)?;
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_1);
+    prompt
+        .add_assistant_message()?
+        .set_content(ASSISTANT_PROMPT_1);
+    prompt.add_user_message()?.set_content(USER_PROMPT_2);
+    let total_tokens = prompt.api_prompt()?.get_total_prompt_tokens()?;
+    assert_eq!(prompt.trim_to_token_budget(total_tokens - 1)?, 1);
+    assert_eq!(prompt.message_count(), 2);
+    assert_eq!(prompt.message(1)?.message_type, PromptMessageType::User);
+    assert_eq!(
+        prompt.message(1)?.get_built_prompt_message()?,
+        USER_PROMPT_2
+    );
+    Ok(())
+}
@@ -85,9 +85,33 @@ fn test_local_templates() -> crate::Result<()> {
             chat_template.bos_token.as_deref(),
             &chat_template.eos_token,
             chat_template.unk_token.as_deref(),
-        );
+        )?;
 
         assert_eq!(res, expected_outputs[i]);
     }
     Ok(())
 }
+
+#[test]
+fn test_chat_template_errors() -> crate::Result<()> {
+    let broken = "{% for message in messages %}{{ message['content'] }";
+    let err = validate_chat_template(broken, None, "</s>", None).unwrap_err();
+    assert!(matches!(err, ChatTemplateError::Compile { .. }));
+    assert!(err.to_string().contains("failed to compile"));
+
+    let no_assistant = "{% for message in messages %}{% if message['role'] == 'assistant' %}{{ raise_exception('assistant not supported') }}{% endif %}{{ message['content'] }}{% endfor %}";
+    let err = validate_chat_template(no_assistant, None, "</s>", None).unwrap_err();
+    match &err {
+        ChatTemplateError::Render {
+            message_index,
+            role,
+            ..
+        } => {
+            assert_eq!(*message_index, 1);
+            assert_eq!(role, "assistant");
+        }
+        _ => panic!("Expected ChatTemplateError::Render, got: {err}"),
+    }
+    assert!(err.to_string().contains("assistant not supported"));
+    Ok(())
+}
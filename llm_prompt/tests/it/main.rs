@@ -5,7 +5,10 @@ mod local;
 #[allow(unused_imports)]
 use anyhow::{anyhow, bail, Error, Result};
 use llm_models::local_model::{gguf::preset::LlmPreset, LocalLlmModel};
-use llm_prompt::{apply_chat_template, LlmPrompt, PromptMessages};
+use llm_prompt::{
+    apply_chat_template, check_and_get_max_tokens, validate_chat_template, ChatTemplateError,
+    LlmPrompt, PromptMessages, RequestTokenLimitError,
+};
 use serde_json;
 use std::collections::HashMap;
 use std::fs;
@@ -61,3 +64,29 @@ fn test_serde() -> crate::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_safety_tokens() {
+    assert_eq!(
+        check_and_get_max_tokens(1000, None, 100, None, None).unwrap(),
+        880
+    );
+    assert_eq!(
+        check_and_get_max_tokens(1000, None, 100, Some(50), None).unwrap(),
+        800
+    );
+    assert!(matches!(
+        check_and_get_max_tokens(1000, None, 900, Some(50), None),
+        Err(RequestTokenLimitError::PromptTokensExceeds { .. })
+    ));
+    for safety_tokens in [500, 1000, 5000, u64::MAX] {
+        assert!(matches!(
+            check_and_get_max_tokens(1000, None, 100, Some(safety_tokens), None),
+            Err(RequestTokenLimitError::SafetyTokensExceeds { ctx_size: 1000, .. })
+        ));
+    }
+    assert!(matches!(
+        check_and_get_max_tokens(1000, Some(100), 100, Some(50), None),
+        Err(RequestTokenLimitError::SafetyTokensExceeds { ctx_size: 100, .. })
+    ));
+}
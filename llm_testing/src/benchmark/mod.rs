@@ -0,0 +1,163 @@
+//! Benchmarks models on the boolean and integer test sets with the reason workflow.
+pub mod report;
+pub mod writer;
+
+use crate::{TestLevel, TestSetsLoader};
+use llm_client::{InstructPromptTrait, LlmClient, ReasonTrait, RequestConfigTrait};
+use report::ComparisonReport;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Instant};
+pub use writer::{append_result_to_jsonl, read_results_from_jsonl};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestType {
+    Boolean,
+    Integer,
+}
+
+/// The result of asking a model one question.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultRow {
+    pub model_id: String,
+    pub test_type: TestType,
+    pub question: String,
+    /// The parsed answer, or the error if the request failed.
+    pub llm_response: String,
+    pub correct_answer: String,
+    pub response_correct: bool,
+    pub duration_ms: u64,
+}
+
+/// Every result for one model, from [LlmBenchmark::run].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelBenchmarkResult {
+    pub model_id: String,
+    pub llm_backend: String,
+    pub results: Vec<ResultRow>,
+}
+
+/// Runs the boolean and integer test sets against each model it's given, keeping the results for comparison.
+///
+/// ```ignore
+/// let mut benchmark = LlmBenchmark::new();
+/// benchmark.stream_results_to("results/run.jsonl");
+/// benchmark.run(&LlmClient::llama_cpp().llama3_1_8b_instruct().init().await?).await?;
+/// benchmark.run(&LlmClient::openai().gpt_4_o().init()?).await?;
+/// ```
+#[derive(Default)]
+pub struct LlmBenchmark {
+    pub test_level: TestLevel,
+    pub stream_results_path: Option<PathBuf>,
+    pub benchmark_results: Vec<ModelBenchmarkResult>,
+}
+
+impl LlmBenchmark {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The test sets' level to run. Defaults to [TestLevel::IntegrationTest].
+    pub fn test_level(&mut self, test_level: TestLevel) -> &mut Self {
+        self.test_level = test_level;
+        self
+    }
+
+    /// Appends a JSON line per question to the file as soon as it completes, so progress can be followed with `tail -f`
+    /// and results aren't lost if the run crashes. Each line is a [ResultRow]; read them back with [read_results_from_jsonl].
+    pub fn stream_results_to<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.stream_results_path = Some(path.into());
+        self
+    }
+
+    /// Benchmarks the client's model on every question. A failed request is recorded as an incorrect answer rather than
+    /// stopping the run.
+    pub async fn run(&mut self, llm_client: &LlmClient) -> crate::Result<&ModelBenchmarkResult> {
+        let loader = TestSetsLoader::new().test_level_enum(&self.test_level);
+        let mut model_result = ModelBenchmarkResult {
+            model_id: llm_client.backend.model_id().to_owned(),
+            llm_backend: llm_client.backend.backend_name().to_owned(),
+            results: vec![],
+        };
+
+        let mut gen = llm_client.reason().boolean();
+        for case in loader.boolean()?.cases {
+            gen.instructions().set_content(&case.question);
+            let start = Instant::now();
+            let response = match gen.return_result().await {
+                Ok(result) => gen.primitive.parse_reason_result(&result),
+                Err(e) => Err(e),
+            };
+            let row = result_row(
+                &model_result.model_id,
+                TestType::Boolean,
+                case.correct_answer(),
+                case.question,
+                response,
+                start,
+            );
+            self.record(&mut model_result, row)?;
+            gen.reset_request();
+        }
+
+        let mut gen = llm_client.reason().integer();
+        for case in loader.integer()?.cases {
+            gen.instructions().set_content(&case.question);
+            let start = Instant::now();
+            let response = match gen.return_result().await {
+                Ok(result) => gen.primitive.parse_reason_result(&result),
+                Err(e) => Err(e),
+            };
+            let row = result_row(
+                &model_result.model_id,
+                TestType::Integer,
+                case.correct_answer(),
+                case.question,
+                response,
+                start,
+            );
+            self.record(&mut model_result, row)?;
+            gen.reset_request();
+        }
+
+        self.benchmark_results.push(model_result);
+        Ok(self.benchmark_results.last().unwrap())
+    }
+
+    /// Aggregates the results of every model benchmarked so far into per-model accuracy, latency, and a per-question
+    /// win/loss matrix. Save it with [ComparisonReport::save].
+    pub fn generate_comparison_report(&self) -> ComparisonReport {
+        ComparisonReport::new(&self.benchmark_results)
+    }
+
+    fn record(&self, model_result: &mut ModelBenchmarkResult, row: ResultRow) -> crate::Result<()> {
+        if let Some(path) = &self.stream_results_path {
+            append_result_to_jsonl(path, &row)?;
+        }
+        model_result.results.push(row);
+        Ok(())
+    }
+}
+
+fn result_row<T: std::fmt::Debug + PartialEq>(
+    model_id: &str,
+    test_type: TestType,
+    correct_answer: Option<T>,
+    question: String,
+    response: crate::Result<Option<T>>,
+    start: Instant,
+) -> ResultRow {
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let (llm_response, response_correct) = match response {
+        Ok(response) => (format!("{response:?}"), response == correct_answer),
+        Err(e) => (format!("error: {e}"), false),
+    };
+    ResultRow {
+        model_id: model_id.to_owned(),
+        test_type,
+        question,
+        llm_response,
+        correct_answer: format!("{correct_answer:?}"),
+        response_correct,
+        duration_ms,
+    }
+}
@@ -0,0 +1,248 @@
+use super::{ModelBenchmarkResult, ResultRow, TestType};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Aggregates the results of every benchmarked model into a single artifact for picking a model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    pub models: Vec<ModelComparison>,
+    pub questions: Vec<QuestionComparison>,
+    /// `win_loss[i][j]` is the number of questions model `i` answered correctly that model `j` did not.
+    pub win_loss: Vec<Vec<u32>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelComparison {
+    pub model_id: String,
+    pub llm_backend: String,
+    pub total_questions: u32,
+    pub overall_accuracy: f32,
+    /// `None` if the model wasn't asked any questions of the type.
+    pub boolean_accuracy: Option<f32>,
+    pub integer_accuracy: Option<f32>,
+    pub average_question_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionComparison {
+    pub question: String,
+    pub test_type: TestType,
+    pub correct_answer: String,
+    /// Whether each model answered correctly, in the same order as [ComparisonReport::models].
+    /// `None` if the model has no result for the question.
+    pub response_correct: Vec<Option<bool>>,
+}
+
+impl ComparisonReport {
+    pub fn new(benchmark_results: &[ModelBenchmarkResult]) -> Self {
+        let mut models = vec![];
+        let mut questions: Vec<QuestionComparison> = vec![];
+        let mut question_indexes: HashMap<String, usize> = HashMap::new();
+
+        for (model_index, benchmark_result) in benchmark_results.iter().enumerate() {
+            models.push(ModelComparison::new(benchmark_result));
+
+            for result in &benchmark_result.results {
+                let question_index = *question_indexes
+                    .entry(result.question.clone())
+                    .or_insert_with(|| {
+                        questions.push(QuestionComparison {
+                            question: result.question.clone(),
+                            test_type: result.test_type,
+                            correct_answer: result.correct_answer.clone(),
+                            response_correct: vec![None; benchmark_results.len()],
+                        });
+                        questions.len() - 1
+                    });
+                questions[question_index].response_correct[model_index] =
+                    Some(result.response_correct);
+            }
+        }
+
+        let mut win_loss = vec![vec![0; models.len()]; models.len()];
+        for question in &questions {
+            for (i, a) in question.response_correct.iter().enumerate() {
+                for (j, b) in question.response_correct.iter().enumerate() {
+                    if *a == Some(true) && *b == Some(false) {
+                        win_loss[i][j] += 1;
+                    }
+                }
+            }
+        }
+
+        Self {
+            models,
+            questions,
+            win_loss,
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        writeln!(markdown, "## Models\n").unwrap();
+        writeln!(
+            markdown,
+            "| model | backend | questions | accuracy | boolean | integer | avg question (ms) |"
+        )
+        .unwrap();
+        writeln!(markdown, "|---|---|---|---|---|---|---|").unwrap();
+        for model in &self.models {
+            writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} | {:.0} |",
+                model.model_id,
+                model.llm_backend,
+                model.total_questions,
+                format_accuracy(Some(model.overall_accuracy)),
+                format_accuracy(model.boolean_accuracy),
+                format_accuracy(model.integer_accuracy),
+                model.average_question_ms
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            markdown,
+            "\n## Win/loss\n\nQuestions the row model answered correctly that the column model did not.\n"
+        )
+        .unwrap();
+        let model_ids: Vec<&str> = self.models.iter().map(|m| m.model_id.as_str()).collect();
+        writeln!(markdown, "| | {} |", model_ids.join(" | ")).unwrap();
+        writeln!(markdown, "|---|{}", "---|".repeat(model_ids.len())).unwrap();
+        for (model_id, row) in model_ids.iter().zip(&self.win_loss) {
+            let row: Vec<String> = row.iter().map(|wins| wins.to_string()).collect();
+            writeln!(markdown, "| {} | {} |", model_id, row.join(" | ")).unwrap();
+        }
+        markdown
+    }
+
+    /// Writes the report to `comparison_report.json` and `comparison_report.md` in the directory, creating it if needed,
+    /// and returns the two paths.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> crate::Result<(PathBuf, PathBuf)> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let json_path = dir.join("comparison_report.json");
+        let markdown_path = dir.join("comparison_report.md");
+        fs::write(&json_path, serde_json::to_string_pretty(self)?)?;
+        fs::write(&markdown_path, self.to_markdown())?;
+        Ok((json_path, markdown_path))
+    }
+}
+
+impl ModelComparison {
+    fn new(benchmark_result: &ModelBenchmarkResult) -> Self {
+        let results = &benchmark_result.results;
+        let total_duration: u64 = results.iter().map(|result| result.duration_ms).sum();
+        Self {
+            model_id: benchmark_result.model_id.clone(),
+            llm_backend: benchmark_result.llm_backend.clone(),
+            total_questions: results.len() as u32,
+            overall_accuracy: accuracy(results.iter()).unwrap_or(0.0),
+            boolean_accuracy: accuracy(
+                results
+                    .iter()
+                    .filter(|result| result.test_type == TestType::Boolean),
+            ),
+            integer_accuracy: accuracy(
+                results
+                    .iter()
+                    .filter(|result| result.test_type == TestType::Integer),
+            ),
+            average_question_ms: if results.is_empty() {
+                0.0
+            } else {
+                total_duration as f64 / results.len() as f64
+            },
+        }
+    }
+}
+
+fn accuracy<'a>(results: impl Iterator<Item = &'a ResultRow>) -> Option<f32> {
+    let (correct, total) = results.fold((0u32, 0u32), |(correct, total), result| {
+        (correct + result.response_correct as u32, total + 1)
+    });
+    if total == 0 {
+        None
+    } else {
+        Some(correct as f32 / total as f32)
+    }
+}
+
+fn format_accuracy(accuracy: Option<f32>) -> String {
+    match accuracy {
+        Some(accuracy) => format!("{:.1}%", accuracy * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_result(model_id: &str, answers: &[(&str, TestType, bool)]) -> ModelBenchmarkResult {
+        ModelBenchmarkResult {
+            model_id: model_id.to_string(),
+            llm_backend: "LlamaCpp".to_string(),
+            results: answers
+                .iter()
+                .map(|(question, test_type, response_correct)| ResultRow {
+                    model_id: model_id.to_string(),
+                    test_type: *test_type,
+                    question: question.to_string(),
+                    llm_response: String::new(),
+                    correct_answer: String::new(),
+                    response_correct: *response_correct,
+                    duration_ms: 100,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_generate_and_save_report() -> crate::Result<()> {
+        let report = ComparisonReport::new(&[
+            model_result(
+                "model-a",
+                &[
+                    ("Is the sky blue?", TestType::Boolean, true),
+                    ("Is fire cold?", TestType::Boolean, true),
+                    ("What is 1 + 1?", TestType::Integer, false),
+                ],
+            ),
+            model_result(
+                "model-b",
+                &[
+                    ("Is the sky blue?", TestType::Boolean, false),
+                    ("What is 1 + 1?", TestType::Integer, true),
+                ],
+            ),
+        ]);
+
+        assert_eq!(report.models[0].total_questions, 3);
+        assert_eq!(report.models[0].boolean_accuracy, Some(1.0));
+        assert_eq!(report.models[0].integer_accuracy, Some(0.0));
+        assert_eq!(report.models[1].boolean_accuracy, Some(0.0));
+        assert_eq!(report.models[1].average_question_ms, 100.0);
+        assert_eq!(report.questions.len(), 3);
+        assert_eq!(report.questions[1].response_correct, vec![Some(true), None]);
+        assert_eq!(report.win_loss, vec![vec![0, 1], vec![1, 0]]);
+
+        let dir = std::env::temp_dir().join(format!(
+            "llm_testing_comparison_report_{}",
+            std::process::id()
+        ));
+        let (json_path, markdown_path) = report.save(&dir)?;
+        let saved: ComparisonReport = serde_json::from_str(&fs::read_to_string(json_path)?)?;
+        assert_eq!(saved, report);
+        let markdown = fs::read_to_string(markdown_path)?;
+        assert!(markdown.contains("| model-a | LlamaCpp | 3 | 66.7% | 100.0% | 0.0% | 100 |"));
+        assert!(markdown.contains("| model-b | 1 | 0 |"));
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
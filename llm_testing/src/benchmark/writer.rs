@@ -0,0 +1,77 @@
+use super::ResultRow;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+/// Appends the row to a JSONL file, creating the file and its directory if needed, and flushes it.
+pub fn append_result_to_jsonl(path: impl AsRef<Path>, result_row: &ResultRow) -> crate::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(result_row)?)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Reads the rows written by [append_result_to_jsonl], in the order they were written.
+pub fn read_results_from_jsonl(path: impl AsRef<Path>) -> crate::Result<Vec<ResultRow>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut rows = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // The last line can be incomplete if the run crashed mid-write.
+        match serde_json::from_str(&line) {
+            Ok(row) => rows.push(row),
+            Err(e) => crate::warn!("LlmBenchmark: skipping unreadable result line: {e}"),
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TestType;
+
+    fn row(question: &str, response_correct: bool) -> ResultRow {
+        ResultRow {
+            model_id: "test-model".to_string(),
+            test_type: TestType::Boolean,
+            question: question.to_string(),
+            llm_response: format!("{:?}", Some(response_correct)),
+            correct_answer: format!("{:?}", Some(true)),
+            response_correct,
+            duration_ms: 12,
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_results() -> crate::Result<()> {
+        let path = std::env::temp_dir()
+            .join(format!("llm_testing_benchmark_{}", std::process::id()))
+            .join("results.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let rows = vec![row("Is the sky blue?", true), row("Is fire cold?", false)];
+        for row in &rows {
+            append_result_to_jsonl(&path, row)?;
+        }
+        assert_eq!(fs::read_to_string(&path)?.lines().count(), 2);
+        assert_eq!(read_results_from_jsonl(&path)?, rows);
+
+        // A line cut off by a crash is skipped.
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        write!(file, r#"{{"model_id":"test-mo"#)?;
+        assert_eq!(read_results_from_jsonl(&path)?, rows);
+
+        fs::remove_dir_all(path.parent().unwrap())?;
+        Ok(())
+    }
+}
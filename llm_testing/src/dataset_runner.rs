@@ -0,0 +1,326 @@
+//! Runs a workflow over every row of a dataset, writing results as they complete so an interrupted run can be resumed.
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    future::Future,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+pub use tokio_util::sync::CancellationToken;
+
+/// An input row. `id` must be unique within the dataset; it's how completed rows are recognized when resuming.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetRow<I> {
+    pub id: String,
+    pub input: I,
+}
+
+/// A line of the output file. `error` is set if the workflow failed on the row, and `output` otherwise, unless the output
+/// serializes to `null`, e.g. `None` or `()`, which reads back as `None`. Use [DatasetRecord::is_success] to tell them apart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetRecord<O> {
+    pub id: String,
+    pub output: Option<O>,
+    pub error: Option<String>,
+}
+
+impl<O> DatasetRecord<O> {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatasetRunSummary {
+    /// Rows the workflow succeeded on during this run.
+    pub succeeded: usize,
+    /// Rows the workflow returned an error for during this run. They're retried on the next run.
+    pub failed: usize,
+    /// Rows skipped because a previous run already succeeded on them.
+    pub skipped: usize,
+    /// Whether the run stopped early because the cancellation token was cancelled.
+    pub cancelled: bool,
+}
+
+/// Runs a workflow closure over a dataset with bounded concurrency. Each result is appended to a JSONL file as soon as it
+/// completes, so a run that's interrupted or cancelled can be resumed by running again with the same output path.
+///
+/// ```ignore
+/// let rows = DatasetRunner::read_jsonl_rows::<String>("reviews.jsonl")?;
+/// let summary = DatasetRunner::new("labels.jsonl")
+///     .concurrency(4)
+///     .run(rows, |review| {
+///         let llm_client = &llm_client;
+///         async move {
+///             let mut reason = llm_client.reason().boolean();
+///             reason.instructions().set_content(format!("Is this review positive? {review}"));
+///             reason.return_primitive().await
+///         }
+///     })
+///     .await?;
+/// ```
+pub struct DatasetRunner {
+    pub output_path: PathBuf,
+    pub concurrency: usize,
+    pub cancellation_token: CancellationToken,
+}
+
+impl DatasetRunner {
+    pub fn new<P: AsRef<Path>>(output_path: P) -> Self {
+        Self {
+            output_path: output_path.as_ref().to_path_buf(),
+            concurrency: 1,
+            cancellation_token: CancellationToken::new(),
+        }
+    }
+
+    /// The maximum number of rows processed at once. Defaults to 1.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Stops the run when `cancellation_token` is cancelled. Rows in progress are dropped without being written, so
+    /// they're processed again on the next run.
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    /// Reads rows from a JSONL file where each line is a [DatasetRow].
+    pub fn read_jsonl_rows<I: DeserializeOwned>(
+        path: impl AsRef<Path>,
+    ) -> crate::Result<Vec<DatasetRow<I>>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut rows = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                rows.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Reads the successful records from the output file. If a row has more than one, the last is returned.
+    pub fn read_records<O: DeserializeOwned>(&self) -> crate::Result<Vec<DatasetRecord<O>>> {
+        let mut records: Vec<DatasetRecord<O>> = vec![];
+        for record in self.read_output_lines::<O>()? {
+            if record.is_success() {
+                records.retain(|r| r.id != record.id);
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// The ids of rows with a successful record in the output file.
+    pub fn completed_ids(&self) -> crate::Result<HashSet<String>> {
+        Ok(self
+            .read_output_lines::<serde_json::Value>()?
+            .into_iter()
+            .filter(|record| record.is_success())
+            .map(|record| record.id)
+            .collect())
+    }
+
+    /// Runs `workflow` on each row that doesn't already have a successful record in the output file.
+    /// Errors from `workflow` are recorded in the output file and counted in the summary; errors writing the output file
+    /// stop the run.
+    pub async fn run<I, O, F, Fut>(
+        &self,
+        rows: impl IntoIterator<Item = DatasetRow<I>>,
+        workflow: F,
+    ) -> crate::Result<DatasetRunSummary>
+    where
+        O: Serialize,
+        F: Fn(I) -> Fut,
+        Fut: Future<Output = crate::Result<O>>,
+    {
+        let completed_ids = self.completed_ids()?;
+        let mut summary = DatasetRunSummary::default();
+        let pending_rows: Vec<DatasetRow<I>> = rows
+            .into_iter()
+            .filter(|row| {
+                let completed = completed_ids.contains(&row.id);
+                if completed {
+                    summary.skipped += 1;
+                }
+                !completed
+            })
+            .collect();
+
+        if let Some(parent) = self.output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.output_path)?;
+
+        let workflow = &workflow;
+        let mut results = futures::stream::iter(pending_rows)
+            .map(|row| async move { (row.id, workflow(row.input).await) })
+            .buffer_unordered(self.concurrency);
+        loop {
+            let (id, result) = tokio::select! {
+                biased;
+                _ = self.cancellation_token.cancelled() => {
+                    summary.cancelled = true;
+                    break;
+                }
+                next = results.next() => match next {
+                    Some(next) => next,
+                    None => break,
+                },
+            };
+            let record = match result {
+                Ok(output) => {
+                    summary.succeeded += 1;
+                    DatasetRecord {
+                        id,
+                        output: Some(output),
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    crate::warn!("DatasetRunner: row {id} failed: {e}");
+                    summary.failed += 1;
+                    DatasetRecord {
+                        id,
+                        output: None,
+                        error: Some(e.to_string()),
+                    }
+                }
+            };
+            writeln!(file, "{}", serde_json::to_string(&record)?)?;
+            file.flush()?;
+        }
+        Ok(summary)
+    }
+
+    fn read_output_lines<O: DeserializeOwned>(&self) -> crate::Result<Vec<DatasetRecord<O>>> {
+        if !self.output_path.exists() {
+            return Ok(vec![]);
+        }
+        let reader = BufReader::new(File::open(&self.output_path)?);
+        let mut records = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // The last line can be incomplete if the process was killed mid-write.
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => crate::warn!("DatasetRunner: skipping unreadable output line: {e}"),
+            }
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(count: u32) -> Vec<DatasetRow<u32>> {
+        (0..count)
+            .map(|n| DatasetRow {
+                id: n.to_string(),
+                input: n,
+            })
+            .collect()
+    }
+
+    fn output_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "llm_testing_dataset_runner_{name}_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_failures() -> crate::Result<()> {
+        let path = output_path("resume");
+        let runner = DatasetRunner::new(&path).concurrency(3);
+
+        let summary = runner
+            .run(rows(10), |n| async move {
+                if n % 4 == 0 {
+                    crate::bail!("divisible by four")
+                }
+                Ok(n * 2)
+            })
+            .await?;
+        assert_eq!(summary.succeeded, 7);
+        assert_eq!(summary.failed, 3);
+
+        let summary = runner.run(rows(10), |n| async move { Ok(n * 2) }).await?;
+        assert_eq!(summary.skipped, 7);
+        assert_eq!(summary.succeeded, 3);
+
+        let mut records = runner.read_records::<u32>()?;
+        records.sort_by_key(|r| r.id.parse::<u32>().unwrap());
+        let outputs: Vec<u32> = records.iter().filter_map(|r| r.output).collect();
+        assert_eq!(outputs, (0..10).map(|n| n * 2).collect::<Vec<_>>());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_null_output_is_completed() -> crate::Result<()> {
+        let path = output_path("null_output");
+        let runner = DatasetRunner::new(&path);
+
+        let summary = runner
+            .run(rows(4), |n| async move { Ok((n % 2 == 0).then_some(n)) })
+            .await?;
+        assert_eq!(summary.succeeded, 4);
+
+        // Rows whose output is `None` aren't run again, and are read back.
+        let summary = runner.run(rows(4), |_| async move { Ok(Some(0)) }).await?;
+        assert_eq!(summary.skipped, 4);
+        assert_eq!(summary.succeeded, 0);
+        let records = runner.read_records::<Option<u32>>()?;
+        assert_eq!(records.len(), 4);
+        assert!(records.iter().all(|r| r.is_success()));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel() -> crate::Result<()> {
+        let path = output_path("cancel");
+        let token = CancellationToken::new();
+        let runner = DatasetRunner::new(&path).cancellation_token(token.clone());
+
+        let summary = runner
+            .run(rows(10), |n| {
+                let token = token.clone();
+                async move {
+                    if n == 4 {
+                        token.cancel();
+                        std::future::pending::<()>().await;
+                    }
+                    Ok(n)
+                }
+            })
+            .await?;
+        assert!(summary.cancelled);
+        assert_eq!(summary.succeeded, 4);
+        assert_eq!(runner.completed_ids()?.len(), 4);
+
+        let summary = DatasetRunner::new(&path)
+            .run(rows(10), |n| async move { Ok(n) })
+            .await?;
+        assert_eq!(summary.skipped, 4);
+        assert_eq!(summary.succeeded, 6);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
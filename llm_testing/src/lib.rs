@@ -4,12 +4,20 @@ pub use anyhow::{anyhow, bail, Result};
 use llm_client::LlmPrompt;
 
 pub use backends::*;
+pub use benchmark::{
+    report::ComparisonReport, LlmBenchmark, ModelBenchmarkResult, ResultRow, TestType,
+};
+pub use dataset_runner::{
+    CancellationToken, DatasetRecord, DatasetRow, DatasetRunSummary, DatasetRunner,
+};
 pub use test_loader::*;
 pub use test_types::*;
 #[allow(unused_imports)]
 pub use tracing::{debug, error, info, span, trace, warn, Level};
 
 pub mod backends;
+pub mod benchmark;
+pub mod dataset_runner;
 pub mod speed_bench;
 mod test_loader;
 mod test_types;
@@ -29,7 +29,7 @@ impl SpeedBenchmark {
                 }
             };
             let res = self.benchmark_model(&llm_client).await?;
-            llm_client.shutdown();
+            llm_client.shutdown_gracefully().await?;
             std::mem::drop(llm_client);
             result.model_results.push(res);
         }
@@ -26,6 +26,10 @@ pub struct BooleanTest {
 }
 
 impl BooleanTest {
+    pub fn correct_answer(&self) -> Option<bool> {
+        self.correct_answer
+    }
+
     pub fn check_result(&self) {
         if self.result == self.correct_answer {
             println!(
@@ -70,6 +74,10 @@ pub struct IntegerTest {
 }
 
 impl IntegerTest {
+    pub fn correct_answer(&self) -> Option<u32> {
+        self.correct_answer
+    }
+
     pub fn check_result(&self) {
         let outcome = if self.result == self.correct_answer {
             format!("🟢 correct response: '{:?}'", self.result)